@@ -1,4 +1,5 @@
 use ::time::UtcOffset;
+use anyhow::Context as _;
 use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter};
 use opentelemetry::trace::TracerProvider;
 use opentelemetry::{global, KeyValue};
@@ -19,27 +20,67 @@ use tracing_core::LevelFilter;
 use tracing_opentelemetry::OpenTelemetryLayer;
 use tracing_subscriber::layer::SubscriberExt as _;
 use tracing_subscriber::util::SubscriberInitExt as _;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::{reload, EnvFilter, Registry};
 use url::Url;
 
 pub struct Analytics {
     meter_provider: Option<SdkMeterProvider>,
     service_name: String,
     collection_endpoint: Option<Url>,
+    log_filter: Option<LogFilterHandle>,
+}
+
+/// Changes the filter of the logs of the node while it is running, with the same syntax as `RUST_LOG`.
+#[derive(Clone)]
+pub struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
+impl LogFilterHandle {
+    fn new_filter(filter: &str) -> anyhow::Result<EnvFilter> {
+        EnvFilter::builder()
+            .with_default_directive(LevelFilter::INFO.into())
+            .parse(filter)
+            .with_context(|| format!("Invalid log filter {filter:?}"))
+    }
+
+    pub fn get(&self) -> anyhow::Result<String> {
+        self.0.with_current(|filter| filter.to_string()).context("Getting the log filter")
+    }
+
+    pub fn set(&self, filter: &str) -> anyhow::Result<()> {
+        let filter = Self::new_filter(filter)?;
+        self.0.reload(filter).context("Setting the log filter")
+    }
+}
+
+impl fmt::Debug for LogFilterHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LogFilterHandle").finish()
+    }
 }
 
 impl Analytics {
     pub fn new(service_name: String, collection_endpoint: Option<Url>) -> anyhow::Result<Self> {
-        Ok(Self { meter_provider: None, service_name, collection_endpoint })
+        Ok(Self { meter_provider: None, service_name, collection_endpoint, log_filter: None })
+    }
+
+    /// Handle to change the log filter at runtime, available once [`Analytics::setup`] has been called.
+    pub fn log_filter(&self) -> Option<LogFilterHandle> {
+        self.log_filter.clone()
     }
 
     pub fn setup(&mut self) -> anyhow::Result<()> {
         let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
         let custom_formatter = CustomFormatter { local_offset };
 
+        // The filter is the first layer so that it can be reloaded through a handle which does not depend on the other
+        // layers.
+        let (env_filter, log_filter) =
+            reload::Layer::new(EnvFilter::builder().with_default_directive(LevelFilter::INFO.into()).from_env()?);
+        self.log_filter = Some(LogFilterHandle(log_filter));
+
         let tracing_subscriber = tracing_subscriber::registry()
-            .with(tracing_subscriber::fmt::layer().event_format(custom_formatter).with_writer(std::io::stderr))
-            .with(EnvFilter::builder().with_default_directive(LevelFilter::INFO.into()).from_env()?);
+            .with(env_filter)
+            .with(tracing_subscriber::fmt::layer().event_format(custom_formatter).with_writer(std::io::stderr));
 
         if self.collection_endpoint.is_none() {
             tracing_subscriber.init();
@@ -367,3 +408,28 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter_reload() {
+        let (env_filter, handle) = reload::Layer::new(LogFilterHandle::new_filter("info").unwrap());
+        let log_filter = LogFilterHandle(handle);
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(env_filter), || {
+            assert_eq!(log_filter.get().unwrap(), "info");
+            assert!(!tracing::enabled!(Level::DEBUG));
+
+            log_filter.set("debug").unwrap();
+            assert_eq!(log_filter.get().unwrap(), "debug");
+            assert!(tracing::enabled!(Level::DEBUG));
+
+            // An invalid filter leaves the current one in place.
+            assert!(log_filter.set("mc_db=loud").is_err());
+            assert_eq!(log_filter.get().unwrap(), "debug");
+            assert!(tracing::enabled!(Level::DEBUG));
+        });
+    }
+}
@@ -41,6 +41,7 @@
 use anyhow::Context;
 use mc_db::{MadaraBackend, MadaraStorageError};
 use metrics::BlockMetrics;
+use mp_chain_config::StarknetVersion;
 use mp_class::{class_hash::ComputeClassHashError, compile::ClassCompilationError};
 use starknet_types_core::felt::Felt;
 use std::{borrow::Cow, sync::Arc};
@@ -99,6 +100,10 @@ pub enum BlockImportError {
     ParentHash { got: Felt, expected: Felt },
     #[error("Global state root mismatch: expected {expected:#x}, got {got:#x}")]
     GlobalStateRoot { got: Felt, expected: Felt },
+    #[error(
+        "Protocol version mismatch for block #{block_n}: the protocol upgrade schedule expects {expected}, got {got}"
+    )]
+    ProtocolVersion { block_n: u64, got: StarknetVersion, expected: StarknetVersion },
 
     /// Internal error, see [`BlockImportError::is_internal`].
     #[error("Internal database error while {context}: {error:#}")]
@@ -174,6 +179,19 @@ impl BlockImporter {
         Ok(result)
     }
 
+    /// Import a block reconstructed from the data availability of the settlement layer. It is stored without its
+    /// transactions and receipts.
+    #[tracing::instrument(skip(self, block, validation), fields(module = "BlockImporter"))]
+    pub async fn import_state_diff_block(
+        &self,
+        block: StateDiffBlock,
+        validation: BlockValidationContext,
+    ) -> Result<BlockImportResult, BlockImportError> {
+        let result = self.verify_apply.verify_apply_state_diff(block, validation).await?;
+        self.metrics.update(&result.header, &self.backend);
+        Ok(result)
+    }
+
     #[tracing::instrument(skip(self, block, validation), fields(module = "BlockImporter"))]
     pub async fn pre_validate_pending(
         &self,
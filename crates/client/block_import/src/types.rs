@@ -169,6 +169,19 @@ pub struct UnverifiedFullBlock {
     pub visited_segments: Option<VisitedSegments>,
 }
 
+/// A block reconstructed from the data availability of the settlement layer: only its state diff and a few header
+/// fields are known, its transactions and receipts are not.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct StateDiffBlock {
+    pub block_number: u64,
+    pub parent_block_hash: Felt,
+    /// Trusted, the header fields it commits to are not part of the data availability.
+    pub block_hash: Felt,
+    /// Expected global state root after applying the state diff.
+    pub global_state_root: Felt,
+    pub state_diff: StateDiff,
+}
+
 // Pre-validate outputs.
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
@@ -1,6 +1,6 @@
 use crate::{
     global_spawn_rayon_task, BlockImportError, BlockImportResult, BlockValidationContext, PendingBlockImportResult,
-    PreValidatedBlock, PreValidatedPendingBlock, UnverifiedHeader, ValidatedCommitments,
+    PreValidatedBlock, PreValidatedPendingBlock, StateDiffBlock, UnverifiedHeader, ValidatedCommitments,
 };
 use itertools::Itertools;
 use mc_db::{MadaraBackend, MadaraStorageError};
@@ -10,6 +10,7 @@ use mp_block::{
     MadaraMaybePendingBlockInfo, MadaraPendingBlockInfo,
 };
 use mp_convert::{FeltHexDisplay, ToFelt};
+use mp_state_update::StateDiff;
 use starknet_api::core::ChainId;
 use starknet_types_core::felt::Felt;
 use starknet_types_core::hash::{Poseidon, StarkHash};
@@ -65,6 +66,22 @@ impl VerifyApply {
         tracing::debug!("releasing verify_apply exclusive (pending)");
         res
     }
+
+    /// See [`Self::verify_apply`].
+    pub async fn verify_apply_state_diff(
+        &self,
+        block: StateDiffBlock,
+        validation: BlockValidationContext,
+    ) -> Result<BlockImportResult, BlockImportError> {
+        tracing::debug!("acquiring verify_apply exclusive (state diff)");
+        let _exclusive = self.mutex.lock().await;
+        tracing::debug!("acquired verify_apply exclusive (state diff)");
+
+        let backend = Arc::clone(&self.backend);
+        let res = global_spawn_rayon_task(move || verify_apply_state_diff_inner(&backend, block, validation)).await;
+        tracing::debug!("releasing verify_apply exclusive (state diff)");
+        res
+    }
 }
 
 /// This needs to be called sequentially, it will apply the state diff to the db, verify the state root and save the block.
@@ -78,6 +95,7 @@ pub fn verify_apply_inner(
     // Check block number and block hash against db
     let (block_number, parent_block_hash) =
         check_parent_hash_and_num(backend, block.header.parent_block_hash, block.unverified_block_number, &validation)?;
+    check_protocol_version(backend, &block.header, block_number)?;
 
     // Update contract and its storage tries
     let global_state_root = update_tries(backend, &block, &validation, block_number)?;
@@ -115,8 +133,9 @@ pub fn verify_apply_pending_inner(
     block: PreValidatedPendingBlock,
     validation: BlockValidationContext,
 ) -> Result<PendingBlockImportResult, BlockImportError> {
-    let (_block_number, parent_block_hash) =
+    let (block_number, parent_block_hash) =
         check_parent_hash_and_num(backend, block.header.parent_block_hash, None, &validation)?;
+    check_protocol_version(backend, &block.header, block_number)?;
 
     let UnverifiedHeader {
         parent_block_hash: _,
@@ -154,6 +173,44 @@ pub fn verify_apply_pending_inner(
     Ok(PendingBlockImportResult {})
 }
 
+/// Applies the state diff of a block reconstructed from the settlement layer data availability, verifies the resulting
+/// global state root, and stores the block without its transactions and receipts.
+///
+/// Header fields which are not part of the data availability (timestamp, sequencer address, gas prices,
+/// transaction and event commitments) are left to their default value.
+pub fn verify_apply_state_diff_inner(
+    backend: &MadaraBackend,
+    block: StateDiffBlock,
+    validation: BlockValidationContext,
+) -> Result<BlockImportResult, BlockImportError> {
+    let (block_number, parent_block_hash) =
+        check_parent_hash_and_num(backend, Some(block.parent_block_hash), Some(block.block_number), &validation)?;
+
+    let global_state_root = state_diff_state_root(backend, &block.state_diff, block_number)?;
+    if global_state_root != block.global_state_root {
+        return Err(BlockImportError::GlobalStateRoot { got: global_state_root, expected: block.global_state_root });
+    }
+
+    let header = Header {
+        parent_block_hash,
+        block_number,
+        global_state_root,
+        state_diff_length: Some(block.state_diff.len() as u64),
+        state_diff_commitment: Some(block.state_diff.compute_hash()),
+        ..Default::default()
+    };
+
+    backend
+        .store_block_without_body(
+            MadaraBlockInfo { header: header.clone(), block_hash: block.block_hash, tx_hashes: vec![] },
+            block.state_diff,
+            vec![],
+        )
+        .map_err(make_db_error("storing block in db"))?;
+
+    Ok(BlockImportResult { header, block_hash: block.block_hash })
+}
+
 fn make_db_error(context: impl Into<Cow<'static, str>>) -> impl FnOnce(MadaraStorageError) -> BlockImportError {
     move |error| BlockImportError::InternalDb { context: context.into(), error }
 }
@@ -194,6 +251,29 @@ fn check_parent_hash_and_num(
     Ok((block_number, expected_parent_block_hash))
 }
 
+/// Checks that the block protocol version follows the protocol upgrade schedule from the chain config.
+/// Chains without a schedule are not checked, as we cannot know the protocol version of their past blocks.
+fn check_protocol_version(
+    backend: &MadaraBackend,
+    header: &UnverifiedHeader,
+    block_number: u64,
+) -> Result<(), BlockImportError> {
+    let chain_config = backend.chain_config();
+    if chain_config.protocol_upgrades.is_empty() {
+        return Ok(());
+    }
+
+    let expected = chain_config.protocol_version_at(block_number, header.block_timestamp.0);
+    if header.protocol_version != expected {
+        return Err(BlockImportError::ProtocolVersion {
+            block_n: block_number,
+            got: header.protocol_version,
+            expected,
+        });
+    }
+    Ok(())
+}
+
 /// "STARKNET_STATE_V0"
 const STARKNET_STATE_PREFIX: Felt = Felt::from_hex_unchecked("0x535441524b4e45545f53544154455f5630");
 
@@ -234,32 +314,41 @@ fn update_tries(
         block.state_diff.deprecated_declared_classes.iter().map(|c| c.hex_display()).format(", ")
     );
 
+    let state_root = state_diff_state_root(backend, &block.state_diff, block_number)?;
+
+    if let Some(expected) = block.unverified_global_state_root {
+        if expected != state_root {
+            return Err(BlockImportError::GlobalStateRoot { got: state_root, expected });
+        }
+    }
+
+    Ok(state_root)
+}
+
+/// Applies a state diff to the global tries, and returns the new global state root.
+fn state_diff_state_root(
+    backend: &MadaraBackend,
+    state_diff: &StateDiff,
+    block_number: u64,
+) -> Result<Felt, BlockImportError> {
     let (contract_trie_root, class_trie_root) = rayon::join(
         || {
             contracts::contract_trie_root(
                 backend,
-                &block.state_diff.deployed_contracts,
-                &block.state_diff.replaced_classes,
-                &block.state_diff.nonces,
-                &block.state_diff.storage_diffs,
+                &state_diff.deployed_contracts,
+                &state_diff.replaced_classes,
+                &state_diff.nonces,
+                &state_diff.storage_diffs,
                 block_number,
             )
         },
-        || classes::class_trie_root(backend, &block.state_diff.declared_classes, block_number),
+        || classes::class_trie_root(backend, &state_diff.declared_classes, block_number),
     );
 
-    let state_root = calculate_state_root(
+    Ok(calculate_state_root(
         contract_trie_root.map_err(make_db_error("updating contract trie root"))?,
         class_trie_root.map_err(make_db_error("updating class trie root"))?,
-    );
-
-    if let Some(expected) = block.unverified_global_state_root {
-        if expected != state_root {
-            return Err(BlockImportError::GlobalStateRoot { got: state_root, expected });
-        }
-    }
-
-    Ok(state_root)
+    ))
 }
 
 /// Returns the block hash and header.
@@ -331,7 +420,7 @@ mod verify_apply_tests {
     use crate::tests::block_import_utils::*;
     use mc_db::tests::common::{finalized_block_zero, finalized_state_diff_zero};
 
-    use mp_chain_config::ChainConfig;
+    use mp_chain_config::{ChainConfig, ProtocolUpgrade, ProtocolUpgradeSchedule, StarknetVersion, UpgradeActivation};
 
     use mp_state_update::{ContractStorageDiffItem, DeployedContractItem, StateDiff, StorageEntry};
 
@@ -428,6 +517,42 @@ mod verify_apply_tests {
         }
     }
 
+    /// Blocks must follow the protocol upgrade schedule, when there is one.
+    #[rstest]
+    #[case::before_upgrade(4, StarknetVersion::V0_13_1, true)]
+    #[case::after_upgrade(5, StarknetVersion::V0_13_2, true)]
+    #[case::missed_upgrade(5, StarknetVersion::V0_13_1, false)]
+    #[case::early_upgrade(4, StarknetVersion::V0_13_2, false)]
+    fn test_check_protocol_version(
+        #[case] block_number: u64,
+        #[case] protocol_version: StarknetVersion,
+        #[case] valid: bool,
+    ) {
+        let chain_config = ChainConfig {
+            latest_protocol_version: StarknetVersion::V0_13_1,
+            protocol_upgrades: ProtocolUpgradeSchedule(vec![ProtocolUpgrade {
+                activation: UpgradeActivation::AtBlock(5),
+                version: StarknetVersion::V0_13_2,
+            }]),
+            ..ChainConfig::madara_test()
+        };
+        let backend = MadaraBackend::open_for_testing(Arc::new(chain_config));
+        let header = UnverifiedHeader { protocol_version, ..Default::default() };
+
+        let result = check_protocol_version(&backend, &header, block_number);
+        if valid {
+            assert!(result.is_ok());
+        } else {
+            assert!(
+                matches!(result, Err(BlockImportError::ProtocolVersion { block_n, got, .. }) if block_n == block_number && got == protocol_version)
+            );
+        }
+
+        // No schedule: anything goes.
+        let backend = MadaraBackend::open_for_testing(Arc::new(ChainConfig::madara_test()));
+        assert!(check_protocol_version(&backend, &header, block_number).is_ok());
+    }
+
     /// Test cases for the `calculate_state_root` function.
     ///
     /// This test uses `rstest` to parameterize different scenarios for calculating
@@ -553,6 +678,34 @@ mod verify_apply_tests {
         }
     }
 
+    /// Blocks reconstructed from the data availability are stored without a body, after checking their state root.
+    #[rstest]
+    #[tokio::test]
+    async fn test_verify_apply_state_diff(setup_test_backend: Arc<MadaraBackend>) {
+        let backend = setup_test_backend;
+        let state_diff = StateDiff {
+            deployed_contracts: vec![DeployedContractItem { address: felt!("0x1"), class_hash: felt!("0x1") }],
+            storage_diffs: vec![ContractStorageDiffItem {
+                address: felt!("0x1"),
+                storage_entries: vec![StorageEntry { key: felt!("0x1"), value: felt!("0x1") }],
+            }],
+            ..Default::default()
+        };
+        let block = StateDiffBlock {
+            block_number: 0,
+            parent_block_hash: Felt::ZERO,
+            block_hash: felt!("0x1234"),
+            global_state_root: felt!("0x738e796f750b21ddb3ce528ca88f7e35fad580768bd58571995b19a6809bb4a"),
+            state_diff: state_diff.clone(),
+        };
+
+        let result = verify_apply_state_diff_inner(&backend, block, create_validation_context(false)).unwrap();
+        assert_eq!(result.block_hash, felt!("0x1234"));
+        assert_eq!(backend.get_latest_block_n().unwrap(), Some(0));
+        assert_eq!(backend.get_block_state_diff(&BlockId::Number(0)).unwrap(), Some(state_diff));
+        assert!(!backend.has_block_body(0).unwrap());
+    }
+
     #[rstest]
     // Case 1: Successful block hash calculation
     #[case::success(
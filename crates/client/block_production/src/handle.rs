@@ -0,0 +1,61 @@
+//! Remote control of the block production task, used by the admin RPC.
+
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+pub(crate) enum BlockProductionCommand {
+    /// Seal the pending block now. Replies with the number of the closed block.
+    CloseBlock { reply: oneshot::Sender<Result<u64, String>> },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlockProductionHandleError {
+    #[error("Block production is not running")]
+    NotRunning,
+    #[error("Failed to close the pending block: {0}")]
+    CloseBlock(String),
+}
+
+/// Creates a new command channel for the block production task.
+///
+/// The receiving end is not tied to a specific [`crate::BlockProductionTask`]: it is handed to every new task with
+/// [`crate::BlockProductionTask::with_commands`], so that handles stay valid when the block production service is
+/// restarted.
+pub fn block_production_channel() -> (BlockProductionHandle, BlockProductionCommands) {
+    let (sender, receiver) = mpsc::channel(16);
+    let commands = BlockProductionCommands(Arc::new(Mutex::new(receiver)));
+    (BlockProductionHandle { sender, commands: commands.clone() }, commands)
+}
+
+/// Receiving end of the block production command channel. The running block production task holds the lock for as
+/// long as it runs.
+#[derive(Clone)]
+pub struct BlockProductionCommands(pub(crate) Arc<Mutex<mpsc::Receiver<BlockProductionCommand>>>);
+
+/// Sends commands to the running block production task.
+#[derive(Clone)]
+pub struct BlockProductionHandle {
+    sender: mpsc::Sender<BlockProductionCommand>,
+    commands: BlockProductionCommands,
+}
+
+impl BlockProductionHandle {
+    /// Closes the pending block now, even if it is empty, and returns its block number.
+    pub async fn close_block(&self) -> Result<u64, BlockProductionHandleError> {
+        // The lock is free when no block production task is currently running: the command would otherwise
+        // never be answered.
+        if self.commands.0.try_lock().is_ok() {
+            return Err(BlockProductionHandleError::NotRunning);
+        }
+
+        let (reply, receiver) = oneshot::channel();
+        self.sender
+            .send(BlockProductionCommand::CloseBlock { reply })
+            .await
+            .map_err(|_| BlockProductionHandleError::NotRunning)?;
+        receiver
+            .await
+            .map_err(|_| BlockProductionHandleError::NotRunning)?
+            .map_err(BlockProductionHandleError::CloseBlock)
+    }
+}
@@ -0,0 +1,277 @@
+//! Sequencer lease, used to run a hot-standby sequencer.
+//!
+//! Only one sequencer may produce blocks for a chain at a time. When several Madara sequencers are configured with the
+//! same lease file (on storage shared between them), the one holding the lease produces blocks and renews the lease
+//! periodically. The others stay in standby: they follow the chain through the L2 sync, and take over block production
+//! once the lease has expired.
+//!
+//! The lease also records the highest block number its holder may have closed. A standby only takes over once it has
+//! synced up to that block, so that it never produces a block conflicting with one already published by the previous
+//! sequencer.
+
+use mc_db::MadaraBackend;
+use mp_utils::service::ServiceContext;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Content of the lease file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    /// Identifier of the sequencer holding the lease.
+    pub holder: String,
+    /// Unix time in milliseconds at which the lease expires, unless renewed.
+    pub expires_at_ms: u64,
+    /// Highest block number the holder may have closed.
+    pub block_n: Option<u64>,
+}
+
+impl LeaseRecord {
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        now_ms >= self.expires_at_ms
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LeaseError {
+    #[error("Accessing lease file {path}: {err:#}")]
+    Io { path: PathBuf, err: io::Error },
+    #[error("Invalid lease file {path}: {err:#}")]
+    Format { path: PathBuf, err: serde_json::Error },
+    #[error("The sequencer lease is held by {holder} until unix time {expires_at_ms}ms")]
+    HeldByOther { holder: String, expires_at_ms: u64 },
+    #[error("The sequencer lease has expired before it could be renewed")]
+    Expired,
+}
+
+/// A sequencer lease backed by a file, see the [module documentation](self).
+///
+/// Writes are atomic (the file is replaced using a rename), but acquiring the lease is not a compare-and-swap: after
+/// writing, the file is read back to detect a concurrent acquisition. This is enough for an active/standby pair; larger
+/// setups should back the lease with a proper coordination service.
+pub struct FileLease {
+    path: PathBuf,
+    holder: String,
+    ttl: Duration,
+    /// When our lease expires, as of the last successful write.
+    expires_at_ms: Option<u64>,
+}
+
+impl FileLease {
+    pub fn new(path: impl Into<PathBuf>, holder: impl Into<String>, ttl: Duration) -> Self {
+        Self { path: path.into(), holder: holder.into(), ttl, expires_at_ms: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// How often the lease should be renewed by its holder.
+    pub fn renew_interval(&self) -> Duration {
+        self.ttl / 3
+    }
+
+    /// Reads the current lease record. Returns `None` when the lease has never been taken.
+    pub fn read(&self) -> Result<Option<LeaseRecord>, LeaseError> {
+        let content = match fs::read(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(LeaseError::Io { path: self.path.clone(), err }),
+        };
+        serde_json::from_slice(&content).map(Some).map_err(|err| LeaseError::Format { path: self.path.clone(), err })
+    }
+
+    /// Whether we hold the lease, as of our last successful write. A safety margin of a third of the lease duration is
+    /// kept, so that we stop producing blocks before a standby can take over.
+    pub fn is_held(&self) -> bool {
+        let margin = self.renew_interval().as_millis() as u64;
+        self.expires_at_ms.is_some_and(|expires_at_ms| now_ms() + margin < expires_at_ms)
+    }
+
+    /// Takes the lease if it is free, expired, or already ours, recording `block_n` as the highest block we may have
+    /// closed.
+    pub fn try_acquire(&mut self, block_n: Option<u64>) -> Result<(), LeaseError> {
+        let now = now_ms();
+        if let Some(record) = self.read()? {
+            if record.holder != self.holder && !record.is_expired(now) {
+                return Err(LeaseError::HeldByOther { holder: record.holder, expires_at_ms: record.expires_at_ms });
+            }
+        }
+
+        let record =
+            LeaseRecord { holder: self.holder.clone(), expires_at_ms: now + self.ttl.as_millis() as u64, block_n };
+        self.write(&record)?;
+
+        // Detect a concurrent acquisition.
+        match self.read()? {
+            Some(read_back) if read_back == record => {
+                self.expires_at_ms = Some(record.expires_at_ms);
+                Ok(())
+            }
+            Some(read_back) => {
+                self.expires_at_ms = None;
+                Err(LeaseError::HeldByOther { holder: read_back.holder, expires_at_ms: read_back.expires_at_ms })
+            }
+            None => Err(LeaseError::Io {
+                path: self.path.clone(),
+                err: io::Error::new(io::ErrorKind::NotFound, "lease file disappeared"),
+            }),
+        }
+    }
+
+    /// Extends the lease we hold. Fails if the lease expired in the meantime, even if nobody else took it: a standby
+    /// may be about to.
+    pub fn renew(&mut self, block_n: Option<u64>) -> Result<(), LeaseError> {
+        if !self.expires_at_ms.is_some_and(|expires_at_ms| now_ms() < expires_at_ms) {
+            self.expires_at_ms = None;
+            return Err(LeaseError::Expired);
+        }
+        self.try_acquire(block_n)
+    }
+
+    /// Gives up the lease, so that a standby can take over right away.
+    pub fn release(&mut self) -> Result<(), LeaseError> {
+        if self.expires_at_ms.take().is_none() {
+            return Ok(());
+        }
+        match self.read()? {
+            Some(record) if record.holder == self.holder => self.write(&LeaseRecord { expires_at_ms: 0, ..record }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Standby mode: waits until the lease can be taken over, then acquires it.
+    ///
+    /// The lease is taken over once it has expired and the local chain has caught up with the highest block the
+    /// previous holder may have closed. As that block may never have been published if the previous sequencer died
+    /// while closing it, we also take over when only that block is missing and `catch_up_timeout` has elapsed since the
+    /// lease expired.
+    ///
+    /// Returns `false` if the service was cancelled before the lease could be acquired.
+    pub async fn wait_for_takeover(
+        &mut self,
+        backend: &MadaraBackend,
+        ctx: &mut ServiceContext,
+        catch_up_timeout: Duration,
+    ) -> anyhow::Result<bool> {
+        let mut interval = tokio::time::interval(self.renew_interval());
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last_holder = None;
+
+        while ctx.run_until_cancelled(interval.tick()).await.is_some() {
+            let now = now_ms();
+            let record = self.read()?;
+            let latest_block_n = backend.get_latest_block_n()?;
+
+            if let Some(record) = &record {
+                if record.holder != self.holder && !record.is_expired(now) {
+                    if last_holder.as_ref() != Some(&record.holder) {
+                        tracing::info!("💤 Standing by: the sequencer lease is held by {}", record.holder);
+                        last_holder = Some(record.holder.clone());
+                    }
+                    continue;
+                }
+
+                let synced = match record.block_n {
+                    Some(block_n) if record.holder != self.holder => {
+                        let grace_elapsed = now >= record.expires_at_ms + catch_up_timeout.as_millis() as u64;
+                        let next_block_n = latest_block_n.map(|n| n + 1).unwrap_or(0);
+                        latest_block_n.is_some_and(|n| n >= block_n) || (grace_elapsed && next_block_n >= block_n)
+                    }
+                    _ => true,
+                };
+                if !synced {
+                    tracing::info!(
+                        "⏳ The sequencer lease of {} has expired, waiting to sync up to block #{} before taking over \
+                         (currently at {latest_block_n:?})",
+                        record.holder,
+                        record.block_n.unwrap_or_default(),
+                    );
+                    continue;
+                }
+            }
+
+            match self.try_acquire(latest_block_n) {
+                Ok(()) => {
+                    tracing::info!("👑 Acquired the sequencer lease at {}", self.path.display());
+                    return Ok(true);
+                }
+                Err(LeaseError::HeldByOther { holder, .. }) => {
+                    tracing::warn!("Sequencer {holder} acquired the lease concurrently, standing by");
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn write(&self, record: &LeaseRecord) -> Result<(), LeaseError> {
+        let to_io_err = |err| LeaseError::Io { path: self.path.clone(), err };
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(format!(".{}.tmp", std::process::id()));
+
+        let content = serde_json::to_vec(record).map_err(|err| LeaseError::Format { path: self.path.clone(), err })?;
+        let mut file = fs::File::create(&tmp_path).map_err(to_io_err)?;
+        file.write_all(&content).map_err(to_io_err)?;
+        file.sync_all().map_err(to_io_err)?;
+        fs::rename(&tmp_path, &self.path).map_err(to_io_err)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("Current time is before the unix epoch").as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_lease_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lease.json");
+        let mut active = FileLease::new(&path, "active", Duration::from_secs(60));
+        let mut standby = FileLease::new(&path, "standby", Duration::from_secs(60));
+
+        assert_eq!(active.read().unwrap(), None);
+        active.try_acquire(Some(4)).unwrap();
+        assert!(active.is_held());
+        assert_eq!(active.read().unwrap().unwrap().block_n, Some(4));
+
+        assert!(matches!(standby.try_acquire(None), Err(LeaseError::HeldByOther { holder, .. }) if holder == "active"));
+        assert!(!standby.is_held());
+
+        active.renew(Some(5)).unwrap();
+        assert_eq!(standby.read().unwrap().unwrap().block_n, Some(5));
+
+        active.release().unwrap();
+        assert!(!active.is_held());
+        standby.try_acquire(Some(5)).unwrap();
+        assert!(standby.is_held());
+        assert!(matches!(active.renew(Some(6)), Err(LeaseError::Expired)));
+    }
+
+    #[rstest]
+    fn test_lease_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lease.json");
+        let mut active = FileLease::new(&path, "active", Duration::from_millis(30));
+        let mut standby = FileLease::new(&path, "standby", Duration::from_secs(60));
+
+        active.try_acquire(None).unwrap();
+        std::thread::sleep(Duration::from_millis(40));
+
+        assert!(!active.is_held());
+        standby.try_acquire(None).unwrap();
+        assert!(matches!(active.renew(None), Err(LeaseError::Expired)));
+        assert_eq!(standby.read().unwrap().unwrap().holder, "standby");
+    }
+}
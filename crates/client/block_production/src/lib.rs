@@ -16,7 +16,10 @@
 //! L1-L2 testing is a bit harder to setup, but we should definitely make the testing more comprehensive here.
 
 use crate::close_block::close_block;
+use crate::handle::BlockProductionCommand;
+use crate::lease::{FileLease, LeaseError};
 use crate::metrics::BlockProductionMetrics;
+use blockifier::blockifier::config::{ConcurrencyConfig, TransactionExecutorConfig};
 use blockifier::blockifier::transaction_executor::{TransactionExecutor, BLOCK_STATE_ACCESS_ERR};
 use blockifier::bouncer::BouncerWeights;
 use blockifier::transaction::errors::TransactionExecutionError;
@@ -28,6 +31,7 @@ use mc_exec::{BlockifierStateAdapter, ExecutionContext};
 use mc_mempool::header::make_pending_header;
 use mc_mempool::{L1DataProvider, MempoolProvider};
 use mp_block::{BlockId, BlockTag, MadaraPendingBlock, VisitedSegments};
+use mp_chain_config::BlockProductionMode;
 use mp_class::compile::ClassCompilationError;
 use mp_class::ConvertedClass;
 use mp_convert::ToFelt;
@@ -42,12 +46,17 @@ use std::collections::VecDeque;
 use std::mem;
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::mpsc;
 
 mod close_block;
 mod finalize_execution_state;
+mod handle;
+pub mod lease;
 pub mod metrics;
 mod re_add_finalized_to_blockifier;
 
+pub use handle::*;
+
 #[derive(Default, Clone)]
 struct ContinueBlockStats {
     /// Number of batches executed before reaching the bouncer capacity.
@@ -76,6 +85,8 @@ pub enum Error {
     PendingClassCompilationError(#[from] ClassCompilationError),
     #[error("State diff error when continuing the pending block: {0:#}")]
     PendingStateDiff(#[from] StateDiffToStateMapError),
+    #[error("Sequencer lease error: {0:#}")]
+    Lease(#[from] LeaseError),
 }
 
 /// Result of a block continuation operation, containing the updated state and execution statistics.
@@ -113,8 +124,12 @@ pub struct BlockProductionTask<Mempool: MempoolProvider> {
     block: MadaraPendingBlock,
     declared_classes: Vec<ConvertedClass>,
     pub(crate) executor: TransactionExecutor<BlockifierStateAdapter>,
+    executor_config: TransactionExecutorConfig,
     l1_data_provider: Arc<dyn L1DataProvider>,
     current_pending_tick: usize,
+    mode: BlockProductionMode,
+    commands: Option<BlockProductionCommands>,
+    lease: Option<FileLease>,
     metrics: Arc<BlockProductionMetrics>,
 }
 
@@ -164,9 +179,11 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
         let parent_block_hash = backend
             .get_block_hash(&BlockId::Tag(BlockTag::Latest))?
             .unwrap_or(/* genesis block's parent hash */ Felt::ZERO);
+        let block_n = backend.get_latest_block_n()?.map(|n| n + 1).unwrap_or(/* genesis */ 0);
 
         let pending_block = MadaraPendingBlock::new_empty(make_pending_header(
             parent_block_hash,
+            block_n,
             backend.chain_config(),
             l1_data_provider.as_ref(),
         ));
@@ -175,11 +192,15 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
             ExecutionContext::new_in_block(Arc::clone(&backend), &pending_block.info.clone().into())?.tx_executor();
 
         Ok(Self {
+            mode: backend.chain_config().block_production_mode,
             importer,
             backend,
             mempool,
             executor,
+            executor_config: TransactionExecutorConfig::default(),
             current_pending_tick: 0,
+            commands: None,
+            lease: None,
             block: pending_block,
             declared_classes: Default::default(),
             l1_data_provider,
@@ -187,6 +208,33 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
         })
     }
 
+    /// Execute the transactions of each batch on `n_workers` threads, using blockifier's optimistic concurrency
+    /// control: transactions that read state written by an earlier transaction of the batch are re-executed, and
+    /// results are committed in order, so that the state diff and receipts are the same as with sequential
+    /// execution. A single worker means sequential execution.
+    pub fn with_execution_workers(mut self, n_workers: usize) -> Self {
+        let concurrency_config = ConcurrencyConfig {
+            enabled: n_workers > 1,
+            n_workers,
+            chunk_size: self.backend.chain_config().execution_batch_size.max(1),
+        };
+        self.executor_config = TransactionExecutorConfig { concurrency_config };
+        self.executor.config = self.executor_config.clone();
+        self
+    }
+
+    /// Only produce blocks while holding this lease, renewing it periodically. See [`lease`].
+    pub fn with_lease(mut self, lease: FileLease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    /// Listen for commands sent through the [`BlockProductionHandle`]s of this channel, such as manual block closing.
+    pub fn with_commands(mut self, commands: BlockProductionCommands) -> Self {
+        self.commands = Some(commands);
+        self
+    }
+
     #[tracing::instrument(skip(self), fields(module = "BlockProductionTask"))]
     fn continue_block(&mut self, bouncer_cap: BouncerWeights) -> Result<ContinueBlockResult, Error> {
         let mut stats = ContinueBlockStats::default();
@@ -306,15 +354,27 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
         start_time: Instant,
     ) -> Result<(), Error> {
         let block_n = self.block_n();
+        if let Some(lease) = &mut self.lease {
+            // Record the block before closing it: a standby must not take over until it has synced it.
+            lease.renew(Some(block_n))?;
+        }
         // Convert the pending block to a closed block and save to db
         let parent_block_hash = Felt::ZERO; // temp parent block hash
         let new_empty_block = MadaraPendingBlock::new_empty(make_pending_header(
             parent_block_hash,
+            block_n + 1,
             self.backend.chain_config(),
             self.l1_data_provider.as_ref(),
         ));
 
         let block_to_close = mem::replace(&mut self.block, new_empty_block);
+        if block_to_close.info.header.protocol_version != self.block.info.header.protocol_version {
+            tracing::info!(
+                "⏫ Scheduled protocol upgrade: block #{} will use protocol version {}",
+                block_n + 1,
+                self.block.info.header.protocol_version
+            );
+        }
         let declared_classes = mem::take(&mut self.declared_classes);
 
         let n_txs = block_to_close.inner.transactions.len();
@@ -338,8 +398,8 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
         self.block.info.header.parent_block_hash = import_result.block_hash;
 
         // Prepare executor for next block
-        self.executor =
-            ExecutionContext::new_in_block(Arc::clone(&self.backend), &self.block.info.clone().into())?.tx_executor();
+        self.executor = ExecutionContext::new_in_block(Arc::clone(&self.backend), &self.block.info.clone().into())?
+            .tx_executor_with_config(self.executor_config.clone());
         self.current_pending_tick = 0;
 
         let end_time = start_time.elapsed();
@@ -407,6 +467,23 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
     /// This creates a block, continuing the current pending block state up to the full bouncer limit.
     #[tracing::instrument(skip(self), fields(module = "BlockProductionTask"))]
     pub(crate) async fn on_block_time(&mut self) -> Result<(), Error> {
+        self.close_pending_block(self.mode.closes_empty_blocks()).await.map(|_| ())
+    }
+
+    /// Instant mining: the transactions waiting in the mempool are executed and sealed into a new block right away.
+    /// This is repeated until the mempool is drained, as a single block may not fit all of them.
+    #[tracing::instrument(skip(self), fields(module = "BlockProductionTask"))]
+    pub async fn on_transactions_received(&mut self) -> Result<(), Error> {
+        while self.close_pending_block(/* close_empty */ false).await?.is_some() {}
+        Ok(())
+    }
+
+    /// Continues the current pending block state up to the full bouncer limit, and closes it. When `close_empty` is
+    /// false and the block has no transaction, it is not closed: the pending block is refreshed instead.
+    ///
+    /// Returns the number of the closed block, if a block was closed.
+    #[tracing::instrument(skip(self), fields(module = "BlockProductionTask"))]
+    pub async fn close_pending_block(&mut self, close_empty: bool) -> Result<Option<u64>, Error> {
         let block_n = self.block_n();
         tracing::debug!("closing block #{}", block_n);
 
@@ -420,6 +497,12 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
             block_now_full: _block_now_full,
         } = self.continue_block(self.backend.chain_config().bouncer_config.block_max_capacity)?;
 
+        if !close_empty && self.block.inner.transactions.is_empty() {
+            tracing::debug!("not closing empty block #{}", block_n);
+            self.refresh_pending_block()?;
+            return Ok(None);
+        }
+
         // SNOS requirement: For blocks >= 10, the hash of the block 10 blocks prior
         // at address 0x1 with the block number as the key
         if block_n >= 10 {
@@ -442,7 +525,55 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
             });
         }
 
-        self.close_and_prepare_next_block(new_state_diff, visited_segments, start_time).await
+        self.close_and_prepare_next_block(new_state_diff, visited_segments, start_time).await?;
+        Ok(Some(block_n))
+    }
+
+    /// Replaces the empty pending block with a new one on top of the same parent block, so that it gets an up-to-date
+    /// timestamp and gas prices.
+    fn refresh_pending_block(&mut self) -> Result<(), Error> {
+        self.block = MadaraPendingBlock::new_empty(make_pending_header(
+            self.block.info.header.parent_block_hash,
+            self.block_n(),
+            self.backend.chain_config(),
+            self.l1_data_provider.as_ref(),
+        ));
+        self.executor = ExecutionContext::new_in_block(Arc::clone(&self.backend), &self.block.info.clone().into())?
+            .tx_executor_with_config(self.executor_config.clone());
+        self.current_pending_tick = 0;
+        Ok(())
+    }
+
+    async fn on_command(&mut self, command: BlockProductionCommand) {
+        match command {
+            BlockProductionCommand::CloseBlock { reply } => {
+                tracing::debug!("received a request to close the pending block");
+                let res = match self.close_pending_block(/* close_empty */ true).await {
+                    Ok(block_n) => block_n.ok_or_else(|| "No block was closed".to_string()),
+                    Err(err) => {
+                        let message = format!("{err:#}");
+                        self.on_block_production_error(err);
+                        Err(message)
+                    }
+                };
+                // The caller may have gone away.
+                let _ = reply.send(res);
+            }
+        }
+    }
+
+    fn on_block_production_error(&self, err: Error) {
+        tracing::error!("Block production task has errored: {err:#}");
+        // Clear pending block. The reason we do this is because
+        // if the error happened because the closed block is
+        // invalid or has not been saved properly, we want to
+        // avoid redoing the same error in the next block. So we
+        // drop all the transactions in the pending block just
+        // in case. If the problem happened after the block was
+        // closed and saved to the db, this will do nothing.
+        if let Err(err) = self.backend.clear_pending_block() {
+            tracing::error!("Error while clearing the pending block in recovery of block production error: {err:#}");
+        }
     }
 
     #[tracing::instrument(skip(self, ctx), fields(module = "BlockProductionTask"))]
@@ -458,31 +589,51 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
 
         self.backend.chain_config().precheck_block_production()?; // check chain config for invalid config
 
-        tracing::info!("⛏️  Starting block production at block #{}", self.block_n());
+        tracing::info!("⛏️  Starting block production at block #{} ({:?} mode)", self.block_n(), self.mode);
+
+        let tx_received = self.mempool.tx_received();
+        if self.mode == BlockProductionMode::Instant {
+            // Pick up the transactions that were already in the mempool.
+            tx_received.notify_one();
+        }
+
+        let mut interval_lease_renewal = tokio::time::interval(
+            self.lease.as_ref().map(FileLease::renew_interval).unwrap_or(self.backend.chain_config().block_time),
+        );
+        interval_lease_renewal.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let commands = self.commands.take();
+        let mut commands = match &commands {
+            Some(commands) => Some(commands.0.lock().await),
+            None => None,
+        };
 
         loop {
+            if self.lease.as_ref().is_some_and(|lease| !lease.is_held()) {
+                // A standby may take over at any moment: stop here to avoid producing conflicting blocks.
+                anyhow::bail!("Lost the sequencer lease, stopping block production");
+            }
+
             tokio::select! {
-                instant = interval_block_time.tick() => {
+                _ = interval_lease_renewal.tick(), if self.lease.is_some() => {
+                    let latest_block_n = self.block_n().checked_sub(1);
+                    if let Some(Err(err)) = self.lease.as_mut().map(|lease| lease.renew(latest_block_n)) {
+                        tracing::error!("Failed to renew the sequencer lease: {err:#}");
+                    }
+                },
+                instant = interval_block_time.tick(), if self.mode.closes_on_block_time() => {
                     if let Err(err) = self.on_block_time().await {
-                        tracing::error!("Block production task has errored: {err:#}");
-                        // Clear pending block. The reason we do this is because
-                        // if the error happened because the closed block is
-                        // invalid or has not been saved properly, we want to
-                        // avoid redoing the same error in the next block. So we
-                        // drop all the transactions in the pending block just
-                        // in case. If the problem happened after the block was
-                        // closed and saved to the db, this will do nothing.
-                        if let Err(err) = self.backend.clear_pending_block() {
-                            tracing::error!("Error while clearing the pending block in recovery of block production error: {err:#}");
-                        }
+                        self.on_block_production_error(err);
                     }
                     // ensure the pending block tick and block time match up
                     interval_pending_block_update.reset_at(instant + interval_pending_block_update.period());
                 },
-                instant = interval_pending_block_update.tick() => {
+                instant = interval_pending_block_update.tick(), if self.mode.updates_pending_block() => {
                     let n_pending_ticks_per_block = self.backend.chain_config().n_pending_ticks_per_block();
 
-                    if self.current_pending_tick == 0 || self.current_pending_tick >= n_pending_ticks_per_block {
+                    if self.current_pending_tick == 0
+                        || (self.mode.closes_on_block_time() && self.current_pending_tick >= n_pending_ticks_per_block)
+                    {
                         // First tick is ignored. Out of range ticks are also
                         // ignored.
                         self.current_pending_tick += 1;
@@ -504,10 +655,29 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
                         }
                     }
                 },
+                _ = tx_received.notified(), if self.mode == BlockProductionMode::Instant => {
+                    if let Err(err) = self.on_transactions_received().await {
+                        self.on_block_production_error(err);
+                    }
+                },
+                Some(command) = recv_command(commands.as_deref_mut()) => {
+                    self.on_command(command).await;
+                    // the next block gets a full block time
+                    let now = tokio::time::Instant::now();
+                    interval_block_time.reset_at(now + interval_block_time.period());
+                    interval_pending_block_update.reset_at(now + interval_pending_block_update.period());
+                },
                 _ = ctx.cancelled() => break,
             }
         }
 
+        if let Some(lease) = &mut self.lease {
+            // Let a standby take over right away.
+            if let Err(err) = lease.release() {
+                tracing::error!("Failed to release the sequencer lease: {err:#}");
+            }
+        }
+
         Ok(())
     }
 
@@ -516,6 +686,13 @@ impl<Mempool: MempoolProvider> BlockProductionTask<Mempool> {
     }
 }
 
+async fn recv_command(commands: Option<&mut mpsc::Receiver<BlockProductionCommand>>) -> Option<BlockProductionCommand> {
+    match commands {
+        Some(commands) => commands.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{collections::HashMap, sync::Arc};
@@ -6,6 +6,8 @@ pub struct BlockProductionMetrics {
     pub block_gauge: Gauge<u64>,
     pub block_counter: Counter<u64>,
     pub transaction_counter: Counter<u64>,
+    /// Mempool transactions waiting to be replicated, per standby sequencer.
+    pub replication_lag: Gauge<u64>,
 }
 
 impl BlockProductionMetrics {
@@ -38,6 +40,14 @@ impl BlockProductionMetrics {
             "transaction".to_string(),
         );
 
-        Self { block_gauge, block_counter, transaction_counter }
+        let replication_lag = register_gauge_metric_instrument(
+            &mempool_meter,
+            "mempool_replication_lag".to_string(),
+            "A gauge to show the number of mempool transactions waiting to be replicated to a standby sequencer"
+                .to_string(),
+            "transaction".to_string(),
+        );
+
+        Self { block_gauge, block_counter, transaction_counter, replication_lag }
     }
 }
@@ -12,6 +12,7 @@ use mp_state_update::StateDiff;
 use rocksdb::WriteOptions;
 use starknet_api::core::ChainId;
 use starknet_types_core::felt::Felt;
+use std::sync::Arc;
 
 type Result<T, E = MadaraStorageError> = std::result::Result<T, E>;
 
@@ -250,6 +251,12 @@ impl MadaraBackend {
         let mut writeopts = WriteOptions::new();
         writeopts.disable_wal(true);
         self.db.write_opt(tx, &writeopts)?;
+
+        if self.sender_pending_block.receiver_count() > 0 {
+            if let Err(e) = self.sender_pending_block.send(Arc::new(block.clone())) {
+                tracing::debug!("Failed to send pending block to subscribers: {e}");
+            }
+        }
         Ok(())
     }
 
@@ -274,6 +281,12 @@ impl MadaraBackend {
         let mut writeopts = WriteOptions::default(); // todo move that in db
         writeopts.disable_wal(true);
         self.db.put_cf_opt(&col, ROW_L1_LAST_CONFIRMED_BLOCK, bincode::serialize(&l1_last)?, &writeopts)?;
+
+        if self.sender_l1_last_confirmed_block.receiver_count() > 0 {
+            if let Err(e) = self.sender_l1_last_confirmed_block.send(l1_last) {
+                tracing::debug!("Failed to send l1 last confirmed block to subscribers: {e}");
+            }
+        }
         Ok(())
     }
 
@@ -282,9 +295,30 @@ impl MadaraBackend {
         self.write_last_confirmed_block(0)
     }
 
+    /// Marks an already stored block as stored without its transactions and receipts. Outside of tests, use
+    /// [`Self::store_block_without_body`] which marks the block in the same write.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn mark_block_without_body(&self, block_n: u64) -> Result<()> {
+        let col = self.db.get_column(Column::BlockNWithoutBody);
+        self.db.put_cf(&col, bincode::serialize(&block_n)?, b"")?;
+        Ok(())
+    }
+
+    /// Whether the transactions and receipts of a stored block are available. See [`Self::store_block_without_body`].
+    #[tracing::instrument(skip(self), fields(module = "BlockDB"))]
+    pub fn has_block_body(&self, block_n: u64) -> Result<bool> {
+        let col = self.db.get_column(Column::BlockNWithoutBody);
+        Ok(self.db.get_pinned_cf(&col, bincode::serialize(&block_n)?)?.is_none())
+    }
+
     /// Also clears pending block
     #[tracing::instrument(skip(self), fields(module = "BlockDB"))]
-    pub(crate) fn block_db_store_block(&self, block: &MadaraBlock, state_diff: &StateDiff) -> Result<()> {
+    pub(crate) fn block_db_store_block(
+        &self,
+        block: &MadaraBlock,
+        state_diff: &StateDiff,
+        has_body: bool,
+    ) -> Result<()> {
         let mut tx = WriteBatchWithTransaction::default();
 
         let tx_hash_to_block_n = self.db.get_column(Column::TxHashToBlockN);
@@ -305,14 +339,12 @@ impl MadaraBackend {
         tx.put_cf(&block_hash_to_block_n, block_hash_encoded, &block_n_encoded);
         tx.put_cf(&block_n_to_block_inner, &block_n_encoded, bincode::serialize(&block.inner)?);
         tx.put_cf(&block_n_to_state_diff, &block_n_encoded, bincode::serialize(state_diff)?);
-        tx.put_cf(&meta, ROW_SYNC_TIP, block_n_encoded);
-
-        // susbcribers
-        if self.sender_block_info.receiver_count() > 0 {
-            if let Err(e) = self.sender_block_info.send(block.info.clone()) {
-                tracing::debug!("Failed to send block info to subscribers: {e}");
-            }
+        if !has_body {
+            tx.put_cf(&self.db.get_column(Column::BlockNWithoutBody), &block_n_encoded, b"");
         }
+        tx.put_cf(&meta, ROW_SYNC_TIP, block_n_encoded);
+        self.msg_to_l1_db_index_block(&mut tx, block)?;
+        self.trace_db_clear_block(&mut tx, block.info.header.block_number);
 
         // clear pending
         tx.delete_cf(&meta, ROW_PENDING_INFO);
@@ -320,8 +352,17 @@ impl MadaraBackend {
         tx.delete_cf(&meta, ROW_PENDING_STATE_UPDATE);
 
         let mut writeopts = WriteOptions::new();
-        writeopts.disable_wal(true);
+        // The body of a block reconstructed from the data availability cannot be recovered after a crash: make sure its
+        // marker is never lost.
+        writeopts.disable_wal(has_body);
         self.db.write_opt(tx, &writeopts)?;
+
+        // susbcribers, notified once the block can be read back
+        if self.sender_block_info.receiver_count() > 0 {
+            if let Err(e) = self.sender_block_info.send(block.info.clone()) {
+                tracing::debug!("Failed to send block info to subscribers: {e}");
+            }
+        }
         Ok(())
     }
 
@@ -400,6 +441,18 @@ impl MadaraBackend {
         self.sender_block_info.subscribe()
     }
 
+    /// Notified with the pending block every time it is stored.
+    #[tracing::instrument(skip(self), fields(module = "BlockDB"))]
+    pub fn subscribe_pending_block(&self) -> tokio::sync::broadcast::Receiver<Arc<MadaraPendingBlock>> {
+        self.sender_pending_block.subscribe()
+    }
+
+    /// Notified every time the last block confirmed on L1 is updated.
+    #[tracing::instrument(skip(self), fields(module = "BlockDB"))]
+    pub fn subscribe_l1_last_confirmed_block(&self) -> tokio::sync::broadcast::Receiver<u64> {
+        self.sender_l1_last_confirmed_block.subscribe()
+    }
+
     #[tracing::instrument(skip(self, id), fields(module = "BlockDB"))]
     pub fn get_block_inner(&self, id: &impl DbBlockIdResolvable) -> Result<Option<MadaraBlockInner>> {
         let Some(ty) = id.resolve_db_block_id(self)? else { return Ok(None) };
@@ -5,6 +5,52 @@ use opentelemetry::global::Error;
 use opentelemetry::metrics::Gauge;
 use opentelemetry::{global, KeyValue};
 use rocksdb::perf::MemoryUsageBuilder;
+use serde::Serialize;
+
+/// Storage size of a column, in bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ColumnStats {
+    pub column: String,
+    pub size: u64,
+}
+
+/// Storage and memory usage of the database, in bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DbStats {
+    pub db_size: u64,
+    pub columns: Vec<ColumnStats>,
+    pub mem_table_total: u64,
+    pub mem_table_unflushed: u64,
+    pub mem_table_readers_total: u64,
+    pub cache_total: u64,
+}
+
+impl DbStats {
+    pub fn collect(db: &DB) -> anyhow::Result<Self> {
+        let columns: Vec<_> = Column::ALL
+            .iter()
+            .map(|&column| {
+                let cf_handle = db.get_column(column);
+                let size = db.get_column_family_metadata_cf(&cf_handle).size;
+                ColumnStats { column: column.rocksdb_name().to_string(), size }
+            })
+            .collect();
+
+        let mut builder = MemoryUsageBuilder::new().context("Creating memory usage builder")?;
+        builder.add_db(db);
+        let mem_usage = builder.build().context("Getting memory usage")?;
+
+        Ok(Self {
+            db_size: columns.iter().map(|col| col.size).sum(),
+            columns,
+            mem_table_total: mem_usage.approximate_mem_table_total(),
+            mem_table_unflushed: mem_usage.approximate_mem_table_unflushed(),
+            mem_table_readers_total: mem_usage.approximate_mem_table_readers_total(),
+            cache_total: mem_usage.approximate_cache_total(),
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DbMetrics {
     pub db_size: Gauge<u64>,
@@ -73,28 +119,19 @@ impl DbMetrics {
     }
 
     pub fn try_update(&self, db: &DB) -> anyhow::Result<u64> {
-        let mut storage_size = 0;
-
-        for &column in Column::ALL.iter() {
-            let cf_handle = db.get_column(column);
-            let cf_metadata = db.get_column_family_metadata_cf(&cf_handle);
-            let column_size = cf_metadata.size;
-            storage_size += column_size;
+        let stats = DbStats::collect(db)?;
 
-            self.column_sizes.record(column_size, &[KeyValue::new("column", column.rocksdb_name())]);
+        for ColumnStats { column, size } in stats.columns {
+            self.column_sizes.record(size, &[KeyValue::new("column", column)]);
         }
 
-        self.db_size.record(storage_size, &[]);
-
-        let mut builder = MemoryUsageBuilder::new().context("Creating memory usage builder")?;
-        builder.add_db(db);
-        let mem_usage = builder.build().context("Getting memory usage")?;
-        self.mem_table_total.record(mem_usage.approximate_mem_table_total(), &[]);
-        self.mem_table_unflushed.record(mem_usage.approximate_mem_table_unflushed(), &[]);
-        self.mem_table_readers_total.record(mem_usage.approximate_mem_table_readers_total(), &[]);
-        self.cache_total.record(mem_usage.approximate_cache_total(), &[]);
+        self.db_size.record(stats.db_size, &[]);
+        self.mem_table_total.record(stats.mem_table_total, &[]);
+        self.mem_table_unflushed.record(stats.mem_table_unflushed, &[]);
+        self.mem_table_readers_total.record(stats.mem_table_readers_total, &[]);
+        self.cache_total.record(stats.cache_total, &[]);
 
-        Ok(storage_size)
+        Ok(stats.db_size)
     }
 
     /// Returns the total storage size
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use starknet_types_core::felt::Felt;
 
 pub const DEVNET_KEYS: &[u8] = b"DEVNET_KEYS";
+pub const DEVNET_NEXT_L1_MESSAGE_NONCE: &[u8] = b"DEVNET_NEXT_L1_MESSAGE_NONCE";
+pub const DEVNET_MESSAGES_TO_L1_FLUSHED: &[u8] = b"DEVNET_MESSAGES_TO_L1_FLUSHED";
 
 type Result<T, E = MadaraStorageError> = std::result::Result<T, E>;
 
@@ -40,4 +42,38 @@ impl MadaraBackend {
         self.db.put_cf_opt(&nonce_column, DEVNET_KEYS, bincode::serialize(&devnet_keys)?, &writeopts)?;
         Ok(())
     }
+
+    /// Get the nonce given to the next message posted to L2 on the devnet, when none is supplied.
+    #[tracing::instrument(skip(self), fields(module = "DevnetDB"))]
+    pub fn get_devnet_next_l1_message_nonce(&self) -> Result<u64> {
+        self.get_devnet_u64(DEVNET_NEXT_L1_MESSAGE_NONCE)
+    }
+
+    #[tracing::instrument(skip(self), fields(module = "DevnetDB"))]
+    pub fn set_devnet_next_l1_message_nonce(&self, nonce: u64) -> Result<()> {
+        self.set_devnet_u64(DEVNET_NEXT_L1_MESSAGE_NONCE, nonce)
+    }
+
+    /// Get the first block whose L2→L1 messages have not been flushed to the (simulated) L1 of the devnet.
+    #[tracing::instrument(skip(self), fields(module = "DevnetDB"))]
+    pub fn get_devnet_messages_to_l1_flushed(&self) -> Result<u64> {
+        self.get_devnet_u64(DEVNET_MESSAGES_TO_L1_FLUSHED)
+    }
+
+    #[tracing::instrument(skip(self), fields(module = "DevnetDB"))]
+    pub fn set_devnet_messages_to_l1_flushed(&self, block_n: u64) -> Result<()> {
+        self.set_devnet_u64(DEVNET_MESSAGES_TO_L1_FLUSHED, block_n)
+    }
+
+    fn get_devnet_u64(&self, key: &[u8]) -> Result<u64> {
+        let col = self.db.get_column(Column::Devnet);
+        let Some(res) = self.db.get_cf(&col, key)? else { return Ok(0) };
+        Ok(bincode::deserialize(&res)?)
+    }
+
+    fn set_devnet_u64(&self, key: &[u8], value: u64) -> Result<()> {
+        let col = self.db.get_column(Column::Devnet);
+        self.db.put_cf(&col, key, bincode::serialize(&value)?)?;
+        Ok(())
+    }
 }
@@ -1,6 +1,8 @@
-use rocksdb::WriteOptions;
+use primitive_types::H256;
+use rocksdb::{IteratorMode, WriteOptions};
 use serde::{Deserialize, Serialize};
 use starknet_api::core::Nonce;
+use starknet_types_core::felt::Felt;
 
 use crate::error::DbError;
 use crate::{Column, DatabaseExt, MadaraBackend, MadaraStorageError};
@@ -23,6 +25,19 @@ impl LastSyncedEventBlock {
     }
 }
 
+/// An L1 to L2 message which was not turned into an L1 handler transaction. It is never retried.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedL1Message {
+    pub l1_block_number: Option<u64>,
+    pub l1_tx_hash: Option<H256>,
+    pub from_address: Felt,
+    pub to_address: Felt,
+    pub selector: Felt,
+    /// `None` when the settlement layer has no messaging fees.
+    pub paid_fee_on_l1: Option<u128>,
+    pub reason: String,
+}
+
 /// We add method in MadaraBackend to be able to handle L1->L2 messaging related data
 impl MadaraBackend {
     /// Retrieves the last stored L1 block data that contains a message from the database.
@@ -128,4 +143,56 @@ impl MadaraBackend {
         self.db.put_cf_opt(&nonce_column, bincode::serialize(&nonce)?, /* empty value */ [], &writeopts)?;
         Ok(())
     }
+
+    /// Record that the L1 transaction `l1_tx_hash` sent a message to L2, which became the L1 handler transaction
+    /// `l1_handler_tx_hash`. An L1 transaction can send several messages.
+    #[tracing::instrument(skip(self), fields(module = "L1DB"))]
+    pub fn add_l1_handler_tx_hash_for_l1_tx(&self, l1_tx_hash: &H256, l1_handler_tx_hash: Felt) -> Result<()> {
+        let col = self.db.get_column(Column::L1MessagingTxHashes);
+        let mut tx_hashes = self.get_l1_handler_tx_hashes(l1_tx_hash)?.unwrap_or_default();
+        if tx_hashes.contains(&l1_handler_tx_hash) {
+            return Ok(());
+        }
+        tx_hashes.push(l1_handler_tx_hash);
+        self.db.put_cf(&col, l1_tx_hash.as_bytes(), bincode::serialize(&tx_hashes)?)?;
+        Ok(())
+    }
+
+    /// The L1 handler transactions created from the messages sent by the L1 transaction `l1_tx_hash`, in the order
+    /// the messages were sent.
+    #[tracing::instrument(skip(self), fields(module = "L1DB"))]
+    pub fn get_l1_handler_tx_hashes(&self, l1_tx_hash: &H256) -> Result<Option<Vec<Felt>>> {
+        let col = self.db.get_column(Column::L1MessagingTxHashes);
+        let Some(res) = self.db.get_cf(&col, l1_tx_hash.as_bytes())? else { return Ok(None) };
+        Ok(Some(bincode::deserialize(&res)?))
+    }
+
+    /// Record a message rejected by the L1 handler policy. The nonce of the message should also be marked as
+    /// processed with [`MadaraBackend::set_l1_messaging_nonce`], so that it is never retried.
+    #[tracing::instrument(skip(self, nonce, message), fields(module = "L1DB"))]
+    pub fn add_rejected_l1_message(&self, nonce: Nonce, message: &RejectedL1Message) -> Result<()> {
+        let col = self.db.get_column(Column::L1MessagingRejected);
+        self.db.put_cf(&col, bincode::serialize(&nonce)?, bincode::serialize(message)?)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, nonce), fields(module = "L1DB"))]
+    pub fn get_rejected_l1_message(&self, nonce: Nonce) -> Result<Option<RejectedL1Message>> {
+        let col = self.db.get_column(Column::L1MessagingRejected);
+        let Some(res) = self.db.get_cf(&col, bincode::serialize(&nonce)?)? else { return Ok(None) };
+        Ok(Some(bincode::deserialize(&res)?))
+    }
+
+    /// All the messages rejected by the L1 handler policy.
+    #[tracing::instrument(skip(self), fields(module = "L1DB"))]
+    pub fn get_rejected_l1_messages(&self) -> Result<Vec<(Nonce, RejectedL1Message)>> {
+        let col = self.db.get_column(Column::L1MessagingRejected);
+        self.db
+            .iterator_cf(&col, IteratorMode::Start)
+            .map(|kv| {
+                let (key, value) = kv?;
+                Ok((bincode::deserialize(&key)?, bincode::deserialize(&value)?))
+            })
+            .collect()
+    }
 }
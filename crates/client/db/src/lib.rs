@@ -4,7 +4,7 @@ use anyhow::Context;
 use block_db::get_latest_block_n;
 use bonsai_db::{BonsaiDb, DatabaseKeyMapping};
 use bonsai_trie::{BonsaiStorage, BonsaiStorageConfig};
-use db_metrics::DbMetrics;
+use db_metrics::{DbMetrics, DbStats};
 use mp_chain_config::ChainConfig;
 use mp_utils::service::{MadaraServiceId, PowerOfTwo, Service, ServiceId};
 use rocksdb::backup::{BackupEngine, BackupEngineOptions};
@@ -12,6 +12,7 @@ use rocksdb::{
     BoundColumnFamily, ColumnFamilyDescriptor, DBWithThreadMode, Env, FlushOptions, MultiThreaded, WriteOptions,
 };
 use rocksdb_options::rocksdb_global_options;
+use serde::Serialize;
 use snapshots::Snapshots;
 use starknet_types_core::hash::{Pedersen, Poseidon, StarkHash};
 use std::path::{Path, PathBuf};
@@ -33,8 +34,11 @@ pub mod db_metrics;
 pub mod devnet_db;
 pub mod l1_db;
 pub mod mempool_db;
+pub mod msg_to_l1_db;
+pub mod settlement_db;
 pub mod storage_updates;
 pub mod tests;
+pub mod trace_db;
 
 pub use bonsai_db::GlobalTrie;
 pub use bonsai_trie::{id::BasicId, MultiProof, ProofNode};
@@ -84,9 +88,17 @@ fn spawn_backup_db_task(
 
     db_restored_cb.send(()).ok().context("Receiver dropped")?;
 
-    while let Some(BackupRequest { callback, db }) = recv.blocking_recv() {
-        engine.create_new_backup_flush(&db, true).context("Creating rocksdb backup")?;
-        let _ = callback.send(());
+    while let Some(request) = recv.blocking_recv() {
+        match request {
+            BackupRequest::Create { callback, db } => {
+                let res = engine.create_new_backup_flush(&db, true).context("Creating rocksdb backup");
+                let _ = callback.send(res);
+            }
+            BackupRequest::List { callback } => {
+                let backups = engine.get_backup_info().into_iter().map(BackupInfo::from).collect();
+                let _ = callback.send(backups);
+            }
+        }
     }
 
     Ok(())
@@ -105,6 +117,8 @@ pub enum Column {
     BlockHashToBlockN,
     /// One To One
     BlockNToStateDiff,
+    /// block_n => () for the blocks stored without their transactions and receipts
+    BlockNWithoutBody,
     /// Meta column for block storage (sync tip, pending block)
     BlockStorageMeta,
 
@@ -146,11 +160,30 @@ pub enum Column {
 
     L1Messaging,
     L1MessagingNonce,
+    /// l1_tx_hash => l1 handler tx hashes
+    L1MessagingTxHashes,
+    /// nonce => L1 to L2 message rejected by the L1 handler policy
+    L1MessagingRejected,
+
+    /// Progress of the settlement of our blocks on the L1 core contract
+    L1Settlement,
+
+    /// L2→L1 messages
+    /// message_hash ++ block_n ++ tx_index ++ message_index => message
+    MsgToL1ByHash,
+    /// to_address ++ block_n ++ tx_index ++ message_index => message_hash
+    MsgToL1ByRecipient,
+    /// message_hash => status on L1
+    MsgToL1Status,
 
     /// Devnet: stores the private keys for the devnet predeployed contracts
     Devnet,
 
     MempoolTransactions,
+
+    /// Transaction traces of closed blocks
+    /// block_n ++ block_hash ++ tx_index => encoded trace
+    TxTraces,
 }
 
 impl fmt::Debug for Column {
@@ -175,6 +208,7 @@ impl Column {
             BlockHashToBlockN,
             BlockStorageMeta,
             BlockNToStateDiff,
+            BlockNWithoutBody,
             ClassInfo,
             ClassCompiled,
             PendingClassInfo,
@@ -193,16 +227,27 @@ impl Column {
             BonsaiClassesLog,
             L1Messaging,
             L1MessagingNonce,
+            L1MessagingTxHashes,
+            L1MessagingRejected,
+            L1Settlement,
+            MsgToL1ByHash,
+            MsgToL1ByRecipient,
+            MsgToL1Status,
             PendingContractToClassHashes,
             PendingContractToNonces,
             PendingContractStorage,
             Devnet,
             MempoolTransactions,
+            TxTraces,
         ]
     };
     pub const NUM_COLUMNS: usize = Self::ALL.len();
 
-    pub(crate) fn rocksdb_name(&self) -> &'static str {
+    pub fn from_rocksdb_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|col| col.rocksdb_name() == name).copied()
+    }
+
+    pub fn rocksdb_name(&self) -> &'static str {
         use Column::*;
         match self {
             BlockNToBlockInfo => "block_n_to_block_info",
@@ -211,6 +256,7 @@ impl Column {
             BlockHashToBlockN => "block_hash_to_block_n",
             BlockStorageMeta => "block_storage_meta",
             BlockNToStateDiff => "block_n_to_state_diff",
+            BlockNWithoutBody => "block_n_without_body",
             BonsaiContractsTrie => "bonsai_contracts_trie",
             BonsaiContractsFlat => "bonsai_contracts_flat",
             BonsaiContractsLog => "bonsai_contracts_log",
@@ -229,11 +275,18 @@ impl Column {
             ContractStorage => "contract_storage",
             L1Messaging => "l1_messaging",
             L1MessagingNonce => "l1_messaging_nonce",
+            L1MessagingTxHashes => "l1_messaging_tx_hashes",
+            L1MessagingRejected => "l1_messaging_rejected",
+            L1Settlement => "l1_settlement",
+            MsgToL1ByHash => "msg_to_l1_by_hash",
+            MsgToL1ByRecipient => "msg_to_l1_by_recipient",
+            MsgToL1Status => "msg_to_l1_status",
             PendingContractToClassHashes => "pending_contract_to_class_hashes",
             PendingContractToNonces => "pending_contract_to_nonces",
             PendingContractStorage => "pending_contract_storage",
             Devnet => "devnet",
             MempoolTransactions => "mempool_transactions",
+            TxTraces => "tx_traces",
         }
     }
 }
@@ -280,6 +333,8 @@ pub struct MadaraBackend {
     snapshots: Arc<Snapshots>,
     trie_log_config: TrieLogConfig,
     sender_block_info: tokio::sync::broadcast::Sender<mp_block::MadaraBlockInfo>,
+    sender_pending_block: tokio::sync::broadcast::Sender<Arc<mp_block::MadaraPendingBlock>>,
+    sender_l1_last_confirmed_block: tokio::sync::broadcast::Sender<u64>,
     write_opt_no_wal: WriteOptions,
     #[cfg(feature = "testing")]
     _temp_dir: Option<tempfile::TempDir>,
@@ -293,6 +348,8 @@ impl fmt::Debug for MadaraBackend {
             .field("chain_config", &self.chain_config)
             .field("db_metrics", &self.db_metrics)
             .field("sender_block_info", &self.sender_block_info)
+            .field("sender_pending_block", &self.sender_pending_block)
+            .field("sender_l1_last_confirmed_block", &self.sender_l1_last_confirmed_block)
             .finish()
     }
 }
@@ -355,9 +412,26 @@ impl ServiceId for DatabaseService {
     }
 }
 
-struct BackupRequest {
-    callback: oneshot::Sender<()>,
-    db: Arc<DB>,
+enum BackupRequest {
+    Create { callback: oneshot::Sender<anyhow::Result<()>>, db: Arc<DB> },
+    List { callback: oneshot::Sender<Vec<BackupInfo>> },
+}
+
+/// A backup of the database, as found in the backup directory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BackupInfo {
+    pub backup_id: u32,
+    /// Unix timestamp of the backup, in seconds.
+    pub timestamp: i64,
+    /// Size of the backup in bytes, excluding the files shared with older backups.
+    pub size: u64,
+    pub num_files: u32,
+}
+
+impl From<rocksdb::backup::BackupEngineInfo> for BackupInfo {
+    fn from(info: rocksdb::backup::BackupEngineInfo) -> Self {
+        Self { backup_id: info.backup_id, timestamp: info.timestamp, size: info.size, num_files: info.num_files }
+    }
 }
 
 impl Drop for MadaraBackend {
@@ -385,6 +459,8 @@ impl MadaraBackend {
             snapshots,
             trie_log_config: Default::default(),
             sender_block_info: tokio::sync::broadcast::channel(100).0,
+            sender_pending_block: tokio::sync::broadcast::channel(100).0,
+            sender_l1_last_confirmed_block: tokio::sync::broadcast::channel(100).0,
             write_opt_no_wal: make_write_opt_no_wal(),
             _temp_dir: Some(temp_dir),
         })
@@ -438,6 +514,8 @@ impl MadaraBackend {
             snapshots,
             trie_log_config,
             sender_block_info: tokio::sync::broadcast::channel(100).0,
+            sender_pending_block: tokio::sync::broadcast::channel(100).0,
+            sender_l1_last_confirmed_block: tokio::sync::broadcast::channel(100).0,
             write_opt_no_wal: make_write_opt_no_wal(),
             #[cfg(feature = "testing")]
             _temp_dir: None,
@@ -460,16 +538,55 @@ impl MadaraBackend {
         Ok(())
     }
 
+    /// Creates a backup, waiting for the backup task to be done with the previous requests.
     #[tracing::instrument(skip(self))]
     pub async fn backup(&self) -> anyhow::Result<()> {
         let (callback_sender, callback_recv) = oneshot::channel();
-        let _res = self
-            .backup_handle
-            .as_ref()
-            .context("backups are not enabled")?
-            .try_send(BackupRequest { callback: callback_sender, db: Arc::clone(&self.db) });
-        callback_recv.await.context("Backups task died :(")?;
-        Ok(())
+        self.backup_handle()?
+            .send(BackupRequest::Create { callback: callback_sender, db: Arc::clone(&self.db) })
+            .await
+            .ok()
+            .context("Backups task died :(")?;
+        callback_recv.await.context("Backups task died :(")?
+    }
+
+    /// Creates a backup, or fails right away if the backup task is busy with another request.
+    #[tracing::instrument(skip(self))]
+    pub async fn try_backup(&self) -> anyhow::Result<()> {
+        let (callback_sender, callback_recv) = oneshot::channel();
+        self.try_send_backup_request(BackupRequest::Create { callback: callback_sender, db: Arc::clone(&self.db) })?;
+        callback_recv.await.context("Backups task died :(")?
+    }
+
+    /// Lists the backups in the backup directory, oldest first. This fails right away if the backup task is busy.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_backups(&self) -> anyhow::Result<Vec<BackupInfo>> {
+        let (callback_sender, callback_recv) = oneshot::channel();
+        self.try_send_backup_request(BackupRequest::List { callback: callback_sender })?;
+        callback_recv.await.context("Backups task died :(")
+    }
+
+    fn backup_handle(&self) -> anyhow::Result<&mpsc::Sender<BackupRequest>> {
+        self.backup_handle.as_ref().context("backups are not enabled")
+    }
+
+    fn try_send_backup_request(&self, request: BackupRequest) -> anyhow::Result<()> {
+        self.backup_handle()?.try_send(request).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => anyhow::anyhow!("The backup task is busy"),
+            mpsc::error::TrySendError::Closed(_) => anyhow::anyhow!("Backups task died :("),
+        })
+    }
+
+    /// Compacts the whole key range of a column, or of every column. This blocks until the compaction is done, which
+    /// can take a long time on a big database.
+    #[tracing::instrument(skip(self))]
+    pub fn compact(&self, column: Option<Column>) {
+        let columns = column.map(|col| vec![col]).unwrap_or_else(|| Column::ALL.to_vec());
+        for column in columns {
+            tracing::debug!("compacting column {column}");
+            let col = self.db.get_column(column);
+            self.db.compact_range_cf(&col, None::<&[u8]>, None::<&[u8]>);
+        }
     }
 
     // tries
@@ -520,6 +637,11 @@ impl MadaraBackend {
     pub fn update_metrics(&self) -> u64 {
         self.db_metrics.update(&self.db)
     }
+
+    /// Current storage size of each column and memory usage of the database.
+    pub fn db_stats(&self) -> anyhow::Result<DbStats> {
+        DbStats::collect(&self.db)
+    }
 }
 
 pub mod bonsai_identifier {
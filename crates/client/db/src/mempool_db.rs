@@ -7,7 +7,7 @@ use starknet_types_core::felt::Felt;
 
 type Result<T, E = MadaraStorageError> = std::result::Result<T, E>;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SavedTransaction {
     pub tx: mp_transactions::Transaction,
     pub paid_fee_on_l1: Option<u128>,
@@ -42,6 +42,13 @@ impl MadaraBackend {
         })
     }
 
+    /// Whether a transaction is waiting in the mempool.
+    #[tracing::instrument(skip(self), fields(module = "MempoolDB"))]
+    pub fn has_mempool_transaction(&self, tx_hash: &Felt) -> Result<bool> {
+        let col = self.db.get_column(Column::MempoolTransactions);
+        Ok(self.db.get_pinned_cf(&col, bincode::serialize(tx_hash)?)?.is_some())
+    }
+
     #[tracing::instrument(skip(self), fields(module = "MempoolDB"))]
     pub fn remove_mempool_transaction(&self, tx_hash: &Felt) -> Result<()> {
         // Note: We do not use WAL here, as it will be flushed by saving the block. This is to
@@ -0,0 +1,252 @@
+//! Index of the L2→L1 messages sent by the transactions of stored blocks, and of their status on L1.
+
+use crate::db_block_id::DbBlockId;
+use crate::devnet_db::DEVNET_MESSAGES_TO_L1_FLUSHED;
+use crate::l1_db::LastSyncedEventBlock;
+use crate::{Column, DatabaseExt, MadaraBackend, MadaraStorageError, WriteBatchWithTransaction};
+use mp_block::MadaraBlock;
+use mp_receipt::MsgToL1;
+use primitive_types::H256;
+use rocksdb::{Direction, IteratorMode};
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt;
+use std::collections::HashMap;
+
+type Result<T, E = MadaraStorageError> = std::result::Result<T, E>;
+
+pub const LAST_SYNCED_MSG_TO_L1_EVENT: &[u8] = b"LAST_SYNCED_MSG_TO_L1_EVENT";
+
+/// An L2→L1 message, and where it was sent from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MsgToL1Entry {
+    pub message_hash: H256,
+    pub transaction_hash: Felt,
+    pub block_n: u64,
+    pub tx_index: u64,
+    /// Index of the message among the messages sent by its transaction.
+    pub message_index: u64,
+    pub message: MsgToL1,
+}
+
+/// Status on L1 of the messages with a given hash.
+///
+/// The core contract does not tell identical messages apart: it only counts how many messages with a given hash can be
+/// consumed. The n-th message with a hash, in L2 order, is considered settled (resp. consumed) once more than n
+/// messages with that hash have been settled (resp. consumed).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MsgToL1Status {
+    /// Number of `LogMessageToL1` events seen: messages settled on L1, ready to be consumed.
+    pub settled: u64,
+    /// Number of `ConsumedMessageToL1` events seen.
+    pub consumed: u64,
+}
+
+/// An L1 event updating the status of an L2→L1 message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsgToL1Event {
+    Settled,
+    Consumed,
+}
+
+/// `block_n ++ tx_index ++ message_index`, so that keys are sorted in L2 order.
+fn position_suffix(block_n: u64, tx_index: u64, message_index: u64) -> [u8; 24] {
+    let mut suffix = [0u8; 24];
+    suffix[..8].copy_from_slice(&block_n.to_be_bytes());
+    suffix[8..16].copy_from_slice(&tx_index.to_be_bytes());
+    suffix[16..].copy_from_slice(&message_index.to_be_bytes());
+    suffix
+}
+
+fn by_hash_key(message_hash: &H256, suffix: &[u8]) -> Vec<u8> {
+    [message_hash.as_bytes(), suffix].concat()
+}
+
+fn by_recipient_key(to_address: &Felt, suffix: &[u8]) -> Vec<u8> {
+    [&to_address.to_bytes_be()[..], suffix].concat()
+}
+
+/// The L2→L1 messages sent by the transactions of a block, in L2 order.
+fn block_messages_to_l1(block: &MadaraBlock) -> Vec<MsgToL1Entry> {
+    let block_n = block.info.header.block_number;
+    block
+        .inner
+        .receipts
+        .iter()
+        .zip(&block.info.tx_hashes)
+        .enumerate()
+        .flat_map(|(tx_index, (receipt, transaction_hash))| {
+            receipt.messages_sent().iter().enumerate().map(move |(message_index, message)| MsgToL1Entry {
+                message_hash: message.compute_hash(),
+                transaction_hash: *transaction_hash,
+                block_n,
+                tx_index: tx_index as u64,
+                message_index: message_index as u64,
+                message: message.clone(),
+            })
+        })
+        .collect()
+}
+
+impl MadaraBackend {
+    /// Index the L2→L1 messages of a block, as part of the write batch storing it.
+    pub(crate) fn msg_to_l1_db_index_block(
+        &self,
+        tx: &mut WriteBatchWithTransaction,
+        block: &MadaraBlock,
+    ) -> Result<()> {
+        let by_hash = self.db.get_column(Column::MsgToL1ByHash);
+        let by_recipient = self.db.get_column(Column::MsgToL1ByRecipient);
+
+        for entry in block_messages_to_l1(block) {
+            let suffix = position_suffix(entry.block_n, entry.tx_index, entry.message_index);
+            tx.put_cf(
+                &by_recipient,
+                by_recipient_key(&entry.message.to_address, &suffix),
+                entry.message_hash.as_bytes(),
+            );
+            tx.put_cf(&by_hash, by_hash_key(&entry.message_hash, &suffix), bincode::serialize(&entry)?);
+        }
+        Ok(())
+    }
+
+    /// The L2→L1 messages sent by the transactions of the closed block `block_n`, in L2 order.
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn get_messages_to_l1_in_block(&self, block_n: u64) -> Result<Vec<MsgToL1Entry>> {
+        let Some(block) = self.get_block(&DbBlockId::Number(block_n))? else { return Ok(vec![]) };
+        let Ok(block) = MadaraBlock::try_from(block) else { return Ok(vec![]) };
+        Ok(block_messages_to_l1(&block))
+    }
+
+    /// All the L2→L1 messages with the given hash, in L2 order.
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn get_messages_to_l1_by_hash(&self, message_hash: &H256) -> Result<Vec<MsgToL1Entry>> {
+        let col = self.db.get_column(Column::MsgToL1ByHash);
+        let prefix = message_hash.as_bytes();
+        self.db
+            .iterator_cf(&col, IteratorMode::From(prefix, Direction::Forward))
+            .map_while(|kv| match kv {
+                Ok((key, value)) if key.starts_with(prefix) => Some(Ok(value)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .map(|value| -> Result<MsgToL1Entry> { Ok(bincode::deserialize(&value?)?) })
+            .collect()
+    }
+
+    /// The L2→L1 messages sent to `to_address`, in L2 order, starting at block `from_block_n`. At most `limit`
+    /// messages are returned.
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn get_messages_to_l1_by_recipient(
+        &self,
+        to_address: &Felt,
+        from_block_n: u64,
+        limit: usize,
+    ) -> Result<Vec<MsgToL1Entry>> {
+        let col = self.db.get_column(Column::MsgToL1ByRecipient);
+        let by_hash = self.db.get_column(Column::MsgToL1ByHash);
+        let prefix = to_address.to_bytes_be();
+        let start = by_recipient_key(to_address, &position_suffix(from_block_n, 0, 0));
+
+        let mut entries = vec![];
+        for kv in self.db.iterator_cf(&col, IteratorMode::From(&start, Direction::Forward)) {
+            if entries.len() >= limit {
+                break;
+            }
+            let (key, message_hash) = kv?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let key = by_hash_key(&H256::from_slice(&message_hash), &key[prefix.len()..]);
+            let Some(entry) = self.db.get_cf(&by_hash, key)? else { continue };
+            entries.push(bincode::deserialize(&entry)?);
+        }
+        Ok(entries)
+    }
+
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn get_msg_to_l1_status(&self, message_hash: &H256) -> Result<MsgToL1Status> {
+        let col = self.db.get_column(Column::MsgToL1Status);
+        let Some(res) = self.db.get_cf(&col, message_hash.as_bytes())? else { return Ok(MsgToL1Status::default()) };
+        Ok(bincode::deserialize(&res)?)
+    }
+
+    /// The last L1 event processed by [`MadaraBackend::msg_to_l1_apply_event`], if any.
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn msg_to_l1_last_synced_event(&self) -> Result<Option<LastSyncedEventBlock>> {
+        let col = self.db.get_column(Column::L1Messaging);
+        let Some(res) = self.db.get_cf(&col, LAST_SYNCED_MSG_TO_L1_EVENT)? else { return Ok(None) };
+        Ok(Some(bincode::deserialize(&res)?))
+    }
+
+    /// Record an L1 event updating the status of the messages with the given hash. The event position is stored in
+    /// the same write, so that events are never counted twice.
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn msg_to_l1_apply_event(
+        &self,
+        message_hash: &H256,
+        event: MsgToL1Event,
+        position: LastSyncedEventBlock,
+    ) -> Result<()> {
+        let mut tx = WriteBatchWithTransaction::default();
+        self.msg_to_l1_update_status(&mut tx, message_hash, event, 1)?;
+        tx.put_cf(
+            &self.db.get_column(Column::L1Messaging),
+            LAST_SYNCED_MSG_TO_L1_EVENT,
+            bincode::serialize(&position)?,
+        );
+        self.db.write(tx)?;
+        Ok(())
+    }
+
+    /// Update the status of the messages with the given hash without any L1 event. This is used by devnets, which
+    /// have no L1 to settle on.
+    #[tracing::instrument(skip(self), fields(module = "MsgToL1DB"))]
+    pub fn msg_to_l1_devnet_apply_event(&self, message_hash: &H256, event: MsgToL1Event) -> Result<()> {
+        let mut tx = WriteBatchWithTransaction::default();
+        self.msg_to_l1_update_status(&mut tx, message_hash, event, 1)?;
+        self.db.write(tx)?;
+        Ok(())
+    }
+
+    /// Settle messages on the simulated L1 of a devnet, and mark the blocks before `flushed_up_to` as flushed, in a
+    /// single write. A hash appearing several times settles as many messages.
+    #[tracing::instrument(skip(self, message_hashes), fields(module = "MsgToL1DB"))]
+    pub fn msg_to_l1_devnet_flush<'a>(
+        &self,
+        message_hashes: impl IntoIterator<Item = &'a H256>,
+        flushed_up_to: u64,
+    ) -> Result<()> {
+        let mut counts: HashMap<H256, u64> = HashMap::new();
+        for message_hash in message_hashes {
+            *counts.entry(*message_hash).or_default() += 1;
+        }
+
+        let mut tx = WriteBatchWithTransaction::default();
+        for (message_hash, count) in counts {
+            self.msg_to_l1_update_status(&mut tx, &message_hash, MsgToL1Event::Settled, count)?;
+        }
+        tx.put_cf(
+            &self.db.get_column(Column::Devnet),
+            DEVNET_MESSAGES_TO_L1_FLUSHED,
+            bincode::serialize(&flushed_up_to)?,
+        );
+        self.db.write(tx)?;
+        Ok(())
+    }
+
+    fn msg_to_l1_update_status(
+        &self,
+        tx: &mut WriteBatchWithTransaction,
+        message_hash: &H256,
+        event: MsgToL1Event,
+        count: u64,
+    ) -> Result<()> {
+        let mut status = self.get_msg_to_l1_status(message_hash)?;
+        match event {
+            MsgToL1Event::Settled => status.settled += count,
+            MsgToL1Event::Consumed => status.consumed += count,
+        }
+        tx.put_cf(&self.db.get_column(Column::MsgToL1Status), message_hash.as_bytes(), bincode::serialize(&status)?);
+        Ok(())
+    }
+}
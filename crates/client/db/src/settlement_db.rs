@@ -0,0 +1,55 @@
+use crate::DatabaseExt;
+use crate::{Column, MadaraBackend, MadaraStorageError};
+use serde::{Deserialize, Serialize};
+
+pub const SETTLEMENT_STATE: &[u8] = b"SETTLEMENT_STATE";
+
+type Result<T, E = MadaraStorageError> = std::result::Result<T, E>;
+
+/// Progress of the settlement of our blocks on the L1 core contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementState {
+    /// Last block whose `updateState` transaction has been confirmed on L1.
+    pub last_settled_block_n: Option<u64>,
+    /// The `updateState` transaction currently waiting to be confirmed, if any.
+    pub inflight: Option<SettlementTx>,
+}
+
+/// An `updateState` transaction sent to L1 and not yet confirmed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementTx {
+    /// The block being settled.
+    pub block_n: u64,
+    /// L1 nonce of the transaction. Replacements reuse the same nonce.
+    pub nonce: u64,
+    /// Hashes of every version of the transaction sent, the last one being the most recent replacement. Any of
+    /// them may end up being included.
+    pub tx_hashes: Vec<[u8; 32]>,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    /// L1 block number at which the last version of the transaction was sent.
+    pub sent_at_l1_block: u64,
+}
+
+impl MadaraBackend {
+    /// Get the settlement progress. Returns the default (empty) state when nothing has been settled yet.
+    #[tracing::instrument(skip(self), fields(module = "SettlementDB"))]
+    pub fn get_settlement_state(&self) -> Result<SettlementState> {
+        let col = self.db.get_column(Column::L1Settlement);
+        let Some(res) = self.db.get_cf(&col, SETTLEMENT_STATE)? else {
+            return Ok(SettlementState::default());
+        };
+        Ok(bincode::deserialize(&res)?)
+    }
+
+    /// Set the settlement progress.
+    ///
+    /// Unlike most of the L1 metadata, this is written with the WAL enabled: losing track of an inflight transaction
+    /// would make us send a second, conflicting one.
+    #[tracing::instrument(skip(self, state), fields(module = "SettlementDB"))]
+    pub fn write_settlement_state(&self, state: &SettlementState) -> Result<()> {
+        let col = self.db.get_column(Column::L1Settlement);
+        self.db.put_cf(&col, SETTLEMENT_STATE, bincode::serialize(state)?)?;
+        Ok(())
+    }
+}
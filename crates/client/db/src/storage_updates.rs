@@ -3,7 +3,10 @@ use crate::MadaraBackend;
 use crate::MadaraStorageError;
 use blockifier::bouncer::BouncerWeights;
 use mp_block::VisitedSegments;
-use mp_block::{MadaraBlock, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo, MadaraPendingBlock};
+use mp_block::{
+    MadaraBlock, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo,
+    MadaraPendingBlock,
+};
 use mp_class::ConvertedClass;
 use mp_state_update::{
     ContractStorageDiffItem, DeployedContractItem, NonceUpdate, ReplacedClassItem, StateDiff, StorageEntry,
@@ -20,6 +23,36 @@ impl MadaraBackend {
         converted_classes: Vec<ConvertedClass>,
         visited_segments: Option<VisitedSegments>,
         bouncer_weights: Option<BouncerWeights>,
+    ) -> Result<(), MadaraStorageError> {
+        self.store_block_inner(block, state_diff, converted_classes, visited_segments, bouncer_weights, true)
+    }
+
+    /// Stores a block without its transactions and receipts, which happens when it is reconstructed from the data
+    /// availability of the settlement layer. The block is marked as such in the same write, see
+    /// [`Self::has_block_body`].
+    ///
+    /// NB: This functions needs to run on the rayon thread pool
+    pub fn store_block_without_body(
+        &self,
+        info: MadaraBlockInfo,
+        state_diff: StateDiff,
+        converted_classes: Vec<ConvertedClass>,
+    ) -> Result<(), MadaraStorageError> {
+        let block = MadaraMaybePendingBlock {
+            info: MadaraMaybePendingBlockInfo::NotPending(info),
+            inner: MadaraBlockInner::default(),
+        };
+        self.store_block_inner(block, state_diff, converted_classes, None, None, false)
+    }
+
+    fn store_block_inner(
+        &self,
+        block: MadaraMaybePendingBlock,
+        state_diff: StateDiff,
+        converted_classes: Vec<ConvertedClass>,
+        visited_segments: Option<VisitedSegments>,
+        bouncer_weights: Option<BouncerWeights>,
+        has_body: bool,
     ) -> Result<(), MadaraStorageError> {
         let block_n = block.info.block_n();
         let state_diff_cpy = state_diff.clone();
@@ -35,7 +68,7 @@ impl MadaraBackend {
                 bouncer_weights,
             ),
             MadaraMaybePendingBlockInfo::NotPending(info) => {
-                self.block_db_store_block(&MadaraBlock { info, inner: block.inner }, &state_diff_cpy)
+                self.block_db_store_block(&MadaraBlock { info, inner: block.inner }, &state_diff_cpy, has_body)
             }
         };
 
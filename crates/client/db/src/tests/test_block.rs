@@ -3,10 +3,14 @@ mod block_tests {
     use super::super::common::temp_db::temp_db;
     use super::super::common::*;
     use crate::db_block_id::DbBlockIdResolvable;
+    use crate::l1_db::LastSyncedEventBlock;
+    use crate::msg_to_l1_db::{MsgToL1Event, MsgToL1Status};
     use crate::{block_db::TxIndex, db_block_id::DbBlockId};
     use mp_block::{BlockId, Header};
     use mp_chain_config::ChainConfig;
+    use mp_receipt::{InvokeTransactionReceipt, MsgToL1};
     use starknet_api::felt;
+    use starknet_types_core::felt::Felt;
 
     #[tokio::test]
     async fn test_chain_info() {
@@ -144,6 +148,17 @@ mod block_tests {
         assert_eq!(backend.get_l1_last_confirmed_block().unwrap().unwrap(), 0);
     }
 
+    #[tokio::test]
+    async fn test_block_without_body() {
+        let db = temp_db().await;
+        let backend = db.backend();
+
+        assert!(backend.has_block_body(0).unwrap());
+        backend.mark_block_without_body(0).unwrap();
+        assert!(!backend.has_block_body(0).unwrap());
+        assert!(backend.has_block_body(1).unwrap());
+    }
+
     #[tokio::test]
     async fn test_store_block_transactions() {
         let db = temp_db().await;
@@ -178,4 +193,60 @@ mod block_tests {
         );
         assert_eq!(backend.find_tx_hash_block(&tx_hash_1).unwrap().unwrap(), (block_pending, TxIndex(1)));
     }
+
+    #[tokio::test]
+    async fn test_messages_to_l1_index() {
+        let db = temp_db().await;
+        let backend = db.backend();
+
+        let message = MsgToL1 { from_address: Felt::from(1), to_address: Felt::from(2), payload: vec![Felt::from(3)] };
+        let mut block = finalized_block_zero(Header::default());
+        block.inner.receipts[0] =
+            InvokeTransactionReceipt { messages_sent: vec![message.clone(), message.clone()], ..Default::default() }
+                .into();
+        backend.store_block(block, finalized_state_diff_zero(), vec![], None, None).unwrap();
+
+        let message_hash = message.compute_hash();
+        let entries = backend.get_messages_to_l1_by_hash(&message_hash).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[1].transaction_hash, entries[1].message_index), (Felt::from(0), 1));
+        assert_eq!(backend.get_messages_to_l1_by_recipient(&Felt::from(2), 0, 10).unwrap(), entries);
+        assert_eq!(backend.get_messages_to_l1_by_recipient(&Felt::from(2), 0, 1).unwrap(), entries[..1]);
+        assert!(backend.get_messages_to_l1_by_recipient(&Felt::from(2), 1, 10).unwrap().is_empty());
+        assert!(backend.get_messages_to_l1_by_recipient(&Felt::from(1), 0, 10).unwrap().is_empty());
+        assert_eq!(backend.get_messages_to_l1_in_block(0).unwrap(), entries);
+        assert!(backend.get_messages_to_l1_in_block(1).unwrap().is_empty());
+
+        backend.msg_to_l1_apply_event(&message_hash, MsgToL1Event::Settled, LastSyncedEventBlock::new(5, 0)).unwrap();
+        assert_eq!(backend.get_msg_to_l1_status(&message_hash).unwrap(), MsgToL1Status { settled: 1, consumed: 0 });
+        assert_eq!(backend.msg_to_l1_last_synced_event().unwrap().unwrap().block_number, 5);
+
+        backend.msg_to_l1_devnet_apply_event(&message_hash, MsgToL1Event::Consumed).unwrap();
+        assert_eq!(backend.get_msg_to_l1_status(&message_hash).unwrap(), MsgToL1Status { settled: 1, consumed: 1 });
+        assert_eq!(backend.msg_to_l1_last_synced_event().unwrap().unwrap().block_number, 5);
+    }
+
+    #[tokio::test]
+    async fn test_tx_traces() {
+        let db = temp_db().await;
+        let backend = db.backend();
+
+        let (hash_a, hash_b) = (Felt::from(0xa), Felt::from(0xb));
+        backend.store_tx_traces(0, &hash_a, [(0, b"trace 0".to_vec()), (1, b"trace 1".to_vec())]).unwrap();
+        backend.store_tx_traces(1, &hash_b, [(0, b"trace 2".to_vec())]).unwrap();
+
+        assert_eq!(backend.get_tx_trace(0, &hash_a, 1).unwrap().unwrap(), b"trace 1");
+        assert!(backend.get_tx_trace(0, &hash_b, 1).unwrap().is_none());
+        assert!(backend.get_tx_trace(0, &hash_a, 2).unwrap().is_none());
+
+        // Another block stored at height 0 replaces its traces.
+        backend
+            .store_block(finalized_block_zero(Header::default()), finalized_state_diff_zero(), vec![], None, None)
+            .unwrap();
+        assert!(backend.get_tx_trace(0, &hash_a, 0).unwrap().is_none());
+        assert_eq!(backend.get_tx_trace(1, &hash_b, 0).unwrap().unwrap(), b"trace 2");
+
+        backend.prune_tx_traces(2).unwrap();
+        assert!(backend.get_tx_trace(1, &hash_b, 0).unwrap().is_none());
+    }
 }
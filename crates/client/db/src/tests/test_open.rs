@@ -17,3 +17,40 @@ async fn test_open_different_chain_id() {
     let chain_config = std::sync::Arc::new(ChainConfig::madara_test());
     assert!(DatabaseService::new(temp_dir.path(), None, false, chain_config, Default::default()).await.is_err());
 }
+
+#[tokio::test]
+async fn test_backups() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("backups");
+    let chain_config = std::sync::Arc::new(ChainConfig::madara_test());
+    let db =
+        DatabaseService::new(temp_dir.path(), Some(backup_dir), false, chain_config, Default::default()).await.unwrap();
+    let backend = db.backend();
+
+    assert_eq!(backend.list_backups().await.unwrap(), vec![]);
+    backend.try_backup().await.unwrap();
+    backend.try_backup().await.unwrap();
+    let backups = backend.list_backups().await.unwrap();
+    assert_eq!(backups.len(), 2);
+    assert!(backups[0].backup_id < backups[1].backup_id);
+
+    // Backups requested while the task is busy wait for their turn.
+    let (a, b, c) = tokio::join!(backend.backup(), backend.backup(), backend.backup());
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+    assert_eq!(backend.list_backups().await.unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_db_stats() {
+    let db = temp_db::temp_db().await;
+    let backend = db.backend();
+
+    backend.flush().unwrap();
+    backend.compact(Some(crate::Column::BlockNToBlockInfo));
+    backend.compact(None);
+    let stats = backend.db_stats().unwrap();
+    assert_eq!(stats.columns.len(), crate::Column::NUM_COLUMNS);
+    assert_eq!(stats.db_size, stats.columns.iter().map(|col| col.size).sum::<u64>());
+}
@@ -0,0 +1,61 @@
+//! Transaction traces of closed blocks, persisted so that they are not computed again by re-executing the block.
+//!
+//! Traces are stored already encoded, this module does not depend on their format. Keys are
+//! `block_n ++ block_hash ++ tx_index`: traces of a block which has been replaced are never read back, and they are
+//! removed when another block is stored at the same height. Old blocks are pruned with a single range delete.
+
+use crate::{Column, DatabaseExt, MadaraBackend, MadaraStorageError, WriteBatchWithTransaction};
+use starknet_types_core::felt::Felt;
+
+type Result<T, E = MadaraStorageError> = std::result::Result<T, E>;
+
+fn block_prefix(block_n: u64) -> [u8; 8] {
+    block_n.to_be_bytes()
+}
+
+fn trace_key(block_n: u64, block_hash: &Felt, tx_index: u64) -> [u8; 48] {
+    let mut key = [0u8; 48];
+    key[..8].copy_from_slice(&block_prefix(block_n));
+    key[8..40].copy_from_slice(&block_hash.to_bytes_be());
+    key[40..].copy_from_slice(&tx_index.to_be_bytes());
+    key
+}
+
+impl MadaraBackend {
+    #[tracing::instrument(skip(self), fields(module = "TraceDB"))]
+    pub fn get_tx_trace(&self, block_n: u64, block_hash: &Felt, tx_index: u64) -> Result<Option<Vec<u8>>> {
+        let col = self.db.get_column(Column::TxTraces);
+        Ok(self.db.get_cf(&col, trace_key(block_n, block_hash, tx_index))?)
+    }
+
+    /// Stores encoded traces of the transactions of a closed block, by transaction index.
+    #[tracing::instrument(skip(self, traces), fields(module = "TraceDB"))]
+    pub fn store_tx_traces(
+        &self,
+        block_n: u64,
+        block_hash: &Felt,
+        traces: impl IntoIterator<Item = (u64, Vec<u8>)>,
+    ) -> Result<()> {
+        let col = self.db.get_column(Column::TxTraces);
+        let mut tx = WriteBatchWithTransaction::default();
+        for (tx_index, trace) in traces {
+            tx.put_cf(&col, trace_key(block_n, block_hash, tx_index), trace);
+        }
+        self.db.write_opt(tx, &self.write_opt_no_wal)?;
+        Ok(())
+    }
+
+    /// Removes the traces of the blocks before `block_n`.
+    #[tracing::instrument(skip(self), fields(module = "TraceDB"))]
+    pub fn prune_tx_traces(&self, block_n: u64) -> Result<()> {
+        let col = self.db.get_column(Column::TxTraces);
+        self.db.delete_range_cf_opt(&col, block_prefix(0), block_prefix(block_n), &self.write_opt_no_wal)?;
+        Ok(())
+    }
+
+    /// Removes the traces of any block previously stored at the height `block_n`.
+    pub(crate) fn trace_db_clear_block(&self, tx: &mut WriteBatchWithTransaction, block_n: u64) {
+        let col = self.db.get_column(Column::TxTraces);
+        tx.delete_range_cf(&col, block_prefix(block_n), block_prefix(block_n.saturating_add(1)));
+    }
+}
@@ -150,12 +150,13 @@ impl ChainGenesisDescription {
     pub fn build(mut self, chain_config: &ChainConfig) -> anyhow::Result<UnverifiedFullBlock> {
         self.initial_balances.to_storage_diffs(chain_config, &mut self.initial_storage);
 
+        let block_timestamp = BlockTimestamp::now();
         Ok(UnverifiedFullBlock {
             header: UnverifiedHeader {
                 parent_block_hash: Some(Felt::ZERO),
                 sequencer_address: chain_config.sequencer_address.to_felt(),
-                block_timestamp: BlockTimestamp::now(),
-                protocol_version: chain_config.latest_protocol_version,
+                block_timestamp,
+                protocol_version: chain_config.protocol_version_at(0, block_timestamp.0),
                 l1_gas_price: GasPrices {
                     eth_l1_gas_price: 5,
                     strk_l1_gas_price: 5,
@@ -777,4 +778,116 @@ mod tests {
         assert_eq!(block.inner.receipts, vec![]);
         assert!(chain.mempool.is_empty());
     }
+
+    #[rstest]
+    fn test_close_pending_block(mut chain: DevnetForTesting) {
+        let contract_0 = &chain.contracts.0[0];
+        let contract_1 = &chain.contracts.0[1];
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // Empty blocks are skipped unless explicitly requested.
+        assert_eq!(rt.block_on(chain.block_production.close_pending_block(false)).unwrap(), None);
+        assert_eq!(chain.backend.get_latest_block_n().unwrap(), Some(0));
+
+        chain
+            .sign_and_add_invoke_tx(
+                BroadcastedInvokeTxn::V3(InvokeTxnV3 {
+                    sender_address: contract_0.address,
+                    calldata: Multicall::default()
+                        .with(Call {
+                            to: ERC20_STRK_CONTRACT_ADDRESS,
+                            selector: Selector::from("transfer"),
+                            calldata: vec![contract_1.address, 15.into(), Felt::ZERO],
+                        })
+                        .flatten()
+                        .collect(),
+                    signature: vec![], // Signature is filled in by `sign_and_add_invoke_tx`.
+                    nonce: 0.into(),
+                    resource_bounds: ResourceBoundsMapping {
+                        l1_gas: ResourceBounds { max_amount: 60000, max_price_per_unit: 10000 },
+                        l2_gas: ResourceBounds { max_amount: 60000, max_price_per_unit: 10000 },
+                    },
+                    tip: 0,
+                    paymaster_data: vec![],
+                    account_deployment_data: vec![],
+                    nonce_data_availability_mode: DaMode::L1,
+                    fee_data_availability_mode: DaMode::L1,
+                }),
+                contract_0,
+            )
+            .unwrap();
+
+        // Instant mining seals the transaction into its own block.
+        rt.block_on(chain.block_production.on_transactions_received()).unwrap();
+        assert_eq!(chain.backend.get_latest_block_n().unwrap(), Some(1));
+        let block = chain.backend.get_block(&BlockId::Number(1)).unwrap().unwrap();
+        assert_eq!(block.inner.transactions.len(), 1);
+        assert!(chain.mempool.is_empty());
+
+        // Manual closing produces a block even when there is no transaction.
+        assert_eq!(rt.block_on(chain.block_production.close_pending_block(true)).unwrap(), Some(2));
+        let block = chain.backend.get_block(&BlockId::Number(2)).unwrap().unwrap();
+        assert_eq!(block.inner.transactions, vec![]);
+    }
+
+    #[rstest]
+    fn test_parallel_execution_matches_sequential() {
+        let mut sequential = chain_with_mempool_limits(MempoolLimits::for_testing());
+        let parallel = chain_with_mempool_limits(MempoolLimits::for_testing());
+        let mut parallel =
+            DevnetForTesting { block_production: parallel.block_production.with_execution_workers(4), ..parallel };
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        for chain in [&mut sequential, &mut parallel] {
+            // All of these conflict: they write to the balance of the recipient and of the sequencer.
+            let recipient = chain.contracts.0[9].address;
+            let senders = [(0, 0), (1, 0), (0, 1), (2, 0), (3, 0), (0, 2), (4, 0)];
+            for (sender, nonce) in senders {
+                let contract = &chain.contracts.0[sender];
+                chain
+                    .sign_and_add_invoke_tx(
+                        BroadcastedInvokeTxn::V3(InvokeTxnV3 {
+                            sender_address: contract.address,
+                            calldata: Multicall::default()
+                                .with(Call {
+                                    to: ERC20_STRK_CONTRACT_ADDRESS,
+                                    selector: Selector::from("transfer"),
+                                    calldata: vec![recipient, (1000 + sender as u128).into(), Felt::ZERO],
+                                })
+                                .flatten()
+                                .collect(),
+                            signature: vec![], // Signature is filled in by `sign_and_add_invoke_tx`.
+                            nonce: nonce.into(),
+                            resource_bounds: ResourceBoundsMapping {
+                                l1_gas: ResourceBounds { max_amount: 60000, max_price_per_unit: 10000 },
+                                l2_gas: ResourceBounds { max_amount: 60000, max_price_per_unit: 10000 },
+                            },
+                            tip: 0,
+                            paymaster_data: vec![],
+                            account_deployment_data: vec![],
+                            nonce_data_availability_mode: DaMode::L1,
+                            fee_data_availability_mode: DaMode::L1,
+                        }),
+                        contract,
+                    )
+                    .unwrap();
+            }
+
+            rt.block_on(async {
+                chain.block_production.set_current_pending_tick(1);
+                chain.block_production.on_pending_time_tick().await.unwrap();
+            });
+        }
+
+        let sequential_block = sequential.backend.get_block(&BlockId::Tag(BlockTag::Pending)).unwrap().unwrap();
+        let parallel_block = parallel.backend.get_block(&BlockId::Tag(BlockTag::Pending)).unwrap().unwrap();
+        assert_eq!(sequential_block.inner.transactions.len(), 7);
+        assert_eq!(parallel_block.inner, sequential_block.inner);
+
+        let mut sequential_state_diff = sequential.backend.get_pending_block_state_update().unwrap();
+        let mut parallel_state_diff = parallel.backend.get_pending_block_state_update().unwrap();
+        sequential_state_diff.sort();
+        parallel_state_diff.sort();
+        assert_eq!(parallel_state_diff, sequential_state_diff);
+    }
 }
@@ -1,21 +1,29 @@
 use crate::client::StarknetCoreContract::StarknetCoreContractInstance;
+use crate::failover::{FailoverConfig, FailoverTransport};
+use crate::state_update::L1StateUpdate;
 use crate::utils::u256_to_felt;
 use alloy::sol_types::SolEvent;
 use alloy::{
     primitives::Address,
-    providers::{Provider, ProviderBuilder, ReqwestProvider, RootProvider},
-    rpc::types::Filter,
+    providers::{Provider, RootProvider},
+    rpc::{
+        client::RpcClient,
+        types::{BlockId, Filter},
+    },
     sol,
-    transports::http::{Client, Http},
 };
-use mc_analytics::register_gauge_metric_instrument;
+use mc_analytics::{register_counter_metric_instrument, register_gauge_metric_instrument};
 use opentelemetry::{global, KeyValue};
-use opentelemetry::{global::Error, metrics::Gauge};
+use opentelemetry::{
+    global::Error,
+    metrics::{Counter, Gauge},
+};
 
 use anyhow::{bail, Context};
 use bitvec::macros::internal::funty::Fundamental;
 use starknet_types_core::felt::Felt;
 use std::sync::Arc;
+use tokio::sync::watch;
 use url::Url;
 
 #[derive(Clone, Debug)]
@@ -25,6 +33,14 @@ pub struct L1BlockMetrics {
     // gas price is also define in sync/metrics/block_metrics.rs but this would be the price from l1
     pub l1_gas_price_wei: Gauge<u64>,
     pub l1_gas_price_strk: Gauge<f64>,
+    pub l1_data_gas_price_wei: Gauge<u64>,
+    pub l1_data_gas_price_strk: Gauge<f64>,
+    // inputs of the gas prices
+    pub l1_base_fee_wei: Gauge<u64>,
+    pub l1_blob_base_fee_wei: Gauge<u64>,
+    pub eth_strk_price: Gauge<f64>,
+    // L1 to L2 messages rejected by the L1 handler policy
+    pub l1_messages_rejected: Counter<u64>,
 }
 
 impl L1BlockMetrics {
@@ -58,7 +74,59 @@ impl L1BlockMetrics {
             "".to_string(),
         );
 
-        Ok(Self { l1_block_number, l1_gas_price_wei, l1_gas_price_strk })
+        let l1_data_gas_price_wei = register_gauge_metric_instrument(
+            &eth_meter,
+            "l1_data_gas_price_wei".to_string(),
+            "Gauge for madara L1 data gas price in wei".to_string(),
+            "".to_string(),
+        );
+
+        let l1_data_gas_price_strk = register_gauge_metric_instrument(
+            &eth_meter,
+            "l1_data_gas_price_strk".to_string(),
+            "Gauge for madara L1 data gas price in strk".to_string(),
+            "".to_string(),
+        );
+
+        let l1_base_fee_wei = register_gauge_metric_instrument(
+            &eth_meter,
+            "l1_base_fee_wei".to_string(),
+            "Gauge for the latest L1 base fee in wei".to_string(),
+            "".to_string(),
+        );
+
+        let l1_blob_base_fee_wei = register_gauge_metric_instrument(
+            &eth_meter,
+            "l1_blob_base_fee_wei".to_string(),
+            "Gauge for the latest L1 blob base fee in wei".to_string(),
+            "".to_string(),
+        );
+
+        let eth_strk_price = register_gauge_metric_instrument(
+            &eth_meter,
+            "eth_strk_price".to_string(),
+            "Gauge for the ETH/STRK price given by the oracle".to_string(),
+            "".to_string(),
+        );
+
+        let l1_messages_rejected = register_counter_metric_instrument(
+            &eth_meter,
+            "l1_messages_rejected".to_string(),
+            "Counter for the L1 to L2 messages rejected by the L1 handler policy".to_string(),
+            "".to_string(),
+        );
+
+        Ok(Self {
+            l1_block_number,
+            l1_gas_price_wei,
+            l1_gas_price_strk,
+            l1_data_gas_price_wei,
+            l1_data_gas_price_strk,
+            l1_base_fee_wei,
+            l1_blob_base_fee_wei,
+            eth_strk_price,
+            l1_messages_rejected,
+        })
     }
 }
 
@@ -71,10 +139,14 @@ sol!(
     "src/abis/starknet_core.json"
 );
 
+/// Provider over all the L1 endpoints, with failover.
+pub type L1Provider = RootProvider<FailoverTransport>;
+
 pub struct EthereumClient {
-    pub provider: Arc<ReqwestProvider>,
-    pub l1_core_contract: StarknetCoreContractInstance<Http<Client>, RootProvider<Http<Client>>>,
+    pub provider: Arc<L1Provider>,
+    pub l1_core_contract: StarknetCoreContractInstance<FailoverTransport, L1Provider>,
     pub l1_block_metrics: L1BlockMetrics,
+    transport: FailoverTransport,
 }
 
 impl Clone for EthereumClient {
@@ -83,27 +155,47 @@ impl Clone for EthereumClient {
             provider: Arc::clone(&self.provider),
             l1_core_contract: self.l1_core_contract.clone(),
             l1_block_metrics: self.l1_block_metrics.clone(),
+            transport: self.transport.clone(),
         }
     }
 }
 
 impl EthereumClient {
-    /// Create a new EthereumClient instance with the given RPC URL
-    pub async fn new(url: Url, l1_core_address: Address, l1_block_metrics: L1BlockMetrics) -> anyhow::Result<Self> {
-        let provider = ProviderBuilder::new().on_http(url);
-
-        EthereumClient::assert_core_contract_exists(&provider, l1_core_address).await?;
+    /// Create a new EthereumClient instance with the given RPC URLs. The first URL is the preferred endpoint, the
+    /// others are used when it is unavailable or lagging behind.
+    pub async fn new(
+        urls: Vec<Url>,
+        l1_core_address: Address,
+        l1_block_metrics: L1BlockMetrics,
+        failover_config: FailoverConfig,
+    ) -> anyhow::Result<Self> {
+        let client = Self::new_unchecked(urls, l1_core_address, l1_block_metrics, failover_config)?;
+        EthereumClient::assert_core_contract_exists(&client.provider, l1_core_address).await?;
+        Ok(client)
+    }
 
+    /// Create a new EthereumClient instance without checking that the core contract exists.
+    pub fn new_unchecked(
+        urls: Vec<Url>,
+        l1_core_address: Address,
+        l1_block_metrics: L1BlockMetrics,
+        failover_config: FailoverConfig,
+    ) -> anyhow::Result<Self> {
+        let transport = FailoverTransport::new(urls, failover_config)?;
+        let provider = RootProvider::new(RpcClient::new(transport.clone(), transport.is_local()));
         let core_contract = StarknetCoreContract::new(l1_core_address, provider.clone());
 
-        Ok(Self { provider: Arc::new(provider), l1_core_contract: core_contract, l1_block_metrics })
+        Ok(Self { provider: Arc::new(provider), l1_core_contract: core_contract, l1_block_metrics, transport })
+    }
+
+    /// Changes every time the active L1 endpoint changes. Filters registered on the previous endpoint, such as event
+    /// watchers, need to be registered again.
+    pub fn subscribe_endpoint_switch(&self) -> watch::Receiver<u64> {
+        self.transport.subscribe_endpoint_switch()
     }
 
     /// Assert that L1 Core contract exists by checking its bytecode.
-    async fn assert_core_contract_exists(
-        provider: &RootProvider<Http<Client>>,
-        l1_core_address: Address,
-    ) -> anyhow::Result<()> {
+    async fn assert_core_contract_exists(provider: &L1Provider, l1_core_address: Address) -> anyhow::Result<()> {
         let l1_core_contract_bytecode = provider.get_code_at(l1_core_address).await?;
         if l1_core_contract_bytecode.is_empty() {
             bail!("The L1 Core Contract could not be found. Check that the L2 chain matches the L1 RPC endpoint.");
@@ -157,6 +249,77 @@ impl EthereumClient {
         let block_hash = self.l1_core_contract.stateBlockHash().call().await?;
         u256_to_felt(block_hash._0)
     }
+
+    /// Get the last Starknet state verified on L1: block number, state root and block hash, all read at the same L1
+    /// block.
+    ///
+    /// When the state read quorum is more than 1, the endpoints first agree on an L1 block: the latest one known to
+    /// at least `state_read_quorum` endpoints. The state is then read at that block on every endpoint, and returned
+    /// only when at least `state_read_quorum` endpoints agree on all of it.
+    pub async fn get_last_verified_state(&self) -> anyhow::Result<L1StateUpdate> {
+        let quorum = self.transport.config().state_read_quorum;
+        if quorum <= 1 {
+            let l1_block = self.provider.get_block_number().await.context("Getting the latest L1 block number")?;
+            return read_core_contract_state(&self.l1_core_contract, l1_block).await;
+        }
+
+        let contracts: Vec<_> = self
+            .transport
+            .endpoints()
+            .into_iter()
+            .map(|transport| {
+                let provider = RootProvider::new(RpcClient::new(transport.clone(), transport.is_local()));
+                StarknetCoreContract::new(*self.l1_core_contract.address(), provider)
+            })
+            .collect();
+
+        let mut l1_blocks: Vec<u64> =
+            futures::future::join_all(contracts.iter().map(|contract| contract.provider().get_block_number()))
+                .await
+                .into_iter()
+                .filter_map(|res| res.inspect_err(|err| tracing::warn!("⟠ L1 block number read failed: {err:#}")).ok())
+                .collect();
+        l1_blocks.sort_unstable_by(|a, b| b.cmp(a));
+        let l1_block = *l1_blocks
+            .get(quorum - 1)
+            .with_context(|| format!("Fewer than {quorum} L1 endpoints are available (got {})", l1_blocks.len()))?;
+
+        let states: Vec<L1StateUpdate> =
+            futures::future::join_all(contracts.iter().map(|contract| read_core_contract_state(contract, l1_block)))
+                .await
+                .into_iter()
+                .filter_map(|res| res.inspect_err(|err| tracing::warn!("⟠ L1 state read failed: {err:#}")).ok())
+                .collect();
+
+        states
+            .iter()
+            .find(|state| states.iter().filter(|other| other == state).count() >= quorum)
+            .cloned()
+            .with_context(|| {
+                format!(
+                    "Fewer than {quorum} L1 endpoints agree on the core contract state at L1 block {l1_block} (got \
+                     {states:?})"
+                )
+            })
+    }
+}
+
+/// Read the state of the core contract at L1 block `l1_block`.
+async fn read_core_contract_state(
+    contract: &StarknetCoreContractInstance<FailoverTransport, L1Provider>,
+    l1_block: u64,
+) -> anyhow::Result<L1StateUpdate> {
+    let at = BlockId::number(l1_block);
+    let (block_number, global_root, block_hash) = futures::try_join!(
+        contract.stateBlockNumber().block(at).call(),
+        contract.stateRoot().block(at).call(),
+        contract.stateBlockHash().block(at).call(),
+    )?;
+    Ok(L1StateUpdate {
+        block_number: block_number._0.into_raw().try_into().context("L1 state block number out of range")?,
+        global_root: u256_to_felt(global_root._0)?,
+        block_hash: u256_to_felt(block_hash._0)?,
+    })
 }
 
 #[cfg(test)]
@@ -165,6 +328,7 @@ pub mod eth_client_getter_test {
     use alloy::{
         node_bindings::{Anvil, AnvilInstance},
         primitives::U256,
+        providers::ProviderBuilder,
     };
 
     use serial_test::serial;
@@ -210,6 +374,45 @@ pub mod eth_client_getter_test {
         AnvilPortNum(guard.next.next().expect("no more port to use"))
     }
 
+    pub const PROGRAM_HASH: u64 = 0x1234;
+    pub const CONFIG_HASH: u64 = 0x5678;
+
+    sol!(
+        #[sol(rpc, bytecode="606460005560c860025561123460035561567860045561012f806100236000396000f360003560e01c80639588eca21461004d57806335befa5d14610054578063382d83e31461005b5780638a9bf09014610062578063e1f1176d14610069578063775526411461007a575b600080fd5b6000610070565b6001610070565b6002610070565b6003610070565b6004610070565b5460005260206000f35b60043560040180356008116100485760200180600001356000541415610048578060400135600154141561004857806060013560016001540114156100485780608001356002541415610048578060c001356003541415610048578060e001356004541415610048578060200135806000556000528060600135806001556020528060a00135806002556040527fd342ddf7a308dec111745b00315c14b7efb2bdae570a6856e088ed0c65a3576c60606000a100")]
+        /// Minimal core contract, starting at block #0 with state root 100 and block hash 200. `updateState` checks the
+        /// program output header against its state and OS hashes, reverting on mismatch, then stores the new state
+        /// and emits `LogStateUpdate`. The bytecode is hand-written, equivalent to this contract.
+        contract MockCoreContract {
+            event LogStateUpdate(uint256 globalRoot, int256 blockNumber, uint256 blockHash);
+
+            uint256 public stateRoot = 100;
+            int256 public stateBlockNumber = 0;
+            uint256 public stateBlockHash = 200;
+            uint256 public programHash = 0x1234;
+            uint256 public configHash = 0x5678;
+
+            function updateState(uint256[] calldata programOutput, uint256, uint256) external {
+                require(programOutput.length >= 8);
+                require(programOutput[0] == stateRoot);
+                require(programOutput[2] == uint256(stateBlockNumber));
+                require(programOutput[3] == uint256(stateBlockNumber + 1));
+                require(programOutput[4] == stateBlockHash);
+                require(programOutput[6] == programHash);
+                require(programOutput[7] == configHash);
+                stateRoot = programOutput[1];
+                stateBlockNumber = int256(programOutput[3]);
+                stateBlockHash = programOutput[5];
+                emit LogStateUpdate(stateRoot, stateBlockNumber, stateBlockHash);
+            }
+        }
+    );
+
+    /// Deploy a [`MockCoreContract`] from the second anvil account. Its address is the same on every fresh anvil.
+    pub async fn deploy_mock_core_contract(anvil: &AnvilInstance) -> Address {
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+        MockCoreContract::deploy_builder(provider).from(anvil.addresses()[1]).deploy().await.unwrap()
+    }
+
     static ANVIL: Mutex<Option<Arc<AnvilInstance>>> = Mutex::new(None);
 
     pub fn get_shared_anvil() -> Arc<AnvilInstance> {
@@ -236,13 +439,10 @@ pub mod eth_client_getter_test {
     pub fn create_ethereum_client(url: Option<&str>) -> EthereumClient {
         let rpc_url: Url = url.unwrap_or("http://localhost:8545").parse().expect("issue while parsing URL");
 
-        let provider = ProviderBuilder::new().on_http(rpc_url.clone());
         let address = Address::parse_checksummed(CORE_CONTRACT_ADDRESS, None).unwrap();
-        let contract = StarknetCoreContract::new(address, provider.clone());
-
         let l1_block_metrics = L1BlockMetrics::register().unwrap();
 
-        EthereumClient { provider: Arc::new(provider), l1_core_contract: contract.clone(), l1_block_metrics }
+        EthereumClient::new_unchecked(vec![rpc_url], address, l1_block_metrics, FailoverConfig::default()).unwrap()
     }
 
     #[serial]
@@ -257,7 +457,9 @@ pub mod eth_client_getter_test {
         let core_contract_address = Address::parse_checksummed(INVALID_CORE_CONTRACT_ADDRESS, None).unwrap();
         let l1_block_metrics = L1BlockMetrics::register().unwrap();
 
-        let new_client_result = EthereumClient::new(rpc_url, core_contract_address, l1_block_metrics).await;
+        let new_client_result =
+            EthereumClient::new(vec![rpc_url], core_contract_address, l1_block_metrics, FailoverConfig::default())
+                .await;
         assert!(new_client_result.is_err(), "EthereumClient::new should fail with an invalid core contract address");
     }
 
@@ -312,4 +514,73 @@ pub mod eth_client_getter_test {
         let block_number = eth_client.get_last_verified_block_number().await.expect("issue");
         assert_eq!(block_number, L2_BLOCK_NUMBER, "verified block number not matching");
     }
+
+    #[serial]
+    #[tokio::test]
+    async fn get_last_verified_state_works() {
+        let anvil = get_shared_anvil();
+        let eth_client = create_ethereum_client(Some(anvil.endpoint().as_str()));
+        let state = eth_client.get_last_verified_state().await.expect("issue while getting the verified state");
+        let expected = L1StateUpdate {
+            block_number: L2_BLOCK_NUMBER,
+            global_root: u256_to_felt(U256::from_str_radix(L2_STATE_ROOT, 10).unwrap()).unwrap(),
+            block_hash: u256_to_felt(U256::from_str_radix(L2_BLOCK_HASH, 10).unwrap()).unwrap(),
+        };
+        assert_eq!(state, expected, "verified state not matching");
+    }
+
+    fn quorum_client(anvils: &[AnvilInstance], core_contract: Address, state_read_quorum: usize) -> EthereumClient {
+        EthereumClient::new_unchecked(
+            anvils.iter().map(|anvil| anvil.endpoint_url()).collect(),
+            core_contract,
+            L1BlockMetrics::register().unwrap(),
+            FailoverConfig { state_read_quorum, ..Default::default() },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_last_verified_state_quorum() {
+        let ports = [get_port(), get_port(), get_port()];
+        let anvils: Vec<AnvilInstance> = ports
+            .iter()
+            .map(|port| Anvil::new().port(port.0).try_spawn().expect("failed to spawn anvil instance"))
+            .collect();
+        let mut core_contract = Address::ZERO;
+        for anvil in &anvils {
+            core_contract = deploy_mock_core_contract(anvil).await;
+        }
+        let providers: Vec<_> =
+            anvils.iter().map(|anvil| ProviderBuilder::new().on_http(anvil.endpoint_url())).collect();
+        let initial = L1StateUpdate { block_number: 0, global_root: Felt::from(100), block_hash: Felt::from(200) };
+        let updated = L1StateUpdate { block_number: 1, global_root: Felt::from(101), block_hash: Felt::from(201) };
+
+        assert_eq!(quorum_client(&anvils, core_contract, 3).get_last_verified_state().await.unwrap(), initial);
+
+        // The state is updated on the first two endpoints, the third one is one L1 block behind.
+        for (anvil, provider) in anvils.iter().zip(&providers).take(2) {
+            MockCoreContract::new(core_contract, provider.clone())
+                .updateState(
+                    [100, 101, 0, 1, 200, 201, PROGRAM_HASH, CONFIG_HASH].map(U256::from).to_vec(),
+                    U256::ZERO,
+                    U256::ZERO,
+                )
+                .from(anvil.addresses()[1])
+                .send()
+                .await
+                .unwrap()
+                .get_receipt()
+                .await
+                .unwrap();
+        }
+
+        // Three endpoints only agree on the L1 block before the update.
+        assert_eq!(quorum_client(&anvils, core_contract, 3).get_last_verified_state().await.unwrap(), initial);
+        assert_eq!(quorum_client(&anvils, core_contract, 2).get_last_verified_state().await.unwrap(), updated);
+
+        // At the same L1 block, the third endpoint disagrees with the first two.
+        let _: serde_json::Value = providers[2].raw_request("anvil_mine".into(), (1,)).await.unwrap();
+        assert!(quorum_client(&anvils, core_contract, 3).get_last_verified_state().await.is_err());
+        assert_eq!(quorum_client(&anvils, core_contract, 2).get_last_verified_state().await.unwrap(), updated);
+    }
 }
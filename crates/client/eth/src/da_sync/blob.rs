@@ -0,0 +1,155 @@
+//! Blob (EIP-4844) data availability.
+//!
+//! The Starknet OS commits to the data availability felts as the coefficients of a polynomial over the BLS12-381
+//! scalar field. A blob holds the evaluations of that polynomial on the 4096th roots of unity, in bit-reversed order:
+//! the felts are recovered with an inverse FFT.
+
+use std::time::Duration;
+
+use alloy::primitives::{uint, Bytes, B256, U256};
+use anyhow::{ensure, Context};
+use starknet_types_core::felt::Felt;
+use url::Url;
+
+use crate::utils::{felt_to_u256, u256_to_felt};
+
+/// Number of field elements in a blob.
+pub const BLOB_LEN: usize = 4096;
+const BLOB_LEN_LOG2: u32 = 12;
+
+/// Modulus of the BLS12-381 scalar field.
+const BLS_MODULUS: U256 = uint!(52435875175126190479447740508185965837690552500527637822603658699938581184513_U256);
+/// Primitive 4096th root of unity of the BLS12-381 scalar field, `7^((BLS_MODULUS - 1) / 4096)`.
+const ROOT_OF_UNITY: U256 = uint!(39033254847818212395286706435128746857159659164139250548781411570340225835782_U256);
+
+/// Client for an endpoint serving blobs by versioned hash.
+///
+/// Blobs are fetched with `GET <endpoint>/blobs/<versioned hash>`, which answers `{ "data": "0x<blob bytes>" }`.
+pub struct BlobClient {
+    client: reqwest::Client,
+    endpoint: Url,
+}
+
+#[derive(serde::Deserialize)]
+struct BlobResponse {
+    data: Bytes,
+}
+
+impl BlobClient {
+    /// Requests taking longer than `timeout` fail, and are retried with the next poll.
+    pub fn new(endpoint: Url, timeout: Duration) -> anyhow::Result<Self> {
+        let client =
+            reqwest::Client::builder().timeout(timeout).build().context("Building the blob endpoint HTTP client")?;
+        Ok(Self { client, endpoint })
+    }
+
+    pub async fn get_blob(&self, versioned_hash: B256) -> anyhow::Result<Vec<u8>> {
+        let url = self
+            .endpoint
+            .join(&format!("blobs/{versioned_hash}"))
+            .with_context(|| format!("Invalid blob endpoint {}", self.endpoint))?;
+        let response: BlobResponse = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Fetching blob {versioned_hash}"))?
+            .json()
+            .await
+            .with_context(|| format!("Reading blob {versioned_hash}"))?;
+        Ok(response.data.into())
+    }
+}
+
+/// Recovers the data availability felts of a blob.
+pub fn blob_to_felts(blob: &[u8]) -> anyhow::Result<Vec<Felt>> {
+    ensure!(blob.len() == BLOB_LEN * 32, "Invalid blob length {}", blob.len());
+    let mut values = blob
+        .chunks_exact(32)
+        .map(|chunk| {
+            let value = U256::from_be_slice(chunk);
+            ensure!(value < BLS_MODULUS, "Blob field element {value:#x} out of range");
+            Ok(value)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // The evaluations are in bit-reversed order, which is the input order of the FFT.
+    fft_bit_reversed_input(&mut values, inverse(ROOT_OF_UNITY));
+    let len_inverse = inverse(U256::from(BLOB_LEN));
+
+    let felt_max = felt_to_u256(Felt::MAX);
+    values
+        .into_iter()
+        .map(|value| {
+            let coefficient = value.mul_mod(len_inverse, BLS_MODULUS);
+            ensure!(coefficient <= felt_max, "Blob coefficient {coefficient:#x} is not a felt");
+            u256_to_felt(coefficient)
+        })
+        .collect()
+}
+
+fn inverse(value: U256) -> U256 {
+    value.pow_mod(BLS_MODULUS - U256::from(2), BLS_MODULUS)
+}
+
+fn bit_reverse(index: usize) -> usize {
+    index.reverse_bits() >> (usize::BITS - BLOB_LEN_LOG2)
+}
+
+/// Iterative radix-2 FFT over the BLS12-381 scalar field, taking its input in bit-reversed order and returning its
+/// output in natural order.
+fn fft_bit_reversed_input(values: &mut [U256], root: U256) {
+    let mut len = 2;
+    while len <= values.len() {
+        let step_root = root.pow_mod(U256::from(values.len() / len), BLS_MODULUS);
+        for chunk in values.chunks_exact_mut(len) {
+            let (low, high) = chunk.split_at_mut(len / 2);
+            let mut w = U256::from(1);
+            for (u, v) in low.iter_mut().zip(high.iter_mut()) {
+                let t = v.mul_mod(w, BLS_MODULUS);
+                *v = u.add_mod(BLS_MODULUS - t, BLS_MODULUS);
+                *u = u.add_mod(t, BLS_MODULUS);
+                w = w.mul_mod(step_root, BLS_MODULUS);
+            }
+        }
+        len *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes felts in a blob, the way the Starknet OS does.
+    fn felts_to_blob(felts: &[Felt]) -> Vec<u8> {
+        let mut coefficients = vec![U256::ZERO; BLOB_LEN];
+        for (i, felt) in felts.iter().enumerate() {
+            coefficients[bit_reverse(i)] = felt_to_u256(*felt);
+        }
+        // Evaluations in natural order.
+        fft_bit_reversed_input(&mut coefficients, ROOT_OF_UNITY);
+        (0..BLOB_LEN).flat_map(|i| coefficients[bit_reverse(i)].to_be_bytes::<32>()).collect()
+    }
+
+    #[test]
+    fn test_root_of_unity() {
+        assert_eq!(ROOT_OF_UNITY.pow_mod(U256::from(BLOB_LEN), BLS_MODULUS), U256::from(1));
+        assert_ne!(ROOT_OF_UNITY.pow_mod(U256::from(BLOB_LEN / 2), BLS_MODULUS), U256::from(1));
+    }
+
+    #[test]
+    fn test_blob_to_felts() {
+        let felts = vec![Felt::from(3), Felt::MAX, Felt::ZERO, Felt::from_hex_unchecked("0x1234567890abcdef")];
+        let blob = felts_to_blob(&felts);
+        // Not the felts themselves.
+        assert_ne!(&blob[..32], &Felt::from(3).to_bytes_be());
+
+        let decoded = blob_to_felts(&blob).unwrap();
+        assert_eq!(decoded.len(), BLOB_LEN);
+        assert_eq!(&decoded[..felts.len()], &felts[..]);
+        assert!(decoded[felts.len()..].iter().all(|felt| *felt == Felt::ZERO));
+
+        assert!(blob_to_felts(&blob[..32]).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+//! Reconstruction of the state of the chain from the data availability posted on L1, without any gateway.
+//!
+//! Every `LogStateUpdate` event of the core contract is emitted by an `updateState` or `updateStateKzgDA`
+//! transaction, whose program output follows the layout of the Starknet OS output (see
+//! [`crate::settlement::build_update_state_call`]). The state diff is read from the calldata or from the blobs of that
+//! transaction, decoded with [`StateDiff::from_da_encoding`], and applied to the global tries. The resulting state root
+//! is checked against the one the core contract verified.
+//!
+//! Only the state diffs are part of the data availability: blocks are stored without their transactions and receipts,
+//! and their block hash is trusted. The uncompressed data availability encoding is expected, one block per state
+//! update.
+
+pub mod blob;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::primitives::B256;
+use alloy::providers::Provider;
+use alloy::sol_types::SolCall;
+use anyhow::{bail, ensure, Context};
+use mc_block_import::{BlockImporter, BlockValidationContext, StateDiffBlock};
+use mc_db::MadaraBackend;
+use mp_block::{BlockId, BlockTag};
+use mp_state_update::StateDiff;
+use mp_utils::service::ServiceContext;
+use starknet_types_core::felt::Felt;
+use url::Url;
+
+use crate::client::EthereumClient;
+use crate::client::StarknetCoreContract::{updateStateCall, updateStateKzgDACall, LogStateUpdate};
+use crate::state_update::L1StateUpdate;
+use crate::utils::{convert_log_state_update, u256_to_felt};
+use blob::{blob_to_felts, BlobClient};
+
+/// Number of fields of the program output header.
+const PROGRAM_OUTPUT_HEADER_LEN: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct DaSyncConfig {
+    /// Endpoint serving the blobs of the state updates using blob data availability, see [`BlobClient`].
+    pub blob_endpoint: Option<Url>,
+    /// Timeout of the requests to the blob endpoint.
+    pub blob_request_timeout: Duration,
+    /// L1 block from which the state updates are looked for, usually the deployment block of the core contract.
+    pub from_l1_block: u64,
+    /// Number of L1 blocks queried at once for state updates.
+    pub l1_blocks_per_query: u64,
+    pub poll_interval: Duration,
+}
+
+impl Default for DaSyncConfig {
+    fn default() -> Self {
+        Self {
+            blob_endpoint: None,
+            blob_request_timeout: Duration::from_secs(30),
+            from_l1_block: 0,
+            l1_blocks_per_query: 10_000,
+            poll_interval: Duration::from_secs(12),
+        }
+    }
+}
+
+/// The fields of a program output used to reconstruct a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramOutput {
+    pub previous_root: Felt,
+    pub new_root: Felt,
+    /// `None` when the state update starts from the genesis.
+    pub previous_block_number: Option<u64>,
+    pub block_number: u64,
+    pub previous_block_hash: Felt,
+    pub block_hash: Felt,
+    pub use_kzg_da: bool,
+    /// State diff posted as calldata, `None` when using blob data availability.
+    pub calldata_da: Option<Vec<Felt>>,
+}
+
+fn felt_to_u64(felt: Felt, name: &str) -> anyhow::Result<u64> {
+    felt.try_into().map_err(|_| anyhow::anyhow!("{name} {felt:#x} out of range"))
+}
+
+/// Parses a program output with the layout of the Starknet OS output: `[initial root, final root, previous block
+/// number, block number, previous block hash, block hash, OS program hash, OS config hash, use KZG DA, full output,
+/// messages to L1 segment, messages to L2 segment, state diff]`. The state diff is only part of the program output
+/// without blob data availability.
+pub fn parse_program_output(output: &[Felt]) -> anyhow::Result<ProgramOutput> {
+    ensure!(output.len() >= PROGRAM_OUTPUT_HEADER_LEN, "Program output too short: {} felts", output.len());
+    let header = &output[..PROGRAM_OUTPUT_HEADER_LEN];
+    let use_kzg_da = header[8] != Felt::ZERO;
+    ensure!(header[9] == Felt::ZERO, "Full output program outputs are not supported");
+
+    let calldata_da = if use_kzg_da {
+        None
+    } else {
+        // Skip the messages to L1 and messages to L2 segments.
+        let mut rest = &output[PROGRAM_OUTPUT_HEADER_LEN..];
+        for segment in ["messages to L1", "messages to L2"] {
+            let (len, segment_data) = rest.split_first().with_context(|| format!("Missing {segment} segment"))?;
+            let len = felt_to_u64(*len, "Segment length")? as usize;
+            ensure!(len <= segment_data.len(), "Truncated {segment} segment");
+            rest = &segment_data[len..];
+        }
+        Some(rest.to_vec())
+    };
+
+    Ok(ProgramOutput {
+        previous_root: header[0],
+        new_root: header[1],
+        // -1 in the field before the first block.
+        previous_block_number: (header[2] != Felt::MAX)
+            .then(|| felt_to_u64(header[2], "Previous block number"))
+            .transpose()?,
+        block_number: felt_to_u64(header[3], "Block number")?,
+        previous_block_hash: header[4],
+        block_hash: header[5],
+        use_kzg_da,
+        calldata_da,
+    })
+}
+
+pub struct DaSync {
+    backend: Arc<MadaraBackend>,
+    eth_client: Arc<EthereumClient>,
+    block_importer: Arc<BlockImporter>,
+    blob_client: Option<BlobClient>,
+    config: DaSyncConfig,
+}
+
+impl DaSync {
+    pub fn new(
+        backend: Arc<MadaraBackend>,
+        eth_client: Arc<EthereumClient>,
+        block_importer: Arc<BlockImporter>,
+        config: DaSyncConfig,
+    ) -> anyhow::Result<Self> {
+        let blob_client = config
+            .blob_endpoint
+            .clone()
+            .map(|endpoint| BlobClient::new(endpoint, config.blob_request_timeout))
+            .transpose()?;
+        Ok(Self { backend, eth_client, block_importer, blob_client, config })
+    }
+
+    fn next_block_n(&self) -> anyhow::Result<u64> {
+        Ok(self.backend.get_latest_block_n()?.map_or(0, |block_n| block_n + 1))
+    }
+
+    /// Import the blocks of the state updates between the L1 blocks `from_l1_block` and `to_l1_block` included.
+    async fn sync_range(&self, from_l1_block: u64, to_l1_block: u64) -> anyhow::Result<()> {
+        let logs = self.eth_client.get_core_contract_logs::<LogStateUpdate>(from_l1_block, to_l1_block).await?;
+        for log in logs {
+            let state_update = convert_log_state_update(log.inner.data)?;
+            let next_block_n = self.next_block_n()?;
+            if state_update.block_number < next_block_n {
+                continue;
+            }
+            if state_update.block_number > next_block_n {
+                bail!(
+                    "State update for block #{} found while expecting block #{next_block_n}, the L1 block to start \
+                     from is probably too late",
+                    state_update.block_number
+                );
+            }
+
+            let tx_hash = log.transaction_hash.context("No transaction hash in log")?;
+            self.import_state_update(tx_hash, &state_update).await.with_context(|| {
+                format!("Reconstructing block #{} from L1 transaction {tx_hash}", state_update.block_number)
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn import_state_update(&self, tx_hash: B256, state_update: &L1StateUpdate) -> anyhow::Result<()> {
+        let tx = self
+            .eth_client
+            .provider
+            .get_transaction_by_hash(tx_hash)
+            .await?
+            .context("State update transaction not found")?;
+
+        let program_output = if let Ok(call) = updateStateCall::abi_decode(&tx.input, true) {
+            call.programOutput
+        } else if let Ok(call) = updateStateKzgDACall::abi_decode(&tx.input, true) {
+            call.programOutput
+        } else {
+            bail!("The state update transaction is not an updateState call")
+        };
+        let program_output = program_output.into_iter().map(u256_to_felt).collect::<anyhow::Result<Vec<_>>>()?;
+        let output = parse_program_output(&program_output)?;
+
+        ensure!(
+            output.block_number == state_update.block_number && output.new_root == state_update.global_root,
+            "The program output does not match the state update event"
+        );
+        ensure!(
+            output.previous_block_number == state_update.block_number.checked_sub(1),
+            "State update from block #{:?} to #{}: reconstructing several blocks at once is not supported",
+            output.previous_block_number,
+            output.block_number
+        );
+
+        let da = match output.calldata_da {
+            Some(da) => da,
+            None => {
+                let blob_client = self
+                    .blob_client
+                    .as_ref()
+                    .context("The state update uses blob data availability, but no blob endpoint was provided")?;
+                let mut da = vec![];
+                for versioned_hash in tx.blob_versioned_hashes.iter().flatten() {
+                    da.extend(blob_to_felts(&blob_client.get_blob(*versioned_hash).await?)?);
+                }
+                da
+            }
+        };
+
+        let latest = BlockId::Tag(BlockTag::Latest);
+        let (state_diff, _) = StateDiff::from_da_encoding(&da, |address| {
+            anyhow::Ok((
+                self.backend.get_contract_class_hash_at(&latest, address)?,
+                self.backend.get_contract_nonce_at(&latest, address)?.unwrap_or_default(),
+            ))
+        })
+        .map_err(|err| anyhow::anyhow!("Decoding the state diff: {err:#}"))?;
+
+        let block = StateDiffBlock {
+            block_number: output.block_number,
+            parent_block_hash: output.previous_block_hash,
+            block_hash: output.block_hash,
+            global_state_root: output.new_root,
+            state_diff,
+        };
+        let validation = BlockValidationContext::new(self.backend.chain_config().chain_id.clone());
+        self.block_importer.import_state_diff_block(block, validation).await?;
+
+        tracing::info!(
+            "📦 Reconstructed block #{} from L1 data availability, state root {:#x}",
+            output.block_number,
+            output.new_root
+        );
+        Ok(())
+    }
+}
+
+/// Follow the state updates of the core contract, and reconstruct the blocks from their data availability.
+pub async fn da_sync_worker(da_sync: DaSync, mut ctx: ServiceContext) -> anyhow::Result<()> {
+    tracing::info!("⛓️  Reconstructing the state from L1 data availability, from block #{}", da_sync.next_block_n()?);
+
+    let mut from_l1_block = da_sync.config.from_l1_block;
+    let mut interval = tokio::time::interval(da_sync.config.poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    while ctx.run_until_cancelled(interval.tick()).await.is_some() {
+        let latest_l1_block = match da_sync.eth_client.get_latest_block_number().await {
+            Ok(block) => block,
+            Err(err) => {
+                tracing::warn!("Failed to get the latest L1 block: {err:#}");
+                continue;
+            }
+        };
+
+        while from_l1_block <= latest_l1_block && !ctx.is_cancelled() {
+            let to_l1_block = latest_l1_block.min(from_l1_block + da_sync.config.l1_blocks_per_query - 1);
+            if let Err(err) = da_sync.sync_range(from_l1_block, to_l1_block).await {
+                tracing::warn!("Failed to reconstruct the state from L1, retrying: {err:#}");
+                break;
+            }
+            from_l1_block = to_l1_block + 1;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settlement::build_update_state_call;
+    use mp_block::{Header, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo};
+    use mp_chain_config::ChainConfig;
+    use mp_state_update::{ContractStorageDiffItem, DeployedContractItem, StorageEntry};
+
+    #[test]
+    fn test_parse_program_output() {
+        let backend = MadaraBackend::open_for_testing(Arc::new(ChainConfig::madara_test()));
+        let state_diff = StateDiff {
+            deployed_contracts: vec![DeployedContractItem { address: Felt::from(1), class_hash: Felt::from(2) }],
+            storage_diffs: vec![ContractStorageDiffItem {
+                address: Felt::from(1),
+                storage_entries: vec![StorageEntry { key: Felt::from(3), value: Felt::from(4) }],
+            }],
+            ..Default::default()
+        };
+        backend
+            .store_block(
+                MadaraMaybePendingBlock {
+                    info: MadaraMaybePendingBlockInfo::NotPending(MadaraBlockInfo {
+                        header: Header { block_number: 0, global_state_root: Felt::from(100), ..Default::default() },
+                        block_hash: Felt::from(200),
+                        tx_hashes: vec![],
+                    }),
+                    inner: MadaraBlockInner { transactions: vec![], receipts: vec![] },
+                },
+                state_diff.clone(),
+                vec![],
+                None,
+                None,
+            )
+            .unwrap();
+
+        let call = build_update_state_call(&backend, 0, Felt::ZERO, Felt::ZERO).unwrap();
+        let program_output = call.programOutput.into_iter().map(|felt| u256_to_felt(felt).unwrap()).collect::<Vec<_>>();
+        let output = parse_program_output(&program_output).unwrap();
+        assert_eq!(output.previous_block_number, None);
+        assert_eq!(output.block_number, 0);
+        assert_eq!(output.new_root, Felt::from(100));
+        assert_eq!(output.block_hash, Felt::from(200));
+        assert!(!output.use_kzg_da);
+
+        let (decoded, _) = StateDiff::from_da_encoding(&output.calldata_da.unwrap(), |_| {
+            Ok::<_, std::convert::Infallible>((None, Felt::ZERO))
+        })
+        .unwrap();
+        assert_eq!(decoded, state_diff);
+
+        assert!(parse_program_output(&program_output[..5]).is_err());
+    }
+}
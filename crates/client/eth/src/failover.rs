@@ -0,0 +1,321 @@
+//! An L1 transport spreading requests over several RPC endpoints.
+//!
+//! Requests go to the active endpoint. When it fails at the transport level (connection error, timeout, HTTP error,
+//! invalid response), the request is retried on the other endpoints, healthy ones first, and the first one to answer becomes
+//! the active endpoint. JSON-RPC error responses, such as reverted calls, are returned as is.
+//!
+//! When there are several endpoints, a background task checks their health periodically: an endpoint is healthy when
+//! it answers `eth_blockNumber` and is not lagging too far behind the others. The active endpoint always is the first
+//! healthy endpoint, in the order they were given, so that we switch back to the preferred endpoint once it recovers.
+//!
+//! Filters created with `eth_newFilter` only exist on the endpoint which created them: event watchers should
+//! re-subscribe when the active endpoint changes, see [`FailoverTransport::subscribe_endpoint_switch`].
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::http::{Client, Http};
+use alloy::transports::{TransportError, TransportErrorKind, TransportFut};
+use anyhow::{ensure, Context as _};
+use tokio::sync::watch;
+use tower::Service;
+use url::{Host, Url};
+
+#[derive(Debug, Clone)]
+pub struct FailoverConfig {
+    /// How often the health of the endpoints is checked.
+    pub health_check_interval: Duration,
+    /// An endpoint more than this many blocks behind the most advanced endpoint is considered unhealthy.
+    pub max_block_lag: u64,
+    /// Number of endpoints which must agree on the state of the core contract, see
+    /// [`crate::client::EthereumClient::get_last_verified_state`]. 1 reads from the active endpoint only.
+    pub state_read_quorum: usize,
+    /// A request taking longer than this is an endpoint failure, and is retried on the other endpoints.
+    pub request_timeout: Duration,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            health_check_interval: Duration::from_secs(10),
+            max_block_lag: 5,
+            state_read_quorum: 1,
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+struct Endpoint {
+    transport: Http<Client>,
+    /// Used for the health checks.
+    provider: ReqwestProvider,
+    /// Only the host is logged: the rest of the URL usually contains an API key.
+    name: String,
+    healthy: AtomicBool,
+}
+
+struct FailoverInner {
+    endpoints: Vec<Endpoint>,
+    active: AtomicUsize,
+    /// Incremented every time the active endpoint changes.
+    switches: watch::Sender<u64>,
+    is_local: bool,
+    config: FailoverConfig,
+}
+
+#[derive(Clone)]
+pub struct FailoverTransport {
+    inner: Arc<FailoverInner>,
+    /// Send all requests to this endpoint, without failover.
+    pinned: Option<usize>,
+}
+
+impl FailoverTransport {
+    /// Create the transport and, when there are several endpoints, start the health checks. The health check task
+    /// stops when the transport is dropped.
+    pub fn new(urls: Vec<Url>, config: FailoverConfig) -> anyhow::Result<Self> {
+        ensure!(!urls.is_empty(), "At least one L1 endpoint is required");
+        ensure!(
+            (1..=urls.len()).contains(&config.state_read_quorum),
+            "The L1 state read quorum ({}) must be between 1 and the number of L1 endpoints ({})",
+            config.state_read_quorum,
+            urls.len()
+        );
+
+        let is_local = urls.iter().all(|url| match url.host() {
+            Some(Host::Domain(domain)) => domain == "localhost",
+            Some(Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        });
+        let client = Client::builder()
+            .timeout(config.request_timeout)
+            .build()
+            .context("Building the L1 endpoints HTTP client")?;
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                name: url.host_str().unwrap_or("unknown host").to_owned(),
+                transport: Http::with_client(client.clone(), url.clone()),
+                provider: ProviderBuilder::new().on_http(url),
+                healthy: AtomicBool::new(true),
+            })
+            .collect::<Vec<_>>();
+        let n_endpoints = endpoints.len();
+        let inner = Arc::new(FailoverInner {
+            endpoints,
+            active: AtomicUsize::new(0),
+            switches: watch::channel(0).0,
+            is_local,
+            config,
+        });
+
+        if n_endpoints > 1 {
+            tokio::spawn(health_check_task(Arc::downgrade(&inner)));
+        }
+        Ok(Self { inner, pinned: None })
+    }
+
+    /// Whether all the endpoints are local, in which case providers poll them more often.
+    pub fn is_local(&self) -> bool {
+        self.inner.is_local
+    }
+
+    pub fn config(&self) -> &FailoverConfig {
+        &self.inner.config
+    }
+
+    /// One transport per endpoint, without failover.
+    pub fn endpoints(&self) -> Vec<FailoverTransport> {
+        (0..self.inner.endpoints.len()).map(|i| Self { inner: Arc::clone(&self.inner), pinned: Some(i) }).collect()
+    }
+
+    /// Changes every time the active endpoint changes.
+    pub fn subscribe_endpoint_switch(&self) -> watch::Receiver<u64> {
+        self.inner.switches.subscribe()
+    }
+
+    async fn send(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let inner = &self.inner;
+        if let Some(pinned) = self.pinned {
+            return inner.endpoints[pinned].transport.clone().call(request).await;
+        }
+
+        // Active endpoint first, then the healthy endpoints, then the unhealthy ones as a last resort.
+        let active = inner.active.load(Ordering::Relaxed);
+        let mut candidates: Vec<usize> = (0..inner.endpoints.len()).filter(|i| *i != active).collect();
+        candidates.sort_by_key(|i| !inner.endpoints[*i].healthy.load(Ordering::Relaxed));
+        candidates.insert(0, active);
+
+        let mut last_error = None;
+        for i in candidates {
+            let endpoint = &inner.endpoints[i];
+            match endpoint.transport.clone().call(request.clone()).await {
+                Ok(response) => {
+                    endpoint.healthy.store(true, Ordering::Relaxed);
+                    inner.set_active(i);
+                    return Ok(response);
+                }
+                Err(err) => {
+                    tracing::warn!("⟠ L1 endpoint {} failed: {err:#}", endpoint.name);
+                    endpoint.healthy.store(false, Ordering::Relaxed);
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| TransportErrorKind::custom_str("No L1 endpoint")))
+    }
+}
+
+impl FailoverInner {
+    fn set_active(&self, i: usize) {
+        let previous = self.active.swap(i, Ordering::Relaxed);
+        if previous != i {
+            tracing::warn!(
+                "⟠ Switching L1 endpoint from {} to {}",
+                self.endpoints[previous].name,
+                self.endpoints[i].name
+            );
+            self.switches.send_modify(|switches| *switches += 1);
+        }
+    }
+
+    async fn check_health(&self) {
+        let timeout = self.config.health_check_interval;
+        let block_numbers = futures::future::join_all(self.endpoints.iter().map(|endpoint| async {
+            match tokio::time::timeout(timeout, endpoint.provider.get_block_number()).await {
+                Ok(Ok(block_number)) => Some(block_number),
+                Ok(Err(err)) => {
+                    tracing::debug!("⟠ L1 endpoint {} health check failed: {err:#}", endpoint.name);
+                    None
+                }
+                Err(_) => {
+                    tracing::debug!("⟠ L1 endpoint {} health check timed out", endpoint.name);
+                    None
+                }
+            }
+        }))
+        .await;
+
+        let highest = block_numbers.iter().flatten().max().copied().unwrap_or_default();
+        for (endpoint, block_number) in self.endpoints.iter().zip(&block_numbers) {
+            let healthy = block_number.is_some_and(|n| highest - n <= self.config.max_block_lag);
+            if endpoint.healthy.swap(healthy, Ordering::Relaxed) != healthy {
+                tracing::info!(
+                    "⟠ L1 endpoint {} is now {} (block {block_number:?}, highest {highest})",
+                    endpoint.name,
+                    if healthy { "healthy" } else { "unhealthy" }
+                );
+            }
+        }
+
+        if let Some(i) = self.endpoints.iter().position(|endpoint| endpoint.healthy.load(Ordering::Relaxed)) {
+            self.set_active(i);
+        }
+    }
+}
+
+async fn health_check_task(inner: Weak<FailoverInner>) {
+    let Some(interval) = inner.upgrade().map(|inner| inner.config.health_check_interval) else { return };
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let Some(inner) = inner.upgrade() else { return };
+        inner.check_health().await;
+    }
+}
+
+impl Service<RequestPacket> for FailoverTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::eth_client_getter_test::get_port;
+    use alloy::node_bindings::Anvil;
+    use alloy::providers::RootProvider;
+    use alloy::rpc::client::RpcClient;
+
+    #[tokio::test]
+    async fn test_failover() {
+        let (port_a, port_b) = (get_port(), get_port());
+        let anvil_a = Anvil::new().port(port_a.0).try_spawn().expect("failed to spawn anvil instance");
+        let anvil_b = Anvil::new().port(port_b.0).try_spawn().expect("failed to spawn anvil instance");
+        // Tell the endpoints apart by their block number.
+        let provider_b = ProviderBuilder::new().on_http(anvil_b.endpoint_url());
+        let _: serde_json::Value = provider_b.raw_request("anvil_mine".into(), (3,)).await.unwrap();
+
+        let transport = FailoverTransport::new(
+            vec![anvil_a.endpoint_url(), anvil_b.endpoint_url()],
+            FailoverConfig { health_check_interval: Duration::from_secs(3600), ..Default::default() },
+        )
+        .unwrap();
+        let mut switches = transport.subscribe_endpoint_switch();
+        let provider = RootProvider::new(RpcClient::new(transport.clone(), true));
+        assert_eq!(provider.get_block_number().await.unwrap(), 0);
+
+        drop(anvil_a);
+        assert_eq!(provider.get_block_number().await.unwrap(), 3);
+        assert!(switches.has_changed().unwrap());
+        switches.borrow_and_update();
+
+        // The health checks switch back to the preferred endpoint once it recovers.
+        let anvil_a = Anvil::new().port(port_a.0).try_spawn().expect("failed to spawn anvil instance");
+        transport.inner.check_health().await;
+        assert!(transport.inner.endpoints[0].healthy.load(Ordering::Relaxed));
+        assert_eq!(provider.get_block_number().await.unwrap(), 0);
+        assert!(switches.has_changed().unwrap());
+
+        let pinned = transport.endpoints();
+        let provider_b = RootProvider::new(RpcClient::new(pinned[1].clone(), true));
+        assert_eq!(provider_b.get_block_number().await.unwrap(), 3);
+        drop(anvil_a);
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_timeout() {
+        // An endpoint accepting connections but never answering.
+        let hanging = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hanging_url = Url::parse(&format!("http://{}", hanging.local_addr().unwrap())).unwrap();
+        let _hanging_task = tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((stream, _)) = hanging.accept().await {
+                connections.push(stream);
+            }
+        });
+        let port = get_port();
+        let anvil = Anvil::new().port(port.0).try_spawn().expect("failed to spawn anvil instance");
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+        let _: serde_json::Value = provider.raw_request("anvil_mine".into(), (3,)).await.unwrap();
+
+        let transport = FailoverTransport::new(
+            vec![hanging_url, anvil.endpoint_url()],
+            FailoverConfig {
+                health_check_interval: Duration::from_secs(3600),
+                request_timeout: Duration::from_millis(500),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let provider = RootProvider::new(RpcClient::new(transport.clone(), true));
+        assert_eq!(provider.get_block_number().await.unwrap(), 3);
+        assert!(!transport.inner.endpoints[0].healthy.load(Ordering::Relaxed));
+        assert_eq!(transport.inner.active.load(Ordering::Relaxed), 1);
+    }
+}
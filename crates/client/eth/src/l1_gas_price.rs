@@ -1,4 +1,5 @@
 use crate::client::EthereumClient;
+use crate::l1_gas_price_strategy::L1GasPriceStrategy;
 use alloy::eips::BlockNumberOrTag;
 use alloy::providers::Provider;
 use anyhow::Context;
@@ -15,9 +16,10 @@ use std::time::SystemTime;
 pub async fn gas_price_worker_once(
     eth_client: &EthereumClient,
     l1_gas_provider: &GasPriceProvider,
+    strategy: &L1GasPriceStrategy,
     gas_price_poll_ms: Duration,
 ) -> anyhow::Result<()> {
-    match update_gas_price(eth_client, l1_gas_provider).await {
+    match update_gas_price(eth_client, l1_gas_provider, strategy).await {
         Ok(_) => tracing::trace!("Updated gas prices"),
         Err(e) => tracing::error!("Failed to update gas prices: {:?}", e),
     }
@@ -40,6 +42,7 @@ pub async fn gas_price_worker_once(
 pub async fn gas_price_worker(
     eth_client: Arc<EthereumClient>,
     l1_gas_provider: GasPriceProvider,
+    strategy: L1GasPriceStrategy,
     gas_price_poll_ms: Duration,
     mut ctx: ServiceContext,
 ) -> anyhow::Result<()> {
@@ -48,54 +51,70 @@ pub async fn gas_price_worker(
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
     while ctx.run_until_cancelled(interval.tick()).await.is_some() {
-        gas_price_worker_once(&eth_client, &l1_gas_provider, gas_price_poll_ms).await?;
+        gas_price_worker_once(&eth_client, &l1_gas_provider, &strategy, gas_price_poll_ms).await?;
     }
 
     anyhow::Ok(())
 }
 
-async fn update_gas_price(eth_client: &EthereumClient, l1_gas_provider: &GasPriceProvider) -> anyhow::Result<()> {
+async fn update_gas_price(
+    eth_client: &EthereumClient,
+    l1_gas_provider: &GasPriceProvider,
+    strategy: &L1GasPriceStrategy,
+) -> anyhow::Result<()> {
     let block_number = eth_client.get_latest_block_number().await?;
-    let fee_history = eth_client.provider.get_fee_history(300, BlockNumberOrTag::Number(block_number), &[]).await?;
-
-    // The RPC responds with 301 elements for some reason. It's also just safer to manually
-    // take the last 300. We choose 300 to get average gas caprice for last one hour (300 * 12 sec block
-    // time).
-    let (_, blob_fee_history_one_hour) =
-        fee_history.base_fee_per_blob_gas.split_at(fee_history.base_fee_per_blob_gas.len().max(300) - 300);
-
-    let avg_blob_base_fee = if !blob_fee_history_one_hour.is_empty() {
-        blob_fee_history_one_hour.iter().sum::<u128>() / blob_fee_history_one_hour.len() as u128
-    } else {
-        0 // in case blob_fee_history_one_hour has 0 length
-    };
-
-    let eth_gas_price = fee_history.base_fee_per_gas.last().context("Getting eth gas price")?;
-
-    l1_gas_provider.update_eth_l1_gas_price(*eth_gas_price);
-    l1_gas_provider.update_eth_l1_data_gas_price(avg_blob_base_fee);
+    let fee_history = eth_client
+        .provider
+        .get_fee_history(strategy.fee_history_blocks(), BlockNumberOrTag::Number(block_number), &[])
+        .await?;
+
+    // The RPC responds with one more element than requested: the base fee of the next block. The strategies only use
+    // the last `window` elements.
+    let base_fee = *fee_history.base_fee_per_gas.last().context("Getting eth gas price")?;
+    let blob_base_fee = fee_history.base_fee_per_blob_gas.last().copied().unwrap_or_default();
+
+    let previous_prices = l1_gas_provider.get_gas_prices();
+    let eth_gas_price = strategy.gas.compute(&fee_history.base_fee_per_gas, previous_prices.eth_l1_gas_price);
+    let eth_data_gas_price =
+        strategy.data_gas.compute(&fee_history.base_fee_per_blob_gas, previous_prices.eth_l1_data_gas_price);
+    tracing::trace!(
+        "L1 base fee {base_fee} wei, blob base fee {blob_base_fee} wei: gas price {eth_gas_price} wei, data gas price \
+        {eth_data_gas_price} wei"
+    );
+
+    let metrics = &eth_client.l1_block_metrics;
+    metrics.l1_base_fee_wei.record(base_fee as u64, &[]);
+    metrics.l1_blob_base_fee_wei.record(blob_base_fee as u64, &[]);
+    metrics.l1_data_gas_price_wei.record(eth_data_gas_price as u64, &[]);
+
+    l1_gas_provider.update_eth_l1_gas_price(eth_gas_price);
+    l1_gas_provider.update_eth_l1_data_gas_price(eth_data_gas_price);
 
     // fetch eth/strk price and update
     if let Some(oracle_provider) = &l1_gas_provider.oracle_provider {
         let (eth_strk_price, decimals) =
             oracle_provider.fetch_eth_strk_price().await.context("failed to retrieve ETH/STRK price")?;
-        let strk_gas_price = (BigDecimal::new((*eth_gas_price).into(), decimals.into())
+        let strk_gas_price = (BigDecimal::new(eth_gas_price.into(), decimals.into())
             / BigDecimal::new(eth_strk_price.into(), decimals.into()))
         .as_bigint_and_exponent();
-        let strk_data_gas_price = (BigDecimal::new(avg_blob_base_fee.into(), decimals.into())
+        let strk_data_gas_price = (BigDecimal::new(eth_data_gas_price.into(), decimals.into())
             / BigDecimal::new(eth_strk_price.into(), decimals.into()))
         .as_bigint_and_exponent();
 
-        l1_gas_provider.update_strk_l1_gas_price(
-            strk_gas_price.0.to_str_radix(10).parse::<u128>().context("failed to update strk l1 gas price")?,
-        );
-        l1_gas_provider.update_strk_l1_data_gas_price(
-            strk_data_gas_price
-                .0
-                .to_str_radix(10)
-                .parse::<u128>()
-                .context("failed to update strk l1 data gas price")?,
-        );
+        let strk_gas_price =
+            strk_gas_price.0.to_str_radix(10).parse::<u128>().context("failed to update strk l1 gas price")?;
+        let strk_data_gas_price = strk_data_gas_price
+            .0
+            .to_str_radix(10)
+            .parse::<u128>()
+            .context("failed to update strk l1 data gas price")?;
+
+        metrics.eth_strk_price.record(eth_strk_price as f64 / 10f64.powi(decimals as i32), &[]);
+        metrics.l1_gas_price_strk.record(strk_gas_price as f64, &[]);
+        metrics.l1_data_gas_price_strk.record(strk_data_gas_price as f64, &[]);
+
+        l1_gas_provider.update_strk_l1_gas_price(strk_gas_price);
+        l1_gas_provider.update_strk_l1_data_gas_price(strk_data_gas_price);
     }
 
     l1_gas_provider.update_last_update_timestamp();
@@ -124,8 +143,6 @@ async fn update_l1_block_metrics(
     eth_client.l1_block_metrics.l1_block_number.record(latest_block_number, &[]);
     eth_client.l1_block_metrics.l1_gas_price_wei.record(eth_gas_price as u64, &[]);
 
-    // We're ignoring l1_gas_price_strk
-
     Ok(())
 }
 
@@ -155,6 +172,7 @@ mod eth_client_gas_price_worker_test {
                 gas_price_worker(
                     Arc::new(eth_client),
                     l1_gas_provider,
+                    L1GasPriceStrategy::default(),
                     Duration::from_millis(200),
                     ServiceContext::new_for_testing(),
                 )
@@ -192,7 +210,12 @@ mod eth_client_gas_price_worker_test {
         let l1_gas_provider = GasPriceProvider::new();
 
         // Run the worker for a short time
-        let worker_handle = gas_price_worker_once(&eth_client, &l1_gas_provider, Duration::from_millis(200));
+        let worker_handle = gas_price_worker_once(
+            &eth_client,
+            &l1_gas_provider,
+            &L1GasPriceStrategy::default(),
+            Duration::from_millis(200),
+        );
 
         // Wait for the worker to complete
         worker_handle.await.expect("issue with the gas worker");
@@ -213,7 +236,12 @@ mod eth_client_gas_price_worker_test {
         l1_gas_provider.set_gas_price_sync_enabled(false);
 
         // Run the worker for a short time
-        let worker_handle = gas_price_worker_once(&eth_client, &l1_gas_provider, Duration::from_millis(200));
+        let worker_handle = gas_price_worker_once(
+            &eth_client,
+            &l1_gas_provider,
+            &L1GasPriceStrategy::default(),
+            Duration::from_millis(200),
+        );
 
         // Wait for the worker to complete
         worker_handle.await.expect("issue with the gas worker");
@@ -234,7 +262,12 @@ mod eth_client_gas_price_worker_test {
         l1_gas_provider.set_data_gas_price_sync_enabled(false);
 
         // Run the worker for a short time
-        let worker_handle = gas_price_worker_once(&eth_client, &l1_gas_provider, Duration::from_millis(200));
+        let worker_handle = gas_price_worker_once(
+            &eth_client,
+            &l1_gas_provider,
+            &L1GasPriceStrategy::default(),
+            Duration::from_millis(200),
+        );
 
         // Wait for the worker to complete
         worker_handle.await.expect("issue with the gas worker");
@@ -287,6 +320,7 @@ mod eth_client_gas_price_worker_test {
             gas_price_worker(
                 Arc::new(eth_client),
                 l1_gas_provider.clone(),
+                L1GasPriceStrategy::default(),
                 Duration::from_millis(200),
                 ServiceContext::new_for_testing(),
             ),
@@ -321,7 +355,9 @@ mod eth_client_gas_price_worker_test {
         l1_gas_provider.update_last_update_timestamp();
 
         // Update gas prices
-        update_gas_price(&eth_client, &l1_gas_provider).await.expect("Failed to update gas prices");
+        update_gas_price(&eth_client, &l1_gas_provider, &L1GasPriceStrategy::default())
+            .await
+            .expect("Failed to update gas prices");
 
         // Access the updated gas prices
         let updated_prices = l1_gas_provider.get_gas_prices();
@@ -0,0 +1,192 @@
+//! How the L1 gas prices we charge are derived from the L1 fee history.
+//!
+//! The raw L1 base fees can swing a lot during congestion. A [`GasPriceStrategy`] smooths them over a window of L1
+//! blocks, adds a markup, limits how much the price can change between two updates, and clamps the result.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+/// How the base fees of the window are combined into a single price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasPriceSmoothing {
+    /// Latest base fee.
+    Last,
+    /// Average over the window.
+    Average,
+    /// Exponential moving average over the window, with a smoothing factor of `2 / (window + 1)`.
+    Ema,
+    /// Nearest-rank percentile over the window, between 1 and 100.
+    Percentile(u8),
+}
+
+impl FromStr for GasPriceSmoothing {
+    type Err = anyhow::Error;
+
+    /// Parses `last`, `average`, `ema`, or `p<percentile>` such as `p75`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "last" => Ok(Self::Last),
+            "average" => Ok(Self::Average),
+            "ema" => Ok(Self::Ema),
+            _ => {
+                let percentile: u8 = s.strip_prefix('p').and_then(|p| p.parse().ok()).with_context(|| {
+                    format!("Invalid gas price smoothing {s:?}: expected last, average, ema or p<1-100>")
+                })?;
+                if !(1..=100).contains(&percentile) {
+                    bail!("Invalid gas price percentile {percentile}: expected a value between 1 and 100");
+                }
+                Ok(Self::Percentile(percentile))
+            }
+        }
+    }
+}
+
+impl fmt::Display for GasPriceSmoothing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Last => write!(f, "last"),
+            Self::Average => write!(f, "average"),
+            Self::Ema => write!(f, "ema"),
+            Self::Percentile(percentile) => write!(f, "p{percentile}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasPriceStrategy {
+    pub smoothing: GasPriceSmoothing,
+    /// Number of L1 blocks the smoothing is computed over.
+    pub window: u64,
+    /// Added on top of the smoothed price, in percent.
+    pub markup_percent: u64,
+    pub min: Option<u128>,
+    pub max: Option<u128>,
+    /// Maximum change of the price between two updates, in percent of the previous price.
+    pub max_change_percent: Option<u64>,
+}
+
+impl GasPriceStrategy {
+    /// The latest L1 base fee, as is.
+    pub fn l1_gas_default() -> Self {
+        Self {
+            smoothing: GasPriceSmoothing::Last,
+            window: 1,
+            markup_percent: 0,
+            min: None,
+            max: None,
+            max_change_percent: None,
+        }
+    }
+
+    /// The average blob base fee over the last hour (300 L1 blocks of 12 seconds).
+    pub fn l1_data_gas_default() -> Self {
+        Self { smoothing: GasPriceSmoothing::Average, window: 300, ..Self::l1_gas_default() }
+    }
+
+    /// Smoothed base fee of the window. `history` holds the base fees in chronological order, only its last `window`
+    /// values are used.
+    pub fn smooth(&self, history: &[u128]) -> u128 {
+        let history = &history[history.len().saturating_sub(self.window.max(1) as usize)..];
+        let Some(&last) = history.last() else { return 0 };
+        match self.smoothing {
+            GasPriceSmoothing::Last => last,
+            GasPriceSmoothing::Average => history.iter().sum::<u128>() / history.len() as u128,
+            GasPriceSmoothing::Ema => {
+                let n = history.len() as u128;
+                history[1..].iter().fold(history[0], |ema, price| (price * 2 + ema * (n - 1)) / (n + 1))
+            }
+            GasPriceSmoothing::Percentile(percentile) => {
+                let mut sorted = history.to_vec();
+                sorted.sort_unstable();
+                let rank = (sorted.len() * percentile as usize).div_ceil(100);
+                sorted[rank.saturating_sub(1)]
+            }
+        }
+    }
+
+    /// The price to use, given the base fee history and the `previous` price. The change rate limit does not apply
+    /// when there is no previous price yet, which is the case when it is 0.
+    pub fn compute(&self, history: &[u128], previous: u128) -> u128 {
+        let mut price = self.smooth(history).saturating_mul(100 + self.markup_percent as u128) / 100;
+
+        if let Some(max_change_percent) = self.max_change_percent {
+            if previous != 0 {
+                let max_change = (previous.saturating_mul(max_change_percent as u128) / 100).max(1);
+                price = price.clamp(previous.saturating_sub(max_change), previous.saturating_add(max_change));
+            }
+        }
+        if let Some(max) = self.max {
+            price = price.min(max);
+        }
+        if let Some(min) = self.min {
+            price = price.max(min);
+        }
+        price
+    }
+}
+
+/// Strategies for the L1 gas price and the L1 data (blob) gas price.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L1GasPriceStrategy {
+    pub gas: GasPriceStrategy,
+    pub data_gas: GasPriceStrategy,
+}
+
+impl Default for L1GasPriceStrategy {
+    fn default() -> Self {
+        Self { gas: GasPriceStrategy::l1_gas_default(), data_gas: GasPriceStrategy::l1_data_gas_default() }
+    }
+}
+
+impl L1GasPriceStrategy {
+    /// Number of L1 blocks of fee history needed by the strategies.
+    pub fn fee_history_blocks(&self) -> u64 {
+        self.gas.window.max(self.data_gas.window).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::last(GasPriceSmoothing::Last, 3, 40)]
+    #[case::average(GasPriceSmoothing::Average, 3, 20)]
+    #[case::average_window(GasPriceSmoothing::Average, 2, 25)]
+    #[case::ema(GasPriceSmoothing::Ema, 3, 25)]
+    #[case::median(GasPriceSmoothing::Percentile(50), 5, 10)]
+    #[case::p90(GasPriceSmoothing::Percentile(90), 5, 100)]
+    #[case::p100(GasPriceSmoothing::Percentile(100), 3, 40)]
+    fn test_smoothing(#[case] smoothing: GasPriceSmoothing, #[case] window: u64, #[case] expected: u128) {
+        let history = [100, 5, 10, 10, 40];
+        let strategy = GasPriceStrategy { smoothing, window, ..GasPriceStrategy::l1_gas_default() };
+        assert_eq!(strategy.smooth(&history), expected);
+    }
+
+    #[test]
+    fn test_compute_bounds() {
+        let strategy = GasPriceStrategy { markup_percent: 50, ..GasPriceStrategy::l1_gas_default() };
+        assert_eq!(strategy.compute(&[100], 0), 150);
+        assert_eq!(strategy.compute(&[], 0), 0);
+
+        let strategy = GasPriceStrategy { max_change_percent: Some(10), min: Some(95), max: Some(1000), ..strategy };
+        // No previous price: not rate limited.
+        assert_eq!(strategy.compute(&[500], 0), 750);
+        assert_eq!(strategy.compute(&[500], 100), 110);
+        assert_eq!(strategy.compute(&[10], 100), 95);
+        assert_eq!(strategy.compute(&[5000], 0), 1000);
+    }
+
+    #[test]
+    fn test_parse_smoothing() {
+        for smoothing in [GasPriceSmoothing::Last, GasPriceSmoothing::Ema, GasPriceSmoothing::Percentile(75)] {
+            assert_eq!(smoothing.to_string().parse::<GasPriceSmoothing>().unwrap(), smoothing);
+        }
+        assert!("p0".parse::<GasPriceSmoothing>().is_err());
+        assert!("p101".parse::<GasPriceSmoothing>().is_err());
+        assert!("median".parse::<GasPriceSmoothing>().is_err());
+    }
+}
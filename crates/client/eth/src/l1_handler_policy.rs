@@ -0,0 +1,172 @@
+//! Checks applied to the messages from the settlement layer before they are turned into L1 handler transactions.
+//!
+//! A message failing one of the checks is rejected: it is recorded in the database with the reason of the rejection,
+//! and it is never retried.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+use starknet_types_core::felt::Felt;
+
+use crate::settlement_layer::MessageToAppchain;
+
+/// A settlement layer contract allowed to send messages to L2, optionally restricted to a single L2 contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRoute {
+    pub from_address: Felt,
+    /// Any L2 contract when `None`.
+    pub to_address: Option<Felt>,
+}
+
+impl MessageRoute {
+    pub fn matches(&self, from_address: Felt, to_address: Felt) -> bool {
+        self.from_address == from_address && self.to_address.map_or(true, |to| to == to_address)
+    }
+}
+
+impl FromStr for MessageRoute {
+    type Err = anyhow::Error;
+
+    /// Parses `<l1 address>` or `<l1 address>:<l2 address>`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (from, to) = match s.split_once(':') {
+            Some((from, to)) => (from, Some(to)),
+            None => (s, None),
+        };
+        let from_address =
+            Felt::from_hex(from).with_context(|| format!("Invalid L1 address {from:?} in route {s:?}"))?;
+        let to_address = to
+            .map(|to| Felt::from_hex(to).with_context(|| format!("Invalid L2 address {to:?} in route {s:?}")))
+            .transpose()?;
+        Ok(Self { from_address, to_address })
+    }
+}
+
+impl fmt::Display for MessageRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.from_address)?;
+        if let Some(to_address) = self.to_address {
+            write!(f, ":{to_address:#x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum L1HandlerRejection {
+    #[error("Messages from {from_address:#x} to {to_address:#x} are not allowed")]
+    RouteNotAllowed { from_address: Felt, to_address: Felt },
+    #[error("Paid fee on L1 {paid_fee} is below the minimum of {min_fee} ({min_fee_percent}% of the estimated fee)")]
+    FeeTooLow { paid_fee: u128, min_fee: u128, min_fee_percent: u64 },
+    #[error("The fee of the L1 handler transaction could not be estimated: {0}")]
+    FeeEstimationFailed(String),
+}
+
+/// The default policy accepts every message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct L1HandlerPolicy {
+    /// Minimum `paid_fee_on_l1`, in percent of the estimated fee of the L1 handler transaction. 0 disables the check,
+    /// which never applies to settlement layers without messaging fees.
+    pub min_fee_percent: u64,
+    /// When set, only the messages matching one of these routes are accepted.
+    pub allowed_routes: Option<Vec<MessageRoute>>,
+}
+
+impl L1HandlerPolicy {
+    /// Checks a message against the policy. `estimate_fee` returns the fee of its L1 handler transaction in wei, as
+    /// estimated by `starknet_estimateMessageFee`, or the reason the estimation failed. It is only called when the
+    /// fee is checked.
+    pub fn check(
+        &self,
+        message: &MessageToAppchain,
+        estimate_fee: impl FnOnce() -> anyhow::Result<Result<u128, String>>,
+    ) -> anyhow::Result<Result<(), L1HandlerRejection>> {
+        let (from_address, to_address) = (message.from_address, message.to_address);
+        if let Some(allowed_routes) = &self.allowed_routes {
+            if !allowed_routes.iter().any(|route| route.matches(from_address, to_address)) {
+                return Ok(Err(L1HandlerRejection::RouteNotAllowed { from_address, to_address }));
+            }
+        }
+
+        if let Some(paid_fee) = message.paid_fee.filter(|_| self.min_fee_percent > 0) {
+            let estimated_fee = match estimate_fee()? {
+                Ok(fee) => fee,
+                Err(reason) => return Ok(Err(L1HandlerRejection::FeeEstimationFailed(reason))),
+            };
+            let min_fee = estimated_fee.saturating_mul(self.min_fee_percent as u128) / 100;
+            if paid_fee < min_fee {
+                return Ok(Err(L1HandlerRejection::FeeTooLow {
+                    paid_fee,
+                    min_fee,
+                    min_fee_percent: self.min_fee_percent,
+                }));
+            }
+        }
+
+        Ok(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(from: Felt, to: u64, fee: Option<u128>) -> MessageToAppchain {
+        MessageToAppchain {
+            from_address: from,
+            to_address: Felt::from(to),
+            selector: Felt::ONE,
+            payload: vec![Felt::ONE; 2],
+            nonce: Felt::ZERO,
+            paid_fee: fee,
+            block_number: 1,
+            tx_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_l1_handler_policy() {
+        let bridge = Felt::from_hex_unchecked("0xae0Ee0A63A2cE6BaeEFFE56e7714FB4EFE48D419");
+        let other = Felt::from_hex_unchecked("0xc662c410C0ECf747543f5bA90660f6ABeBD9C8c4");
+
+        let no_estimate = || panic!("the fee should not be estimated");
+        let estimate = || Ok(Ok(1200));
+        assert_eq!(L1HandlerPolicy::default().check(&message(other, 1, Some(0)), no_estimate).unwrap(), Ok(()));
+
+        let policy = L1HandlerPolicy {
+            min_fee_percent: 50,
+            allowed_routes: Some(vec![
+                MessageRoute { from_address: bridge, to_address: Some(Felt::from(1)) },
+                MessageRoute { from_address: other, to_address: None },
+            ]),
+        };
+        assert_eq!(policy.check(&message(bridge, 1, Some(600)), estimate).unwrap(), Ok(()));
+        assert_eq!(policy.check(&message(other, 5, Some(600)), estimate).unwrap(), Ok(()));
+        assert_eq!(
+            policy.check(&message(bridge, 1, Some(599)), estimate).unwrap(),
+            Err(L1HandlerRejection::FeeTooLow { paid_fee: 599, min_fee: 600, min_fee_percent: 50 })
+        );
+        assert_eq!(
+            policy.check(&message(bridge, 1, Some(600)), || Ok(Err("reverted".into()))).unwrap(),
+            Err(L1HandlerRejection::FeeEstimationFailed("reverted".into()))
+        );
+        assert_eq!(
+            policy.check(&message(bridge, 2, Some(600)), no_estimate).unwrap(),
+            Err(L1HandlerRejection::RouteNotAllowed { from_address: bridge, to_address: Felt::from(2) })
+        );
+        // The settlement layer has no messaging fees.
+        assert_eq!(policy.check(&message(bridge, 1, None), no_estimate).unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_message_route() {
+        let route: MessageRoute = "0xae0Ee0A63A2cE6BaeEFFE56e7714FB4EFE48D419:0x1234".parse().unwrap();
+        assert_eq!(route.to_address, Some(Felt::from(0x1234)));
+        assert_eq!(route.to_string().parse::<MessageRoute>().unwrap(), route);
+        let route: MessageRoute = "0xae0Ee0A63A2cE6BaeEFFE56e7714FB4EFE48D419".parse().unwrap();
+        assert_eq!(route.to_address, None);
+        assert!("bridge".parse::<MessageRoute>().is_err());
+        assert!("0x1234:bridge".parse::<MessageRoute>().is_err());
+    }
+}
@@ -1,18 +1,22 @@
 use crate::client::StarknetCoreContract::LogMessageToL2;
 use crate::client::{EthereumClient, StarknetCoreContract};
+use crate::l1_handler_policy::{L1HandlerPolicy, L1HandlerRejection};
+use crate::settlement_layer::MessageToAppchain;
 use crate::utils::u256_to_felt;
 use alloy::eips::BlockNumberOrTag;
-use alloy::primitives::{keccak256, FixedBytes, U256};
+use alloy::primitives::{keccak256, FixedBytes, B256, U256};
 use alloy::sol_types::SolValue;
 use anyhow::Context;
 use futures::StreamExt;
-use mc_db::{l1_db::LastSyncedEventBlock, MadaraBackend};
+use mc_db::l1_db::{LastSyncedEventBlock, RejectedL1Message};
+use mc_db::MadaraBackend;
 use mc_mempool::{Mempool, MempoolProvider};
 use mp_utils::service::ServiceContext;
-use starknet_api::core::{ChainId, ContractAddress, EntryPointSelector, Nonce};
-use starknet_api::transaction::{Calldata, L1HandlerTransaction, TransactionVersion};
+use primitive_types::H256;
+use starknet_api::core::{ChainId, Nonce};
 use starknet_types_core::felt::Felt;
 use std::sync::Arc;
+use std::time::Duration;
 
 impl EthereumClient {
     /// Get cancellation status of an L1 to L2 message
@@ -35,40 +39,68 @@ impl EthereumClient {
     }
 }
 
+/// Outcome of the processing of an L1 to L2 message.
+#[derive(Debug, PartialEq, Eq)]
+pub enum L1MessageOutcome {
+    /// The L1 handler transaction with this hash was submitted to the mempool.
+    Submitted(Felt),
+    AlreadyProcessed,
+    Rejected(L1HandlerRejection),
+}
+
 pub async fn sync(
     backend: Arc<MadaraBackend>,
     client: Arc<EthereumClient>,
-    chain_id: ChainId,
+    _chain_id: ChainId,
     mempool: Arc<Mempool>,
+    policy: L1HandlerPolicy,
     mut ctx: ServiceContext,
 ) -> anyhow::Result<()> {
     tracing::info!("⟠ Starting L1 Messages Syncing...");
 
-    let last_synced_event_block = match backend.messaging_last_synced_l1_block_with_event() {
-        Ok(Some(blk)) => blk,
-        Ok(None) => {
-            unreachable!("Should never be None")
-        }
-        Err(e) => {
-            tracing::error!("⟠ Madara Messaging DB unavailable: {:?}", e);
-            return Err(e.into());
-        }
-    };
-    let event_filter = client.l1_core_contract.event_filter::<StarknetCoreContract::LogMessageToL2>();
-
-    let mut event_stream = event_filter
-        .from_block(last_synced_event_block.block_number)
-        .to_block(BlockNumberOrTag::Finalized)
-        .watch()
-        .await
-        .context(
-            "Failed to watch event filter - Ensure you are using an L1 RPC endpoint that points to an archive node",
-        )?
-        .into_stream();
-
-    while let Some(Some(event_result)) = ctx.run_until_cancelled(event_stream.next()).await {
-        if let Ok((event, meta)) = event_result {
-            tracing::info!(
+    let mut endpoint_switch = client.subscribe_endpoint_switch();
+    loop {
+        let last_synced_event_block = match backend.messaging_last_synced_l1_block_with_event() {
+            Ok(Some(blk)) => blk,
+            Ok(None) => {
+                unreachable!("Should never be None")
+            }
+            Err(e) => {
+                tracing::error!("⟠ Madara Messaging DB unavailable: {:?}", e);
+                return Err(e.into());
+            }
+        };
+        let event_filter = client.l1_core_contract.event_filter::<StarknetCoreContract::LogMessageToL2>();
+
+        let mut event_stream = event_filter
+            .from_block(last_synced_event_block.block_number)
+            .to_block(BlockNumberOrTag::Finalized)
+            .watch()
+            .await
+            .context(
+                "Failed to watch event filter - Ensure you are using an L1 RPC endpoint that points to an archive node",
+            )?
+            .into_stream();
+        // The filter was registered on the current endpoint.
+        endpoint_switch.borrow_and_update();
+
+        loop {
+            let event_result = tokio::select! {
+                event = ctx.run_until_cancelled(event_stream.next()) => match event {
+                    Some(Some(event)) => event,
+                    Some(None) => {
+                        tracing::warn!("⟠ L1 Messages event stream ended, re-subscribing");
+                        break;
+                    }
+                    None => return Ok(()),
+                },
+                Ok(()) = endpoint_switch.changed() => {
+                    tracing::warn!("⟠ L1 endpoint changed, re-subscribing to L1 Messages");
+                    break;
+                }
+            };
+            if let Ok((event, meta)) = event_result {
+                tracing::info!(
                 "⟠ Processing L1 Message from block: {:?}, transaction_hash: {:?}, log_index: {:?}, fromAddress: {:?}",
                 meta.block_number,
                 meta.transaction_hash,
@@ -76,125 +108,158 @@ pub async fn sync(
                 event.fromAddress
             );
 
-            // Check if cancellation was initiated
-            let event_hash = get_l1_to_l2_msg_hash(&event)?;
-            tracing::info!("⟠ Checking for cancelation, event hash : {:?}", event_hash);
-            let cancellation_timestamp = client.get_l1_to_l2_message_cancellations(event_hash).await?;
-            if cancellation_timestamp != Felt::ZERO {
-                tracing::info!("⟠ L1 Message was cancelled in block at timestamp : {:?}", cancellation_timestamp);
-                let tx_nonce = Nonce(u256_to_felt(event.nonce)?);
-                // cancelled message nonce should be inserted to avoid reprocessing
-                match backend.has_l1_messaging_nonce(tx_nonce) {
-                    Ok(false) => {
-                        backend.set_l1_messaging_nonce(tx_nonce)?;
-                    }
-                    Ok(true) => {}
-                    Err(e) => {
-                        tracing::error!("⟠ Unexpected DB error: {:?}", e);
-                        return Err(e.into());
-                    }
-                };
-                continue;
-            }
+                // Check if cancellation was initiated
+                let event_hash = get_l1_to_l2_msg_hash(&event)?;
+                tracing::info!("⟠ Checking for cancelation, event hash : {:?}", event_hash);
+                let cancellation_timestamp = client.get_l1_to_l2_message_cancellations(event_hash).await?;
+                if cancellation_timestamp != Felt::ZERO {
+                    tracing::info!("⟠ L1 Message was cancelled in block at timestamp : {:?}", cancellation_timestamp);
+                    let tx_nonce = Nonce(u256_to_felt(event.nonce)?);
+                    // cancelled message nonce should be inserted to avoid reprocessing
+                    match backend.has_l1_messaging_nonce(tx_nonce) {
+                        Ok(false) => {
+                            backend.set_l1_messaging_nonce(tx_nonce)?;
+                        }
+                        Ok(true) => {}
+                        Err(e) => {
+                            tracing::error!("⟠ Unexpected DB error: {:?}", e);
+                            return Err(e.into());
+                        }
+                    };
+                    continue;
+                }
 
-            match process_l1_message(&backend, &event, &meta.block_number, &meta.log_index, &chain_id, mempool.clone())
-                .await
-            {
-                Ok(Some(tx_hash)) => {
-                    tracing::info!(
-                        "⟠ L1 Message from block: {:?}, transaction_hash: {:?}, log_index: {:?} submitted, \
+                match process_l1_message(
+                    &backend,
+                    &event,
+                    &meta.block_number,
+                    &meta.log_index,
+                    &meta.transaction_hash,
+                    &mempool,
+                    &policy,
+                ) {
+                    Ok(L1MessageOutcome::Submitted(tx_hash)) => {
+                        tracing::info!(
+                            "⟠ L1 Message from block: {:?}, transaction_hash: {:?}, log_index: {:?} submitted, \
                         transaction hash on L2: {:?}",
-                        meta.block_number,
-                        meta.transaction_hash,
-                        meta.log_index,
-                        tx_hash
-                    );
-                }
-                Ok(None) => {}
-                Err(e) => {
-                    tracing::error!(
-                        "⟠ Unexpected error while processing L1 Message from block: {:?}, transaction_hash: {:?}, \
+                            meta.block_number,
+                            meta.transaction_hash,
+                            meta.log_index,
+                            tx_hash
+                        );
+                    }
+                    Ok(L1MessageOutcome::Rejected(_)) => {
+                        client.l1_block_metrics.l1_messages_rejected.add(1, &[]);
+                    }
+                    Ok(L1MessageOutcome::AlreadyProcessed) => {}
+                    Err(e) => {
+                        tracing::error!(
+                            "⟠ Unexpected error while processing L1 Message from block: {:?}, transaction_hash: {:?}, \
                     log_index: {:?}, error: {:?}",
-                        meta.block_number,
-                        meta.transaction_hash,
-                        meta.log_index,
-                        e
-                    )
+                            meta.block_number,
+                            meta.transaction_hash,
+                            meta.log_index,
+                            e
+                        )
+                    }
                 }
             }
         }
-    }
 
-    Ok(())
+        // Blocks already processed are skipped thanks to their nonce.
+        if ctx.run_until_cancelled(tokio::time::sleep(Duration::from_secs(1))).await.is_none() {
+            return Ok(());
+        }
+    }
 }
 
-async fn process_l1_message(
+/// Turns an L1 to L2 message into an L1 handler transaction in the mempool.
+fn process_l1_message(
     backend: &MadaraBackend,
     event: &LogMessageToL2,
     l1_block_number: &Option<u64>,
     event_index: &Option<u64>,
-    _chain_id: &ChainId,
-    mempool: Arc<Mempool>,
-) -> anyhow::Result<Option<Felt>> {
-    let transaction = parse_handle_l1_message_transaction(event)?;
+    l1_tx_hash: &Option<B256>,
+    mempool: &Mempool,
+    policy: &L1HandlerPolicy,
+) -> anyhow::Result<L1MessageOutcome> {
+    let l1_block_number = l1_block_number.context("No block number in L1 message log")?;
+    let event_index = event_index.context("No log index in L1 message log")?;
+    let message =
+        MessageToAppchain::from_log_message_to_l2(event, l1_block_number, l1_tx_hash.map(|hash| H256(hash.0)))?;
+
+    let outcome = process_message(backend, mempool, policy, &message)?;
+    if outcome != L1MessageOutcome::AlreadyProcessed {
+        backend.messaging_update_last_synced_l1_block_with_event(LastSyncedEventBlock::new(
+            l1_block_number,
+            event_index,
+        ))?;
+    }
+    Ok(outcome)
+}
+
+/// Turns a message from the settlement layer into an L1 handler transaction in the mempool, unless it was already
+/// processed or it is rejected by the policy.
+///
+/// The nonce of the message is marked as processed once its transaction is in the mempool, or once it is rejected: a
+/// message failing for any other reason, such as a database error, is retried.
+pub(crate) fn process_message(
+    backend: &MadaraBackend,
+    mempool: &Mempool,
+    policy: &L1HandlerPolicy,
+    message: &MessageToAppchain,
+) -> anyhow::Result<L1MessageOutcome> {
+    let transaction = message.to_l1_handler_transaction()?;
     let tx_nonce = transaction.nonce;
-    let fees: u128 = event.fee.try_into()?;
+    if backend.has_l1_messaging_nonce(tx_nonce)? {
+        tracing::debug!("⟠ Message already processed: {message:?}");
+        return Ok(L1MessageOutcome::AlreadyProcessed);
+    }
+
+    let estimate_fee = || match mempool.estimate_l1_handler_fee(transaction.clone().into()) {
+        Ok(fee) => Ok(Ok(fee)),
+        Err(err) if err.is_internal() => Err(anyhow::Error::from(err).context("Estimating the L1 handler fee")),
+        Err(err) => Ok(Err(format!("{err:#}"))),
+    };
+    let paid_fee = match (policy.check(message, estimate_fee)?, message.paid_fee) {
+        (Ok(()), Some(paid_fee)) => Ok(paid_fee),
+        // Settlement layers without messaging fees do not report any, but L1 handler transactions must have paid a fee
+        // on L1: they are considered to have paid exactly their estimated fee.
+        (Ok(()), None) => estimate_fee()?.map_err(L1HandlerRejection::FeeEstimationFailed),
+        (Err(rejection), _) => Err(rejection),
+    };
 
-    // Ensure that L1 message has not been executed
-    match backend.has_l1_messaging_nonce(tx_nonce) {
-        Ok(false) => {
+    let paid_fee = match paid_fee {
+        Ok(paid_fee) => paid_fee,
+        Err(rejection) => {
+            tracing::warn!("⟠ Rejecting L1 Message with nonce {:#x}: {rejection}", tx_nonce.0);
+            backend.add_rejected_l1_message(
+                tx_nonce,
+                &RejectedL1Message {
+                    l1_block_number: Some(message.block_number),
+                    l1_tx_hash: message.tx_hash,
+                    from_address: message.from_address,
+                    to_address: message.to_address,
+                    selector: message.selector,
+                    paid_fee_on_l1: message.paid_fee,
+                    reason: rejection.to_string(),
+                },
+            )?;
+            // A rejected message is never retried.
             backend.set_l1_messaging_nonce(tx_nonce)?;
-        }
-        Ok(true) => {
-            tracing::debug!("⟠ Event already processed: {:?}", transaction);
-            return Ok(None);
-        }
-        Err(e) => {
-            tracing::error!("⟠ Unexpected DB error: {:?}", e);
-            return Err(e.into());
+            return Ok(L1MessageOutcome::Rejected(rejection));
         }
     };
 
-    let res = mempool.accept_l1_handler_tx(transaction.into(), fees)?;
-
-    // TODO: remove unwraps
-    // Ques: shall it panic if no block number of event_index?
-    let block_sent = LastSyncedEventBlock::new(l1_block_number.unwrap(), event_index.unwrap());
-    backend.messaging_update_last_synced_l1_block_with_event(block_sent)?;
-
-    Ok(Some(res.transaction_hash))
-}
-
-pub fn parse_handle_l1_message_transaction(event: &LogMessageToL2) -> anyhow::Result<L1HandlerTransaction> {
-    // L1 from address.
-    let from_address = u256_to_felt(event.fromAddress.into_word().into())?;
-
-    // L2 contract to call.
-    let contract_address = u256_to_felt(event.toAddress)?;
-
-    // Function of the contract to call.
-    let entry_point_selector = u256_to_felt(event.selector)?;
-
-    // L1 message nonce.
-    let nonce = u256_to_felt(event.nonce)?;
-
-    let event_payload = event.payload.clone().into_iter().map(u256_to_felt).collect::<anyhow::Result<Vec<_>>>()?;
-
-    let calldata: Calldata = {
-        let mut calldata: Vec<_> = Vec::with_capacity(event.payload.len() + 1);
-        calldata.push(from_address);
-        calldata.extend(event_payload);
+    let res = mempool.accept_l1_handler_tx(transaction.into(), paid_fee)?;
+    backend.set_l1_messaging_nonce(tx_nonce)?;
 
-        Calldata(Arc::new(calldata))
-    };
+    // Bridges track their deposits with `starknet_getMessagesStatus`, which is indexed by L1 transaction hash.
+    if let Some(l1_tx_hash) = &message.tx_hash {
+        backend.add_l1_handler_tx_hash_for_l1_tx(l1_tx_hash, res.transaction_hash)?;
+    }
 
-    Ok(L1HandlerTransaction {
-        nonce: Nonce(nonce),
-        contract_address: ContractAddress(contract_address.try_into()?),
-        entry_point_selector: EntryPointSelector(entry_point_selector),
-        calldata,
-        version: TransactionVersion(Felt::ZERO),
-    })
+    Ok(L1MessageOutcome::Submitted(res.transaction_hash))
 }
 
 /// Computes the message hashed with the given event data
@@ -218,10 +283,9 @@ mod l1_messaging_tests {
 
     use crate::l1_messaging::sync;
     use crate::{
-        client::{
-            EthereumClient, L1BlockMetrics,
-            StarknetCoreContract::{self, LogMessageToL2},
-        },
+        client::{EthereumClient, L1BlockMetrics, StarknetCoreContract::LogMessageToL2},
+        failover::FailoverConfig,
+        l1_handler_policy::{L1HandlerPolicy, MessageRoute},
         l1_messaging::get_l1_to_l2_msg_hash,
         utils::felt_to_u256,
     };
@@ -373,13 +437,13 @@ mod l1_messaging_tests {
         // Set up dummy contract
         let contract = DummyContract::deploy(provider.clone()).await.unwrap();
 
-        let core_contract = StarknetCoreContract::new(*contract.address(), provider.clone());
-
-        let eth_client = EthereumClient {
-            provider: Arc::new(provider.clone()),
-            l1_core_contract: core_contract.clone(),
-            l1_block_metrics: l1_block_metrics.clone(),
-        };
+        let eth_client = EthereumClient::new_unchecked(
+            vec![anvil.endpoint_url()],
+            *contract.address(),
+            l1_block_metrics.clone(),
+            FailoverConfig::default(),
+        )
+        .unwrap();
 
         TestRunner { anvil, chain_config, db_service: db, dummy_contract: contract, eth_client, mempool }
     }
@@ -412,6 +476,7 @@ mod l1_messaging_tests {
                     Arc::new(eth_client),
                     chain_config.chain_id.clone(),
                     mempool,
+                    L1HandlerPolicy::default(),
                     ServiceContext::new_for_testing(),
                 )
                 .await
@@ -420,7 +485,7 @@ mod l1_messaging_tests {
 
         let _ = contract.setIsCanceled(false).send().await;
         // Send a Event and wait for processing, Panic if fail
-        let _ = contract.fireEvent().send().await.expect("Failed to fire event");
+        let l1_tx_hash = *contract.fireEvent().send().await.expect("Failed to fire event").tx_hash();
         tokio::time::sleep(Duration::from_secs(5)).await;
 
         // Assert that event was caught by the worker with correct data
@@ -441,6 +506,10 @@ mod l1_messaging_tests {
         assert_ne!(last_block.block_number, 0);
         let nonce = Nonce(Felt::from_dec_str("10000000000000000").expect("failed to parse nonce string"));
         assert!(db.backend().has_l1_messaging_nonce(nonce).unwrap());
+        // Assert that the L1 handler tx can be found from the L1 tx hash
+        let l1_handler_tx_hashes =
+            db.backend().get_l1_handler_tx_hashes(&primitive_types::H256(l1_tx_hash.0)).unwrap().unwrap();
+        assert_eq!(l1_handler_tx_hashes.len(), 1);
         // TODO : Assert that the tx was correctly executed
 
         // Explicitly cancel the listen task, else it would be running in the background
@@ -473,6 +542,7 @@ mod l1_messaging_tests {
                     Arc::new(eth_client),
                     chain_config.chain_id.clone(),
                     mempool,
+                    L1HandlerPolicy::default(),
                     ServiceContext::new_for_testing(),
                 )
                 .await
@@ -529,6 +599,7 @@ mod l1_messaging_tests {
                     Arc::new(eth_client),
                     chain_config.chain_id.clone(),
                     mempool,
+                    L1HandlerPolicy::default(),
                     ServiceContext::new_for_testing(),
                 )
                 .await
@@ -550,6 +621,64 @@ mod l1_messaging_tests {
         worker_handle.abort();
     }
 
+    /// Test the workflow of l1 -> l2 messaging with a message rejected by the L1 handler policy
+    ///
+    /// This test performs the following steps:
+    /// 1. Sets up test environemment
+    /// 2. Starts worker, only allowing messages from another L1 contract
+    /// 3. Fires a Message event from the dummy contract
+    /// 4. Waits for event to be processed
+    /// 5. Assert that the message is recorded as rejected, and will not be retried
+    #[rstest]
+    #[traced_test]
+    #[tokio::test]
+    async fn e2e_test_message_rejected(#[future] setup_test_env: TestRunner) {
+        let TestRunner { chain_config, db_service: db, dummy_contract: contract, eth_client, anvil: _anvil, mempool } =
+            setup_test_env.await;
+
+        let policy = L1HandlerPolicy {
+            allowed_routes: Some(vec![MessageRoute { from_address: Felt::ZERO, to_address: None }]),
+            ..Default::default()
+        };
+
+        // Start worker
+        let worker_handle = {
+            let db = Arc::clone(&db);
+            let mempool = Arc::clone(&mempool);
+            tokio::spawn(async move {
+                sync(
+                    Arc::clone(db.backend()),
+                    Arc::new(eth_client),
+                    chain_config.chain_id.clone(),
+                    mempool,
+                    policy,
+                    ServiceContext::new_for_testing(),
+                )
+                .await
+            })
+        };
+
+        let _ = contract.setIsCanceled(false).send().await;
+        let l1_tx_hash = *contract.fireEvent().send().await.expect("Failed to fire event").tx_hash();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let last_block =
+            db.backend().messaging_last_synced_l1_block_with_event().expect("failed to retrieve block").unwrap();
+        assert_ne!(last_block.block_number, 0);
+        let nonce = Nonce(Felt::from_dec_str("10000000000000000").expect("failed to parse nonce string"));
+        // rejected message nonce should be inserted to avoid reprocessing
+        assert!(db.backend().has_l1_messaging_nonce(nonce).unwrap());
+        let rejected = db.backend().get_rejected_l1_message(nonce).unwrap().expect("message should be rejected");
+        assert_eq!(rejected.l1_tx_hash, Some(primitive_types::H256(l1_tx_hash.0)));
+        assert_eq!(rejected.from_address, Felt::from_hex("0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419").unwrap());
+        assert!(rejected.reason.contains("are not allowed"));
+        assert_eq!(db.backend().get_rejected_l1_messages().unwrap().len(), 1);
+        assert!(db.backend().get_l1_handler_tx_hashes(&primitive_types::H256(l1_tx_hash.0)).unwrap().is_none());
+        assert!(mempool.is_empty());
+
+        worker_handle.abort();
+    }
+
     /// Test taken from starknet.rs to ensure consistency
     /// https://github.com/xJonathanLEI/starknet-rs/blob/2ddc69479d326ed154df438d22f2d720fbba746e/starknet-core/src/types/msg.rs#L96
     #[test]
@@ -1,7 +1,14 @@
 pub mod client;
+pub mod da_sync;
 pub mod error;
+pub mod failover;
 pub mod l1_gas_price;
+pub mod l1_gas_price_strategy;
+pub mod l1_handler_policy;
 pub mod l1_messaging;
+pub mod msg_to_l1;
+pub mod settlement;
+pub mod settlement_layer;
 pub mod state_update;
 pub mod sync;
 pub mod utils;
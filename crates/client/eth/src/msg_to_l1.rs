@@ -0,0 +1,106 @@
+//! Tracking of the status of L2→L1 messages on the core contract.
+//!
+//! A message becomes consumable on L1 when the state update of its block is settled, at which point the core contract
+//! emits a `LogMessageToL1` event. The L1 recipient then consumes it, which emits a `ConsumedMessageToL1` event. The
+//! messages themselves are indexed by the database when blocks are stored, see [`mc_db::msg_to_l1_db`].
+
+use crate::client::EthereumClient;
+use crate::client::StarknetCoreContract::{ConsumedMessageToL1, LogMessageToL1};
+use crate::utils::u256_to_felt;
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy::sol_types::SolEvent;
+use anyhow::Context;
+use futures::StreamExt;
+use mc_db::l1_db::LastSyncedEventBlock;
+use mc_db::msg_to_l1_db::MsgToL1Event;
+use mc_db::MadaraBackend;
+use mp_receipt::MsgToL1;
+use mp_utils::service::ServiceContext;
+use starknet_types_core::felt::Felt;
+use std::sync::Arc;
+
+/// Convert the message of a `LogMessageToL1` or `ConsumedMessageToL1` event back to its L2 representation.
+pub fn event_to_msg_to_l1(from_address: U256, to_address: Address, payload: &[U256]) -> anyhow::Result<MsgToL1> {
+    Ok(MsgToL1 {
+        from_address: u256_to_felt(from_address)?,
+        to_address: Felt::from_bytes_be_slice(to_address.as_slice()),
+        payload: payload.iter().map(|felt| u256_to_felt(*felt)).collect::<Result<_, _>>()?,
+    })
+}
+
+pub async fn msg_to_l1_worker(
+    backend: Arc<MadaraBackend>,
+    client: Arc<EthereumClient>,
+    mut ctx: ServiceContext,
+) -> anyhow::Result<()> {
+    let mut last_synced = backend.msg_to_l1_last_synced_event()?;
+
+    let filter = Filter::new()
+        .address(*client.l1_core_contract.address())
+        .event_signature(vec![LogMessageToL1::SIGNATURE_HASH, ConsumedMessageToL1::SIGNATURE_HASH])
+        .from_block(last_synced.as_ref().map_or(0, |event| event.block_number))
+        .to_block(BlockNumberOrTag::Finalized);
+
+    let mut log_stream = match ctx.run_until_cancelled(client.provider.watch_logs(&filter)).await {
+        Some(res) => res
+            .context(
+                "Failed to watch event filter - Ensure you are using an L1 RPC endpoint that points to an archive node",
+            )?
+            .into_stream()
+            .flat_map(futures::stream::iter),
+        None => return Ok(()),
+    };
+    tracing::info!("⟠ Tracking L2→L1 messages on L1");
+
+    while let Some(Some(log)) = ctx.run_until_cancelled(log_stream.next()).await {
+        let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else { continue };
+        // Logs of the last synced block are returned again on restart.
+        if last_synced.as_ref().is_some_and(|last| (block_number, log_index) <= (last.block_number, last.event_index)) {
+            continue;
+        }
+
+        let (message, event) = if let Ok(log) = log.log_decode::<LogMessageToL1>() {
+            let data = log.inner.data;
+            (event_to_msg_to_l1(data.fromAddress, data.toAddress, &data.payload)?, MsgToL1Event::Settled)
+        } else if let Ok(log) = log.log_decode::<ConsumedMessageToL1>() {
+            let data = log.inner.data;
+            (event_to_msg_to_l1(data.fromAddress, data.toAddress, &data.payload)?, MsgToL1Event::Consumed)
+        } else {
+            continue;
+        };
+
+        let message_hash = message.compute_hash();
+        tracing::debug!("⟠ L2→L1 message {message_hash:?} {event:?} in L1 block {block_number}");
+        let position = LastSyncedEventBlock::new(block_number, log_index);
+        backend.msg_to_l1_apply_event(&message_hash, event, position.clone())?;
+        last_synced = Some(position);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::keccak256;
+    use alloy::sol_types::SolValue;
+
+    #[test]
+    fn test_msg_to_l1_hash_matches_core_contract() {
+        let from_address = U256::from(0x1234);
+        let to_address: Address = "0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419".parse().unwrap();
+        let payload = vec![U256::from(1), U256::from(2)];
+
+        // `keccak256(abi.encodePacked(fromAddress, toAddress, payload.length, payload))` in the core contract.
+        let expected = keccak256(
+            (from_address, U256::from_be_slice(to_address.as_slice()), U256::from(payload.len()), payload.clone())
+                .abi_encode_packed(),
+        );
+
+        let message = event_to_msg_to_l1(from_address, to_address, &payload).unwrap();
+        assert_eq!(message.compute_hash().as_bytes(), expected.as_slice());
+    }
+}
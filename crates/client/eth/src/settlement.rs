@@ -0,0 +1,620 @@
+//! Settlement of the blocks we produce on the L1 core contract.
+//!
+//! When running as a sequencer for an appchain settling on Ethereum, each closed block is published on the Starknet core
+//! contract using an `updateState` transaction. The program output follows the layout of the Starknet OS output, with the
+//! state diff published as calldata data availability. The OS program and config hashes written in the output are the
+//! ones registered on the core contract, unless overridden in the [`SettlementConfig`].
+//!
+//! Blob (KZG) data availability is out of scope: `updateStateKzgDA` needs the KZG commitments and proofs computed by
+//! the Starknet OS, which Madara does not run. The state diff is always published as calldata, even though the block
+//! headers report blob data availability.
+//!
+//! Blocks are settled one at a time and in order: there is at most one inflight `updateState` transaction. Its status is
+//! persisted in the database, so that it is tracked again after a restart instead of being sent twice. A transaction
+//! that is not included after [`SettlementConfig::replace_after_blocks`] L1 blocks is replaced by the same transaction
+//! with higher fees, and a reverted transaction is sent again.
+//!
+//! Note that the core contract only accepts program outputs whose fact has been registered by its verifier: without
+//! a prover, the core contract must be deployed with a verifier accepting any fact.
+
+use crate::client::StarknetCoreContract::{self, StarknetCoreContractInstance};
+use crate::client::{EthereumClient, L1Provider};
+use crate::failover::FailoverTransport;
+use crate::utils::{felt_to_u256, u256_to_felt};
+use alloy::network::{EthereumWallet, TransactionBuilder};
+use alloy::primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::SolCall;
+use anyhow::Context;
+use mc_db::db_block_id::DbBlockId;
+use mc_db::settlement_db::SettlementTx;
+use mc_db::MadaraBackend;
+use mp_transactions::Transaction;
+use mp_utils::service::ServiceContext;
+use starknet_types_core::felt::Felt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Minimum fee increase for a replacement transaction to be accepted by L1 nodes, in percent.
+const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+#[derive(Clone, Debug)]
+pub struct SettlementConfig {
+    /// Number of L1 blocks, including the one with the `updateState` transaction, before a block is considered
+    /// settled.
+    pub confirmations: u64,
+    /// How often the inflight transaction is checked, and new blocks are looked for.
+    pub poll_interval: Duration,
+    /// Replace the inflight transaction when it has not been included after this many L1 blocks.
+    pub replace_after_blocks: u64,
+    /// Fee increase of replacement transactions, in percent. At least 10%.
+    pub fee_bump_percent: u64,
+    /// Upper bound for the max fee per gas of settlement transactions, in wei.
+    pub max_fee_per_gas: Option<u128>,
+    /// Starknet OS program hash written in the program output. Defaults to the program hash registered on the core
+    /// contract: `updateState` reverts when they differ.
+    pub os_program_hash: Option<Felt>,
+    /// Starknet OS config hash written in the program output. Defaults to the config hash registered on the core
+    /// contract.
+    pub os_config_hash: Option<Felt>,
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            confirmations: 6,
+            poll_interval: Duration::from_secs(12),
+            replace_after_blocks: 5,
+            fee_bump_percent: 20,
+            max_fee_per_gas: None,
+            os_program_hash: None,
+            os_config_hash: None,
+        }
+    }
+}
+
+/// Status of the inflight `updateState` transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InflightStatus {
+    /// Included with enough confirmations.
+    Confirmed(B256),
+    /// Included, waiting for more confirmations.
+    Included,
+    /// Included but reverted.
+    Reverted(B256),
+    /// None of the versions of the transaction are included.
+    NotIncluded,
+}
+
+pub struct SettlementClient {
+    provider: Arc<L1Provider>,
+    core_contract: StarknetCoreContractInstance<FailoverTransport, L1Provider>,
+    wallet: EthereumWallet,
+    sender: Address,
+    chain_id: u64,
+    os_program_hash: Felt,
+    os_config_hash: Felt,
+    config: SettlementConfig,
+}
+
+impl SettlementClient {
+    /// Create a settlement client sending transactions to the core contract of `eth_client`, signed by `signer`.
+    pub async fn new(
+        eth_client: &EthereumClient,
+        signer: PrivateKeySigner,
+        config: SettlementConfig,
+    ) -> anyhow::Result<Self> {
+        let chain_id = eth_client.provider.get_chain_id().await.context("Getting the L1 chain id")?;
+        let sender = signer.address();
+        let core_contract = eth_client.l1_core_contract.clone();
+
+        let os_program_hash = match config.os_program_hash {
+            Some(hash) => hash,
+            None => u256_to_felt(core_contract.programHash().call().await.context("Getting the OS program hash")?._0)?,
+        };
+        let os_config_hash = match config.os_config_hash {
+            Some(hash) => hash,
+            None => u256_to_felt(core_contract.configHash().call().await.context("Getting the OS config hash")?._0)?,
+        };
+        tracing::debug!("Settling with OS program hash {os_program_hash:#x} and config hash {os_config_hash:#x}");
+
+        Ok(Self {
+            provider: Arc::clone(&eth_client.provider),
+            core_contract,
+            wallet: EthereumWallet::from(signer),
+            sender,
+            chain_id,
+            os_program_hash,
+            os_config_hash,
+            config,
+        })
+    }
+
+    /// The L1 account sending the settlement transactions.
+    pub fn sender(&self) -> Address {
+        self.sender
+    }
+
+    /// Make progress on the settlement: track the inflight transaction if there is one, otherwise send the
+    /// `updateState` transaction for the next block to settle, if it has been closed.
+    pub async fn settle_next(&self, backend: &MadaraBackend) -> anyhow::Result<()> {
+        let mut state = backend.get_settlement_state()?;
+        let l1_block = self.provider.get_block_number().await.context("Getting the latest L1 block number")?;
+
+        if let Some(inflight) = state.inflight.take() {
+            match self.inflight_status(&inflight, l1_block).await? {
+                InflightStatus::Confirmed(tx_hash) => {
+                    tracing::info!("🧾 Block #{} settled on L1 in transaction {tx_hash}", inflight.block_n);
+                    state.last_settled_block_n = Some(inflight.block_n);
+                }
+                InflightStatus::Reverted(tx_hash) => {
+                    // Sent again on the next tick. The gas estimation fails if the revert is deterministic, so we do
+                    // not keep spending gas on it.
+                    tracing::warn!(
+                        "Settlement transaction {tx_hash} for block #{} reverted on L1, retrying",
+                        inflight.block_n
+                    );
+                }
+                InflightStatus::NotIncluded
+                    if l1_block >= inflight.sent_at_l1_block + self.config.replace_after_blocks =>
+                {
+                    state.inflight = Some(self.replace(backend, inflight, l1_block).await?);
+                }
+                InflightStatus::Included | InflightStatus::NotIncluded => {
+                    state.inflight = Some(inflight);
+                    return Ok(());
+                }
+            }
+            backend.write_settlement_state(&state)?;
+            return Ok(());
+        }
+
+        let block_n = match state.last_settled_block_n {
+            Some(block_n) => block_n + 1,
+            None => self.first_block_to_settle().await?,
+        };
+        if backend.get_latest_block_n()?.map_or(true, |latest| latest < block_n) {
+            return Ok(());
+        }
+
+        let call = build_update_state_call(backend, block_n, self.os_program_hash, self.os_config_hash)?;
+        let nonce = self
+            .provider
+            .get_transaction_count(self.sender)
+            .pending()
+            .await
+            .context("Getting the settlement account nonce")?;
+        let (max_fee_per_gas, max_priority_fee_per_gas) = self.fees(None).await?;
+        let tx_hash = self.send(call.abi_encode().into(), nonce, max_fee_per_gas, max_priority_fee_per_gas).await?;
+        tracing::debug!("Sent settlement transaction {tx_hash} for block #{block_n} with nonce {nonce}");
+
+        state.inflight = Some(SettlementTx {
+            block_n,
+            nonce,
+            tx_hashes: vec![tx_hash.0],
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            sent_at_l1_block: l1_block,
+        });
+        backend.write_settlement_state(&state)?;
+        Ok(())
+    }
+
+    /// The first block to settle when nothing has been settled by us yet: the block after the one the core contract is
+    /// at.
+    async fn first_block_to_settle(&self) -> anyhow::Result<u64> {
+        let state_block_number = self.core_contract.stateBlockNumber().call().await?._0;
+        // The state block number is -1 before the first block is settled.
+        Ok(u64::try_from(state_block_number).map(|block_n| block_n + 1).unwrap_or(0))
+    }
+
+    async fn inflight_status(&self, inflight: &SettlementTx, l1_block: u64) -> anyhow::Result<InflightStatus> {
+        // All the versions share the same nonce, at most one of them can be included.
+        for tx_hash in inflight.tx_hashes.iter().rev().map(|hash| B256::from(*hash)) {
+            let Some(receipt) = self.provider.get_transaction_receipt(tx_hash).await? else { continue };
+            let Some(included_at) = receipt.block_number else { continue };
+            if !receipt.status() {
+                return Ok(InflightStatus::Reverted(tx_hash));
+            }
+            if l1_block + 1 >= included_at + self.config.confirmations.max(1) {
+                return Ok(InflightStatus::Confirmed(tx_hash));
+            }
+            return Ok(InflightStatus::Included);
+        }
+        Ok(InflightStatus::NotIncluded)
+    }
+
+    /// Replace a stuck transaction by the same transaction with higher fees.
+    async fn replace(
+        &self,
+        backend: &MadaraBackend,
+        mut inflight: SettlementTx,
+        l1_block: u64,
+    ) -> anyhow::Result<SettlementTx> {
+        let (max_fee_per_gas, max_priority_fee_per_gas) = self.fees(Some(&inflight)).await?;
+        if max_fee_per_gas <= inflight.max_fee_per_gas {
+            tracing::warn!(
+                "Settlement transaction for block #{} is stuck, but its fees are already at the configured maximum",
+                inflight.block_n
+            );
+            // Wait for another `replace_after_blocks` L1 blocks instead of retrying on every tick.
+            inflight.sent_at_l1_block = l1_block;
+            return Ok(inflight);
+        }
+
+        let call = build_update_state_call(backend, inflight.block_n, self.os_program_hash, self.os_config_hash)?;
+        let tx_hash =
+            self.send(call.abi_encode().into(), inflight.nonce, max_fee_per_gas, max_priority_fee_per_gas).await?;
+        tracing::info!(
+            "🧾 Replaced stuck settlement transaction for block #{} with {tx_hash} (max fee per gas {} -> {} wei)",
+            inflight.block_n,
+            inflight.max_fee_per_gas,
+            max_fee_per_gas
+        );
+
+        inflight.tx_hashes.push(tx_hash.0);
+        inflight.max_fee_per_gas = max_fee_per_gas;
+        inflight.max_priority_fee_per_gas = max_priority_fee_per_gas;
+        inflight.sent_at_l1_block = l1_block;
+        Ok(inflight)
+    }
+
+    /// Fees for a new transaction, or for the replacement of `previous`.
+    async fn fees(&self, previous: Option<&SettlementTx>) -> anyhow::Result<(u128, u128)> {
+        let estimation = self.provider.estimate_eip1559_fees(None).await.context("Estimating L1 fees")?;
+        let mut max_fee_per_gas = estimation.max_fee_per_gas;
+        let mut max_priority_fee_per_gas = estimation.max_priority_fee_per_gas;
+
+        if let Some(previous) = previous {
+            let bump_percent = self.config.fee_bump_percent.max(MIN_FEE_BUMP_PERCENT) as u128;
+            let bump = |fee: u128| fee + (fee * bump_percent).div_ceil(100);
+            max_fee_per_gas = max_fee_per_gas.max(bump(previous.max_fee_per_gas));
+            max_priority_fee_per_gas = max_priority_fee_per_gas.max(bump(previous.max_priority_fee_per_gas));
+        }
+        if let Some(cap) = self.config.max_fee_per_gas {
+            max_fee_per_gas = max_fee_per_gas.min(cap);
+        }
+        Ok((max_fee_per_gas, max_priority_fee_per_gas.min(max_fee_per_gas)))
+    }
+
+    async fn send(
+        &self,
+        calldata: Bytes,
+        nonce: u64,
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    ) -> anyhow::Result<B256> {
+        let tx = TransactionRequest::default()
+            .with_from(self.sender)
+            .with_to(*self.core_contract.address())
+            .with_input(calldata)
+            .with_nonce(nonce)
+            .with_chain_id(self.chain_id)
+            .with_max_fee_per_gas(max_fee_per_gas)
+            .with_max_priority_fee_per_gas(max_priority_fee_per_gas);
+        let gas_limit = self.provider.estimate_gas(&tx).await.context("Estimating the updateState gas")?;
+        // Leave some headroom, the state of the core contract may change before the transaction is included.
+        let tx = tx.with_gas_limit(gas_limit + gas_limit / 5);
+
+        let envelope = tx.build(&self.wallet).await.context("Signing the updateState transaction")?;
+        let pending = self.provider.send_tx_envelope(envelope).await.context("Sending the updateState transaction")?;
+        Ok(*pending.tx_hash())
+    }
+}
+
+/// Build the `updateState` call settling block `block_n`.
+///
+/// The program output follows the layout of the Starknet OS output:
+/// `[initial root, final root, previous block number, block number, previous block hash, block hash, OS program hash,
+/// OS config hash, use KZG DA, full output, messages to L1 segment, messages to L2 segment, state diff]`.
+pub fn build_update_state_call(
+    backend: &MadaraBackend,
+    block_n: u64,
+    os_program_hash: Felt,
+    os_config_hash: Felt,
+) -> anyhow::Result<StarknetCoreContract::updateStateCall> {
+    let block_id = DbBlockId::Number(block_n);
+    let block = backend.get_block(&block_id)?.with_context(|| format!("Block #{block_n} not found"))?;
+    let info = block.info.as_nonpending().context("Settled block should not be pending")?;
+
+    let (previous_root, previous_block_number, previous_block_hash) = match block_n.checked_sub(1) {
+        Some(parent_n) => {
+            let parent = backend
+                .get_block_info(&DbBlockId::Number(parent_n))?
+                .and_then(|info| info.as_nonpending_owned())
+                .with_context(|| format!("Block #{parent_n} not found"))?;
+            (parent.header.global_state_root, Felt::from(parent_n), parent.block_hash)
+        }
+        // -1 in the field, matching the state block number of a fresh core contract.
+        None => (Felt::ZERO, Felt::MAX, Felt::ZERO),
+    };
+
+    let mut output = vec![
+        previous_root,
+        info.header.global_state_root,
+        previous_block_number,
+        Felt::from(block_n),
+        previous_block_hash,
+        info.block_hash,
+        os_program_hash,
+        os_config_hash,
+        Felt::ZERO, // use KZG DA
+        Felt::ZERO, // full output
+    ];
+
+    let messages_to_l1: Vec<Felt> = block
+        .inner
+        .receipts
+        .iter()
+        .flat_map(|receipt| receipt.messages_sent())
+        .flat_map(|message| {
+            [message.from_address, message.to_address, Felt::from(message.payload.len() as u64)]
+                .into_iter()
+                .chain(message.payload.iter().copied())
+        })
+        .collect();
+    output.push(Felt::from(messages_to_l1.len() as u64));
+    output.extend(messages_to_l1);
+
+    let messages_to_l2: Vec<Felt> = block
+        .inner
+        .transactions
+        .iter()
+        .filter_map(|tx| match tx {
+            Transaction::L1Handler(tx) => Some(tx),
+            _ => None,
+        })
+        .flat_map(|tx| {
+            // The first calldata element of an L1 handler transaction is the L1 sender, the rest is the payload.
+            let (from_address, payload) =
+                tx.calldata.split_first().map(|(from, rest)| (*from, rest)).unwrap_or_default();
+            [
+                from_address,
+                tx.contract_address,
+                Felt::from(tx.nonce),
+                tx.entry_point_selector,
+                Felt::from(payload.len() as u64),
+            ]
+            .into_iter()
+            .chain(payload.iter().copied())
+        })
+        .collect();
+    output.push(Felt::from(messages_to_l2.len() as u64));
+    output.extend(messages_to_l2);
+
+    let state_diff = backend.get_block_state_diff(&block_id)?.with_context(|| format!("Block #{block_n} not found"))?;
+    let da = state_diff.to_da_encoding(|address| {
+        backend.get_contract_nonce_at(&block_id, address).map(|nonce| nonce.unwrap_or_default())
+    })?;
+    let onchain_data_hash = keccak256(da.iter().flat_map(|felt| felt.to_bytes_be()).collect::<Vec<u8>>());
+    let onchain_data_size = U256::from(da.len());
+    output.extend(da);
+
+    Ok(StarknetCoreContract::updateStateCall {
+        programOutput: output.into_iter().map(felt_to_u256).collect(),
+        onchainDataHash: U256::from_be_bytes(onchain_data_hash.0),
+        onchainDataSize: onchain_data_size,
+    })
+}
+
+pub async fn settlement_worker(
+    backend: Arc<MadaraBackend>,
+    client: Arc<SettlementClient>,
+    mut ctx: ServiceContext,
+) -> anyhow::Result<()> {
+    tracing::info!("🧾 Settling blocks on L1 from account {}", client.sender());
+
+    let mut interval = tokio::time::interval(client.config.poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    while ctx.run_until_cancelled(interval.tick()).await.is_some() {
+        if let Err(err) = client.settle_next(&backend).await {
+            tracing::warn!("Failed to make progress on the L1 settlement, retrying: {err:#}");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::eth_client_getter_test::{
+        deploy_mock_core_contract, get_port, AnvilPortNum, CONFIG_HASH, PROGRAM_HASH,
+    };
+    use crate::client::L1BlockMetrics;
+    use crate::failover::FailoverConfig;
+    use alloy::node_bindings::{Anvil, AnvilInstance};
+    use alloy::primitives::I256;
+    use mc_db::settlement_db::SettlementState;
+    use mp_block::{Header, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo};
+    use mp_chain_config::ChainConfig;
+    use mp_state_update::{ContractStorageDiffItem, StateDiff, StorageEntry};
+    use rstest::*;
+
+    fn store_block(backend: &MadaraBackend, block_n: u64) {
+        let state_diff = StateDiff {
+            storage_diffs: vec![ContractStorageDiffItem {
+                address: Felt::from(1),
+                storage_entries: vec![StorageEntry { key: Felt::from(block_n), value: Felt::from(2) }],
+            }],
+            ..Default::default()
+        };
+        backend
+            .store_block(
+                MadaraMaybePendingBlock {
+                    info: MadaraMaybePendingBlockInfo::NotPending(MadaraBlockInfo {
+                        header: Header {
+                            block_number: block_n,
+                            global_state_root: Felt::from(100 + block_n),
+                            ..Default::default()
+                        },
+                        block_hash: Felt::from(200 + block_n),
+                        tx_hashes: vec![],
+                    }),
+                    inner: MadaraBlockInner { transactions: vec![], receipts: vec![] },
+                },
+                state_diff,
+                vec![],
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    struct TestContext {
+        backend: Arc<MadaraBackend>,
+        client: SettlementClient,
+        anvil: AnvilInstance,
+        _port: AnvilPortNum,
+    }
+
+    /// A local anvil with a mock core contract at block #0, and blocks #0 and #1 in the database.
+    #[fixture]
+    async fn ctx() -> TestContext {
+        let port = get_port();
+        let anvil = Anvil::new().port(port.0).try_spawn().expect("failed to spawn anvil instance");
+
+        // Deployed from another account, so that the settlement account nonce starts at 0.
+        let core_contract = deploy_mock_core_contract(&anvil).await;
+
+        let eth_client = EthereumClient::new_unchecked(
+            vec![anvil.endpoint_url()],
+            core_contract,
+            L1BlockMetrics::register().unwrap(),
+            FailoverConfig::default(),
+        )
+        .unwrap();
+        let signer = PrivateKeySigner::from_bytes(&B256::from_slice(&anvil.keys()[0].to_bytes())).unwrap();
+        let config = SettlementConfig { confirmations: 2, replace_after_blocks: 1, ..Default::default() };
+        let client = SettlementClient::new(&eth_client, signer, config).await.unwrap();
+        assert_eq!(client.os_program_hash, Felt::from(PROGRAM_HASH));
+        assert_eq!(client.os_config_hash, Felt::from(CONFIG_HASH));
+
+        let backend = MadaraBackend::open_for_testing(Arc::new(ChainConfig::madara_test()));
+        store_block(&backend, 0);
+        store_block(&backend, 1);
+
+        TestContext { backend, client, anvil, _port: port }
+    }
+
+    /// State of the core contract: state root, block number and block hash.
+    async fn core_contract_state(client: &SettlementClient) -> (U256, I256, U256) {
+        let contract = &client.core_contract;
+        (
+            contract.stateRoot().call().await.unwrap()._0,
+            contract.stateBlockNumber().call().await.unwrap()._0,
+            contract.stateBlockHash().call().await.unwrap()._0,
+        )
+    }
+
+    async fn mine(client: &SettlementClient) {
+        let _: serde_json::Value = client.provider.raw_request("anvil_mine".into(), (1,)).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_settle_block(#[future] ctx: TestContext) {
+        let ctx = ctx.await;
+        let (backend, client) = (&ctx.backend, &ctx.client);
+
+        client.settle_next(backend).await.unwrap();
+        let inflight = backend.get_settlement_state().unwrap().inflight.expect("transaction sent");
+        assert_eq!(inflight.block_n, 1);
+        assert_eq!(inflight.nonce, 0);
+
+        let tx = client.provider.get_transaction_by_hash(inflight.tx_hashes[0].into()).await.unwrap().unwrap();
+        let call = StarknetCoreContract::updateStateCall::abi_decode(&tx.input, true).unwrap();
+        let expected_header = [100u64, 101, 0, 1, 200, 201].map(U256::from);
+        assert_eq!(call.programOutput[..6], expected_header);
+
+        // Automine included the transaction, but one more confirmation is needed.
+        client.settle_next(backend).await.unwrap();
+        assert!(backend.get_settlement_state().unwrap().inflight.is_some());
+
+        mine(client).await;
+        client.settle_next(backend).await.unwrap();
+        assert_eq!(
+            backend.get_settlement_state().unwrap(),
+            SettlementState { last_settled_block_n: Some(1), inflight: None }
+        );
+        assert_eq!(core_contract_state(client).await, (U256::from(101), I256::ONE, U256::from(201)));
+
+        // Nothing to settle anymore.
+        client.settle_next(backend).await.unwrap();
+        assert_eq!(backend.get_settlement_state().unwrap().inflight, None);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_replace_stuck_transaction(#[future] ctx: TestContext) {
+        let ctx = ctx.await;
+        let (backend, client) = (&ctx.backend, &ctx.client);
+        let _: serde_json::Value = client.provider.raw_request("evm_setAutomine".into(), (false,)).await.unwrap();
+
+        client.settle_next(backend).await.unwrap();
+        let sent = backend.get_settlement_state().unwrap().inflight.unwrap();
+
+        // The transaction is dropped from the mempool while L1 blocks keep coming.
+        let _: serde_json::Value = client
+            .provider
+            .raw_request("anvil_dropTransaction".into(), (B256::from(sent.tx_hashes[0]),))
+            .await
+            .unwrap();
+        mine(client).await;
+
+        client.settle_next(backend).await.unwrap();
+        let replaced = backend.get_settlement_state().unwrap().inflight.unwrap();
+        assert_eq!(replaced.block_n, sent.block_n);
+        assert_eq!(replaced.nonce, sent.nonce);
+        assert_eq!(replaced.tx_hashes.len(), 2);
+        assert!(replaced.max_fee_per_gas > sent.max_fee_per_gas);
+
+        mine(client).await;
+        mine(client).await;
+        client.settle_next(backend).await.unwrap();
+        let state = backend.get_settlement_state().unwrap();
+        assert_eq!(state, SettlementState { last_settled_block_n: Some(1), inflight: None });
+        assert_eq!(client.provider.get_transaction_count(ctx.anvil.addresses()[0]).await.unwrap(), 1);
+        assert_eq!(core_contract_state(client).await, (U256::from(101), I256::ONE, U256::from(201)));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_stuck_transaction_at_fee_cap(#[future] ctx: TestContext) {
+        let ctx = ctx.await;
+        let (backend, mut client) = (&ctx.backend, ctx.client);
+        let _: serde_json::Value = client.provider.raw_request("evm_setAutomine".into(), (false,)).await.unwrap();
+
+        client.settle_next(backend).await.unwrap();
+        let sent = backend.get_settlement_state().unwrap().inflight.unwrap();
+        client.config.max_fee_per_gas = Some(sent.max_fee_per_gas);
+        let _: serde_json::Value = client
+            .provider
+            .raw_request("anvil_dropTransaction".into(), (B256::from(sent.tx_hashes[0]),))
+            .await
+            .unwrap();
+        mine(&client).await;
+
+        // The fees cannot be bumped: the transaction is kept, and only looked at again after `replace_after_blocks`.
+        client.settle_next(backend).await.unwrap();
+        let kept = backend.get_settlement_state().unwrap().inflight.unwrap();
+        assert_eq!(kept.tx_hashes, sent.tx_hashes);
+        assert_eq!(kept.max_fee_per_gas, sent.max_fee_per_gas);
+        assert_eq!(kept.sent_at_l1_block, sent.sent_at_l1_block + 1);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_program_output_rejected(#[future] ctx: TestContext) {
+        let ctx = ctx.await;
+        let (backend, mut client) = (&ctx.backend, ctx.client);
+        // `updateState` reverts, the gas estimation fails and nothing is sent.
+        client.os_config_hash = Felt::from(CONFIG_HASH + 1);
+
+        assert!(client.settle_next(backend).await.is_err());
+        assert_eq!(backend.get_settlement_state().unwrap(), SettlementState::default());
+        assert_eq!(core_contract_state(&client).await, (U256::from(100), I256::ZERO, U256::from(200)));
+    }
+}
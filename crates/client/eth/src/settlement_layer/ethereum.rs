@@ -0,0 +1,99 @@
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy::sol_types::SolEvent;
+use anyhow::Context;
+use async_trait::async_trait;
+use primitive_types::H256;
+
+use super::{MessageToAppchain, SettlementLayerClient, SettlementLayerGasPrices};
+use crate::client::EthereumClient;
+use crate::client::StarknetCoreContract::{LogMessageToL2, LogStateUpdate};
+use crate::state_update::L1StateUpdate;
+use crate::utils::{convert_log_state_update, u256_to_felt};
+
+impl EthereumClient {
+    pub(crate) async fn get_core_contract_logs<T: SolEvent>(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<alloy::rpc::types::Log<T>>> {
+        let filter = Filter::new()
+            .address(*self.l1_core_contract.address())
+            .event_signature(T::SIGNATURE_HASH)
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = self.provider.get_logs(&filter).await?;
+        logs.into_iter().map(|log| log.log_decode::<T>().context("Decoding core contract log")).collect()
+    }
+}
+
+impl MessageToAppchain {
+    /// The message of a `LogMessageToL2` event of the core contract, emitted in the L1 block `block_number`.
+    pub fn from_log_message_to_l2(
+        event: &LogMessageToL2,
+        block_number: u64,
+        tx_hash: Option<H256>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            from_address: u256_to_felt(event.fromAddress.into_word().into())?,
+            to_address: u256_to_felt(event.toAddress)?,
+            selector: u256_to_felt(event.selector)?,
+            payload: event.payload.iter().map(|felt| u256_to_felt(*felt)).collect::<Result<_, _>>()?,
+            nonce: u256_to_felt(event.nonce)?,
+            paid_fee: Some(event.fee.try_into().context("Paid fee on L1 out of range")?),
+            block_number,
+            tx_hash,
+        })
+    }
+}
+
+/// Ethereum followed by polling, with the fees of its latest block. The node follows Ethereum with the event
+/// watchers of this crate instead, see the [module documentation](super).
+#[async_trait]
+impl SettlementLayerClient for EthereumClient {
+    fn name(&self) -> &'static str {
+        "Ethereum"
+    }
+
+    async fn get_latest_block_number(&self) -> anyhow::Result<u64> {
+        EthereumClient::get_latest_block_number(self).await
+    }
+
+    async fn get_last_state_update(&self) -> anyhow::Result<L1StateUpdate> {
+        self.get_last_verified_state().await
+    }
+
+    async fn get_state_updates(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<L1StateUpdate>> {
+        self.get_core_contract_logs::<LogStateUpdate>(from_block, to_block)
+            .await?
+            .into_iter()
+            .map(|log| convert_log_state_update(log.inner.data))
+            .collect()
+    }
+
+    async fn get_messages_to_appchain(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<MessageToAppchain>> {
+        self.get_core_contract_logs::<LogMessageToL2>(from_block, to_block)
+            .await?
+            .iter()
+            .map(|log| {
+                let block_number = log.block_number.context("No block number in log")?;
+                MessageToAppchain::from_log_message_to_l2(
+                    &log.inner.data,
+                    block_number,
+                    log.transaction_hash.map(|hash| H256(hash.0)),
+                )
+            })
+            .collect()
+    }
+
+    async fn get_gas_prices(&self) -> anyhow::Result<SettlementLayerGasPrices> {
+        let fee_history = self.provider.get_fee_history(1, BlockNumberOrTag::Latest, &[]).await?;
+        Ok(SettlementLayerGasPrices {
+            gas_price_wei: *fee_history.base_fee_per_gas.last().context("Getting eth gas price")?,
+            data_gas_price_wei: fee_history.base_fee_per_blob_gas.last().copied().unwrap_or_default(),
+            gas_price_strk: None,
+            data_gas_price_strk: None,
+        })
+    }
+}
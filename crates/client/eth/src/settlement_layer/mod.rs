@@ -0,0 +1,431 @@
+//! The settlement layer is the chain this chain settles on, and reads its confirmed state and its incoming messages
+//! from: Ethereum for Starknet and most appchains, or a Starknet chain for L3 appchains.
+//!
+//! Both Ethereum and Starknet implement [`SettlementLayerClient`], and can be followed by polling them with
+//! [`settlement_layer_worker`]. The node uses it for Starknet base layers. Ethereum is followed by the workers of
+//! [`crate::sync`] instead: they listen to the core contract events using alloy filters, and also apply the L1 gas
+//! price strategy, track the messages to L1 and settle our blocks. Messages from every settlement layer go through the
+//! same processing and L1 handler policy.
+
+pub mod ethereum;
+pub mod starknet;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use mc_db::l1_db::LastSyncedEventBlock;
+use mc_db::MadaraBackend;
+use mc_mempool::{GasPriceProvider, Mempool};
+use mp_utils::service::ServiceContext;
+use primitive_types::H256;
+use starknet_api::core::{ContractAddress, EntryPointSelector, Nonce};
+use starknet_api::transaction::{Calldata, L1HandlerTransaction, TransactionVersion};
+use starknet_types_core::felt::Felt;
+
+use crate::client::L1BlockMetrics;
+use crate::l1_handler_policy::L1HandlerPolicy;
+use crate::l1_messaging::{process_message, L1MessageOutcome};
+use crate::state_update::{update_l1, L1StateUpdate};
+
+/// A message sent from the settlement layer to this chain, which becomes an L1 handler transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageToAppchain {
+    pub from_address: Felt,
+    pub to_address: Felt,
+    pub selector: Felt,
+    pub payload: Vec<Felt>,
+    pub nonce: Felt,
+    /// Fee paid on the settlement layer, when the settlement layer has a notion of it.
+    pub paid_fee: Option<u128>,
+    /// Settlement layer block and transaction which sent the message.
+    pub block_number: u64,
+    pub tx_hash: Option<H256>,
+}
+
+impl MessageToAppchain {
+    pub fn to_l1_handler_transaction(&self) -> anyhow::Result<L1HandlerTransaction> {
+        let mut calldata = Vec::with_capacity(self.payload.len() + 1);
+        calldata.push(self.from_address);
+        calldata.extend_from_slice(&self.payload);
+
+        Ok(L1HandlerTransaction {
+            nonce: Nonce(self.nonce),
+            contract_address: ContractAddress(self.to_address.try_into()?),
+            entry_point_selector: EntryPointSelector(self.selector),
+            calldata: Calldata(Arc::new(calldata)),
+            version: TransactionVersion(Felt::ZERO),
+        })
+    }
+}
+
+/// Gas prices of the settlement layer, used for the fees of this chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettlementLayerGasPrices {
+    pub gas_price_wei: u128,
+    pub data_gas_price_wei: u128,
+    /// Prices in STRK (fri), when the settlement layer gives them.
+    pub gas_price_strk: Option<u128>,
+    pub data_gas_price_strk: Option<u128>,
+}
+
+#[async_trait]
+pub trait SettlementLayerClient: Send + Sync {
+    /// Name of the settlement layer, for the logs.
+    fn name(&self) -> &'static str;
+
+    async fn get_latest_block_number(&self) -> anyhow::Result<u64>;
+
+    /// State of this chain last verified by the core contract.
+    async fn get_last_state_update(&self) -> anyhow::Result<L1StateUpdate>;
+
+    /// State updates of the core contract between the settlement layer blocks `from_block` and `to_block` included,
+    /// in order.
+    async fn get_state_updates(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<L1StateUpdate>>;
+
+    /// Messages sent to this chain between the settlement layer blocks `from_block` and `to_block` included, in order.
+    async fn get_messages_to_appchain(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<MessageToAppchain>>;
+
+    async fn get_gas_prices(&self) -> anyhow::Result<SettlementLayerGasPrices>;
+}
+
+/// Follow the settlement layer: confirmed blocks, messages sent to this chain, and gas prices.
+#[allow(clippy::too_many_arguments)]
+pub async fn settlement_layer_worker(
+    backend: Arc<MadaraBackend>,
+    client: Arc<dyn SettlementLayerClient>,
+    l1_gas_provider: GasPriceProvider,
+    gas_price_sync_disabled: bool,
+    mempool: Arc<Mempool>,
+    policy: L1HandlerPolicy,
+    l1_block_metrics: L1BlockMetrics,
+    poll: Duration,
+    mut ctx: ServiceContext,
+) -> anyhow::Result<()> {
+    backend.clear_last_confirmed_block().context("Clearing l1 last confirmed block number")?;
+    let initial_state =
+        client.get_last_state_update().await.with_context(|| format!("Getting the initial {} state", client.name()))?;
+    update_l1(&backend, initial_state, &l1_block_metrics)?;
+
+    let mut from_block = backend
+        .messaging_last_synced_l1_block_with_event()
+        .context("Getting the last synced settlement layer block")?
+        .map(|block| block.block_number)
+        .unwrap_or_default();
+
+    tracing::info!("🚀 Following the {} settlement layer from block #{from_block}", client.name());
+    if !gas_price_sync_disabled {
+        l1_gas_provider.update_last_update_timestamp();
+    }
+    let mut interval = tokio::time::interval(poll);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    while ctx.run_until_cancelled(interval.tick()).await.is_some() {
+        if let Err(err) =
+            sync_blocks(&backend, client.as_ref(), &mempool, &policy, &l1_block_metrics, &mut from_block).await
+        {
+            tracing::warn!("Failed to sync the {} settlement layer: {err:#}", client.name());
+        }
+        if !gas_price_sync_disabled {
+            if let Err(err) = sync_gas_prices(client.as_ref(), &l1_gas_provider, &l1_block_metrics).await {
+                tracing::warn!("Failed to update the {} gas prices: {err:#}", client.name());
+            }
+        }
+    }
+
+    anyhow::Ok(())
+}
+
+async fn sync_blocks(
+    backend: &MadaraBackend,
+    client: &dyn SettlementLayerClient,
+    mempool: &Mempool,
+    policy: &L1HandlerPolicy,
+    l1_block_metrics: &L1BlockMetrics,
+    from_block: &mut u64,
+) -> anyhow::Result<()> {
+    let latest_block = client.get_latest_block_number().await?;
+    if latest_block < *from_block {
+        return Ok(());
+    }
+
+    if let Some(state_update) = client.get_state_updates(*from_block, latest_block).await?.pop() {
+        update_l1(backend, state_update, l1_block_metrics)?;
+    }
+    for message in client.get_messages_to_appchain(*from_block, latest_block).await? {
+        match process_message(backend, mempool, policy, &message)? {
+            L1MessageOutcome::Submitted(tx_hash) => tracing::info!(
+                "⟠ Message with nonce {:#x} from block #{} submitted, transaction hash on L2: {tx_hash:#x}",
+                message.nonce,
+                message.block_number,
+            ),
+            L1MessageOutcome::Rejected(_) => l1_block_metrics.l1_messages_rejected.add(1, &[]),
+            L1MessageOutcome::AlreadyProcessed => continue,
+        }
+        backend.messaging_update_last_synced_l1_block_with_event(LastSyncedEventBlock::new(message.block_number, 0))?;
+    }
+
+    *from_block = latest_block + 1;
+    Ok(())
+}
+
+async fn sync_gas_prices(
+    client: &dyn SettlementLayerClient,
+    l1_gas_provider: &GasPriceProvider,
+    l1_block_metrics: &L1BlockMetrics,
+) -> anyhow::Result<()> {
+    let prices = client.get_gas_prices().await?;
+    l1_gas_provider.update_eth_l1_gas_price(prices.gas_price_wei);
+    l1_gas_provider.update_eth_l1_data_gas_price(prices.data_gas_price_wei);
+    if let Some(gas_price_strk) = prices.gas_price_strk {
+        l1_gas_provider.update_strk_l1_gas_price(gas_price_strk);
+    }
+    if let Some(data_gas_price_strk) = prices.data_gas_price_strk {
+        l1_gas_provider.update_strk_l1_data_gas_price(data_gas_price_strk);
+    }
+    l1_gas_provider.update_last_update_timestamp();
+
+    l1_block_metrics.l1_gas_price_wei.record(u64::try_from(prices.gas_price_wei).unwrap_or(u64::MAX), &[]);
+    l1_block_metrics.l1_data_gas_price_wei.record(u64::try_from(prices.data_gas_price_wei).unwrap_or(u64::MAX), &[]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_mempool::{L1DataProvider, MempoolLimits};
+    use mp_chain_config::ChainConfig;
+    use rstest::*;
+    use std::sync::Mutex;
+
+    /// A settlement layer whose blocks, state updates and messages are set by the test.
+    #[derive(Default)]
+    struct MockSettlementLayer {
+        latest_block: Mutex<u64>,
+        /// State updates with the settlement layer block they were emitted in.
+        state_updates: Mutex<Vec<(u64, L1StateUpdate)>>,
+        messages: Mutex<Vec<MessageToAppchain>>,
+        /// The next `get_messages_to_appchain` call fails.
+        fail_next: Mutex<bool>,
+        /// Block ranges asked for the messages.
+        requested: Mutex<Vec<(u64, u64)>>,
+    }
+
+    impl MockSettlementLayer {
+        fn add_block(&self, state_update: Option<L1StateUpdate>, messages: Vec<MessageToAppchain>) -> u64 {
+            let mut latest_block = self.latest_block.lock().unwrap();
+            *latest_block += 1;
+            if let Some(state_update) = state_update {
+                self.state_updates.lock().unwrap().push((*latest_block, state_update));
+            }
+            self.messages.lock().unwrap().extend(
+                messages.into_iter().map(|message| MessageToAppchain { block_number: *latest_block, ..message }),
+            );
+            *latest_block
+        }
+    }
+
+    #[async_trait]
+    impl SettlementLayerClient for MockSettlementLayer {
+        fn name(&self) -> &'static str {
+            "Mock"
+        }
+
+        async fn get_latest_block_number(&self) -> anyhow::Result<u64> {
+            Ok(*self.latest_block.lock().unwrap())
+        }
+
+        async fn get_last_state_update(&self) -> anyhow::Result<L1StateUpdate> {
+            let state_updates = self.state_updates.lock().unwrap();
+            Ok(state_updates.last().map(|(_, state_update)| state_update.clone()).unwrap_or(L1StateUpdate {
+                block_number: 0,
+                global_root: Felt::ZERO,
+                block_hash: Felt::ZERO,
+            }))
+        }
+
+        async fn get_state_updates(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<L1StateUpdate>> {
+            let state_updates = self.state_updates.lock().unwrap();
+            Ok(state_updates
+                .iter()
+                .filter(|(block, _)| (from_block..=to_block).contains(block))
+                .map(|(_, state_update)| state_update.clone())
+                .collect())
+        }
+
+        async fn get_messages_to_appchain(
+            &self,
+            from_block: u64,
+            to_block: u64,
+        ) -> anyhow::Result<Vec<MessageToAppchain>> {
+            if std::mem::take(&mut *self.fail_next.lock().unwrap()) {
+                anyhow::bail!("Settlement layer unavailable");
+            }
+            self.requested.lock().unwrap().push((from_block, to_block));
+            let messages = self.messages.lock().unwrap();
+            Ok(messages
+                .iter()
+                .filter(|message| (from_block..=to_block).contains(&message.block_number))
+                .cloned()
+                .collect())
+        }
+
+        async fn get_gas_prices(&self) -> anyhow::Result<SettlementLayerGasPrices> {
+            Ok(SettlementLayerGasPrices {
+                gas_price_wei: u128::MAX,
+                data_gas_price_wei: 7,
+                gas_price_strk: Some(8),
+                data_gas_price_strk: None,
+            })
+        }
+    }
+
+    fn state_update(block_number: u64) -> L1StateUpdate {
+        L1StateUpdate {
+            block_number,
+            global_root: Felt::from(100 + block_number),
+            block_hash: Felt::from(200 + block_number),
+        }
+    }
+
+    fn message(nonce: u64) -> MessageToAppchain {
+        MessageToAppchain {
+            from_address: Felt::from_hex_unchecked("0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419"),
+            to_address: Felt::from(0x1234),
+            selector: Felt::from(0x5678),
+            payload: vec![Felt::ONE, Felt::TWO],
+            nonce: Felt::from(nonce),
+            paid_fee: Some(1),
+            block_number: 0,
+            tx_hash: Some(H256::from_low_u64_be(nonce)),
+        }
+    }
+
+    struct TestContext {
+        backend: Arc<MadaraBackend>,
+        mempool: Arc<Mempool>,
+        client: Arc<MockSettlementLayer>,
+        metrics: L1BlockMetrics,
+    }
+
+    impl TestContext {
+        async fn sync_blocks(&self, from_block: &mut u64) -> anyhow::Result<()> {
+            sync_blocks(
+                &self.backend,
+                self.client.as_ref(),
+                &self.mempool,
+                &L1HandlerPolicy::default(),
+                &self.metrics,
+                from_block,
+            )
+            .await
+        }
+    }
+
+    #[fixture]
+    fn ctx() -> TestContext {
+        let backend = MadaraBackend::open_for_testing(Arc::new(ChainConfig::madara_test()));
+        let l1_data_provider: Arc<dyn L1DataProvider> = Arc::new(GasPriceProvider::new());
+        let mempool = Arc::new(Mempool::new(Arc::clone(&backend), l1_data_provider, MempoolLimits::for_testing()));
+        TestContext {
+            backend,
+            mempool,
+            client: Arc::new(MockSettlementLayer::default()),
+            metrics: L1BlockMetrics::register().unwrap(),
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_sync_blocks(ctx: TestContext) {
+        let mut from_block = 0;
+        ctx.client.add_block(Some(state_update(1)), vec![message(1)]);
+        let last_block = ctx.client.add_block(Some(state_update(2)), vec![message(2), message(3)]);
+
+        ctx.sync_blocks(&mut from_block).await.unwrap();
+        assert_eq!(from_block, last_block + 1);
+        // Only the latest state update is kept.
+        assert_eq!(ctx.backend.get_l1_last_confirmed_block().unwrap(), Some(2));
+        for nonce in 1..=3 {
+            assert!(ctx.backend.has_l1_messaging_nonce(Nonce(Felt::from(nonce))).unwrap());
+            let l1_handler_tx_hashes = ctx.backend.get_l1_handler_tx_hashes(&H256::from_low_u64_be(nonce)).unwrap();
+            assert_eq!(l1_handler_tx_hashes.map(|hashes| hashes.len()), Some(1));
+        }
+        assert_eq!(
+            ctx.backend.messaging_last_synced_l1_block_with_event().unwrap().map(|block| block.block_number),
+            Some(last_block)
+        );
+
+        // No new block: the settlement layer is not asked for anything.
+        ctx.sync_blocks(&mut from_block).await.unwrap();
+        assert_eq!(*ctx.client.requested.lock().unwrap(), [(0, last_block)]);
+
+        // Only the new blocks are asked for, and already processed messages are skipped.
+        let new_block = ctx.client.add_block(None, vec![message(3), message(4)]);
+        ctx.sync_blocks(&mut from_block).await.unwrap();
+        assert_eq!(from_block, new_block + 1);
+        assert_eq!(*ctx.client.requested.lock().unwrap(), [(0, last_block), (new_block, new_block)]);
+        assert!(ctx.backend.has_l1_messaging_nonce(Nonce(Felt::from(4))).unwrap());
+        assert_eq!(ctx.backend.get_l1_handler_tx_hashes(&H256::from_low_u64_be(3)).unwrap().unwrap().len(), 1);
+        assert_eq!(ctx.backend.get_l1_last_confirmed_block().unwrap(), Some(2));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_sync_blocks_retries_after_error(ctx: TestContext) {
+        let mut from_block = 0;
+        let last_block = ctx.client.add_block(Some(state_update(1)), vec![message(1)]);
+        *ctx.client.fail_next.lock().unwrap() = true;
+
+        assert!(ctx.sync_blocks(&mut from_block).await.is_err());
+        assert_eq!(from_block, 0);
+        assert!(!ctx.backend.has_l1_messaging_nonce(Nonce(Felt::ONE)).unwrap());
+
+        ctx.sync_blocks(&mut from_block).await.unwrap();
+        assert_eq!(from_block, last_block + 1);
+        assert!(ctx.backend.has_l1_messaging_nonce(Nonce(Felt::ONE)).unwrap());
+        assert_eq!(*ctx.client.requested.lock().unwrap(), [(0, last_block)]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_settlement_layer_worker(ctx: TestContext) {
+        ctx.client.add_block(Some(state_update(1)), vec![]);
+        let l1_gas_provider = GasPriceProvider::new();
+        let service_ctx = ServiceContext::new_for_testing();
+
+        let worker = tokio::spawn(settlement_layer_worker(
+            Arc::clone(&ctx.backend),
+            Arc::clone(&ctx.client) as Arc<dyn SettlementLayerClient>,
+            l1_gas_provider.clone(),
+            false,
+            Arc::clone(&ctx.mempool),
+            L1HandlerPolicy::default(),
+            ctx.metrics.clone(),
+            Duration::from_millis(10),
+            service_ctx.clone(),
+        ));
+
+        // The worker starts at the last verified state, then follows the new blocks.
+        ctx.client.add_block(Some(state_update(2)), vec![message(1)]);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !ctx.backend.has_l1_messaging_nonce(Nonce(Felt::ONE)).unwrap()
+                || l1_gas_provider.get_gas_prices().eth_l1_gas_price == 0
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("settlement layer not followed");
+        assert_eq!(ctx.backend.get_l1_last_confirmed_block().unwrap(), Some(2));
+
+        // The gas price does not fit in the metrics, it is still used as is.
+        let prices = l1_gas_provider.get_gas_prices();
+        assert_eq!(prices.eth_l1_gas_price, u128::MAX);
+        assert_eq!(prices.eth_l1_data_gas_price, 7);
+        assert_eq!(prices.strk_l1_gas_price, 8);
+
+        service_ctx.cancel_global();
+        worker.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+//! A Starknet chain as the settlement layer of an L3 appchain, reached through its JSON-RPC API.
+//!
+//! The appchain core contract is a Cairo contract on the base layer, following the interface of the
+//! [piltover](https://github.com/keep-starknet-strange/piltover) appchain contract:
+//! - `get_state()` returns `(state_root, block_number, block_hash)`.
+//! - `LogStateUpdate` events have no keys besides the event selector, and `[state_root, block_hash, block_number]`
+//!   as data.
+//! - `MessageSent` events have `[selector, message_hash, from_address, to_address]` as keys, and
+//!   `[selector, nonce, payload_len, ...payload]` as data.
+//!
+//! Several endpoints can be given: requests go to the active endpoint, and are retried on the other endpoints in order
+//! when it fails or times out. The first endpoint to answer becomes the active endpoint. JSON-RPC errors, such as
+//! reverted calls, are returned as is.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use anyhow::{ensure, Context};
+use async_trait::async_trait;
+use primitive_types::H256;
+use starknet_core::types::{
+    BlockId, BlockTag, EmittedEvent, EventFilter, FunctionCall, MaybePendingBlockWithTxHashes, ResourcePrice,
+};
+use starknet_core::utils::{get_selector_from_name, starknet_keccak};
+use starknet_providers::jsonrpc::HttpTransport;
+use starknet_providers::{JsonRpcClient, Provider, ProviderError};
+use starknet_types_core::felt::Felt;
+use url::Url;
+
+use super::{MessageToAppchain, SettlementLayerClient, SettlementLayerGasPrices};
+use crate::state_update::L1StateUpdate;
+
+/// Number of events fetched per `starknet_getEvents` request.
+const EVENTS_CHUNK_SIZE: u64 = 1000;
+
+struct Endpoint {
+    provider: JsonRpcClient<HttpTransport>,
+    /// Only the host is logged: the rest of the URL usually contains an API key.
+    name: String,
+}
+
+pub struct StarknetClient {
+    endpoints: Vec<Endpoint>,
+    active: AtomicUsize,
+    /// A request taking longer than this is an endpoint failure, and is retried on the other endpoints.
+    request_timeout: Duration,
+    core_contract_address: Felt,
+}
+
+impl StarknetClient {
+    /// Create a client for the appchain core contract at `core_contract_address` on the Starknet chain at `urls`.
+    pub async fn new(urls: Vec<Url>, core_contract_address: Felt, request_timeout: Duration) -> anyhow::Result<Self> {
+        ensure!(!urls.is_empty(), "At least one base layer endpoint is required");
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                name: url.host_str().unwrap_or("unknown host").to_owned(),
+                provider: JsonRpcClient::new(HttpTransport::new(url)),
+            })
+            .collect();
+        let client = Self { endpoints, active: AtomicUsize::new(0), request_timeout, core_contract_address };
+        client
+            .request(|provider| provider.get_class_hash_at(BlockId::Tag(BlockTag::Latest), core_contract_address))
+            .await
+            .context("The appchain core contract could not be found. Check that the chain config matches the base layer RPC endpoint.")?;
+        Ok(client)
+    }
+
+    /// Send a request to the active endpoint, then to the other endpoints in order until one of them answers.
+    async fn request<'a, T, F, Fut>(&'a self, request: F) -> anyhow::Result<T>
+    where
+        F: Fn(&'a JsonRpcClient<HttpTransport>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let active = self.active.load(Ordering::Relaxed);
+        let candidates = std::iter::once(active).chain((0..self.endpoints.len()).filter(|i| *i != active));
+
+        let mut last_error = None;
+        for i in candidates {
+            let endpoint = &self.endpoints[i];
+            let err = match tokio::time::timeout(self.request_timeout, request(&endpoint.provider)).await {
+                Ok(Ok(res)) => {
+                    let previous = self.active.swap(i, Ordering::Relaxed);
+                    if previous != i {
+                        tracing::warn!(
+                            "Switching base layer endpoint from {} to {}",
+                            self.endpoints[previous].name,
+                            endpoint.name
+                        );
+                    }
+                    return Ok(res);
+                }
+                // The endpoint answered, with an error.
+                Ok(Err(err @ ProviderError::StarknetError(_))) => return Err(err.into()),
+                Ok(Err(err)) => anyhow::Error::from(err),
+                Err(_) => anyhow::anyhow!("Request timed out after {:?}", self.request_timeout),
+            };
+            tracing::warn!("Base layer endpoint {} failed: {err:#}", endpoint.name);
+            last_error = Some(err);
+        }
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No base layer endpoint")))
+    }
+
+    async fn get_core_contract_events(
+        &self,
+        event_name: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<EmittedEvent>> {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from_block)),
+            to_block: Some(BlockId::Number(to_block)),
+            address: Some(self.core_contract_address),
+            keys: Some(vec![vec![starknet_keccak(event_name.as_bytes())]]),
+        };
+
+        let mut events = vec![];
+        let mut continuation_token = None;
+        loop {
+            let page = self
+                .request(|provider| provider.get_events(filter.clone(), continuation_token.clone(), EVENTS_CHUNK_SIZE))
+                .await?;
+            events.extend(page.events);
+            continuation_token = page.continuation_token;
+            if continuation_token.is_none() {
+                return Ok(events);
+            }
+        }
+    }
+}
+
+fn felt_to_u64(felt: Felt, name: &str) -> anyhow::Result<u64> {
+    felt.try_into().map_err(|_| anyhow::anyhow!("{name} {felt:#x} out of range"))
+}
+
+fn felt_to_u128(felt: Felt, name: &str) -> anyhow::Result<u128> {
+    felt.try_into().map_err(|_| anyhow::anyhow!("{name} {felt:#x} out of range"))
+}
+
+fn parse_state_update_event(event: &EmittedEvent) -> anyhow::Result<L1StateUpdate> {
+    let [global_root, block_hash, block_number] = event.data[..] else {
+        anyhow::bail!("Invalid LogStateUpdate event data: {:?}", event.data)
+    };
+    Ok(L1StateUpdate { block_number: felt_to_u64(block_number, "Block number")?, global_root, block_hash })
+}
+
+fn parse_message_event(event: &EmittedEvent) -> anyhow::Result<MessageToAppchain> {
+    let [_, _message_hash, from_address, to_address] = event.keys[..] else {
+        anyhow::bail!("Invalid MessageSent event keys: {:?}", event.keys)
+    };
+    let [selector, nonce, payload_len, ref payload @ ..] = event.data[..] else {
+        anyhow::bail!("Invalid MessageSent event data: {:?}", event.data)
+    };
+    ensure!(
+        payload.len() as u64 == felt_to_u64(payload_len, "Payload length")?,
+        "Invalid MessageSent event payload length: {:?}",
+        event.data
+    );
+
+    Ok(MessageToAppchain {
+        from_address,
+        to_address,
+        selector,
+        payload: payload.to_vec(),
+        nonce,
+        paid_fee: None,
+        block_number: event.block_number.context("Message sent in a pending block")?,
+        tx_hash: Some(H256(event.transaction_hash.to_bytes_be())),
+    })
+}
+
+fn resource_price(price: &ResourcePrice) -> anyhow::Result<(u128, u128)> {
+    Ok((felt_to_u128(price.price_in_wei, "Price in wei")?, felt_to_u128(price.price_in_fri, "Price in fri")?))
+}
+
+#[async_trait]
+impl SettlementLayerClient for StarknetClient {
+    fn name(&self) -> &'static str {
+        "Starknet"
+    }
+
+    async fn get_latest_block_number(&self) -> anyhow::Result<u64> {
+        self.request(|provider| provider.block_number()).await
+    }
+
+    async fn get_last_state_update(&self) -> anyhow::Result<L1StateUpdate> {
+        let call = FunctionCall {
+            contract_address: self.core_contract_address,
+            entry_point_selector: get_selector_from_name("get_state")?,
+            calldata: vec![],
+        };
+        let res = self.request(|provider| provider.call(call.clone(), BlockId::Tag(BlockTag::Latest))).await?;
+        let [global_root, block_number, block_hash] = res[..] else {
+            anyhow::bail!("Invalid get_state response: {res:?}")
+        };
+        Ok(L1StateUpdate { block_number: felt_to_u64(block_number, "Block number")?, global_root, block_hash })
+    }
+
+    async fn get_state_updates(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<L1StateUpdate>> {
+        self.get_core_contract_events("LogStateUpdate", from_block, to_block)
+            .await?
+            .iter()
+            .map(parse_state_update_event)
+            .collect()
+    }
+
+    async fn get_messages_to_appchain(&self, from_block: u64, to_block: u64) -> anyhow::Result<Vec<MessageToAppchain>> {
+        self.get_core_contract_events("MessageSent", from_block, to_block)
+            .await?
+            .iter()
+            .map(parse_message_event)
+            .collect()
+    }
+
+    /// The L1 gas prices of the base layer, which already include both the wei and fri prices.
+    async fn get_gas_prices(&self) -> anyhow::Result<SettlementLayerGasPrices> {
+        let (gas_price, data_gas_price) =
+            match self.request(|provider| provider.get_block_with_tx_hashes(BlockId::Tag(BlockTag::Latest))).await? {
+                MaybePendingBlockWithTxHashes::Block(block) => (block.l1_gas_price, block.l1_data_gas_price),
+                MaybePendingBlockWithTxHashes::PendingBlock(block) => (block.l1_gas_price, block.l1_data_gas_price),
+            };
+        let (gas_price_wei, gas_price_strk) = resource_price(&gas_price)?;
+        let (data_gas_price_wei, data_gas_price_strk) = resource_price(&data_gas_price)?;
+        Ok(SettlementLayerGasPrices {
+            gas_price_wei,
+            data_gas_price_wei,
+            gas_price_strk: Some(gas_price_strk),
+            data_gas_price_strk: Some(data_gas_price_strk),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(keys: Vec<Felt>, data: Vec<Felt>) -> EmittedEvent {
+        EmittedEvent {
+            from_address: Felt::from(1),
+            keys,
+            data,
+            block_hash: Some(Felt::from(2)),
+            block_number: Some(12),
+            transaction_hash: Felt::from(0xabc),
+        }
+    }
+
+    #[test]
+    fn test_parse_state_update_event() {
+        let selector = starknet_keccak(b"LogStateUpdate");
+        let state_update =
+            parse_state_update_event(&event(vec![selector], vec![Felt::from(10), Felt::from(11), Felt::from(5)]))
+                .unwrap();
+        assert_eq!(
+            state_update,
+            L1StateUpdate { block_number: 5, global_root: Felt::from(10), block_hash: Felt::from(11) }
+        );
+        assert!(parse_state_update_event(&event(vec![selector], vec![Felt::from(10)])).is_err());
+    }
+
+    #[test]
+    fn test_parse_message_event() {
+        let keys = vec![starknet_keccak(b"MessageSent"), Felt::from(0x1234), Felt::from(0x10), Felt::from(0x20)];
+        let data = vec![Felt::from(0x30), Felt::from(7), Felt::from(2), Felt::from(100), Felt::from(200)];
+        let message = parse_message_event(&event(keys.clone(), data)).unwrap();
+        assert_eq!(
+            message,
+            MessageToAppchain {
+                from_address: Felt::from(0x10),
+                to_address: Felt::from(0x20),
+                selector: Felt::from(0x30),
+                payload: vec![Felt::from(100), Felt::from(200)],
+                nonce: Felt::from(7),
+                paid_fee: None,
+                block_number: 12,
+                tx_hash: Some(H256::from_low_u64_be(0xabc)),
+            }
+        );
+
+        let l1_handler = message.to_l1_handler_transaction().unwrap();
+        assert_eq!(l1_handler.calldata.0.as_slice(), &[Felt::from(0x10), Felt::from(100), Felt::from(200)]);
+
+        // Wrong payload length.
+        let data = vec![Felt::from(0x30), Felt::from(7), Felt::from(3), Felt::from(100), Felt::from(200)];
+        assert!(parse_message_event(&event(keys, data)).is_err());
+    }
+}
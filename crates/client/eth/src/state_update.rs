@@ -22,11 +22,7 @@ pub struct L1StateUpdate {
 
 /// Get the last Starknet state update verified on the L1
 pub async fn get_initial_state(client: &EthereumClient) -> anyhow::Result<L1StateUpdate> {
-    let block_number = client.get_last_verified_block_number().await?;
-    let block_hash = client.get_last_verified_block_hash().await?;
-    let global_root = client.get_last_state_root().await?;
-
-    Ok(L1StateUpdate { global_root, block_number, block_hash })
+    client.get_last_verified_state().await
 }
 
 pub fn update_l1(
@@ -89,6 +85,7 @@ mod eth_client_event_subscription_test {
     use super::*;
     use std::{sync::Arc, time::Duration};
 
+    use crate::failover::FailoverConfig;
     use alloy::{node_bindings::Anvil, providers::ProviderBuilder, sol};
     use mc_db::DatabaseService;
     use mp_chain_config::ChainConfig;
@@ -158,10 +155,14 @@ mod eth_client_event_subscription_test {
         let provider = ProviderBuilder::new().on_http(rpc_url);
 
         let contract = DummyContract::deploy(provider.clone()).await.unwrap();
-        let core_contract = StarknetCoreContract::new(*contract.address(), provider.clone());
 
-        let eth_client =
-            EthereumClient { provider: Arc::new(provider), l1_core_contract: core_contract.clone(), l1_block_metrics };
+        let eth_client = EthereumClient::new_unchecked(
+            vec![anvil.endpoint_url()],
+            *contract.address(),
+            l1_block_metrics,
+            FailoverConfig::default(),
+        )
+        .unwrap();
 
         // Start listening for state updates
         let listen_handle = {
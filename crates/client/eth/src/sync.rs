@@ -1,6 +1,10 @@
 use crate::client::EthereumClient;
 use crate::l1_gas_price::gas_price_worker;
+use crate::l1_gas_price_strategy::L1GasPriceStrategy;
+use crate::l1_handler_policy::L1HandlerPolicy;
 use crate::l1_messaging::sync;
+use crate::msg_to_l1::msg_to_l1_worker;
+use crate::settlement::{settlement_worker, SettlementClient};
 use crate::state_update::state_update_worker;
 use mc_mempool::{GasPriceProvider, Mempool};
 use mp_utils::service::ServiceContext;
@@ -17,17 +21,38 @@ pub async fn l1_sync_worker(
     chain_id: ChainId,
     l1_gas_provider: GasPriceProvider,
     gas_price_sync_disabled: bool,
+    gas_price_strategy: L1GasPriceStrategy,
     gas_price_poll_ms: Duration,
     mempool: Arc<Mempool>,
+    l1_handler_policy: L1HandlerPolicy,
+    settlement: Option<Arc<SettlementClient>>,
     ctx: ServiceContext,
 ) -> anyhow::Result<()> {
     let mut join_set = tokio::task::JoinSet::new();
 
     join_set.spawn(state_update_worker(Arc::clone(&backend), Arc::clone(&eth_client), ctx.clone()));
-    join_set.spawn(sync(Arc::clone(&backend), Arc::clone(&eth_client), chain_id, mempool, ctx.clone()));
+    join_set.spawn(sync(
+        Arc::clone(&backend),
+        Arc::clone(&eth_client),
+        chain_id,
+        mempool,
+        l1_handler_policy,
+        ctx.clone(),
+    ));
+    join_set.spawn(msg_to_l1_worker(Arc::clone(&backend), Arc::clone(&eth_client), ctx.clone()));
 
     if !gas_price_sync_disabled {
-        join_set.spawn(gas_price_worker(Arc::clone(&eth_client), l1_gas_provider, gas_price_poll_ms, ctx.clone()));
+        join_set.spawn(gas_price_worker(
+            Arc::clone(&eth_client),
+            l1_gas_provider,
+            gas_price_strategy,
+            gas_price_poll_ms,
+            ctx.clone(),
+        ));
+    }
+
+    if let Some(settlement) = settlement {
+        join_set.spawn(settlement_worker(Arc::clone(&backend), settlement, ctx.clone()));
     }
 
     while let Some(res) = join_set.join_next().await {
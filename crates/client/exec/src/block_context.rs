@@ -1,4 +1,8 @@
-use crate::{blockifier_state_adapter::BlockifierStateAdapter, Error};
+use crate::{
+    blockifier_state_adapter::BlockifierStateAdapter,
+    state_overrides::{BlockOverrides, StateOverrides},
+    Error,
+};
 use blockifier::{
     blockifier::{
         config::TransactionExecutorConfig, stateful_validator::StatefulValidator,
@@ -16,16 +20,21 @@ pub struct ExecutionContext {
     pub(crate) backend: Arc<MadaraBackend>,
     pub(crate) block_context: BlockContext,
     pub(crate) db_id: DbBlockId,
+    pub(crate) state_overrides: Arc<StateOverrides>,
 }
 
 impl ExecutionContext {
     pub fn tx_executor(&self) -> TransactionExecutor<BlockifierStateAdapter> {
-        TransactionExecutor::new(
-            self.init_cached_state(),
-            self.block_context.clone(),
-            // No concurrency yet.
-            TransactionExecutorConfig { concurrency_config: Default::default() },
-        )
+        self.tx_executor_with_config(TransactionExecutorConfig { concurrency_config: Default::default() })
+    }
+
+    /// Same as [`ExecutionContext::tx_executor`], with an explicit config. This is used to enable concurrent
+    /// execution.
+    pub fn tx_executor_with_config(
+        &self,
+        config: TransactionExecutorConfig,
+    ) -> TransactionExecutor<BlockifierStateAdapter> {
+        TransactionExecutor::new(self.init_cached_state(), self.block_context.clone(), config)
     }
 
     pub fn tx_validator(&self) -> StatefulValidator<BlockifierStateAdapter> {
@@ -47,17 +56,35 @@ impl ExecutionContext {
             self.block_context.block_info().block_number.0
         );
 
-        CachedState::new(BlockifierStateAdapter::new(
-            Arc::clone(&self.backend),
-            self.block_context.block_info().block_number.0,
-            on_top_of,
-        ))
+        CachedState::new(
+            BlockifierStateAdapter::new(
+                Arc::clone(&self.backend),
+                self.block_context.block_info().block_number.0,
+                on_top_of,
+            )
+            .with_overrides(Arc::clone(&self.state_overrides)),
+        )
+    }
+
+    /// Layers state overrides over the database state this context executes on top of.
+    pub fn with_state_overrides(mut self, state_overrides: StateOverrides) -> Self {
+        self.state_overrides = Arc::new(state_overrides);
+        self
     }
 
     /// Create an execution context for executing transactions **within** that block.
-    #[tracing::instrument(skip(backend, block_info), fields(module = "ExecutionContext"))]
     pub fn new_in_block(backend: Arc<MadaraBackend>, block_info: &MadaraMaybePendingBlockInfo) -> Result<Self, Error> {
-        let (db_id, protocol_version, block_number, block_timestamp, sequencer_address, l1_gas_price, l1_da_mode) =
+        Self::new_in_block_with_overrides(backend, block_info, &BlockOverrides::default())
+    }
+
+    /// Same as [`ExecutionContext::new_in_block`], with some fields of the block context overridden.
+    #[tracing::instrument(skip(backend, block_info), fields(module = "ExecutionContext"))]
+    pub fn new_in_block_with_overrides(
+        backend: Arc<MadaraBackend>,
+        block_info: &MadaraMaybePendingBlockInfo,
+        block_overrides: &BlockOverrides,
+    ) -> Result<Self, Error> {
+        let (db_id, protocol_version, block_number, block_timestamp, sequencer_address, mut l1_gas_price, l1_da_mode) =
             match block_info {
                 MadaraMaybePendingBlockInfo::Pending(block) => (
                     DbBlockId::Pending,
@@ -81,6 +108,10 @@ impl ExecutionContext {
                 ),
             };
 
+        let block_timestamp = block_overrides.block_timestamp.unwrap_or(block_timestamp.0);
+        let sequencer_address = block_overrides.sequencer_address.unwrap_or(sequencer_address);
+        block_overrides.apply_gas_prices(&mut l1_gas_price);
+
         let versioned_constants = backend.chain_config().exec_constants_by_protocol_version(protocol_version)?;
         let chain_info = ChainInfo {
             chain_id: backend.chain_config().chain_id.clone(),
@@ -91,7 +122,7 @@ impl ExecutionContext {
         };
         let block_info = blockifier::blockifier::block::BlockInfo {
             block_number: BlockNumber(block_number),
-            block_timestamp: BlockTimestamp(block_timestamp.0),
+            block_timestamp: BlockTimestamp(block_timestamp),
             sequencer_address: sequencer_address
                 .try_into()
                 .map_err(|_| Error::InvalidSequencerAddress(sequencer_address))?,
@@ -108,6 +139,7 @@ impl ExecutionContext {
             ),
             db_id,
             backend,
+            state_overrides: Default::default(),
         })
     }
 }
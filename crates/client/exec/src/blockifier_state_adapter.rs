@@ -1,3 +1,4 @@
+use crate::state_overrides::StateOverrides;
 use blockifier::execution::contract_class::ContractClass;
 use blockifier::state::errors::StateError;
 use blockifier::state::state_api::{StateReader, StateResult};
@@ -18,16 +19,28 @@ pub struct BlockifierStateAdapter {
     /// When this value is None, we are executing the genesis block.
     pub on_top_of_block_id: Option<DbBlockId>,
     pub block_number: u64,
+    /// Values returned instead of the ones in the db.
+    overrides: Arc<StateOverrides>,
 }
 
 impl BlockifierStateAdapter {
     pub fn new(backend: Arc<MadaraBackend>, block_number: u64, on_top_of_block_id: Option<DbBlockId>) -> Self {
-        Self { backend, on_top_of_block_id, block_number }
+        Self { backend, on_top_of_block_id, block_number, overrides: Default::default() }
+    }
+
+    /// Layers state overrides over the db state.
+    pub fn with_overrides(mut self, overrides: Arc<StateOverrides>) -> Self {
+        self.overrides = overrides;
+        self
     }
 }
 
 impl StateReader for BlockifierStateAdapter {
     fn get_storage_at(&self, contract_address: ContractAddress, key: StorageKey) -> StateResult<Felt> {
+        if let Some(value) = self.overrides.storage.get(&(contract_address.to_felt(), key.to_felt())) {
+            return Ok(*value);
+        }
+
         // The `0x1` address is reserved for block hashes: https://docs.starknet.io/architecture-and-concepts/network-architecture/starknet-state/#address_0x1
         if *contract_address.key() == Felt::ONE {
             let requested_block_number = (*key.0.key()).try_into().map_err(|_| StateError::OldBlockHashNotProvided)?;
@@ -72,6 +85,9 @@ impl StateReader for BlockifierStateAdapter {
 
     fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
         tracing::debug!("get_nonce_at for {}", contract_address);
+        if let Some(nonce) = self.overrides.nonces.get(&contract_address.to_felt()) {
+            return Ok(Nonce(*nonce));
+        }
         let Some(on_top_of_block_id) = self.on_top_of_block_id else { return Ok(Nonce::default()) };
 
         Ok(Nonce(
@@ -87,6 +103,9 @@ impl StateReader for BlockifierStateAdapter {
 
     fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
         tracing::debug!("get_class_hash_at for {}", contract_address);
+        if let Some(class_hash) = self.overrides.class_hashes.get(&contract_address.to_felt()) {
+            return Ok(ClassHash(*class_hash));
+        }
         let Some(on_top_of_block_id) = self.on_top_of_block_id else { return Ok(ClassHash::default()) };
 
         // Note that blockifier is fine with us returning ZERO as a class_hash if it is not found, they do the check on their end after
@@ -107,17 +126,22 @@ impl StateReader for BlockifierStateAdapter {
     fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
         tracing::debug!("get_compiled_contract_class for {:#x}", class_hash.to_felt());
 
-        let Some(on_top_of_block_id) = self.on_top_of_block_id else {
-            return Err(StateError::UndeclaredClassHash(class_hash));
-        };
-
-        let Some(converted_class) =
-            self.backend.get_converted_class(&on_top_of_block_id, &class_hash.to_felt()).map_err(|err| {
-                tracing::warn!("Failed to retrieve class {class_hash:#}: {err:#}");
-                StateError::StateReadError(format!("Failed to retrieve class {class_hash:#}"))
-            })?
-        else {
-            return Err(StateError::UndeclaredClassHash(class_hash));
+        let converted_class = if let Some(converted_class) = self.overrides.classes.get(&class_hash.to_felt()) {
+            converted_class.clone()
+        } else {
+            let Some(on_top_of_block_id) = self.on_top_of_block_id else {
+                return Err(StateError::UndeclaredClassHash(class_hash));
+            };
+
+            let Some(converted_class) =
+                self.backend.get_converted_class(&on_top_of_block_id, &class_hash.to_felt()).map_err(|err| {
+                    tracing::warn!("Failed to retrieve class {class_hash:#}: {err:#}");
+                    StateError::StateReadError(format!("Failed to retrieve class {class_hash:#}"))
+                })?
+            else {
+                return Err(StateError::UndeclaredClassHash(class_hash));
+            };
+            converted_class
         };
 
         converted_class.to_blockifier_class().map_err(|err| {
@@ -129,19 +153,24 @@ impl StateReader for BlockifierStateAdapter {
     fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
         tracing::debug!("get_compiled_class_hash for {:#x}", class_hash.to_felt());
 
-        let Some(on_top_of_block_id) = self.on_top_of_block_id else {
-            return Err(StateError::UndeclaredClassHash(class_hash));
-        };
-        let Some(class_info) =
-            self.backend.get_class_info(&on_top_of_block_id, &class_hash.to_felt()).map_err(|err| {
-                tracing::warn!("Failed to retrieve compiled class hash {:#x}: {err:#}", class_hash.to_felt());
-                StateError::StateReadError(format!(
-                    "Failed to retrieve compiled class hash {:#x}",
-                    class_hash.to_felt()
-                ))
-            })?
-        else {
-            return Err(StateError::UndeclaredClassHash(class_hash));
+        let class_info = if let Some(converted_class) = self.overrides.classes.get(&class_hash.to_felt()) {
+            converted_class.info()
+        } else {
+            let Some(on_top_of_block_id) = self.on_top_of_block_id else {
+                return Err(StateError::UndeclaredClassHash(class_hash));
+            };
+            let Some(class_info) =
+                self.backend.get_class_info(&on_top_of_block_id, &class_hash.to_felt()).map_err(|err| {
+                    tracing::warn!("Failed to retrieve compiled class hash {:#x}: {err:#}", class_hash.to_felt());
+                    StateError::StateReadError(format!(
+                        "Failed to retrieve compiled class hash {:#x}",
+                        class_hash.to_felt()
+                    ))
+                })?
+            else {
+                return Err(StateError::UndeclaredClassHash(class_hash));
+            };
+            class_info
         };
 
         match class_info {
@@ -166,9 +195,10 @@ fn block_hash_storage_check_range(chain_id: &ChainId, current_block: u64, to_che
 
 #[cfg(test)]
 mod tests {
+    use mp_chain_config::ChainConfig;
     use starknet_api::core::ChainId;
 
-    use super::block_hash_storage_check_range;
+    use super::*;
 
     #[test]
     fn check_block_n_range() {
@@ -181,4 +211,34 @@ mod tests {
         assert!(block_hash_storage_check_range(&chain_id, 50 + 11, 50));
         assert!(!block_hash_storage_check_range(&ChainId::Mainnet, 50 + 11, 50));
     }
+
+    #[test]
+    fn state_overrides() {
+        let backend = MadaraBackend::open_for_testing(Arc::new(ChainConfig::madara_test()));
+        let contract = ContractAddress::try_from(Felt::from(0x1234)).unwrap();
+        let other_contract = ContractAddress::try_from(Felt::from(0x5678)).unwrap();
+        let key = StorageKey::try_from(Felt::from(0x10)).unwrap();
+
+        let overrides = StateOverrides {
+            storage: [((contract.to_felt(), key.to_felt()), Felt::from(42))].into(),
+            nonces: [(contract.to_felt(), Felt::from(3))].into(),
+            class_hashes: [(contract.to_felt(), Felt::from(0xc1a55))].into(),
+            ..Default::default()
+        };
+        let adapter = BlockifierStateAdapter::new(Arc::clone(&backend), 0, Some(DbBlockId::Pending))
+            .with_overrides(Arc::new(overrides));
+
+        assert_eq!(adapter.get_storage_at(contract, key).unwrap(), Felt::from(42));
+        assert_eq!(adapter.get_nonce_at(contract).unwrap(), Nonce(Felt::from(3)));
+        assert_eq!(adapter.get_class_hash_at(contract).unwrap(), ClassHash(Felt::from(0xc1a55)));
+
+        // Not overridden: read from the db.
+        assert_eq!(adapter.get_storage_at(other_contract, key).unwrap(), Felt::ZERO);
+        assert_eq!(adapter.get_nonce_at(other_contract).unwrap(), Nonce::default());
+        assert_eq!(adapter.get_class_hash_at(other_contract).unwrap(), ClassHash::default());
+        assert!(matches!(
+            adapter.get_compiled_class_hash(ClassHash(Felt::from(0xc1a55))),
+            Err(StateError::UndeclaredClassHash(_))
+        ));
+    }
 }
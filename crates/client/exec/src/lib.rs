@@ -15,11 +15,13 @@ mod blockifier_state_adapter;
 mod call;
 pub mod execution;
 mod fee;
+mod state_overrides;
 mod trace;
 pub mod transaction;
 
 pub use block_context::ExecutionContext;
 pub use blockifier_state_adapter::BlockifierStateAdapter;
+pub use state_overrides::{BlockOverrides, StateOverrideError, StateOverrides};
 pub use trace::execution_result_to_tx_trace;
 
 #[derive(thiserror::Error, Debug)]
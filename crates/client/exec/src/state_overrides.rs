@@ -0,0 +1,90 @@
+//! Overrides layered over the database state and the block context, used to execute calls and simulations against a
+//! hypothetical state. Nothing here is ever written to the database.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use mp_block::header::GasPrices;
+use mp_class::class_hash::ComputeClassHashError;
+use mp_class::compile::ClassCompilationError;
+use mp_class::{
+    ContractClass, ConvertedClass, LegacyClassInfo, LegacyConvertedClass, SierraClassInfo, SierraConvertedClass,
+};
+use starknet_types_core::felt::Felt;
+
+#[derive(thiserror::Error, Debug)]
+pub enum StateOverrideError {
+    #[error("Computing the class hash of an overridden class: {0:#}")]
+    ClassHash(#[from] ComputeClassHashError),
+    #[error("Compiling an overridden class: {0:#}")]
+    Compilation(#[from] ClassCompilationError),
+}
+
+/// Values returned by [`crate::BlockifierStateAdapter`] instead of the ones in the database.
+#[derive(Debug, Clone, Default)]
+pub struct StateOverrides {
+    /// Storage values, by contract address and key.
+    pub storage: HashMap<(Felt, Felt), Felt>,
+    /// Nonces, by contract address.
+    pub nonces: HashMap<Felt, Felt>,
+    /// Class hashes, by contract address. This also deploys a contract at an address which has none.
+    pub class_hashes: HashMap<Felt, Felt>,
+    /// Classes which are declared on top of the database state, by class hash.
+    pub classes: HashMap<Felt, ConvertedClass>,
+}
+
+impl StateOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty() && self.nonces.is_empty() && self.class_hashes.is_empty() && self.classes.is_empty()
+    }
+
+    /// Declares a class in the overridden state, compiling it if it is a Sierra class. Returns its class hash.
+    pub fn declare_class(&mut self, contract_class: ContractClass) -> Result<Felt, StateOverrideError> {
+        let converted_class = match contract_class {
+            ContractClass::Sierra(contract_class) => {
+                let class_hash = contract_class.compute_class_hash()?;
+                let (compiled_class_hash, compiled) = contract_class.compile_to_casm()?;
+                ConvertedClass::Sierra(SierraConvertedClass {
+                    class_hash,
+                    info: SierraClassInfo { contract_class, compiled_class_hash },
+                    compiled: Arc::new(compiled),
+                })
+            }
+            ContractClass::Legacy(contract_class) => {
+                let class_hash = contract_class.compute_class_hash()?;
+                ConvertedClass::Legacy(LegacyConvertedClass { class_hash, info: LegacyClassInfo { contract_class } })
+            }
+        };
+
+        let class_hash = converted_class.class_hash();
+        self.classes.insert(class_hash, converted_class);
+        Ok(class_hash)
+    }
+}
+
+/// Block context fields used instead of the ones of the block the execution happens in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockOverrides {
+    pub block_timestamp: Option<u64>,
+    pub sequencer_address: Option<Felt>,
+    pub eth_l1_gas_price: Option<u128>,
+    pub strk_l1_gas_price: Option<u128>,
+    pub eth_l1_data_gas_price: Option<u128>,
+    pub strk_l1_data_gas_price: Option<u128>,
+}
+
+impl BlockOverrides {
+    pub(crate) fn apply_gas_prices(&self, gas_prices: &mut GasPrices) {
+        let overrides = [
+            (self.eth_l1_gas_price, &mut gas_prices.eth_l1_gas_price),
+            (self.strk_l1_gas_price, &mut gas_prices.strk_l1_gas_price),
+            (self.eth_l1_data_gas_price, &mut gas_prices.eth_l1_data_gas_price),
+            (self.strk_l1_data_gas_price, &mut gas_prices.strk_l1_data_gas_price),
+        ];
+        for (price, gas_price) in overrides {
+            if let Some(price) = price {
+                *gas_price = price;
+            }
+        }
+    }
+}
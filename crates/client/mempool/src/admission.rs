@@ -0,0 +1,261 @@
+//! Sequencer transaction admission policy, see [`AdmissionPolicy`].
+
+use blockifier::transaction::account_transaction::AccountTransaction;
+use blockifier::transaction::transaction_execution::Transaction;
+use mp_chain_config::AdmissionPolicy;
+use mp_convert::ToFelt;
+use starknet_api::transaction::{DeclareTransaction, DeployAccountTransaction, InvokeTransaction};
+use starknet_types_core::felt::Felt;
+
+use crate::contract_addr;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum AdmissionPolicyViolation {
+    #[error("Sender {0:#x} is not allowed to submit transactions")]
+    SenderNotAllowed(Felt),
+    #[error("Contract {0:#x} is not an allowed call target")]
+    TargetNotAllowed(Felt),
+    #[error("Entry-point selector {selector:#x} is blocked (called on contract {target:#x})")]
+    SelectorBlocked { target: Felt, selector: Felt },
+    #[error("Tip {tip} is lower than the minimum tip of {min_tip}")]
+    TipTooLow { tip: u64, min_tip: u64 },
+    #[error("Calldata length {len} exceeds the maximum of {max}")]
+    CalldataTooLong { len: usize, max: usize },
+    #[error("Sender {0:#x} is not allowed to declare classes")]
+    DeclareNotAllowed(Felt),
+    #[error("Calldata does not follow the standard multicall encoding, call targets cannot be checked")]
+    UndecodableCalldata,
+}
+
+/// A call decoded from an invoke transaction: `(to, selector)`.
+type DecodedCall = (Felt, Felt);
+
+/// Checks a transaction against the admission policy. L1 handler transactions are always admitted.
+pub fn check_admission_policy(policy: &AdmissionPolicy, tx: &Transaction) -> Result<(), AdmissionPolicyViolation> {
+    let Transaction::AccountTransaction(account_tx) = tx else { return Ok(()) };
+
+    let sender = contract_addr(tx).to_felt();
+    if policy.sender_deny_list.contains(&sender)
+        || policy.sender_allow_list.as_ref().is_some_and(|allowed| !allowed.contains(&sender))
+    {
+        return Err(AdmissionPolicyViolation::SenderNotAllowed(sender));
+    }
+
+    let min_tip = policy.min_tip;
+    let tip = tx_tip(account_tx);
+    if tip < min_tip {
+        return Err(AdmissionPolicyViolation::TipTooLow { tip, min_tip });
+    }
+
+    if let Some(max) = policy.max_calldata_length {
+        let len = account_tx.calldata_length();
+        if len > max {
+            return Err(AdmissionPolicyViolation::CalldataTooLong { len, max });
+        }
+    }
+
+    match account_tx {
+        AccountTransaction::Declare(_) => {
+            if policy.declare_allow_list.as_ref().is_some_and(|allowed| !allowed.contains(&sender)) {
+                return Err(AdmissionPolicyViolation::DeclareNotAllowed(sender));
+            }
+        }
+        AccountTransaction::Invoke(tx) if policy.filters_calls() => {
+            for (target, selector) in invoke_calls(&tx.tx)? {
+                if policy.target_deny_list.contains(&target)
+                    || policy.target_allow_list.as_ref().is_some_and(|allowed| !allowed.contains(&target))
+                {
+                    return Err(AdmissionPolicyViolation::TargetNotAllowed(target));
+                }
+                if policy.blocked_selectors.contains(&selector) {
+                    return Err(AdmissionPolicyViolation::SelectorBlocked { target, selector });
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn tx_tip(tx: &AccountTransaction) -> u64 {
+    match tx {
+        AccountTransaction::Declare(tx) => match &tx.tx {
+            DeclareTransaction::V3(tx) => tx.tip.0,
+            _ => 0,
+        },
+        AccountTransaction::DeployAccount(tx) => match &tx.tx {
+            DeployAccountTransaction::V3(tx) => tx.tip.0,
+            _ => 0,
+        },
+        AccountTransaction::Invoke(tx) => match &tx.tx {
+            InvokeTransaction::V3(tx) => tx.tip.0,
+            _ => 0,
+        },
+    }
+}
+
+/// Decodes the calls made by an invoke transaction.
+///
+/// Invoke v0 transactions call a single entry-point directly. For later versions, the calldata is passed to the
+/// account's `__execute__` entry-point: we assume the standard Cairo 1 multicall encoding, which is
+/// `[n_calls, (to, selector, calldata_len, calldata...)...]`. Calldata that cannot be decoded this way is rejected,
+/// as we would have no way of enforcing the policy.
+fn invoke_calls(tx: &InvokeTransaction) -> Result<Vec<DecodedCall>, AdmissionPolicyViolation> {
+    let calldata = match tx {
+        InvokeTransaction::V0(tx) => return Ok(vec![(tx.contract_address.to_felt(), tx.entry_point_selector.0)]),
+        InvokeTransaction::V1(tx) => &tx.calldata,
+        InvokeTransaction::V3(tx) => &tx.calldata,
+    };
+
+    let felt_to_usize = |felt: &Felt| usize::try_from(*felt).map_err(|_| AdmissionPolicyViolation::UndecodableCalldata);
+
+    let (n_calls, mut rest) = calldata.0.split_first().ok_or(AdmissionPolicyViolation::UndecodableCalldata)?;
+    let n_calls = felt_to_usize(n_calls)?;
+    let mut calls = Vec::new();
+    for _ in 0..n_calls {
+        let [to, selector, len, tail @ ..] = rest else { return Err(AdmissionPolicyViolation::UndecodableCalldata) };
+        let len = felt_to_usize(len)?;
+        if tail.len() < len {
+            return Err(AdmissionPolicyViolation::UndecodableCalldata);
+        }
+        calls.push((*to, *selector));
+        rest = &tail[len..];
+    }
+    if !rest.is_empty() {
+        return Err(AdmissionPolicyViolation::UndecodableCalldata);
+    }
+
+    Ok(calls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use starknet_api::core::{ContractAddress, EntryPointSelector};
+    use starknet_api::transaction::{Calldata, InvokeTransactionV0, InvokeTransactionV3, Tip, TransactionHash};
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    const SENDER: Felt = Felt::from_hex_unchecked("0x1234");
+    const TARGET: Felt = Felt::from_hex_unchecked("0x5678");
+    const SELECTOR: Felt = Felt::from_hex_unchecked("0x9abc");
+
+    fn invoke_v3(calldata: Vec<Felt>, tip: u64) -> Transaction {
+        Transaction::AccountTransaction(AccountTransaction::Invoke(
+            blockifier::transaction::transactions::InvokeTransaction {
+                tx: InvokeTransaction::V3(InvokeTransactionV3 {
+                    resource_bounds: Default::default(),
+                    tip: Tip(tip),
+                    signature: Default::default(),
+                    nonce: Default::default(),
+                    sender_address: ContractAddress::try_from(SENDER).unwrap(),
+                    calldata: Calldata(Arc::new(calldata)),
+                    nonce_data_availability_mode: starknet_api::data_availability::DataAvailabilityMode::L1,
+                    fee_data_availability_mode: starknet_api::data_availability::DataAvailabilityMode::L1,
+                    paymaster_data: Default::default(),
+                    account_deployment_data: Default::default(),
+                }),
+                tx_hash: TransactionHash(Felt::ZERO),
+                only_query: false,
+            },
+        ))
+    }
+
+    fn single_call() -> Vec<Felt> {
+        vec![Felt::ONE, TARGET, SELECTOR, Felt::TWO, Felt::ONE, Felt::TWO]
+    }
+
+    #[rstest]
+    fn test_default_policy_admits_everything() {
+        let policy = AdmissionPolicy::default();
+        assert_eq!(check_admission_policy(&policy, &invoke_v3(vec![Felt::THREE], 0)), Ok(()));
+    }
+
+    #[rstest]
+    fn test_sender_lists() {
+        let tx = invoke_v3(single_call(), 0);
+
+        let policy = AdmissionPolicy { sender_deny_list: HashSet::from([SENDER]), ..Default::default() };
+        assert_eq!(check_admission_policy(&policy, &tx), Err(AdmissionPolicyViolation::SenderNotAllowed(SENDER)));
+
+        let policy = AdmissionPolicy { sender_allow_list: Some(HashSet::from([TARGET])), ..Default::default() };
+        assert_eq!(check_admission_policy(&policy, &tx), Err(AdmissionPolicyViolation::SenderNotAllowed(SENDER)));
+
+        let policy = AdmissionPolicy { sender_allow_list: Some(HashSet::from([SENDER])), ..Default::default() };
+        assert_eq!(check_admission_policy(&policy, &tx), Ok(()));
+    }
+
+    #[rstest]
+    fn test_min_tip_and_calldata_length() {
+        let policy = AdmissionPolicy { min_tip: 10, max_calldata_length: Some(6), ..Default::default() };
+
+        assert_eq!(
+            check_admission_policy(&policy, &invoke_v3(single_call(), 9)),
+            Err(AdmissionPolicyViolation::TipTooLow { tip: 9, min_tip: 10 })
+        );
+        assert_eq!(check_admission_policy(&policy, &invoke_v3(single_call(), 10)), Ok(()));
+
+        let mut calldata = single_call();
+        calldata.push(Felt::ZERO);
+        assert_eq!(
+            check_admission_policy(&policy, &invoke_v3(calldata, 10)),
+            Err(AdmissionPolicyViolation::CalldataTooLong { len: 7, max: 6 })
+        );
+    }
+
+    #[rstest]
+    fn test_call_filters() {
+        let tx = invoke_v3(single_call(), 0);
+
+        let policy = AdmissionPolicy { target_deny_list: HashSet::from([TARGET]), ..Default::default() };
+        assert_eq!(check_admission_policy(&policy, &tx), Err(AdmissionPolicyViolation::TargetNotAllowed(TARGET)));
+
+        let policy = AdmissionPolicy { target_allow_list: Some(HashSet::from([TARGET])), ..Default::default() };
+        assert_eq!(check_admission_policy(&policy, &tx), Ok(()));
+
+        let policy = AdmissionPolicy { blocked_selectors: HashSet::from([SELECTOR]), ..Default::default() };
+        assert_eq!(
+            check_admission_policy(&policy, &tx),
+            Err(AdmissionPolicyViolation::SelectorBlocked { target: TARGET, selector: SELECTOR })
+        );
+
+        // Second call of a multicall is checked too.
+        let tx = invoke_v3(vec![Felt::TWO, SENDER, Felt::ONE, Felt::ZERO, TARGET, SELECTOR, Felt::ZERO], 0);
+        assert_eq!(
+            check_admission_policy(&policy, &tx),
+            Err(AdmissionPolicyViolation::SelectorBlocked { target: TARGET, selector: SELECTOR })
+        );
+    }
+
+    #[rstest]
+    #[case::empty(vec![])]
+    #[case::missing_call(vec![Felt::TWO, TARGET, SELECTOR, Felt::ZERO])]
+    #[case::short_data(vec![Felt::ONE, TARGET, SELECTOR, Felt::THREE, Felt::ONE])]
+    #[case::trailing_data(vec![Felt::ONE, TARGET, SELECTOR, Felt::ZERO, Felt::ONE])]
+    fn test_undecodable_calldata(#[case] calldata: Vec<Felt>) {
+        let policy = AdmissionPolicy { blocked_selectors: HashSet::from([SELECTOR]), ..Default::default() };
+        assert_eq!(
+            check_admission_policy(&policy, &invoke_v3(calldata, 0)),
+            Err(AdmissionPolicyViolation::UndecodableCalldata)
+        );
+    }
+
+    #[rstest]
+    fn test_invoke_v0_call() {
+        let tx = Transaction::AccountTransaction(AccountTransaction::Invoke(
+            blockifier::transaction::transactions::InvokeTransaction {
+                tx: InvokeTransaction::V0(InvokeTransactionV0 {
+                    contract_address: ContractAddress::try_from(TARGET).unwrap(),
+                    entry_point_selector: EntryPointSelector(SELECTOR),
+                    ..Default::default()
+                }),
+                tx_hash: TransactionHash(Felt::ZERO),
+                only_query: false,
+            },
+        ));
+        let policy = AdmissionPolicy { target_deny_list: HashSet::from([TARGET]), ..Default::default() };
+        assert_eq!(check_admission_policy(&policy, &tx), Err(AdmissionPolicyViolation::TargetNotAllowed(TARGET)));
+    }
+}
@@ -3,16 +3,20 @@ use mp_block::header::{BlockTimestamp, PendingHeader};
 use mp_chain_config::ChainConfig;
 use starknet_types_core::felt::Felt;
 
+/// Makes the header of a new pending block. `block_n` is the block number of the new block, and is used to select the
+/// protocol version following the chain config's protocol upgrade schedule.
 pub fn make_pending_header(
     parent_block_hash: Felt,
+    block_n: u64,
     chain_config: &ChainConfig,
     l1_info: &dyn L1DataProvider,
 ) -> PendingHeader {
+    let block_timestamp = BlockTimestamp::now();
     PendingHeader {
         parent_block_hash,
         sequencer_address: **chain_config.sequencer_address,
-        block_timestamp: BlockTimestamp::now(),
-        protocol_version: chain_config.latest_protocol_version,
+        block_timestamp,
+        protocol_version: chain_config.protocol_version_at(block_n, block_timestamp.0),
         l1_gas_price: l1_info.get_gas_prices(),
         l1_da_mode: l1_info.get_da_mode(),
     }
@@ -10,7 +10,8 @@ use mc_db::db_block_id::DbBlockId;
 use mc_db::{MadaraBackend, MadaraStorageError};
 use mc_exec::ExecutionContext;
 use metrics::MempoolMetrics;
-use mp_block::{BlockId, BlockTag, MadaraPendingBlockInfo};
+use mp_block::{BlockId, BlockTag, MadaraMaybePendingBlockInfo, MadaraPendingBlockInfo};
+use mp_chain_config::AdmissionPolicy;
 use mp_class::ConvertedClass;
 use mp_convert::ToFelt;
 use mp_transactions::BroadcastedDeclareTransactionV0;
@@ -18,6 +19,7 @@ use mp_transactions::BroadcastedTransactionExt;
 use mp_transactions::L1HandlerTransaction;
 use mp_transactions::L1HandlerTransactionResult;
 use mp_transactions::ToBlockifierError;
+use replication::ReplicaBuffer;
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::transaction::TransactionHash;
 use starknet_types_core::felt::Felt;
@@ -25,8 +27,9 @@ use starknet_types_rpc::{
     AddInvokeTransactionResult, BroadcastedDeclareTxn, BroadcastedDeployAccountTxn, BroadcastedInvokeTxn,
     BroadcastedTxn, ClassAndTxnHash, ContractAndTxnHash,
 };
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::SystemTime;
+use tokio::sync::{broadcast, Notify};
 use tx::blockifier_to_saved_tx;
 use tx::saved_to_blockifier_tx;
 
@@ -34,13 +37,17 @@ use tx::saved_to_blockifier_tx;
 pub use l1::MockL1DataProvider;
 pub use l1::{GasPriceProvider, L1DataProvider};
 
+mod admission;
 pub mod header;
 mod inner;
 mod l1;
 pub mod metrics;
+mod replication;
 mod tx;
 
+pub use admission::*;
 pub use inner::*;
+pub use replication::ReplicatedTransaction;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -54,6 +61,8 @@ pub enum Error {
     Exec(#[from] mc_exec::Error),
     #[error("Preprocessing transaction: {0:#}")]
     BroadcastedToBlockifier(#[from] ToBlockifierError),
+    #[error("Rejected by the admission policy: {0}")]
+    AdmissionPolicy(#[from] AdmissionPolicyViolation),
 }
 impl Error {
     pub fn is_internal(&self) -> bool {
@@ -90,25 +99,86 @@ pub trait MempoolProvider: Send + Sync {
     where
         Self: Sized;
     fn chain_id(&self) -> Felt;
+    /// Notified every time new transactions are inserted into the mempool.
+    fn tx_received(&self) -> Arc<Notify>;
 }
 
 pub struct Mempool {
     backend: Arc<MadaraBackend>,
     l1_data_provider: Arc<dyn L1DataProvider>,
     inner: RwLock<MempoolInner>,
+    admission_policy: RwLock<AdmissionPolicy>,
+    tx_received: Arc<Notify>,
+    accepted_txs: broadcast::Sender<Arc<ReplicatedTransaction>>,
+    replica: Mutex<ReplicaBuffer>,
     metrics: MempoolMetrics,
 }
 
 impl Mempool {
     pub fn new(backend: Arc<MadaraBackend>, l1_data_provider: Arc<dyn L1DataProvider>, limits: MempoolLimits) -> Self {
+        let admission_policy = RwLock::new(backend.chain_config().mempool_admission_policy.clone());
+        let replica = Mutex::new(ReplicaBuffer::new(limits.max_transactions));
         Mempool {
             backend,
             l1_data_provider,
             inner: RwLock::new(MempoolInner::new(limits)),
+            admission_policy,
+            tx_received: Arc::new(Notify::new()),
+            accepted_txs: broadcast::channel(1024).0,
+            replica,
             metrics: MempoolMetrics::register(),
         }
     }
 
+    /// Subscribes to the transactions accepted into the mempool after submission. Transactions re-added by the block
+    /// production or inserted without validation are not sent.
+    pub fn subscribe_accepted_txs(&self) -> broadcast::Receiver<Arc<ReplicatedTransaction>> {
+        self.accepted_txs.subscribe()
+    }
+
+    /// Stores transactions replicated from the active sequencer, when running as a hot-standby sequencer. They are
+    /// only inserted into the mempool by [`Mempool::promote_replicated_txs`].
+    pub fn add_replicated_txs(&self, txs: Vec<ReplicatedTransaction>) {
+        self.replica.lock().expect("Poisoned lock").extend(txs);
+    }
+
+    /// Inserts the transactions replicated from the previous sequencer into the mempool, when taking over block
+    /// production. The transactions already included in a block are skipped. Returns the number of transactions
+    /// inserted.
+    pub fn promote_replicated_txs(&self) -> Result<usize, Error> {
+        let txs = self.replica.lock().expect("Poisoned lock").take();
+        let mut inserted = 0;
+        for ReplicatedTransaction { tx_hash, tx, converted_class } in txs {
+            if self.backend.find_tx_hash_block_info(&tx_hash)?.is_some() {
+                continue;
+            }
+            let (tx, arrived_at) = match saved_to_blockifier_tx(tx, tx_hash, &converted_class) {
+                Ok(tx) => tx,
+                Err(err) => {
+                    tracing::warn!("Could not convert replicated transaction {tx_hash:#x}: {err:#}");
+                    continue;
+                }
+            };
+            match self.accept_tx(tx, converted_class, arrived_at) {
+                Ok(()) => inserted += 1,
+                Err(err) => tracing::debug!("Replicated transaction {tx_hash:#x} was not re-added: {err:#}"),
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// The admission policy currently enforced. It is initially loaded from the chain config.
+    pub fn admission_policy(&self) -> AdmissionPolicy {
+        self.admission_policy.read().expect("Poisoned lock").clone()
+    }
+
+    /// Replaces the admission policy. This only affects transactions submitted after this call: transactions already
+    /// in the mempool are kept.
+    pub fn set_admission_policy(&self, policy: AdmissionPolicy) {
+        tracing::info!("🛂 Mempool admission policy updated");
+        *self.admission_policy.write().expect("Poisoned lock") = policy;
+    }
+
     pub fn load_txs_from_db(&mut self) -> Result<(), anyhow::Error> {
         for res in self.backend.get_mempool_transactions() {
             let (tx_hash, saved_tx, converted_class) = res.context("Getting mempool transactions")?;
@@ -132,21 +202,14 @@ impl Mempool {
         converted_class: Option<ConvertedClass>,
         arrived_at: SystemTime,
     ) -> Result<(), Error> {
-        // Get pending block.
-        let pending_block_info = if let Some(block) = self.backend.get_block_info(&DbBlockId::Pending)? {
-            block
-        } else {
-            // No current pending block, we'll make an unsaved empty one for the sake of validating this tx.
-            let parent_block_hash = self
-                .backend
-                .get_block_hash(&BlockId::Tag(BlockTag::Latest))?
-                .unwrap_or(/* genesis block's parent hash */ Felt::ZERO);
-            MadaraPendingBlockInfo::new(
-                make_pending_header(parent_block_hash, self.backend.chain_config(), self.l1_data_provider.as_ref()),
-                vec![],
-            )
-            .into()
-        };
+        // Check the admission policy before doing any expensive validation.
+        if let Err(err) = check_admission_policy(&self.admission_policy.read().expect("Poisoned lock"), &tx) {
+            tracing::debug!("Mempool admission policy rejected tx_hash={:#x}: {err}", tx_hash(&tx).to_felt());
+            self.metrics.rejected_transaction_counter.add(1, &[]);
+            return Err(err.into());
+        }
+
+        let pending_block_info = self.pending_block_info()?;
 
         // If the contract has been deployed for the same block is is invoked, we need to skip validations.
         // NB: the lock is NOT taken the entire time the tx is being validated. As such, the deploy tx
@@ -179,6 +242,13 @@ impl Mempool {
             let saved_tx = blockifier_to_saved_tx(&tx, arrived_at);
             self.backend.save_mempool_transaction(&saved_tx, tx_hash, &converted_class)?;
 
+            // Only clone the class when someone is listening.
+            let accepted = (self.accepted_txs.receiver_count() > 0).then(|| ReplicatedTransaction {
+                tx_hash,
+                tx: saved_tx,
+                converted_class: converted_class.clone(),
+            });
+
             // Add it to the inner mempool
             let force = false;
             self.inner.write().expect("Poisoned lock").insert_tx(
@@ -186,6 +256,11 @@ impl Mempool {
                 force,
                 true,
             )?;
+            self.tx_received.notify_one();
+
+            if let Some(accepted) = accepted {
+                let _ = self.accepted_txs.send(Arc::new(accepted));
+            }
 
             self.metrics.accepted_transaction_counter.add(1, &[]);
         }
@@ -193,6 +268,47 @@ impl Mempool {
         Ok(())
     }
 
+    /// The pending block, in which transactions are validated.
+    fn pending_block_info(&self) -> Result<MadaraMaybePendingBlockInfo, Error> {
+        if let Some(block) = self.backend.get_block_info(&DbBlockId::Pending)? {
+            return Ok(block);
+        }
+        // No current pending block, we'll make an unsaved empty one for the sake of validating this tx.
+        let parent_block_hash = self
+            .backend
+            .get_block_hash(&BlockId::Tag(BlockTag::Latest))?
+            .unwrap_or(/* genesis block's parent hash */ Felt::ZERO);
+        let block_n = self.backend.get_latest_block_n()?.map(|n| n + 1).unwrap_or(/* genesis */ 0);
+        Ok(MadaraPendingBlockInfo::new(
+            make_pending_header(
+                parent_block_hash,
+                block_n,
+                self.backend.chain_config(),
+                self.l1_data_provider.as_ref(),
+            ),
+            vec![],
+        )
+        .into())
+    }
+
+    /// Estimates the fee of an L1 handler transaction on top of the pending block, in wei. This is the estimation of
+    /// `starknet_estimateMessageFee`.
+    #[tracing::instrument(skip(self), fields(module = "Mempool"))]
+    pub fn estimate_l1_handler_fee(&self, tx: L1HandlerTransaction) -> Result<u128, Error> {
+        let (btx, _class) = tx.into_blockifier(
+            self.chain_id(),
+            self.backend.chain_config().latest_protocol_version,
+            /* paid_fees_on_l1 */ 1,
+        )?;
+        let exec_context = ExecutionContext::new_in_block(Arc::clone(&self.backend), &self.pending_block_info()?)?;
+        let execution_result = exec_context
+            .re_execute_transactions([], [btx], /* charge_fee */ false, /* validate */ true)?
+            .pop()
+            .expect("One transaction was executed");
+        let fee_estimate = exec_context.execution_result_to_fee_estimate(&execution_result);
+        Ok(u128::try_from(fee_estimate.overall_fee).unwrap_or(u128::MAX))
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub fn is_empty(&self) -> bool {
         self.inner.read().expect("Poisoned lock").is_empty()
@@ -331,12 +447,18 @@ impl MempoolProvider for Mempool {
         }
         let mut inner = self.inner.write().expect("Poisoned lock");
         inner.insert_txs(txs, force)?;
+        drop(inner);
+        self.tx_received.notify_one();
         Ok(())
     }
 
     fn chain_id(&self) -> Felt {
         Felt::from_bytes_be_slice(format!("{}", self.backend.chain_config().chain_id).as_bytes())
     }
+
+    fn tx_received(&self) -> Arc<Notify> {
+        Arc::clone(&self.tx_received)
+    }
 }
 
 pub(crate) fn is_only_query(tx: &Transaction) -> bool {
@@ -487,4 +609,26 @@ mod test {
         let result = mempool.accept_tx(tx_account_v1_invalid, None, ArrivedAtTimestamp::now());
         assert_matches::assert_matches!(result, Err(crate::Error::Validation(_)));
     }
+
+    #[rstest::rstest]
+    fn mempool_accept_tx_fail_admission_policy(
+        backend: Arc<mc_db::MadaraBackend>,
+        l1_data_provider: Arc<MockL1DataProvider>,
+        tx_account_v0_valid: blockifier::transaction::transaction_execution::Transaction,
+    ) {
+        let mempool = Mempool::new(backend, l1_data_provider, MempoolLimits::for_testing());
+        assert_eq!(mempool.admission_policy(), AdmissionPolicy::default());
+
+        let sender = contract_addr(&tx_account_v0_valid).to_felt();
+        mempool.set_admission_policy(AdmissionPolicy {
+            sender_deny_list: [sender].into_iter().collect(),
+            ..Default::default()
+        });
+        let result = mempool.accept_tx(tx_account_v0_valid, None, ArrivedAtTimestamp::now());
+        assert_matches::assert_matches!(
+            result,
+            Err(crate::Error::AdmissionPolicy(AdmissionPolicyViolation::SenderNotAllowed(addr))) if addr == sender
+        );
+        assert!(mempool.is_empty());
+    }
 }
@@ -4,6 +4,7 @@ use opentelemetry::{global, KeyValue};
 
 pub struct MempoolMetrics {
     pub accepted_transaction_counter: Counter<u64>,
+    pub rejected_transaction_counter: Counter<u64>,
 }
 
 impl MempoolMetrics {
@@ -24,6 +25,13 @@ impl MempoolMetrics {
             "transaction".to_string(),
         );
 
-        Self { accepted_transaction_counter }
+        let rejected_transaction_counter = register_counter_metric_instrument(
+            &mempool_meter,
+            "rejected_transaction_count".to_string(),
+            "A counter to show transactions rejected by the mempool admission policy".to_string(),
+            "transaction".to_string(),
+        );
+
+        Self { accepted_transaction_counter, rejected_transaction_counter }
     }
 }
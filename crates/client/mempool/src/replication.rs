@@ -0,0 +1,75 @@
+//! Mempool replication between hot-standby sequencers.
+//!
+//! The sequencer holding the block production lease forwards every transaction accepted into its mempool to the
+//! standby sequencers. A standby does not execute nor validate these transactions while it follows the chain: they are
+//! kept in a bounded [`ReplicaBuffer`], and only inserted into its mempool when it takes over block production. At that
+//! point, the transactions which have been included in a block in the meantime are skipped, and the others go through
+//! the usual mempool validation.
+
+use mc_db::mempool_db::SavedTransaction;
+use mp_class::ConvertedClass;
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt;
+use std::collections::VecDeque;
+
+/// A transaction accepted into the mempool of the active sequencer, as sent to the standby sequencers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplicatedTransaction {
+    pub tx_hash: Felt,
+    pub tx: SavedTransaction,
+    pub converted_class: Option<ConvertedClass>,
+}
+
+/// The transactions replicated from the active sequencer, waiting for this node to take over block production.
+///
+/// This is bounded by the mempool transaction limit: when full, the oldest transactions are dropped first, as they are
+/// the most likely to have been included in a block already.
+pub(crate) struct ReplicaBuffer {
+    txs: VecDeque<ReplicatedTransaction>,
+    max_transactions: usize,
+}
+
+impl ReplicaBuffer {
+    pub fn new(max_transactions: usize) -> Self {
+        Self { txs: VecDeque::new(), max_transactions }
+    }
+
+    pub fn extend(&mut self, txs: impl IntoIterator<Item = ReplicatedTransaction>) {
+        self.txs.extend(txs);
+        let excess = self.txs.len().saturating_sub(self.max_transactions);
+        self.txs.drain(..excess);
+    }
+
+    pub fn take(&mut self) -> VecDeque<ReplicatedTransaction> {
+        std::mem::take(&mut self.txs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replicated_tx(tx_hash: u64) -> ReplicatedTransaction {
+        ReplicatedTransaction {
+            tx_hash: Felt::from(tx_hash),
+            tx: SavedTransaction {
+                tx: mp_transactions::Transaction::L1Handler(Default::default()),
+                paid_fee_on_l1: Some(1),
+                contract_address: None,
+                only_query: false,
+                arrived_at: 0,
+            },
+            converted_class: None,
+        }
+    }
+
+    #[test]
+    fn replica_buffer_drops_oldest() {
+        let mut buffer = ReplicaBuffer::new(2);
+        buffer.extend([replicated_tx(1), replicated_tx(2)]);
+        buffer.extend([replicated_tx(3)]);
+        let hashes: Vec<_> = buffer.take().into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(hashes, vec![Felt::from(2), Felt::from(3)]);
+        assert!(buffer.take().is_empty());
+    }
+}
@@ -2,3 +2,7 @@
 pub const MAX_EVENTS_KEYS: usize = 100;
 /// Maximum number of events that can be fetched in a single chunk for the `get_events` RPC.
 pub const MAX_EVENTS_CHUNK_SIZE: usize = 1000;
+/// Maximum number of sender addresses that can be passed to the `subscribePendingTransactions` RPC.
+pub const MAX_SENDER_ADDRESSES: usize = 1000;
+/// Maximum number of L2→L1 messages that can be fetched in a single chunk for the `getMessagesToL1ByRecipient` RPC.
+pub const MAX_MESSAGES_TO_L1_CHUNK_SIZE: usize = 1000;
@@ -99,12 +99,16 @@ pub enum StarknetRpcApiError {
     ErrUnexpectedError { data: String },
     #[error("Internal server error")]
     InternalServerError,
+    #[error("Invalid params")]
+    InvalidParams { data: String },
     #[error("Unimplemented method")]
     UnimplementedMethod,
     #[error("Proof limit exceeded")]
     ProofLimitExceeded { kind: StorageProofLimit, limit: usize, got: usize },
     #[error("Cannot create a storage proof for a block that old")]
     CannotMakeProofOnOldBlock,
+    #[error("The transactions and receipts of this block are not available")]
+    BlockBodyUnavailable { block_n: u64 },
 }
 
 impl From<&StarknetRpcApiError> for i32 {
@@ -140,9 +144,11 @@ impl From<&StarknetRpcApiError> for i32 {
             StarknetRpcApiError::UnsupportedContractClassVersion => 62,
             StarknetRpcApiError::ErrUnexpectedError { .. } => 63,
             StarknetRpcApiError::InternalServerError => 500,
+            StarknetRpcApiError::InvalidParams { .. } => jsonrpsee::types::error::INVALID_PARAMS_CODE,
             StarknetRpcApiError::UnimplementedMethod => 501,
             StarknetRpcApiError::ProofLimitExceeded { .. } => 10000,
             StarknetRpcApiError::CannotMakeProofOnOldBlock => 10001,
+            StarknetRpcApiError::BlockBodyUnavailable { .. } => 10002,
         }
     }
 }
@@ -151,6 +157,7 @@ impl StarknetRpcApiError {
     pub fn data(&self) -> Option<serde_json::Value> {
         match self {
             StarknetRpcApiError::ErrUnexpectedError { data } => Some(json!(data)),
+            StarknetRpcApiError::InvalidParams { data } => Some(json!(data)),
             StarknetRpcApiError::ValidationFailure { error } => Some(json!(error)),
             StarknetRpcApiError::FailedToReceiveTxn { err } => err.as_ref().map(|err| json!(err)),
             StarknetRpcApiError::TxnExecutionError { tx_index, error } => Some(json!({
@@ -160,6 +167,10 @@ impl StarknetRpcApiError {
             StarknetRpcApiError::ProofLimitExceeded { kind, limit, got } => {
                 Some(json!({ "kind": kind, "limit": limit, "got": got }))
             }
+            StarknetRpcApiError::BlockBodyUnavailable { block_n } => Some(json!({
+                "block_number": block_n,
+                "reason": "The block was reconstructed from the data availability of the settlement layer",
+            })),
             _ => None,
         }
     }
@@ -235,6 +246,9 @@ pub enum StarknetWsApiError {
     NoBlocks,
     BlockNotFound,
     Pending,
+    TooManyKeysInFilter,
+    TooManyAddressesInFilter,
+    BlockBodyUnavailable,
     Internal,
 }
 
@@ -246,6 +260,9 @@ impl StarknetWsApiError {
             Self::NoBlocks => 32,
             Self::BlockNotFound => 24,
             Self::Pending => 69,
+            Self::TooManyKeysInFilter => 34,
+            Self::TooManyAddressesInFilter => 67,
+            Self::BlockBodyUnavailable => 10002,
             Self::Internal => jsonrpsee::types::error::INTERNAL_ERROR_CODE,
         }
     }
@@ -257,6 +274,9 @@ impl StarknetWsApiError {
             Self::BlockNotFound => "Block not found",
             // See https://github.com/starkware-libs/starknet-specs/pull/237
             Self::Pending => "The pending block is not supported on this method call",
+            Self::TooManyKeysInFilter => "Too many keys provided in a filter",
+            Self::TooManyAddressesInFilter => "Too many addresses in filter sender_address filter",
+            Self::BlockBodyUnavailable => "The transactions and receipts of this block are not available",
             Self::Internal => jsonrpsee::types::error::INTERNAL_ERROR_MSG,
         }
     }
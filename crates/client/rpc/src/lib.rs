@@ -4,16 +4,21 @@
 
 mod constants;
 mod errors;
+mod openrpc;
 pub mod providers;
 #[cfg(test)]
 pub mod test_utils;
+mod trace_cache;
 mod types;
 pub mod utils;
 pub mod versions;
 
 use jsonrpsee::RpcModule;
+use mc_analytics::LogFilterHandle;
+use mc_block_production::BlockProductionHandle;
 use mc_db::db_block_id::DbBlockIdResolvable;
 use mc_db::MadaraBackend;
+use mc_mempool::Mempool;
 use mp_block::{BlockId, BlockTag, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo};
 use mp_chain_config::ChainConfig;
 use mp_convert::ToFelt;
@@ -21,9 +26,12 @@ use mp_utils::service::ServiceContext;
 use providers::AddTransactionProvider;
 use starknet_types_core::felt::Felt;
 use std::sync::Arc;
+use trace_cache::TraceCache;
 use utils::ResultExt;
+use versions::user::v0_8_0::methods::ws::WsSubscriptions;
 
 pub use errors::{StarknetRpcApiError, StarknetRpcResult};
+pub use trace_cache::{populate_trace_cache, TraceCacheConfig};
 
 /// Limits to the storage proof endpoint.
 #[derive(Clone, Debug)]
@@ -48,6 +56,18 @@ pub struct Starknet {
     backend: Arc<MadaraBackend>,
     pub(crate) add_transaction_provider: Arc<dyn AddTransactionProvider>,
     storage_proof_config: StorageProofConfig,
+    /// Only available on sequencers, used by the admin RPC to manage the mempool.
+    mempool: Option<Arc<Mempool>>,
+    /// Only available on sequencers, used by the admin RPC to control block production.
+    block_production: Option<BlockProductionHandle>,
+    /// Enables the devnet admin methods, which simulate the L1 side of messaging.
+    devnet: bool,
+    /// Traces of the transactions of closed blocks, used by the trace methods.
+    trace_cache: Option<Arc<TraceCache>>,
+    /// Used by the admin RPC to change the log filter at runtime.
+    log_filter: Option<LogFilterHandle>,
+    /// Websocket subscriptions in progress, cancelled by `starknet_unsubscribe`.
+    ws_subscriptions: Arc<WsSubscriptions>,
     pub ctx: ServiceContext,
 }
 
@@ -58,7 +78,65 @@ impl Starknet {
         storage_proof_config: StorageProofConfig,
         ctx: ServiceContext,
     ) -> Self {
-        Self { backend, add_transaction_provider, storage_proof_config, ctx }
+        Self {
+            backend,
+            add_transaction_provider,
+            storage_proof_config,
+            mempool: None,
+            block_production: None,
+            devnet: false,
+            trace_cache: None,
+            log_filter: None,
+            ws_subscriptions: Default::default(),
+            ctx,
+        }
+    }
+
+    pub fn with_mempool(self, mempool: Arc<Mempool>) -> Self {
+        Self { mempool: Some(mempool), ..self }
+    }
+
+    pub fn mempool(&self) -> StarknetRpcResult<&Arc<Mempool>> {
+        self.mempool
+            .as_ref()
+            .ok_or_else(|| StarknetRpcApiError::ErrUnexpectedError { data: "Mempool is not available".to_string() })
+    }
+
+    pub fn with_block_production(self, block_production: BlockProductionHandle) -> Self {
+        Self { block_production: Some(block_production), ..self }
+    }
+
+    pub fn block_production(&self) -> StarknetRpcResult<&BlockProductionHandle> {
+        self.block_production.as_ref().ok_or_else(|| StarknetRpcApiError::ErrUnexpectedError {
+            data: "Block production is not available".to_string(),
+        })
+    }
+
+    pub fn with_devnet(self) -> Self {
+        Self { devnet: true, ..self }
+    }
+
+    pub fn check_devnet(&self) -> StarknetRpcResult<()> {
+        if !self.devnet {
+            return Err(StarknetRpcApiError::ErrUnexpectedError {
+                data: "This method is only available in devnet mode".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn with_trace_cache(self, config: TraceCacheConfig) -> Self {
+        Self { trace_cache: config.is_enabled().then(|| Arc::new(TraceCache::new(config))), ..self }
+    }
+
+    pub fn with_log_filter(self, log_filter: LogFilterHandle) -> Self {
+        Self { log_filter: Some(log_filter), ..self }
+    }
+
+    pub fn log_filter(&self) -> StarknetRpcResult<&LogFilterHandle> {
+        self.log_filter.as_ref().ok_or_else(|| StarknetRpcApiError::ErrUnexpectedError {
+            data: "Changing the log filter is not available".to_string(),
+        })
     }
 
     pub fn clone_backend(&self) -> Arc<MadaraBackend> {
@@ -87,10 +165,23 @@ impl Starknet {
     }
 
     pub fn get_block(&self, block_id: &impl DbBlockIdResolvable) -> StarknetRpcResult<MadaraMaybePendingBlock> {
-        self.backend
+        let block = self
+            .backend
             .get_block(block_id)
             .or_internal_server_error("Error getting block from storage")?
-            .ok_or(StarknetRpcApiError::BlockNotFound)
+            .ok_or(StarknetRpcApiError::BlockNotFound)?;
+        self.check_block_body(&block.info)?;
+        Ok(block)
+    }
+
+    /// Blocks reconstructed from the data availability of the settlement layer are stored without their transactions
+    /// and receipts: report it instead of answering with an empty block.
+    pub fn check_block_body(&self, block_info: &MadaraMaybePendingBlockInfo) -> StarknetRpcResult<()> {
+        let Some(block_n) = block_info.block_n() else { return Ok(()) };
+        if !self.backend.has_block_body(block_n).or_internal_server_error("Error getting block from storage")? {
+            return Err(StarknetRpcApiError::BlockBodyUnavailable { block_n });
+        }
+        Ok(())
     }
 
     pub fn chain_id(&self) -> Felt {
@@ -114,11 +205,42 @@ impl Starknet {
 pub fn rpc_api_user(starknet: &Starknet) -> anyhow::Result<RpcModule<()>> {
     let mut rpc_api = RpcModule::new(());
 
+    rpc_api.merge(versions::user::v0_6_0::StarknetReadRpcApiV0_6_0Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::user::v0_7_1::StarknetReadRpcApiV0_7_1Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::user::v0_8_0::StarknetReadRpcApiV0_8_0Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::user::v0_7_1::StarknetWriteRpcApiV0_7_1Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::user::v0_6_0::StarknetTraceRpcApiV0_6_0Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::user::v0_7_1::StarknetTraceRpcApiV0_7_1Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::user::v0_7_1::MadaraStateOverrideRpcApiV0_7_1Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::user::v0_8_0::StarknetWsRpcApiV0_8_0Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::user::v0_8_0::MadaraMessagingRpcApiV0_8_0Server::into_rpc(starknet.clone()))?;
+
+    // All the websocket subscriptions are cancelled by `starknet_unsubscribe`, the unsubscribe method jsonrpsee needs
+    // for each of them is not part of the API.
+    for method in [
+        "starknet_V0_8_0_unsubscribeNewHeads",
+        "starknet_V0_8_0_unsubscribeEvents",
+        "starknet_V0_8_0_unsubscribeTransactionStatus",
+        "starknet_V0_8_0_unsubscribePendingTransactions",
+    ] {
+        rpc_api.remove_method(method);
+    }
+
+    openrpc::register_discover(
+        &mut rpc_api,
+        "Madara JSON-RPC",
+        &[
+            versions::user::v0_6_0::STARKNET_READ_RPC_API_V0_6_0_METHODS,
+            versions::user::v0_7_1::STARKNET_READ_RPC_API_V0_7_1_METHODS,
+            versions::user::v0_8_0::STARKNET_READ_RPC_API_V0_8_0_METHODS,
+            versions::user::v0_7_1::STARKNET_WRITE_RPC_API_V0_7_1_METHODS,
+            versions::user::v0_6_0::STARKNET_TRACE_RPC_API_V0_6_0_METHODS,
+            versions::user::v0_7_1::STARKNET_TRACE_RPC_API_V0_7_1_METHODS,
+            versions::user::v0_7_1::MADARA_STATE_OVERRIDE_RPC_API_V0_7_1_METHODS,
+            versions::user::v0_8_0::STARKNET_WS_RPC_API_V0_8_0_METHODS,
+            versions::user::v0_8_0::MADARA_MESSAGING_RPC_API_V0_8_0_METHODS,
+        ],
+    )?;
 
     Ok(rpc_api)
 }
@@ -129,6 +251,29 @@ pub fn rpc_api_admin(starknet: &Starknet) -> anyhow::Result<RpcModule<()>> {
     rpc_api.merge(versions::admin::v0_1_0::MadaraWriteRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::admin::v0_1_0::MadaraStatusRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
     rpc_api.merge(versions::admin::v0_1_0::MadaraServicesRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::admin::v0_1_0::MadaraMempoolRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::admin::v0_1_0::MadaraBlockProductionRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::admin::v0_1_0::MadaraL1MessagingRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::admin::v0_1_0::MadaraDatabaseRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+    rpc_api.merge(versions::admin::v0_1_0::MadaraLoggingRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+
+    let mut methods = vec![
+        versions::admin::v0_1_0::MADARA_WRITE_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_STATUS_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_SERVICES_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_MEMPOOL_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_BLOCK_PRODUCTION_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_L1_MESSAGING_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_DATABASE_RPC_API_V0_1_0_METHODS,
+        versions::admin::v0_1_0::MADARA_LOGGING_RPC_API_V0_1_0_METHODS,
+    ];
+    // The devnet methods simulate the L1 side of messaging, they are not exposed outside of devnets.
+    if starknet.devnet {
+        rpc_api.merge(versions::admin::v0_1_0::MadaraDevnetRpcApiV0_1_0Server::into_rpc(starknet.clone()))?;
+        methods.push(versions::admin::v0_1_0::MADARA_DEVNET_RPC_API_V0_1_0_METHODS);
+    }
+
+    openrpc::register_discover(&mut rpc_api, "Madara admin JSON-RPC", &methods)?;
 
     Ok(rpc_api)
 }
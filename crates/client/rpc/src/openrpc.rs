@@ -0,0 +1,252 @@
+//! [OpenRPC](https://spec.open-rpc.org) documents served by `rpc.discover`, built from the method metadata emitted by
+//! the `versioned_rpc` macro.
+//!
+//! The metadata only knows the Rust types of the parameters and results. Their schemas reference the Starknet specs
+//! of the version of the document, or of the latest version for other namespaces. Madara types which have no schema
+//! in the specs only get a `title`.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use jsonrpsee::RpcModule;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const OPENRPC_VERSION: &str = "1.2.6";
+const STARKNET_SPECS_URL: &str = "https://raw.githubusercontent.com/starkware-libs/starknet-specs";
+/// Version of the Starknet specs used for the methods outside of the `starknet` namespace.
+const STARKNET_SPECS_LATEST: &str = "v0.8.0";
+
+#[derive(Debug, Clone, Deserialize)]
+struct MethodMeta {
+    name: String,
+    namespace: String,
+    kind: String,
+    versions: Vec<String>,
+    summary: String,
+    params: Vec<ParamMeta>,
+    result: String,
+    #[serde(default)]
+    unsubscribe: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ParamMeta {
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    required: bool,
+}
+
+fn parse_methods(methods: &[&str]) -> anyhow::Result<Vec<MethodMeta>> {
+    let mut parsed = vec![];
+    for methods in methods {
+        let methods: Vec<MethodMeta> = serde_json::from_str(methods).context("Parsing RPC method metadata")?;
+        parsed.extend(methods);
+    }
+    Ok(parsed)
+}
+
+/// Builds the OpenRPC document of the methods served under `version`, such as `V0_8_0`.
+fn openrpc_document(title: &str, version: &str, methods: &[MethodMeta]) -> Value {
+    let mut methods: Vec<_> = methods.iter().filter(|method| method.versions.iter().any(|v| v == version)).collect();
+    methods.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+
+    let methods: Vec<_> = methods
+        .into_iter()
+        .map(|method| {
+            let specs = match method.namespace.as_str() {
+                "starknet" => format!("{STARKNET_SPECS_URL}/v{}/api", version_number(version)),
+                _ => format!("{STARKNET_SPECS_URL}/{STARKNET_SPECS_LATEST}/api"),
+            };
+            let params: Vec<_> = method
+                .params
+                .iter()
+                .map(|param| {
+                    json!({
+                        "name": param.name,
+                        "required": param.required,
+                        "schema": schema(&param.ty, &specs),
+                    })
+                })
+                .collect();
+
+            let mut document = json!({
+                "name": format!("{}_{}", method.namespace, method.name),
+                "summary": method.summary,
+                "params": params,
+                "result": {
+                    "name": "result",
+                    "schema": schema(&method.result, &specs),
+                },
+            });
+            if method.kind == "subscription" {
+                document["x-subscription"] = json!({ "unsubscribe": method.unsubscribe });
+            }
+            document
+        })
+        .collect();
+
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": title,
+            "version": version_number(version),
+        },
+        "methods": methods,
+    })
+}
+
+/// `V0_8_0` to `0.8.0`.
+fn version_number(version: &str) -> String {
+    version.trim_start_matches('V').replace('_', ".")
+}
+
+/// The generic argument of a `Wrapper<T>` type.
+fn generic_arg<'a>(ty: &'a str, wrapper: &str) -> Option<&'a str> {
+    ty.strip_prefix(wrapper)?.strip_prefix('<')?.strip_suffix('>')
+}
+
+/// JSON Schema of a Rust type, as rendered in the method metadata. `specs` is the URL of the Starknet specs the
+/// Starknet types refer to.
+fn schema(ty: &str, specs: &str) -> Value {
+    let api = |name: &str| json!({ "$ref": format!("{specs}/starknet_api_openrpc.json#/components/schemas/{name}") });
+    let trace =
+        |name: &str| json!({ "$ref": format!("{specs}/starknet_trace_api_openrpc.json#/components/schemas/{name}") });
+    let one_of = |names: &[&str]| json!({ "oneOf": names.iter().map(|name| api(name)).collect::<Vec<_>>() });
+    let object = |properties: Value| {
+        let required: Vec<_> = properties.as_object().map(|p| p.keys().cloned().collect()).unwrap_or_default();
+        json!({ "type": "object", "properties": properties, "required": required })
+    };
+
+    if let Some(ty) = generic_arg(ty, "Option") {
+        return schema(ty, specs);
+    }
+    if let Some(ty) = generic_arg(ty, "Vec") {
+        return json!({ "type": "array", "items": schema(ty, specs) });
+    }
+
+    // Starknet types are generic over the field element type, which is always `Felt`.
+    let name = ty.strip_suffix("<Felt>").unwrap_or(ty);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    match name {
+        "()" => json!({ "type": "null" }),
+        "bool" => json!({ "type": "boolean" }),
+        "String" => json!({ "type": "string" }),
+        "u64" | "u128" => json!({ "type": "integer", "minimum": 0 }),
+        "Value" => json!({}),
+        "H256" => json!({ "type": "string", "pattern": "^0x[a-fA-F0-9]{64}$" }),
+        "SubscriptionId" => json!({ "oneOf": [{ "type": "integer", "minimum": 0 }, { "type": "string" }] }),
+        "Felt" => api("FELT"),
+        "BlockId" => api("BLOCK_ID"),
+        "NewHead" => api("BLOCK_HEADER"),
+        "EmittedEvent" => api("EMITTED_EVENT"),
+        "EventsChunk" => api("EVENTS_CHUNK"),
+        "FunctionCall" => api("FUNCTION_CALL"),
+        "MsgFromL1" => api("MSG_FROM_L1"),
+        "FeeEstimate" => api("FEE_ESTIMATE"),
+        "BroadcastedTxn" => api("BROADCASTED_TXN"),
+        "BroadcastedInvokeTxn" => api("BROADCASTED_INVOKE_TXN"),
+        "BroadcastedDeclareTxn" => api("BROADCASTED_DECLARE_TXN"),
+        "BroadcastedDeployAccountTxn" => api("BROADCASTED_DEPLOY_ACCOUNT_TXN"),
+        "TxnReceiptWithBlockInfo" => api("TXN_RECEIPT_WITH_BLOCK_INFO"),
+        "SimulationFlagForEstimateFee" => api("SIMULATION_FLAG_FOR_ESTIMATE_FEE"),
+        "SimulationFlag" => trace("SIMULATION_FLAG"),
+        "TransactionTrace" => trace("TRANSACTION_TRACE"),
+        "MaybePendingBlockWithTxHashes" => one_of(&["BLOCK_WITH_TX_HASHES", "PENDING_BLOCK_WITH_TX_HASHES"]),
+        "MaybePendingBlockWithTxs" => one_of(&["BLOCK_WITH_TXS", "PENDING_BLOCK_WITH_TXS"]),
+        "StarknetGetBlockWithTxsAndReceiptsResult" => one_of(&["BLOCK_WITH_RECEIPTS", "PENDING_BLOCK_WITH_RECEIPTS"]),
+        "MaybePendingStateUpdate" => one_of(&["STATE_UPDATE", "PENDING_STATE_UPDATE"]),
+        "MaybeDeprecatedContractClass" => one_of(&["DEPRECATED_CONTRACT_CLASS", "CONTRACT_CLASS"]),
+        "SyncingStatus" => json!({ "oneOf": [{ "type": "boolean", "const": false }, api("SYNC_STATUS")] }),
+        "EventFilterWithPageRequest" => json!({ "allOf": [api("EVENT_FILTER"), api("RESULT_PAGE_REQUEST")] }),
+        "TxnWithHash" => json!({ "allOf": [api("TXN"), object(json!({ "transaction_hash": api("TXN_HASH") }))] }),
+        "BlockHashAndNumber" => object(json!({ "block_hash": api("BLOCK_HASH"), "block_number": api("BLOCK_NUMBER") })),
+        "AddInvokeTransactionResult" => object(json!({ "transaction_hash": api("TXN_HASH") })),
+        "ClassAndTxnHash" => object(json!({ "transaction_hash": api("TXN_HASH"), "class_hash": api("FELT") })),
+        "ContractAndTxnHash" => object(json!({ "transaction_hash": api("TXN_HASH"), "contract_address": api("FELT") })),
+        "TraceBlockTransactionsResult" => {
+            object(json!({ "transaction_hash": api("FELT"), "trace_root": trace("TRANSACTION_TRACE") }))
+        }
+        "SimulateTransactionsResult" => {
+            object(json!({ "transaction_trace": trace("TRANSACTION_TRACE"), "fee_estimation": api("FEE_ESTIMATE") }))
+        }
+        _ => json!({ "title": name }),
+    }
+}
+
+/// Registers an `rpc_{version}_discover` method for every version of the `methods`, as emitted by `versioned_rpc`.
+/// The RPC server serves it as `rpc.discover` under the path of each version.
+pub fn register_discover(rpc_api: &mut RpcModule<()>, title: &str, methods: &[&str]) -> anyhow::Result<()> {
+    let methods = parse_methods(methods)?;
+    let versions: BTreeSet<_> = methods.iter().flat_map(|method| method.versions.iter()).collect();
+
+    for version in versions {
+        let document = openrpc_document(title, version, &methods);
+        // jsonrpsee only takes static method names. This runs once per version when the server starts.
+        let name: &'static str = Box::leak(format!("rpc_{version}_discover").into_boxed_str());
+        rpc_api.register_method(name, move |_, _| document.clone())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versions::user::{v0_7_1, v0_8_0};
+
+    fn method_names(document: &Value) -> Vec<&str> {
+        document["methods"].as_array().unwrap().iter().map(|method| method["name"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_openrpc_document() {
+        let methods =
+            parse_methods(&[v0_7_1::STARKNET_READ_RPC_API_V0_7_1_METHODS, v0_8_0::STARKNET_WS_RPC_API_V0_8_0_METHODS])
+                .unwrap();
+
+        let document = openrpc_document("Madara", "V0_7_1", &methods);
+        assert_eq!(document["info"]["version"], "0.7.1");
+        let names = method_names(&document);
+        assert!(names.contains(&"starknet_blockNumber"));
+        assert!(!names.contains(&"starknet_subscribeNewHeads"));
+
+        let document = openrpc_document("Madara", "V0_8_0", &methods);
+        let names = method_names(&document);
+        // Served under both versions with `and_versions`.
+        assert!(names.contains(&"starknet_blockNumber"));
+        assert!(names.contains(&"starknet_subscribeNewHeads"));
+
+        let get_nonce =
+            document["methods"].as_array().unwrap().iter().find(|m| m["name"] == "starknet_getNonce").unwrap();
+        assert_eq!(get_nonce["params"][0]["name"], "block_id");
+        assert_eq!(get_nonce["params"][0]["required"], true);
+        assert_eq!(
+            get_nonce["result"]["schema"]["$ref"],
+            "https://raw.githubusercontent.com/starkware-libs/starknet-specs/v0.8.0/api/starknet_api_openrpc.json#/components/schemas/FELT"
+        );
+
+        let new_heads =
+            document["methods"].as_array().unwrap().iter().find(|m| m["name"] == "starknet_subscribeNewHeads").unwrap();
+        assert_eq!(new_heads["x-subscription"]["unsubscribe"], "starknet_unsubscribe");
+    }
+
+    #[test]
+    fn test_schema() {
+        let specs = "https://specs";
+        assert_eq!(schema("Option<u64>", specs), json!({ "type": "integer", "minimum": 0 }));
+        assert_eq!(
+            schema("Vec<Felt>", specs),
+            json!({ "type": "array", "items": { "$ref": "https://specs/starknet_api_openrpc.json#/components/schemas/FELT" } })
+        );
+        assert_eq!(
+            schema("starknet_types_rpc::EmittedEvent<Felt>", specs),
+            json!({ "$ref": "https://specs/starknet_api_openrpc.json#/components/schemas/EMITTED_EVENT" })
+        );
+        assert_eq!(
+            schema("Vec<TraceBlockTransactionsResult<Felt>>", specs)["items"]["properties"]["trace_root"]["$ref"],
+            "https://specs/starknet_trace_api_openrpc.json#/components/schemas/TRANSACTION_TRACE"
+        );
+        assert_eq!(schema("BackupInfo", specs), json!({ "title": "BackupInfo" }));
+    }
+}
@@ -36,6 +36,9 @@ impl From<mc_mempool::Error> for StarknetRpcApiError {
                     err: Some("A transaction with this nonce and sender address already exists".into()),
                 }
             }
+            mc_mempool::Error::AdmissionPolicy(err) => {
+                StarknetRpcApiError::FailedToReceiveTxn { err: Some(format!("{err}").into()) }
+            }
             mc_mempool::Error::Validation(err) => {
                 StarknetRpcApiError::ValidationFailure { error: format!("{err:#}").into() }
             }
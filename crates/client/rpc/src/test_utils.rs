@@ -1,11 +1,14 @@
 use jsonrpsee::core::{async_trait, RpcResult};
+use mc_block_import::{BlockImporter, BlockValidationContext};
 use mc_db::MadaraBackend;
+use mc_devnet::{Call, ChainGenesisDescription, DevnetKeys, Multicall, Selector};
 use mp_block::{
     header::{BlockTimestamp, GasPrices, L1DataAvailabilityMode, PendingHeader},
     Header, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo,
     MadaraPendingBlockInfo,
 };
 use mp_chain_config::{ChainConfig, StarknetVersion};
+use mp_convert::ToFelt;
 use mp_receipt::{
     ExecutionResources, ExecutionResult, FeePayment, InvokeTransactionReceipt, PriceUnit, TransactionReceipt,
 };
@@ -19,7 +22,8 @@ use rstest::fixture;
 use starknet_types_core::felt::Felt;
 use starknet_types_rpc::{
     AddInvokeTransactionResult, BroadcastedDeclareTxn, BroadcastedDeployAccountTxn, BroadcastedInvokeTxn,
-    ClassAndTxnHash, ContractAndTxnHash, TxnReceipt, TxnWithHash,
+    BroadcastedTxn, ClassAndTxnHash, ContractAndTxnHash, DaMode, InvokeTxnV3, ResourceBounds, ResourceBoundsMapping,
+    TxnReceipt, TxnWithHash,
 };
 use std::sync::Arc;
 
@@ -70,6 +74,59 @@ pub fn rpc_test_setup() -> (Arc<MadaraBackend>, Starknet) {
     (backend, rpc)
 }
 
+/// A devnet genesis block with its predeployed accounts and fee tokens, for the tests which execute transactions.
+#[fixture]
+pub fn devnet_rpc_test_setup() -> (DevnetKeys, Starknet) {
+    let mut genesis = ChainGenesisDescription::base_config().unwrap();
+    let contracts = genesis.add_devnet_contracts(2).unwrap();
+
+    let chain_config = Arc::new(ChainConfig::madara_devnet());
+    let block = genesis.build(&chain_config).unwrap();
+    let backend = MadaraBackend::open_for_testing(Arc::clone(&chain_config));
+    let importer = BlockImporter::new(Arc::clone(&backend), None).unwrap();
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(
+            importer
+                .add_block(block, BlockValidationContext::new(chain_config.chain_id.clone()).trust_class_hashes(true)),
+        )
+        .unwrap();
+
+    let rpc = Starknet::new(
+        backend,
+        Arc::new(TestTransactionProvider),
+        Default::default(),
+        ServiceContext::new_for_testing(),
+    );
+    (contracts, rpc)
+}
+
+/// An unsigned transfer of STRK between the first two devnet accounts, to be executed without validation.
+pub fn devnet_transfer(contracts: &DevnetKeys, rpc: &Starknet) -> BroadcastedTxn<Felt> {
+    BroadcastedTxn::Invoke(BroadcastedInvokeTxn::V3(InvokeTxnV3 {
+        sender_address: contracts.0[0].address,
+        calldata: Multicall::default()
+            .with(Call {
+                to: rpc.backend.chain_config().native_fee_token_address.to_felt(),
+                selector: Selector::from("transfer"),
+                calldata: vec![contracts.0[1].address, Felt::from(1000), Felt::ZERO],
+            })
+            .flatten()
+            .collect(),
+        signature: vec![],
+        nonce: Felt::ZERO,
+        resource_bounds: ResourceBoundsMapping {
+            l1_gas: ResourceBounds { max_amount: 60000, max_price_per_unit: 1_000_000 },
+            l2_gas: ResourceBounds { max_amount: 60000, max_price_per_unit: 1_000_000 },
+        },
+        tip: 0,
+        paymaster_data: vec![],
+        account_deployment_data: vec![],
+        nonce_data_availability_mode: DaMode::L1,
+        fee_data_availability_mode: DaMode::L1,
+    }))
+}
+
 // This sample chain is only used to test get tx / get block rpcs.
 pub struct SampleChainForBlockGetters {
     pub block_hashes: Vec<Felt>,
@@ -0,0 +1,255 @@
+//! Cache of the transaction traces of closed blocks, keyed by block hash and transaction index.
+//!
+//! Tracing a transaction re-executes every transaction before it in its block, which is expensive for busy blocks.
+//! Traces of closed blocks never change, so they are kept in a bounded in-memory cache, and optionally in the database
+//! for the latest blocks. Pending blocks are never cached, and the traces of a reverted block are never served since
+//! they are looked up by the hash of the block.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use mc_db::MadaraBackend;
+use mp_block::{BlockId, MadaraBlockInfo};
+use mp_utils::service::ServiceContext;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::TransactionTrace;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::versions::user::v0_7_1::methods::trace::trace_block_transactions::trace_block_transactions;
+use crate::Starknet;
+
+#[derive(Clone, Debug, Default)]
+pub struct TraceCacheConfig {
+    /// Size of the traces kept in memory, in bytes. Traces are measured by the size of their JSON encoding.
+    pub max_bytes: usize,
+    /// Also store the traces of the latest `persist_blocks` blocks in the database.
+    pub persist_blocks: Option<u64>,
+}
+
+impl TraceCacheConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes > 0 || self.persist_blocks.is_some_and(|blocks| blocks > 0)
+    }
+}
+
+/// Position of a transaction in a closed block: block number, block hash and transaction index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TracePosition {
+    pub block_n: u64,
+    pub block_hash: Felt,
+    pub tx_index: u64,
+}
+
+/// Values bounded by their total size, the oldest entries are evicted first.
+struct MemoryCache<T> {
+    entries: HashMap<TracePosition, (T, usize)>,
+    /// Insertion order.
+    order: VecDeque<TracePosition>,
+    size: usize,
+    max_size: usize,
+}
+
+impl<T: Clone> MemoryCache<T> {
+    fn new(max_size: usize) -> Self {
+        Self { entries: Default::default(), order: Default::default(), size: 0, max_size }
+    }
+
+    fn get(&self, position: &TracePosition) -> Option<T> {
+        self.entries.get(position).map(|(value, _)| value.clone())
+    }
+
+    fn insert(&mut self, position: TracePosition, value: T, size: usize) {
+        match self.entries.insert(position, (value, size)) {
+            Some((_, previous_size)) => self.size -= previous_size,
+            None => self.order.push_back(position),
+        }
+        self.size += size;
+
+        while self.size > self.max_size {
+            let Some(position) = self.order.pop_front() else { break };
+            if let Some((_, size)) = self.entries.remove(&position) {
+                self.size -= size;
+            }
+        }
+    }
+}
+
+pub struct TraceCache {
+    config: TraceCacheConfig,
+    memory: Mutex<MemoryCache<TransactionTrace<Felt>>>,
+}
+
+impl TraceCache {
+    pub fn new(config: TraceCacheConfig) -> Self {
+        let memory = Mutex::new(MemoryCache::new(config.max_bytes));
+        Self { config, memory }
+    }
+
+    pub fn get(&self, backend: &MadaraBackend, position: &TracePosition) -> Option<TransactionTrace<Felt>> {
+        if let Some(trace) = self.memory.lock().expect("Poisoned lock").get(position) {
+            return Some(trace);
+        }
+        self.config.persist_blocks?;
+
+        let encoded = backend
+            .get_tx_trace(position.block_n, &position.block_hash, position.tx_index)
+            .inspect_err(|err| tracing::warn!("Failed to read cached trace at {position:?}: {err:#}"))
+            .ok()??;
+        let trace: TransactionTrace<Felt> = serde_json::from_slice(&encoded)
+            .inspect_err(|err| tracing::warn!("Failed to decode cached trace at {position:?}: {err:#}"))
+            .ok()?;
+        self.insert_memory([(*position, trace.clone(), encoded.len())]);
+        Some(trace)
+    }
+
+    /// Caches the traces of transactions of the closed block `block_n`, by transaction index.
+    pub fn insert(
+        &self,
+        backend: &MadaraBackend,
+        block_n: u64,
+        block_hash: Felt,
+        traces: impl IntoIterator<Item = (u64, TransactionTrace<Felt>)>,
+    ) {
+        let traces = traces
+            .into_iter()
+            .map(|(tx_index, trace)| {
+                let encoded = serde_json::to_vec(&trace)?;
+                Ok((TracePosition { block_n, block_hash, tx_index }, trace, encoded))
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>();
+        let traces = match traces {
+            Ok(traces) => traces,
+            Err(err) => {
+                tracing::warn!("Failed to encode the traces of block #{block_n}: {err:#}");
+                return;
+            }
+        };
+
+        if let Some(persist_blocks) = self.config.persist_blocks.filter(|blocks| *blocks > 0) {
+            if let Err(err) = self.persist(backend, persist_blocks, block_n, &block_hash, &traces) {
+                tracing::warn!("Failed to store the traces of block #{block_n}: {err:#}");
+            }
+        }
+        self.insert_memory(traces.into_iter().map(|(position, trace, encoded)| (position, trace, encoded.len())));
+    }
+
+    fn insert_memory(&self, traces: impl IntoIterator<Item = (TracePosition, TransactionTrace<Felt>, usize)>) {
+        if self.config.max_bytes == 0 {
+            return;
+        }
+        let mut memory = self.memory.lock().expect("Poisoned lock");
+        for (position, trace, size) in traces {
+            memory.insert(position, trace, size);
+        }
+    }
+
+    /// Stores the traces if the block is one of the latest `persist_blocks` blocks, and prunes older blocks.
+    fn persist(
+        &self,
+        backend: &MadaraBackend,
+        persist_blocks: u64,
+        block_n: u64,
+        block_hash: &Felt,
+        traces: &[(TracePosition, TransactionTrace<Felt>, Vec<u8>)],
+    ) -> anyhow::Result<()> {
+        let latest_block_n = backend.get_latest_block_n()?.unwrap_or(block_n).max(block_n);
+        let first_kept = latest_block_n.saturating_sub(persist_blocks - 1);
+        if block_n < first_kept {
+            return Ok(());
+        }
+
+        let encoded = traces.iter().map(|(position, _, encoded)| (position.tx_index, encoded.clone()));
+        backend.store_tx_traces(block_n, block_hash, encoded)?;
+        backend.prune_tx_traces(first_kept)?;
+        Ok(())
+    }
+}
+
+impl Starknet {
+    /// The cached trace of a transaction, if its block is closed.
+    pub(crate) fn cached_trace(&self, block_info: &MadaraBlockInfo, tx_index: u64) -> Option<TransactionTrace<Felt>> {
+        let position =
+            TracePosition { block_n: block_info.header.block_number, block_hash: block_info.block_hash, tx_index };
+        self.trace_cache.as_ref()?.get(&self.backend, &position)
+    }
+
+    /// Caches the traces of the first transactions of a closed block, in order.
+    pub(crate) fn cache_traces<'a>(
+        &self,
+        block_info: &MadaraBlockInfo,
+        traces: impl IntoIterator<Item = &'a TransactionTrace<Felt>>,
+    ) {
+        let Some(trace_cache) = &self.trace_cache else { return };
+        trace_cache.insert(
+            &self.backend,
+            block_info.header.block_number,
+            block_info.block_hash,
+            traces.into_iter().enumerate().map(|(tx_index, trace)| (tx_index as u64, trace.clone())),
+        );
+    }
+}
+
+/// Traces every block once it is imported, so that the traces of its transactions are already cached when they are
+/// requested.
+pub async fn populate_trace_cache(starknet: Starknet, mut ctx: ServiceContext) {
+    let mut rx = starknet.backend.subscribe_block_info();
+    loop {
+        let block_info = match ctx.run_until_cancelled(rx.recv()).await {
+            Some(Ok(block_info)) => block_info,
+            Some(Err(RecvError::Lagged(skipped))) => {
+                tracing::debug!("Trace cache population skipped {skipped} blocks");
+                continue;
+            }
+            Some(Err(RecvError::Closed)) | None => return,
+        };
+
+        let block_n = block_info.header.block_number;
+        if let Err(err) = trace_block_transactions(&starknet, BlockId::Number(block_n)).await {
+            tracing::debug!("Failed to trace block #{block_n} for the trace cache: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(tx_index: u64) -> TracePosition {
+        TracePosition { block_n: 1, block_hash: Felt::ONE, tx_index }
+    }
+
+    #[test]
+    fn memory_cache_hit_and_miss() {
+        let mut cache = MemoryCache::new(100);
+        cache.insert(position(0), "trace 0", 10);
+
+        assert_eq!(cache.get(&position(0)), Some("trace 0"));
+        assert_eq!(cache.get(&position(1)), None);
+        // Traces of a block with the same number but another hash, such as a reverted block, are not served.
+        assert_eq!(cache.get(&TracePosition { block_hash: Felt::TWO, ..position(0) }), None);
+    }
+
+    #[test]
+    fn memory_cache_evicts_oldest_past_max_size() {
+        let mut cache = MemoryCache::new(100);
+        for tx_index in 0..4 {
+            cache.insert(position(tx_index), tx_index, 30);
+        }
+        assert_eq!(cache.size, 90);
+        assert_eq!(cache.get(&position(0)), None);
+        assert_eq!(cache.get(&position(1)), Some(1));
+        assert_eq!(cache.get(&position(3)), Some(3));
+
+        // Replacing an entry accounts for its new size only.
+        cache.insert(position(3), 3, 50);
+        assert_eq!(cache.size, 80);
+        assert_eq!(cache.get(&position(1)), None);
+        assert_eq!(cache.get(&position(2)), Some(2));
+
+        // An entry larger than the cache is not kept.
+        cache.insert(position(4), 4, 200);
+        assert_eq!(cache.size, 0);
+        assert_eq!(cache.get(&position(4)), None);
+        assert!(cache.order.is_empty());
+    }
+}
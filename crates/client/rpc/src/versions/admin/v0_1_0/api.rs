@@ -1,11 +1,19 @@
 use jsonrpsee::core::RpcResult;
 use m_proc_macros::versioned_rpc;
+use mc_db::db_metrics::DbStats;
+use mc_db::l1_db::RejectedL1Message;
+use mc_db::BackupInfo;
+use mc_mempool::ReplicatedTransaction;
+use mp_chain_config::AdmissionPolicy;
 use mp_transactions::BroadcastedDeclareTransactionV0;
 use mp_utils::service::{MadaraServiceId, MadaraServiceStatus};
+use primitive_types::H256;
 use serde::{Deserialize, Serialize};
 use starknet_types_core::felt::Felt;
 use starknet_types_rpc::ClassAndTxnHash;
 
+use crate::versions::user::v0_8_0::MessageToL1WithStatus;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ServiceRequest {
@@ -14,6 +22,35 @@ pub enum ServiceRequest {
     Restart,
 }
 
+/// An L1→L2 message, posted directly to the mempool of a devnet as an L1 handler transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DevnetMessageToL2 {
+    pub l1_contract_address: Felt,
+    pub l2_contract_address: Felt,
+    pub entry_point_selector: Felt,
+    pub payload: Vec<Felt>,
+    /// Defaults to the next nonce not used by any message.
+    #[serde(default)]
+    pub nonce: Option<u64>,
+    /// Fee paid on L1 for the message, in wei. Defaults to 1, as any non-zero fee is accepted.
+    #[serde(default)]
+    pub paid_fee_on_l1: Option<u128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DevnetMessageToL2Result {
+    pub transaction_hash: Felt,
+    pub nonce: u64,
+}
+
+/// An L1→L2 message rejected by the L1 handler policy, with the nonce of the message on L1.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RejectedL1MessageWithNonce {
+    pub nonce: Felt,
+    #[serde(flatten)]
+    pub message: RejectedL1Message,
+}
+
 /// This is an admin method, so semver is different!
 #[versioned_rpc("V0_1_0", "madara")]
 pub trait MadaraWriteRpcApi {
@@ -62,3 +99,118 @@ pub trait MadaraServicesRpcApi {
     #[method(name = "service")]
     async fn service(&self, service: Vec<MadaraServiceId>, status: ServiceRequest) -> RpcResult<MadaraServiceStatus>;
 }
+
+#[versioned_rpc("V0_1_0", "madara")]
+pub trait MadaraMempoolRpcApi {
+    /// Returns the transaction admission policy currently enforced by the mempool.
+    #[method(name = "getAdmissionPolicy")]
+    async fn get_admission_policy(&self) -> RpcResult<AdmissionPolicy>;
+
+    /// Replaces the transaction admission policy enforced by the mempool. Transactions which are already in the
+    /// mempool are not affected.
+    ///
+    /// This change is not persisted: the policy from the chain config is used again on restart.
+    #[method(name = "setAdmissionPolicy")]
+    async fn set_admission_policy(&self, policy: AdmissionPolicy) -> RpcResult<()>;
+
+    /// Receives the transactions accepted into the mempool of the active sequencer, when this node is a hot-standby
+    /// sequencer. They are kept aside, and only inserted into the mempool when this node takes over block production.
+    #[method(name = "replicateMempoolTransactions")]
+    async fn replicate_mempool_transactions(&self, transactions: Vec<ReplicatedTransaction>) -> RpcResult<()>;
+}
+
+#[versioned_rpc("V0_1_0", "madara")]
+pub trait MadaraBlockProductionRpcApi {
+    /// Seals the pending block now, even if it has no transactions. This works in every block production mode, and is
+    /// the only way to close blocks in `manual` mode.
+    ///
+    /// # Returns
+    ///
+    /// * The number of the closed block.
+    #[method(name = "closeBlock")]
+    async fn close_block(&self) -> RpcResult<u64>;
+}
+
+/// Messaging on a devnet, which has no L1: messages to L2 are posted directly, and messages to L1 are settled and
+/// consumed on demand. Only available in devnet mode.
+#[versioned_rpc("V0_1_0", "madara")]
+pub trait MadaraDevnetRpcApi {
+    /// Adds an L1 handler transaction for the message to the mempool, as if it had been sent on L1.
+    #[method(name = "devnetPostMessageToL2")]
+    async fn devnet_post_message_to_l2(&self, message: DevnetMessageToL2) -> RpcResult<DevnetMessageToL2Result>;
+
+    /// Returns the L2→L1 messages sent starting at block `from_block`, along with their status on L1. Messages are
+    /// returned by whole blocks, until at least `chunk_size` messages are found.
+    #[method(name = "devnetGetMessagesToL1")]
+    async fn devnet_get_messages_to_l1(
+        &self,
+        from_block: Option<u64>,
+        chunk_size: Option<u64>,
+    ) -> RpcResult<Vec<MessageToL1WithStatus>>;
+
+    /// Settles on L1 all the L2→L1 messages sent since the last flush, so that they can be consumed.
+    ///
+    /// # Returns
+    ///
+    /// * The flushed messages.
+    #[method(name = "devnetFlushMessagesToL1")]
+    async fn devnet_flush_messages_to_l1(&self) -> RpcResult<Vec<MessageToL1WithStatus>>;
+
+    /// Consumes on L1 the first settled L2→L1 message with the given hash which was not consumed yet.
+    ///
+    /// # Returns
+    ///
+    /// * The consumed message.
+    #[method(name = "devnetConsumeMessageToL1")]
+    async fn devnet_consume_message_to_l1(&self, message_hash: H256) -> RpcResult<MessageToL1WithStatus>;
+}
+
+#[versioned_rpc("V0_1_0", "madara")]
+pub trait MadaraL1MessagingRpcApi {
+    /// Returns the L1→L2 messages which were rejected by the L1 handler policy, by increasing nonce. These messages
+    /// are never retried.
+    #[method(name = "getRejectedL1Messages")]
+    async fn get_rejected_l1_messages(&self) -> RpcResult<Vec<RejectedL1MessageWithNonce>>;
+}
+
+/// Database maintenance, which would otherwise need a restart of the node with different options.
+#[versioned_rpc("V0_1_0", "madara")]
+pub trait MadaraDatabaseRpcApi {
+    /// Creates a new backup of the database in the backup directory. Only available when backups are enabled.
+    ///
+    /// # Returns
+    ///
+    /// * The created backup.
+    #[method(name = "backupDatabase")]
+    async fn backup_database(&self) -> RpcResult<BackupInfo>;
+
+    /// Lists the backups in the backup directory, oldest first. Only available when backups are enabled.
+    #[method(name = "listBackups")]
+    async fn list_backups(&self) -> RpcResult<Vec<BackupInfo>>;
+
+    /// Flushes the mem-tables of every column to disk, and waits for the flush to complete.
+    #[method(name = "flushDatabase")]
+    async fn flush_database(&self) -> RpcResult<()>;
+
+    /// Starts a manual compaction of a column, or of every column if none is given. This returns immediately, the
+    /// compaction runs in the background.
+    #[method(name = "compactDatabase")]
+    async fn compact_database(&self, column: Option<String>) -> RpcResult<()>;
+
+    /// Returns the storage size of each column, and the memory usage of the database.
+    #[method(name = "getDatabaseStats")]
+    async fn get_database_stats(&self) -> RpcResult<DbStats>;
+}
+
+#[versioned_rpc("V0_1_0", "madara")]
+pub trait MadaraLoggingRpcApi {
+    /// Returns the current log filter, with the same syntax as `RUST_LOG`.
+    #[method(name = "getLogFilter")]
+    async fn get_log_filter(&self) -> RpcResult<String>;
+
+    /// Replaces the log filter, with the same syntax as `RUST_LOG`.
+    ///
+    /// This change is not persisted: the `RUST_LOG` environment variable is used again on restart.
+    #[method(name = "setLogFilter")]
+    async fn set_log_filter(&self, filter: String) -> RpcResult<()>;
+}
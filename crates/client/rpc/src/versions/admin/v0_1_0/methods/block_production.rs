@@ -0,0 +1,14 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+
+use crate::{versions::admin::v0_1_0::MadaraBlockProductionRpcApiV0_1_0Server, Starknet, StarknetRpcApiError};
+
+#[async_trait]
+impl MadaraBlockProductionRpcApiV0_1_0Server for Starknet {
+    async fn close_block(&self) -> RpcResult<u64> {
+        Ok(self
+            .block_production()?
+            .close_block()
+            .await
+            .map_err(|err| StarknetRpcApiError::ErrUnexpectedError { data: format!("{err:#}") })?)
+    }
+}
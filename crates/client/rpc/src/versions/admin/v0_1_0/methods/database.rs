@@ -0,0 +1,131 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use mc_db::db_metrics::DbStats;
+use mc_db::{BackupInfo, Column};
+
+use crate::{versions::admin::v0_1_0::MadaraDatabaseRpcApiV0_1_0Server, Starknet, StarknetRpcApiError};
+
+fn unexpected_error(err: anyhow::Error) -> StarknetRpcApiError {
+    StarknetRpcApiError::ErrUnexpectedError { data: format!("{err:#}") }
+}
+
+#[async_trait]
+impl MadaraDatabaseRpcApiV0_1_0Server for Starknet {
+    async fn backup_database(&self) -> RpcResult<BackupInfo> {
+        tracing::info!("💾 Backing up the database...");
+        self.backend.try_backup().await.map_err(unexpected_error)?;
+        let backups = self.backend.list_backups().await.map_err(unexpected_error)?;
+        Ok(backups
+            .into_iter()
+            .max_by_key(|backup| backup.backup_id)
+            .ok_or_else(|| StarknetRpcApiError::ErrUnexpectedError { data: "The backup was not found".to_string() })?)
+    }
+
+    async fn list_backups(&self) -> RpcResult<Vec<BackupInfo>> {
+        Ok(self.backend.list_backups().await.map_err(unexpected_error)?)
+    }
+
+    async fn flush_database(&self) -> RpcResult<()> {
+        let backend = self.clone_backend();
+        tokio::task::spawn_blocking(move || backend.flush())
+            .await
+            .map_err(|err| unexpected_error(err.into()))?
+            .map_err(unexpected_error)?;
+        Ok(())
+    }
+
+    async fn compact_database(&self, column: Option<String>) -> RpcResult<()> {
+        let column = column
+            .map(|name| {
+                Column::from_rocksdb_name(&name)
+                    .ok_or_else(|| StarknetRpcApiError::ErrUnexpectedError { data: format!("Unknown column {name:?}") })
+            })
+            .transpose()?;
+
+        let backend = self.clone_backend();
+        tokio::task::spawn_blocking(move || {
+            tracing::info!("💾 Compacting the database...");
+            backend.compact(column);
+            tracing::info!("💾 Database compaction complete");
+        });
+        Ok(())
+    }
+
+    async fn get_database_stats(&self) -> RpcResult<DbStats> {
+        Ok(self.backend.db_stats().map_err(unexpected_error)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{rpc_test_setup, TestTransactionProvider};
+    use jsonrpsee::core::params::ArrayParams;
+    use jsonrpsee::rpc_params;
+    use mc_db::{DatabaseService, MadaraBackend};
+    use mp_chain_config::ChainConfig;
+    use mp_utils::service::ServiceContext;
+    use rstest::rstest;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_backup_methods() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let chain_config = Arc::new(ChainConfig::madara_test());
+        let db = DatabaseService::new(temp_dir.path(), Some(backup_dir), false, chain_config, Default::default())
+            .await
+            .unwrap();
+        let rpc = Starknet::new(
+            Arc::clone(db.backend()),
+            Arc::new(TestTransactionProvider),
+            Default::default(),
+            ServiceContext::new_for_testing(),
+        );
+        let rpc_api = MadaraDatabaseRpcApiV0_1_0Server::into_rpc(rpc);
+
+        let backups: Value = rpc_api.call("madara_V0_1_0_listBackups", ArrayParams::new()).await.unwrap();
+        assert_eq!(backups, serde_json::json!([]));
+
+        let backup: Value = rpc_api.call("madara_V0_1_0_backupDatabase", ArrayParams::new()).await.unwrap();
+        assert_eq!(backup["backup_id"], 1);
+        assert!(backup["size"].as_u64().unwrap() > 0);
+        assert!(backup["num_files"].as_u64().unwrap() > 0);
+
+        let backup: Value = rpc_api.call("madara_V0_1_0_backupDatabase", ArrayParams::new()).await.unwrap();
+        assert_eq!(backup["backup_id"], 2);
+        let backups: Value = rpc_api.call("madara_V0_1_0_listBackups", ArrayParams::new()).await.unwrap();
+        assert_eq!(backups.as_array().unwrap().iter().map(|backup| &backup["backup_id"]).collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_backups_not_enabled(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (_, rpc) = rpc_test_setup;
+        let rpc_api = MadaraDatabaseRpcApiV0_1_0Server::into_rpc(rpc);
+
+        assert!(rpc_api.call::<_, Value>("madara_V0_1_0_backupDatabase", ArrayParams::new()).await.is_err());
+        assert!(rpc_api.call::<_, Value>("madara_V0_1_0_listBackups", ArrayParams::new()).await.is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_database_stats(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (_, rpc) = rpc_test_setup;
+        let rpc_api = MadaraDatabaseRpcApiV0_1_0Server::into_rpc(rpc);
+
+        let _: Value = rpc_api.call("madara_V0_1_0_flushDatabase", ArrayParams::new()).await.unwrap();
+        let _: Value =
+            rpc_api.call("madara_V0_1_0_compactDatabase", rpc_params!["block_n_to_block_info"]).await.unwrap();
+        assert!(rpc_api.call::<_, Value>("madara_V0_1_0_compactDatabase", rpc_params!["not_a_column"]).await.is_err());
+
+        let stats: Value = rpc_api.call("madara_V0_1_0_getDatabaseStats", ArrayParams::new()).await.unwrap();
+        let columns = stats["columns"].as_array().unwrap();
+        assert_eq!(columns.len(), Column::NUM_COLUMNS);
+        assert!(columns.iter().any(|column| column["column"] == "block_n_to_block_info"));
+        assert_eq!(
+            stats["db_size"].as_u64().unwrap(),
+            columns.iter().map(|column| column["size"].as_u64().unwrap()).sum::<u64>()
+        );
+    }
+}
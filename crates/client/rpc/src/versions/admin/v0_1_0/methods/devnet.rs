@@ -0,0 +1,279 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use mc_db::msg_to_l1_db::{MsgToL1Entry, MsgToL1Event};
+use mc_mempool::MempoolProvider;
+use mp_transactions::L1HandlerTransaction;
+use primitive_types::H256;
+use starknet_api::core::Nonce;
+use starknet_types_core::felt::Felt;
+
+use crate::constants::MAX_MESSAGES_TO_L1_CHUNK_SIZE;
+use crate::errors::{StarknetRpcApiError, StarknetRpcResult};
+use crate::utils::ResultExt;
+use crate::versions::admin::v0_1_0::{DevnetMessageToL2, DevnetMessageToL2Result, MadaraDevnetRpcApiV0_1_0Server};
+use crate::versions::user::v0_8_0::methods::messaging::get_messages_to_l1::with_statuses;
+use crate::versions::user::v0_8_0::MessageToL1WithStatus;
+use crate::Starknet;
+
+#[async_trait]
+impl MadaraDevnetRpcApiV0_1_0Server for Starknet {
+    async fn devnet_post_message_to_l2(&self, message: DevnetMessageToL2) -> RpcResult<DevnetMessageToL2Result> {
+        Ok(post_message_to_l2(self, message)?)
+    }
+
+    async fn devnet_get_messages_to_l1(
+        &self,
+        from_block: Option<u64>,
+        chunk_size: Option<u64>,
+    ) -> RpcResult<Vec<MessageToL1WithStatus>> {
+        Ok(get_messages_to_l1(self, from_block, chunk_size)?)
+    }
+
+    async fn devnet_flush_messages_to_l1(&self) -> RpcResult<Vec<MessageToL1WithStatus>> {
+        Ok(flush_messages_to_l1(self)?)
+    }
+
+    async fn devnet_consume_message_to_l1(&self, message_hash: H256) -> RpcResult<MessageToL1WithStatus> {
+        Ok(consume_message_to_l1(self, message_hash)?)
+    }
+}
+
+fn has_nonce(starknet: &Starknet, nonce: u64) -> StarknetRpcResult<bool> {
+    starknet
+        .backend
+        .has_l1_messaging_nonce(Nonce(Felt::from(nonce)))
+        .or_internal_server_error("Error checking the L1 messaging nonce")
+}
+
+pub fn post_message_to_l2(
+    starknet: &Starknet,
+    message: DevnetMessageToL2,
+) -> StarknetRpcResult<DevnetMessageToL2Result> {
+    starknet.check_devnet()?;
+    let mempool = starknet.mempool()?;
+    let backend = &starknet.backend;
+
+    let next_nonce =
+        backend.get_devnet_next_l1_message_nonce().or_internal_server_error("Error getting the next message nonce")?;
+    let nonce = match message.nonce {
+        Some(nonce) if has_nonce(starknet, nonce)? => {
+            return Err(StarknetRpcApiError::ErrUnexpectedError {
+                data: format!("A message with nonce {nonce} was already posted"),
+            })
+        }
+        Some(nonce) => nonce,
+        None => {
+            let mut nonce = next_nonce;
+            while has_nonce(starknet, nonce)? {
+                nonce += 1;
+            }
+            nonce
+        }
+    };
+
+    let mut calldata = Vec::with_capacity(message.payload.len() + 1);
+    calldata.push(message.l1_contract_address);
+    calldata.extend(message.payload);
+    let transaction = L1HandlerTransaction {
+        version: Felt::ZERO,
+        nonce,
+        contract_address: message.l2_contract_address,
+        entry_point_selector: message.entry_point_selector,
+        calldata,
+    };
+    let res = mempool.accept_l1_handler_tx(transaction, message.paid_fee_on_l1.unwrap_or(1))?;
+
+    backend
+        .set_l1_messaging_nonce(Nonce(Felt::from(nonce)))
+        .or_internal_server_error("Error setting the L1 messaging nonce")?;
+    if nonce >= next_nonce {
+        backend
+            .set_devnet_next_l1_message_nonce(nonce + 1)
+            .or_internal_server_error("Error setting the next message nonce")?;
+    }
+
+    tracing::info!("⟠ Devnet message with nonce {nonce} posted, transaction hash on L2: {:#x}", res.transaction_hash);
+    Ok(DevnetMessageToL2Result { transaction_hash: res.transaction_hash, nonce })
+}
+
+pub fn get_messages_to_l1(
+    starknet: &Starknet,
+    from_block: Option<u64>,
+    chunk_size: Option<u64>,
+) -> StarknetRpcResult<Vec<MessageToL1WithStatus>> {
+    starknet.check_devnet()?;
+    let chunk_size = chunk_size.map_or(MAX_MESSAGES_TO_L1_CHUNK_SIZE, |size| size as usize);
+    if chunk_size > MAX_MESSAGES_TO_L1_CHUNK_SIZE {
+        return Err(StarknetRpcApiError::PageSizeTooBig);
+    }
+
+    let mut entries = vec![];
+    for block_n in from_block.unwrap_or(0)..=latest_block_n(starknet)?.unwrap_or_default() {
+        if entries.len() >= chunk_size {
+            break;
+        }
+        entries.extend(messages_in_block(starknet, block_n)?);
+    }
+    with_statuses(starknet, entries)
+}
+
+pub fn flush_messages_to_l1(starknet: &Starknet) -> StarknetRpcResult<Vec<MessageToL1WithStatus>> {
+    starknet.check_devnet()?;
+    let backend = &starknet.backend;
+    let Some(latest_block_n) = latest_block_n(starknet)? else { return Ok(vec![]) };
+    let from_block_n =
+        backend.get_devnet_messages_to_l1_flushed().or_internal_server_error("Error getting the last flushed block")?;
+
+    let mut entries = vec![];
+    for block_n in from_block_n..=latest_block_n {
+        entries.extend(messages_in_block(starknet, block_n)?);
+    }
+    backend
+        .msg_to_l1_devnet_flush(entries.iter().map(|entry| &entry.message_hash), latest_block_n + 1)
+        .or_internal_server_error("Error flushing messages to L1")?;
+
+    if !entries.is_empty() {
+        tracing::info!("⟠ Flushed {} devnet messages to L1 up to block #{latest_block_n}", entries.len());
+    }
+    with_statuses(starknet, entries)
+}
+
+pub fn consume_message_to_l1(starknet: &Starknet, message_hash: H256) -> StarknetRpcResult<MessageToL1WithStatus> {
+    starknet.check_devnet()?;
+    let backend = &starknet.backend;
+
+    let status =
+        backend.get_msg_to_l1_status(&message_hash).or_internal_server_error("Error getting message to L1 status")?;
+    if status.consumed >= status.settled {
+        return Err(StarknetRpcApiError::ErrUnexpectedError {
+            data: format!("No message with hash {message_hash:?} is waiting to be consumed, flush the messages first"),
+        });
+    }
+    backend
+        .msg_to_l1_devnet_apply_event(&message_hash, MsgToL1Event::Consumed)
+        .or_internal_server_error("Error consuming message to L1")?;
+
+    // Messages with the same hash are consumed in L2 order.
+    let entry = backend
+        .get_messages_to_l1_by_hash(&message_hash)
+        .or_internal_server_error("Error getting messages to L1 by hash")?
+        .into_iter()
+        .nth(status.consumed as usize)
+        .ok_or(StarknetRpcApiError::InternalServerError)?;
+    with_statuses(starknet, vec![entry])?.pop().ok_or(StarknetRpcApiError::InternalServerError)
+}
+
+fn latest_block_n(starknet: &Starknet) -> StarknetRpcResult<Option<u64>> {
+    starknet.backend.get_latest_block_n().or_internal_server_error("Error getting latest block")
+}
+
+fn messages_in_block(starknet: &Starknet, block_n: u64) -> StarknetRpcResult<Vec<MsgToL1Entry>> {
+    starknet.backend.get_messages_to_l1_in_block(block_n).or_internal_server_error("Error getting messages to L1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::rpc_test_setup;
+    use crate::versions::user::v0_8_0::MessageToL1Status;
+    use jsonrpsee::core::params::ArrayParams;
+    use jsonrpsee::RpcModule;
+    use mc_db::msg_to_l1_db::MsgToL1Status;
+    use mc_db::MadaraBackend;
+    use mp_block::{Header, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo};
+    use mp_receipt::{InvokeTransactionReceipt, MsgToL1};
+    use mp_state_update::StateDiff;
+    use mp_transactions::InvokeTransactionV0;
+    use rstest::rstest;
+    use std::sync::Arc;
+
+    fn store_block_with_messages(backend: &MadaraBackend, block_n: u64, messages: Vec<MsgToL1>) {
+        backend
+            .store_block(
+                MadaraMaybePendingBlock {
+                    info: MadaraMaybePendingBlockInfo::NotPending(MadaraBlockInfo {
+                        header: Header { block_number: block_n, ..Default::default() },
+                        block_hash: Felt::from(block_n),
+                        tx_hashes: vec![Felt::from(100 + block_n)],
+                    }),
+                    inner: MadaraBlockInner {
+                        transactions: vec![InvokeTransactionV0::default().into()],
+                        receipts: vec![
+                            InvokeTransactionReceipt { messages_sent: messages, ..Default::default() }.into()
+                        ],
+                    },
+                },
+                StateDiff::default(),
+                vec![],
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    #[rstest]
+    fn test_devnet_messages_to_l1(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (backend, rpc) = rpc_test_setup;
+        assert!(get_messages_to_l1(&rpc, None, None).is_err());
+        let rpc = rpc.with_devnet();
+
+        let message = MsgToL1 { from_address: Felt::ONE, to_address: Felt::TWO, payload: vec![Felt::THREE] };
+        let message_hash = message.compute_hash();
+        store_block_with_messages(&backend, 0, vec![message.clone()]);
+        store_block_with_messages(&backend, 1, vec![]);
+
+        let statuses = |res: Vec<MessageToL1WithStatus>| res.into_iter().map(|m| m.status).collect::<Vec<_>>();
+
+        assert_eq!(statuses(get_messages_to_l1(&rpc, None, None).unwrap()), [MessageToL1Status::AcceptedOnL2]);
+        assert!(consume_message_to_l1(&rpc, message_hash).is_err());
+
+        assert_eq!(statuses(flush_messages_to_l1(&rpc).unwrap()), [MessageToL1Status::AcceptedOnL1]);
+        // Already flushed.
+        assert!(flush_messages_to_l1(&rpc).unwrap().is_empty());
+
+        store_block_with_messages(&backend, 2, vec![message.clone()]);
+        assert_eq!(
+            statuses(get_messages_to_l1(&rpc, Some(0), Some(1)).unwrap()),
+            [MessageToL1Status::AcceptedOnL1],
+            "pages end on a block boundary"
+        );
+        assert_eq!(statuses(flush_messages_to_l1(&rpc).unwrap()), [MessageToL1Status::AcceptedOnL1]);
+
+        let consumed = consume_message_to_l1(&rpc, message_hash).unwrap();
+        assert_eq!((consumed.block_number, consumed.status), (0, MessageToL1Status::ConsumedOnL1));
+        let consumed = consume_message_to_l1(&rpc, message_hash).unwrap();
+        assert_eq!((consumed.block_number, consumed.status), (2, MessageToL1Status::ConsumedOnL1));
+        assert!(consume_message_to_l1(&rpc, message_hash).is_err());
+
+        // Identical messages flushed together are all settled.
+        store_block_with_messages(&backend, 3, vec![message.clone(), message.clone()]);
+        assert_eq!(
+            statuses(flush_messages_to_l1(&rpc).unwrap()),
+            [MessageToL1Status::AcceptedOnL1, MessageToL1Status::AcceptedOnL1]
+        );
+        assert_eq!(backend.get_msg_to_l1_status(&message_hash).unwrap(), MsgToL1Status { settled: 4, consumed: 2 });
+        assert_eq!(backend.get_devnet_messages_to_l1_flushed().unwrap(), 4);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_devnet_methods_only_in_devnet(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (_, rpc) = rpc_test_setup;
+        const METHOD: &str = "madara_V0_1_0_devnetFlushMessagesToL1";
+        let discovered = |rpc_api: RpcModule<()>| async move {
+            let document: serde_json::Value = rpc_api.call("rpc_V0_1_0_discover", ArrayParams::new()).await.unwrap();
+            document["methods"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|method| method["name"] == "madara_devnetFlushMessagesToL1")
+        };
+
+        let rpc_api = crate::rpc_api_admin(&rpc).unwrap();
+        assert!(!rpc_api.method_names().any(|name| name == METHOD));
+        assert!(!discovered(rpc_api).await);
+
+        let rpc_api = crate::rpc_api_admin(&rpc.with_devnet()).unwrap();
+        assert!(rpc_api.method_names().any(|name| name == METHOD));
+        assert!(discovered(rpc_api).await);
+    }
+}
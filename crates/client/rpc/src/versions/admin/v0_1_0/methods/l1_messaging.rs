@@ -0,0 +1,18 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+
+use crate::versions::admin::v0_1_0::{MadaraL1MessagingRpcApiV0_1_0Server, RejectedL1MessageWithNonce};
+use crate::{Starknet, StarknetRpcApiError};
+
+#[async_trait]
+impl MadaraL1MessagingRpcApiV0_1_0Server for Starknet {
+    async fn get_rejected_l1_messages(&self) -> RpcResult<Vec<RejectedL1MessageWithNonce>> {
+        let messages = self
+            .backend
+            .get_rejected_l1_messages()
+            .map_err(|err| StarknetRpcApiError::ErrUnexpectedError { data: format!("{err:#}") })?;
+        Ok(messages
+            .into_iter()
+            .map(|(nonce, message)| RejectedL1MessageWithNonce { nonce: nonce.0, message })
+            .collect())
+    }
+}
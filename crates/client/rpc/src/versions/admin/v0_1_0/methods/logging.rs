@@ -0,0 +1,21 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+
+use crate::{versions::admin::v0_1_0::MadaraLoggingRpcApiV0_1_0Server, Starknet, StarknetRpcApiError};
+
+#[async_trait]
+impl MadaraLoggingRpcApiV0_1_0Server for Starknet {
+    async fn get_log_filter(&self) -> RpcResult<String> {
+        Ok(self
+            .log_filter()?
+            .get()
+            .map_err(|err| StarknetRpcApiError::ErrUnexpectedError { data: format!("{err:#}") })?)
+    }
+
+    async fn set_log_filter(&self, filter: String) -> RpcResult<()> {
+        self.log_filter()?
+            .set(&filter)
+            .map_err(|err| StarknetRpcApiError::ErrUnexpectedError { data: format!("{err:#}") })?;
+        tracing::info!("📝 Log filter set to {filter:?}");
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use mc_mempool::ReplicatedTransaction;
+use mp_chain_config::AdmissionPolicy;
+
+use crate::{versions::admin::v0_1_0::MadaraMempoolRpcApiV0_1_0Server, Starknet};
+
+#[async_trait]
+impl MadaraMempoolRpcApiV0_1_0Server for Starknet {
+    async fn get_admission_policy(&self) -> RpcResult<AdmissionPolicy> {
+        Ok(self.mempool()?.admission_policy())
+    }
+
+    async fn set_admission_policy(&self, policy: AdmissionPolicy) -> RpcResult<()> {
+        self.mempool()?.set_admission_policy(policy);
+        Ok(())
+    }
+
+    async fn replicate_mempool_transactions(&self, transactions: Vec<ReplicatedTransaction>) -> RpcResult<()> {
+        self.mempool()?.add_replicated_txs(transactions);
+        Ok(())
+    }
+}
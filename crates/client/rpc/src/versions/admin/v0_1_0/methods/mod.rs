@@ -1,3 +1,9 @@
+pub mod block_production;
+pub mod database;
+pub mod devnet;
+pub mod l1_messaging;
+pub mod logging;
+pub mod mempool;
 pub mod services;
 pub mod status;
 pub mod write;
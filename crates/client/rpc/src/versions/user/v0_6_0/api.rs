@@ -0,0 +1,422 @@
+use jsonrpsee::core::RpcResult;
+use m_proc_macros::versioned_rpc;
+use mp_block::BlockId;
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{
+    BlockStatus, BroadcastedTxn, ComputationResources, Event, ExecuteInvocation, FeePayment, FunctionInvocation,
+    MsgFromL1, MsgToL1, PriceUnit, ResourcePrice, SimulationFlag, SimulationFlagForEstimateFee, StateDiff,
+    TxnExecutionStatus, TxnFinalityStatus, TxnWithHash,
+};
+
+// Starknet RPC API v0.6 compatibility layer
+//
+// Only the methods whose schema changed in v0.7 are defined here: the others are served by the v0.7 implementation
+// with `and_versions`. The v0.6 schemas have no L1 data gas, neither in block headers nor in fee estimates, and only
+// computation resources in receipts and traces. Resource bounds are the same as in v0.7: `l1_gas` and `l2_gas`.
+
+/// Block header, without the L1 data gas price and data availability mode added in v0.7.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub block_hash: Felt,
+    pub parent_hash: Felt,
+    pub block_number: u64,
+    pub new_root: Felt,
+    pub timestamp: u64,
+    pub sequencer_address: Felt,
+    pub l1_gas_price: ResourcePrice<Felt>,
+    pub starknet_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingBlockHeader {
+    pub parent_hash: Felt,
+    pub timestamp: u64,
+    pub sequencer_address: Felt,
+    pub l1_gas_price: ResourcePrice<Felt>,
+    pub starknet_version: String,
+}
+
+/// A block with its transaction hashes or its full transactions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block<T> {
+    #[serde(flatten)]
+    pub block_header: BlockHeader,
+    pub status: BlockStatus,
+    pub transactions: Vec<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingBlock<T> {
+    #[serde(flatten)]
+    pub pending_block_header: PendingBlockHeader,
+    pub transactions: Vec<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MaybePendingBlock<T> {
+    Block(Block<T>),
+    Pending(PendingBlock<T>),
+}
+
+pub type MaybePendingBlockWithTxHashes = MaybePendingBlock<Felt>;
+pub type MaybePendingBlockWithTxs = MaybePendingBlock<TxnWithHash<Felt>>;
+
+impl From<starknet_types_rpc::BlockHeader<Felt>> for BlockHeader {
+    fn from(header: starknet_types_rpc::BlockHeader<Felt>) -> Self {
+        Self {
+            block_hash: header.block_hash,
+            parent_hash: header.parent_hash,
+            block_number: header.block_number,
+            new_root: header.new_root,
+            timestamp: header.timestamp,
+            sequencer_address: header.sequencer_address,
+            l1_gas_price: header.l1_gas_price,
+            starknet_version: header.starknet_version,
+        }
+    }
+}
+
+impl From<starknet_types_rpc::PendingBlockHeader<Felt>> for PendingBlockHeader {
+    fn from(header: starknet_types_rpc::PendingBlockHeader<Felt>) -> Self {
+        Self {
+            parent_hash: header.parent_hash,
+            timestamp: header.timestamp,
+            sequencer_address: header.sequencer_address,
+            l1_gas_price: header.l1_gas_price,
+            starknet_version: header.starknet_version,
+        }
+    }
+}
+
+impl From<starknet_types_rpc::MaybePendingBlockWithTxHashes<Felt>> for MaybePendingBlockWithTxHashes {
+    fn from(block: starknet_types_rpc::MaybePendingBlockWithTxHashes<Felt>) -> Self {
+        match block {
+            starknet_types_rpc::MaybePendingBlockWithTxHashes::Block(block) => Self::Block(Block {
+                block_header: block.block_header.into(),
+                status: block.status,
+                transactions: block.transactions,
+            }),
+            starknet_types_rpc::MaybePendingBlockWithTxHashes::Pending(block) => Self::Pending(PendingBlock {
+                pending_block_header: block.pending_block_header.into(),
+                transactions: block.transactions,
+            }),
+        }
+    }
+}
+
+impl From<starknet_types_rpc::MaybePendingBlockWithTxs<Felt>> for MaybePendingBlockWithTxs {
+    fn from(block: starknet_types_rpc::MaybePendingBlockWithTxs<Felt>) -> Self {
+        match block {
+            starknet_types_rpc::MaybePendingBlockWithTxs::Block(block) => Self::Block(Block {
+                block_header: block.block_header.into(),
+                status: block.status,
+                transactions: block.transactions,
+            }),
+            starknet_types_rpc::MaybePendingBlockWithTxs::Pending(block) => Self::Pending(PendingBlock {
+                pending_block_header: block.pending_block_header.into(),
+                transactions: block.transactions,
+            }),
+        }
+    }
+}
+
+/// Properties shared by every receipt. Execution resources are only computation resources, without the data
+/// availability resources added in v0.7.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommonReceiptProperties {
+    pub transaction_hash: Felt,
+    pub actual_fee: FeePayment<Felt>,
+    pub execution_status: TxnExecutionStatus,
+    pub finality_status: TxnFinalityStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    pub messages_sent: Vec<MsgToL1<Felt>>,
+    pub events: Vec<Event<Felt>>,
+    pub execution_resources: ComputationResources,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct L1HandlerTxnReceipt {
+    pub message_hash: String,
+    #[serde(flatten)]
+    pub common_receipt_properties: CommonReceiptProperties,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeployTxnReceipt {
+    pub contract_address: Felt,
+    #[serde(flatten)]
+    pub common_receipt_properties: CommonReceiptProperties,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TxnReceipt {
+    #[serde(rename = "INVOKE")]
+    Invoke(CommonReceiptProperties),
+    #[serde(rename = "L1_HANDLER")]
+    L1Handler(L1HandlerTxnReceipt),
+    #[serde(rename = "DECLARE")]
+    Declare(CommonReceiptProperties),
+    #[serde(rename = "DEPLOY")]
+    Deploy(DeployTxnReceipt),
+    #[serde(rename = "DEPLOY_ACCOUNT")]
+    DeployAccount(DeployTxnReceipt),
+}
+
+/// A receipt, with the hash and number of its block unless it is pending.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxnReceiptWithBlockInfo {
+    #[serde(flatten)]
+    pub transaction_receipt: TxnReceipt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<Felt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+}
+
+fn computation_resources(resources: starknet_types_rpc::ExecutionResources) -> ComputationResources {
+    ComputationResources {
+        steps: resources.steps,
+        memory_holes: resources.memory_holes,
+        range_check_builtin_applications: resources.range_check_builtin_applications,
+        pedersen_builtin_applications: resources.pedersen_builtin_applications,
+        poseidon_builtin_applications: resources.poseidon_builtin_applications,
+        ec_op_builtin_applications: resources.ec_op_builtin_applications,
+        ecdsa_builtin_applications: resources.ecdsa_builtin_applications,
+        bitwise_builtin_applications: resources.bitwise_builtin_applications,
+        keccak_builtin_applications: resources.keccak_builtin_applications,
+        segment_arena_builtin: resources.segment_arena_builtin,
+    }
+}
+
+impl From<starknet_types_rpc::CommonReceiptProperties<Felt>> for CommonReceiptProperties {
+    fn from(properties: starknet_types_rpc::CommonReceiptProperties<Felt>) -> Self {
+        let (execution_status, revert_reason) = match properties.execution_status {
+            starknet_types_rpc::ExecutionStatus::Successful => (TxnExecutionStatus::Succeeded, None),
+            starknet_types_rpc::ExecutionStatus::Reverted(reason) => (TxnExecutionStatus::Reverted, Some(reason)),
+        };
+        Self {
+            transaction_hash: properties.transaction_hash,
+            actual_fee: properties.actual_fee,
+            execution_status,
+            finality_status: properties.finality_status,
+            revert_reason,
+            messages_sent: properties.messages_sent,
+            events: properties.events,
+            execution_resources: computation_resources(properties.execution_resources),
+        }
+    }
+}
+
+impl From<starknet_types_rpc::TxnReceipt<Felt>> for TxnReceipt {
+    fn from(receipt: starknet_types_rpc::TxnReceipt<Felt>) -> Self {
+        match receipt {
+            starknet_types_rpc::TxnReceipt::Invoke(receipt) => Self::Invoke(receipt.common_receipt_properties.into()),
+            starknet_types_rpc::TxnReceipt::L1Handler(receipt) => Self::L1Handler(L1HandlerTxnReceipt {
+                message_hash: receipt.message_hash,
+                common_receipt_properties: receipt.common_receipt_properties.into(),
+            }),
+            starknet_types_rpc::TxnReceipt::Declare(receipt) => Self::Declare(receipt.common_receipt_properties.into()),
+            starknet_types_rpc::TxnReceipt::Deploy(receipt) => Self::Deploy(DeployTxnReceipt {
+                contract_address: receipt.contract_address,
+                common_receipt_properties: receipt.common_receipt_properties.into(),
+            }),
+            starknet_types_rpc::TxnReceipt::DeployAccount(receipt) => Self::DeployAccount(DeployTxnReceipt {
+                contract_address: receipt.contract_address,
+                common_receipt_properties: receipt.common_receipt_properties.into(),
+            }),
+        }
+    }
+}
+
+impl From<starknet_types_rpc::TxnReceiptWithBlockInfo<Felt>> for TxnReceiptWithBlockInfo {
+    fn from(receipt: starknet_types_rpc::TxnReceiptWithBlockInfo<Felt>) -> Self {
+        Self {
+            transaction_receipt: receipt.transaction_receipt.into(),
+            block_hash: receipt.block_hash,
+            block_number: receipt.block_number,
+        }
+    }
+}
+
+/// Fee estimate, without the L1 data gas added in v0.7. The overall fee still accounts for the data gas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub gas_consumed: Felt,
+    pub gas_price: Felt,
+    pub overall_fee: Felt,
+    pub unit: PriceUnit,
+}
+
+impl From<starknet_types_rpc::FeeEstimate<Felt>> for FeeEstimate {
+    fn from(estimate: starknet_types_rpc::FeeEstimate<Felt>) -> Self {
+        Self {
+            gas_consumed: estimate.gas_consumed,
+            gas_price: estimate.gas_price,
+            overall_fee: estimate.overall_fee,
+            unit: estimate.unit,
+        }
+    }
+}
+
+/// Transaction traces, without the transaction-wide execution resources added in v0.7.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TransactionTrace {
+    #[serde(rename = "INVOKE")]
+    Invoke(InvokeTransactionTrace),
+    #[serde(rename = "DECLARE")]
+    Declare(DeclareTransactionTrace),
+    #[serde(rename = "DEPLOY_ACCOUNT")]
+    DeployAccount(DeployAccountTransactionTrace),
+    #[serde(rename = "L1_HANDLER")]
+    L1Handler(L1HandlerTransactionTrace),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvokeTransactionTrace {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation<Felt>>,
+    pub execute_invocation: ExecuteInvocation<Felt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation<Felt>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<StateDiff<Felt>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeclareTransactionTrace {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation<Felt>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation<Felt>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<StateDiff<Felt>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployAccountTransactionTrace {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation<Felt>>,
+    pub constructor_invocation: FunctionInvocation<Felt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation<Felt>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<StateDiff<Felt>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct L1HandlerTransactionTrace {
+    pub function_invocation: FunctionInvocation<Felt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<StateDiff<Felt>>,
+}
+
+impl From<starknet_types_rpc::TransactionTrace<Felt>> for TransactionTrace {
+    fn from(trace: starknet_types_rpc::TransactionTrace<Felt>) -> Self {
+        match trace {
+            starknet_types_rpc::TransactionTrace::Invoke(trace) => Self::Invoke(InvokeTransactionTrace {
+                validate_invocation: trace.validate_invocation,
+                execute_invocation: trace.execute_invocation,
+                fee_transfer_invocation: trace.fee_transfer_invocation,
+                state_diff: trace.state_diff,
+            }),
+            starknet_types_rpc::TransactionTrace::Declare(trace) => Self::Declare(DeclareTransactionTrace {
+                validate_invocation: trace.validate_invocation,
+                fee_transfer_invocation: trace.fee_transfer_invocation,
+                state_diff: trace.state_diff,
+            }),
+            starknet_types_rpc::TransactionTrace::DeployAccount(trace) => {
+                Self::DeployAccount(DeployAccountTransactionTrace {
+                    validate_invocation: trace.validate_invocation,
+                    constructor_invocation: trace.constructor_invocation,
+                    fee_transfer_invocation: trace.fee_transfer_invocation,
+                    state_diff: trace.state_diff,
+                })
+            }
+            starknet_types_rpc::TransactionTrace::L1Handler(trace) => Self::L1Handler(L1HandlerTransactionTrace {
+                function_invocation: trace.function_invocation,
+                state_diff: trace.state_diff,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulateTransactionsResult {
+    pub transaction_trace: TransactionTrace,
+    pub fee_estimation: FeeEstimate,
+}
+
+impl From<starknet_types_rpc::SimulateTransactionsResult<Felt>> for SimulateTransactionsResult {
+    fn from(result: starknet_types_rpc::SimulateTransactionsResult<Felt>) -> Self {
+        Self { transaction_trace: result.transaction_trace.into(), fee_estimation: result.fee_estimation.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceBlockTransactionsResult {
+    pub transaction_hash: Felt,
+    pub trace_root: TransactionTrace,
+}
+
+impl From<starknet_types_rpc::TraceBlockTransactionsResult<Felt>> for TraceBlockTransactionsResult {
+    fn from(result: starknet_types_rpc::TraceBlockTransactionsResult<Felt>) -> Self {
+        Self { transaction_hash: result.transaction_hash, trace_root: result.trace_root.into() }
+    }
+}
+
+#[versioned_rpc("V0_6_0", "starknet")]
+pub trait StarknetReadRpcApi {
+    /// Get the Version of the StarkNet JSON-RPC Specification Being Used
+    #[method(name = "specVersion")]
+    fn spec_version(&self) -> RpcResult<String>;
+
+    /// Estimate the fee associated with transaction
+    #[method(name = "estimateFee")]
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlagForEstimateFee>,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<FeeEstimate>>;
+
+    /// Estimate the L2 fee of a message sent on L1
+    #[method(name = "estimateMessageFee")]
+    async fn estimate_message_fee(&self, message: MsgFromL1<Felt>, block_id: BlockId) -> RpcResult<FeeEstimate>;
+
+    /// Get block information with transaction hashes given the block id
+    #[method(name = "getBlockWithTxHashes")]
+    fn get_block_with_tx_hashes(&self, block_id: BlockId) -> RpcResult<MaybePendingBlockWithTxHashes>;
+
+    /// Get block information with full transactions given the block id
+    #[method(name = "getBlockWithTxs")]
+    fn get_block_with_txs(&self, block_id: BlockId) -> RpcResult<MaybePendingBlockWithTxs>;
+
+    /// Returns the receipt of a transaction by transaction hash.
+    #[method(name = "getTransactionReceipt")]
+    async fn get_transaction_receipt(&self, transaction_hash: Felt) -> RpcResult<TxnReceiptWithBlockInfo>;
+}
+
+#[versioned_rpc("V0_6_0", "starknet")]
+pub trait StarknetTraceRpcApi {
+    /// Returns the execution trace of a transaction by simulating it in the runtime.
+    #[method(name = "simulateTransactions")]
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlag>,
+    ) -> RpcResult<Vec<SimulateTransactionsResult>>;
+
+    #[method(name = "traceBlockTransactions")]
+    /// Returns the execution traces of all transactions included in the given block
+    async fn trace_block_transactions(&self, block_id: BlockId) -> RpcResult<Vec<TraceBlockTransactionsResult>>;
+
+    #[method(name = "traceTransaction")]
+    /// Returns the execution trace of a transaction
+    async fn trace_transaction(&self, transaction_hash: Felt) -> RpcResult<TransactionTrace>;
+}
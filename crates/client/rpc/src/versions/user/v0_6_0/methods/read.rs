@@ -0,0 +1,134 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use mp_block::BlockId;
+use mp_chain_config::RpcVersion;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{BroadcastedTxn, MsgFromL1, SimulationFlagForEstimateFee};
+
+use crate::versions::user::v0_6_0::{
+    FeeEstimate, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, StarknetReadRpcApiV0_6_0Server,
+    TxnReceiptWithBlockInfo,
+};
+use crate::versions::user::v0_7_1::methods::read::{
+    estimate_fee::estimate_fee, estimate_message_fee::estimate_message_fee,
+    get_block_with_tx_hashes::get_block_with_tx_hashes, get_block_with_txs::get_block_with_txs,
+    get_transaction_receipt::get_transaction_receipt,
+};
+use crate::Starknet;
+
+#[async_trait]
+impl StarknetReadRpcApiV0_6_0Server for Starknet {
+    fn spec_version(&self) -> RpcResult<String> {
+        Ok(RpcVersion::RPC_VERSION_0_6_0.to_string())
+    }
+
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlagForEstimateFee>,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<FeeEstimate>> {
+        let estimates = estimate_fee(self, request, simulation_flags, block_id, None).await?;
+        Ok(estimates.into_iter().map(FeeEstimate::from).collect())
+    }
+
+    async fn estimate_message_fee(&self, message: MsgFromL1<Felt>, block_id: BlockId) -> RpcResult<FeeEstimate> {
+        Ok(estimate_message_fee(self, message, block_id).await?.into())
+    }
+
+    fn get_block_with_tx_hashes(&self, block_id: BlockId) -> RpcResult<MaybePendingBlockWithTxHashes> {
+        Ok(get_block_with_tx_hashes(self, block_id)?.into())
+    }
+
+    fn get_block_with_txs(&self, block_id: BlockId) -> RpcResult<MaybePendingBlockWithTxs> {
+        Ok(get_block_with_txs(self, block_id)?.into())
+    }
+
+    async fn get_transaction_receipt(&self, transaction_hash: Felt) -> RpcResult<TxnReceiptWithBlockInfo> {
+        Ok(get_transaction_receipt(self, transaction_hash)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        devnet_rpc_test_setup, devnet_transfer, sample_chain_for_block_getters, SampleChainForBlockGetters,
+    };
+    use jsonrpsee::rpc_params;
+    use mc_devnet::DevnetKeys;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_v0_6_schemas(sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet)) {
+        let (SampleChainForBlockGetters { block_hashes, tx_hashes, .. }, rpc) = sample_chain_for_block_getters;
+        let rpc_api = StarknetReadRpcApiV0_6_0Server::into_rpc(rpc);
+
+        let block: Value = rpc_api
+            .call("starknet_V0_6_0_getBlockWithTxHashes", rpc_params![json!({ "block_number": 0 })])
+            .await
+            .unwrap();
+        assert_eq!(block["block_hash"], serde_json::to_value(block_hashes[0]).unwrap());
+        assert!(block.get("l1_gas_price").is_some());
+        assert!(block.get("l1_data_gas_price").is_none());
+        assert!(block.get("l1_da_mode").is_none());
+
+        let block: Value =
+            rpc_api.call("starknet_V0_6_0_getBlockWithTxs", rpc_params![json!({ "block_number": 0 })]).await.unwrap();
+        assert_eq!(block["transactions"][0]["transaction_hash"], serde_json::to_value(tx_hashes[0]).unwrap());
+        assert!(block.get("l1_data_gas_price").is_none());
+
+        let receipt: Value =
+            rpc_api.call("starknet_V0_6_0_getTransactionReceipt", rpc_params![tx_hashes[2]]).await.unwrap();
+        assert_eq!(receipt["type"], "INVOKE");
+        assert_eq!(receipt["execution_status"], "REVERTED");
+        assert_eq!(receipt["revert_reason"], "too bad");
+        assert!(receipt["execution_resources"].get("steps").is_some());
+        assert!(receipt["execution_resources"].get("data_availability").is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_v0_6_methods(sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet)) {
+        let (_, rpc) = sample_chain_for_block_getters;
+        let rpc_api = crate::rpc_api_user(&rpc).unwrap();
+
+        for method in ["estimateFee", "getBlockWithTxHashes", "getTransactionReceipt", "simulateTransactions", "call"] {
+            assert!(rpc_api.method_names().any(|name| name == format!("starknet_V0_6_0_{method}")), "{method}");
+        }
+        // Added in v0.7.
+        assert!(!rpc_api.method_names().any(|name| name == "starknet_V0_6_0_getBlockWithReceipts"));
+        assert!(rpc_api
+            .call::<_, Value>("starknet_V0_6_0_getBlockWithReceipts", rpc_params![json!({ "block_number": 0 })])
+            .await
+            .is_err());
+        let _: Value = rpc_api
+            .call("starknet_V0_7_1_getBlockWithReceipts", rpc_params![json!({ "block_number": 0 })])
+            .await
+            .unwrap();
+
+        let document: Value = rpc_api.call("rpc_V0_6_0_discover", rpc_params![]).await.unwrap();
+        let methods = document["methods"].as_array().unwrap();
+        assert!(methods.iter().any(|method| method["name"] == "starknet_estimateFee"));
+        assert!(!methods.iter().any(|method| method["name"] == "starknet_getBlockWithReceipts"));
+    }
+
+    #[rstest]
+    fn test_v0_6_estimate_fee(devnet_rpc_test_setup: (DevnetKeys, Starknet)) {
+        let (contracts, rpc) = devnet_rpc_test_setup;
+        let transfer = devnet_transfer(&contracts, &rpc);
+        let rpc_api = StarknetReadRpcApiV0_6_0Server::into_rpc(rpc);
+
+        let estimates: Value = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(rpc_api.call("starknet_V0_6_0_estimateFee", rpc_params![[transfer], ["SKIP_VALIDATE"], "latest"]))
+            .unwrap();
+        let estimate = estimates[0].as_object().unwrap();
+        assert_eq!(
+            estimate.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["gas_consumed", "gas_price", "overall_fee", "unit"]
+        );
+        assert_eq!(estimate["unit"], "FRI");
+    }
+}
@@ -0,0 +1,162 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use mp_block::BlockId;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{BroadcastedTxn, SimulationFlag};
+
+use crate::versions::user::v0_6_0::{
+    SimulateTransactionsResult, StarknetTraceRpcApiV0_6_0Server, TraceBlockTransactionsResult, TransactionTrace,
+};
+use crate::versions::user::v0_7_1::methods::trace::{
+    simulate_transactions::simulate_transactions, trace_block_transactions::trace_block_transactions,
+    trace_transaction::trace_transaction,
+};
+use crate::Starknet;
+
+#[async_trait]
+impl StarknetTraceRpcApiV0_6_0Server for Starknet {
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlag>,
+    ) -> RpcResult<Vec<SimulateTransactionsResult>> {
+        let results = simulate_transactions(self, block_id, transactions, simulation_flags, None).await?;
+        Ok(results.into_iter().map(SimulateTransactionsResult::from).collect())
+    }
+
+    async fn trace_block_transactions(&self, block_id: BlockId) -> RpcResult<Vec<TraceBlockTransactionsResult>> {
+        let traces = trace_block_transactions(self, block_id).await?;
+        Ok(traces.into_iter().map(TraceBlockTransactionsResult::from).collect())
+    }
+
+    async fn trace_transaction(&self, transaction_hash: Felt) -> RpcResult<TransactionTrace> {
+        Ok(trace_transaction(self, transaction_hash).await?.trace_root.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{devnet_rpc_test_setup, devnet_transfer};
+    use crate::versions::user::v0_7_1::StarknetTraceRpcApiV0_7_1Server;
+    use jsonrpsee::rpc_params;
+    use mc_devnet::DevnetKeys;
+    use mp_block::BlockTag;
+    use rstest::rstest;
+    use serde_json::Value;
+
+    #[rstest]
+    fn test_v0_6_simulate_transactions(devnet_rpc_test_setup: (DevnetKeys, Starknet)) {
+        let (contracts, rpc) = devnet_rpc_test_setup;
+        let transfer = devnet_transfer(&contracts, &rpc);
+        let rpc_api = StarknetTraceRpcApiV0_6_0Server::into_rpc(rpc);
+
+        let results: Value = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(
+                rpc_api
+                    .call("starknet_V0_6_0_simulateTransactions", rpc_params!["latest", [transfer], ["SKIP_VALIDATE"]]),
+            )
+            .unwrap();
+        let result = &results[0];
+        assert!(result["fee_estimation"].get("data_gas_consumed").is_none());
+        assert!(result["fee_estimation"].get("data_gas_price").is_none());
+        assert!(result["fee_estimation"].get("overall_fee").is_some());
+
+        let trace = &result["transaction_trace"];
+        assert_eq!(trace["type"], "INVOKE");
+        assert!(trace.get("execution_resources").is_none());
+        assert!(trace.get("validate_invocation").is_none(), "validation was skipped");
+        assert!(trace["execute_invocation"].get("function_call").is_some());
+        assert!(trace["fee_transfer_invocation"].get("function_call").is_some());
+    }
+
+    #[rstest]
+    fn test_v0_6_trace_mappings(devnet_rpc_test_setup: (DevnetKeys, Starknet)) {
+        let (contracts, rpc) = devnet_rpc_test_setup;
+        let transfer = devnet_transfer(&contracts, &rpc);
+        let results = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(StarknetTraceRpcApiV0_7_1Server::simulate_transactions(
+                &rpc,
+                BlockId::Tag(BlockTag::Latest),
+                vec![transfer],
+                vec![SimulationFlag::SkipValidate],
+            ))
+            .unwrap();
+        let starknet_types_rpc::TransactionTrace::Invoke(invoke) = results[0].transaction_trace.clone() else {
+            unreachable!("the transaction is an invoke")
+        };
+        let starknet_types_rpc::ExecuteInvocation::FunctionInvocation(invocation) = invoke.execute_invocation.clone()
+        else {
+            unreachable!("the transaction is not reverted")
+        };
+
+        // Builds a v0.7 trace of each type out of the invocations of the transfer.
+        let traces = [
+            ("INVOKE", starknet_types_rpc::TransactionTrace::Invoke(invoke.clone())),
+            (
+                "DECLARE",
+                starknet_types_rpc::TransactionTrace::Declare(starknet_types_rpc::DeclareTransactionTrace {
+                    validate_invocation: Some(invocation.clone()),
+                    fee_transfer_invocation: invoke.fee_transfer_invocation.clone(),
+                    state_diff: invoke.state_diff.clone(),
+                    execution_resources: invoke.execution_resources.clone(),
+                }),
+            ),
+            (
+                "DEPLOY_ACCOUNT",
+                starknet_types_rpc::TransactionTrace::DeployAccount(
+                    starknet_types_rpc::DeployAccountTransactionTrace {
+                        validate_invocation: Some(invocation.clone()),
+                        constructor_invocation: invocation.clone(),
+                        fee_transfer_invocation: invoke.fee_transfer_invocation.clone(),
+                        state_diff: invoke.state_diff.clone(),
+                        execution_resources: invoke.execution_resources.clone(),
+                    },
+                ),
+            ),
+            (
+                "L1_HANDLER",
+                starknet_types_rpc::TransactionTrace::L1Handler(starknet_types_rpc::L1HandlerTransactionTrace {
+                    function_invocation: invocation.clone(),
+                    state_diff: invoke.state_diff.clone(),
+                    execution_resources: invoke.execution_resources.clone(),
+                }),
+            ),
+        ];
+
+        let invocation = serde_json::to_value(&invocation).unwrap();
+        for (trace_type, trace) in traces {
+            let v0_7 = serde_json::to_value(&trace).unwrap();
+            assert!(v0_7.get("execution_resources").is_some());
+
+            let v0_6 = serde_json::to_value(TransactionTrace::from(trace)).unwrap();
+            assert_eq!(v0_6["type"], trace_type);
+            assert!(v0_6.get("execution_resources").is_none(), "{trace_type} trace");
+
+            // Every other field is kept as is, absent fields are skipped instead of null.
+            let mut expected = v0_7.as_object().unwrap().clone();
+            expected.remove("execution_resources");
+            expected.retain(|_, value| !value.is_null());
+            assert_eq!(v0_6, Value::Object(expected), "{trace_type} trace");
+            let invocation_field = match trace_type {
+                "INVOKE" => "execute_invocation",
+                "DECLARE" => "validate_invocation",
+                "DEPLOY_ACCOUNT" => "constructor_invocation",
+                _ => "function_invocation",
+            };
+            assert_eq!(v0_6[invocation_field], invocation, "{trace_type} trace");
+        }
+
+        let block_result = serde_json::to_value(TraceBlockTransactionsResult::from(
+            starknet_types_rpc::TraceBlockTransactionsResult {
+                transaction_hash: Felt::ONE,
+                trace_root: results[0].transaction_trace.clone(),
+            },
+        ))
+        .unwrap();
+        assert_eq!(block_result["transaction_hash"], "0x1");
+        assert!(block_result["trace_root"].get("execution_resources").is_none());
+    }
+}
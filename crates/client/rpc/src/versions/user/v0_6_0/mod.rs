@@ -0,0 +1,4 @@
+pub mod api;
+pub mod methods;
+
+pub use api::*;
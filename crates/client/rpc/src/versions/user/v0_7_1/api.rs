@@ -1,14 +1,15 @@
 use jsonrpsee::core::RpcResult;
 use m_proc_macros::versioned_rpc;
 use mp_block::BlockId;
+use serde::{Deserialize, Serialize};
 use starknet_types_core::felt::Felt;
 use starknet_types_rpc::{
     AddInvokeTransactionResult, BlockHashAndNumber, BroadcastedDeclareTxn, BroadcastedDeployAccountTxn,
     BroadcastedInvokeTxn, BroadcastedTxn, ClassAndTxnHash, ContractAndTxnHash, EventFilterWithPageRequest, EventsChunk,
     FeeEstimate, FunctionCall, MaybeDeprecatedContractClass, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
-    MaybePendingStateUpdate, MsgFromL1, SimulateTransactionsResult, SimulationFlag, SimulationFlagForEstimateFee,
-    StarknetGetBlockWithTxsAndReceiptsResult, SyncingStatus, TraceBlockTransactionsResult,
-    TxnFinalityAndExecutionStatus, TxnReceiptWithBlockInfo, TxnWithHash,
+    MaybePendingStateUpdate, MsgFromL1, ResourcePrice, SimulateTransactionsResult, SimulationFlag,
+    SimulationFlagForEstimateFee, StarknetGetBlockWithTxsAndReceiptsResult, SyncingStatus,
+    TraceBlockTransactionsResult, TxnFinalityAndExecutionStatus, TxnReceiptWithBlockInfo, TxnWithHash,
 };
 
 // Starknet RPC API trait and types
@@ -17,24 +18,68 @@ use starknet_types_rpc::{
 // using the openRPC specification.
 // This crate uses `jsonrpsee` to define such an API in Rust terms.
 
+/// State and block context overrides accepted by the `madara_*WithOverrides` variants of `starknet_call`,
+/// `starknet_estimateFee` and `starknet_simulateTransactions`. They are layered over the state of the requested block
+/// and are never persisted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateOverride {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contracts: Vec<ContractOverride>,
+    /// Classes declared on top of the state. Contracts can be overridden to one of them with their class hash.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub declared_classes: Vec<MaybeDeprecatedContractClass<Felt>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<BlockOverride>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractOverride {
+    pub address: Felt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<Felt>,
+    /// Replaces the class of the contract, or deploys it if there is no contract at this address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class_hash: Option<Felt>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub storage: Vec<StorageOverride>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageOverride {
+    pub key: Felt,
+    pub value: Felt,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequencer_address: Option<Felt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_gas_price: Option<ResourcePrice<Felt>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_data_gas_price: Option<ResourcePrice<Felt>>,
+}
+
 #[versioned_rpc("V0_7_1", "starknet")]
 pub trait StarknetWriteRpcApi {
     /// Submit a new transaction to be added to the chain
-    #[method(name = "addInvokeTransaction", and_versions = ["V0_8_0"])]
+    #[method(name = "addInvokeTransaction", and_versions = ["V0_6_0", "V0_8_0"])]
     async fn add_invoke_transaction(
         &self,
         invoke_transaction: BroadcastedInvokeTxn<Felt>,
     ) -> RpcResult<AddInvokeTransactionResult<Felt>>;
 
     /// Submit a new deploy account transaction
-    #[method(name = "addDeployAccountTransaction", and_versions = ["V0_8_0"])]
+    #[method(name = "addDeployAccountTransaction", and_versions = ["V0_6_0", "V0_8_0"])]
     async fn add_deploy_account_transaction(
         &self,
         deploy_account_transaction: BroadcastedDeployAccountTxn<Felt>,
     ) -> RpcResult<ContractAndTxnHash<Felt>>;
 
     /// Submit a new class declaration transaction
-    #[method(name = "addDeclareTransaction", and_versions = ["V0_8_0"])]
+    #[method(name = "addDeclareTransaction", and_versions = ["V0_6_0", "V0_8_0"])]
     async fn add_declare_transaction(
         &self,
         declare_transaction: BroadcastedDeclareTxn<Felt>,
@@ -48,23 +93,23 @@ pub trait StarknetReadRpcApi {
     fn spec_version(&self) -> RpcResult<String>;
 
     /// Get the most recent accepted block number
-    #[method(name = "blockNumber", and_versions = ["V0_8_0"])]
+    #[method(name = "blockNumber", and_versions = ["V0_6_0", "V0_8_0"])]
     fn block_number(&self) -> RpcResult<u64>;
 
     // Get the most recent accepted block hash and number
-    #[method(name = "blockHashAndNumber", and_versions = ["V0_8_0"])]
+    #[method(name = "blockHashAndNumber", and_versions = ["V0_6_0", "V0_8_0"])]
     fn block_hash_and_number(&self) -> RpcResult<BlockHashAndNumber<Felt>>;
 
     /// Call a contract function at a given block id
-    #[method(name = "call", and_versions = ["V0_8_0"])]
+    #[method(name = "call", and_versions = ["V0_6_0", "V0_8_0"])]
     fn call(&self, request: FunctionCall<Felt>, block_id: BlockId) -> RpcResult<Vec<Felt>>;
 
     /// Get the chain id
-    #[method(name = "chainId", and_versions = ["V0_8_0"])]
+    #[method(name = "chainId", and_versions = ["V0_6_0", "V0_8_0"])]
     fn chain_id(&self) -> RpcResult<Felt>;
 
     /// Get the number of transactions in a block given a block id
-    #[method(name = "getBlockTransactionCount", and_versions = ["V0_8_0"])]
+    #[method(name = "getBlockTransactionCount", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_block_transaction_count(&self, block_id: BlockId) -> RpcResult<u128>;
 
     /// Estimate the fee associated with transaction
@@ -96,36 +141,36 @@ pub trait StarknetReadRpcApi {
     fn get_block_with_txs(&self, block_id: BlockId) -> RpcResult<MaybePendingBlockWithTxs<Felt>>;
 
     /// Get the contract class at a given contract address for a given block id
-    #[method(name = "getClassAt", and_versions = ["V0_8_0"])]
+    #[method(name = "getClassAt", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_class_at(&self, block_id: BlockId, contract_address: Felt) -> RpcResult<MaybeDeprecatedContractClass<Felt>>;
 
     /// Get the contract class hash in the given block for the contract deployed at the given
     /// address
-    #[method(name = "getClassHashAt", and_versions = ["V0_8_0"])]
+    #[method(name = "getClassHashAt", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_class_hash_at(&self, block_id: BlockId, contract_address: Felt) -> RpcResult<Felt>;
 
     /// Get the contract class definition in the given block associated with the given hash
-    #[method(name = "getClass", and_versions = ["V0_8_0"])]
+    #[method(name = "getClass", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_class(&self, block_id: BlockId, class_hash: Felt) -> RpcResult<MaybeDeprecatedContractClass<Felt>>;
 
     /// Returns all events matching the given filter
-    #[method(name = "getEvents", and_versions = ["V0_8_0"])]
+    #[method(name = "getEvents", and_versions = ["V0_6_0", "V0_8_0"])]
     async fn get_events(&self, filter: EventFilterWithPageRequest<Felt>) -> RpcResult<EventsChunk<Felt>>;
 
     /// Get the nonce associated with the given address at the given block
-    #[method(name = "getNonce", and_versions = ["V0_8_0"])]
+    #[method(name = "getNonce", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_nonce(&self, block_id: BlockId, contract_address: Felt) -> RpcResult<Felt>;
 
     /// Get the value of the storage at the given address and key, at the given block id
-    #[method(name = "getStorageAt", and_versions = ["V0_8_0"])]
+    #[method(name = "getStorageAt", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_storage_at(&self, contract_address: Felt, key: Felt, block_id: BlockId) -> RpcResult<Felt>;
 
     /// Get the details of a transaction by a given block id and index
-    #[method(name = "getTransactionByBlockIdAndIndex", and_versions = ["V0_8_0"])]
+    #[method(name = "getTransactionByBlockIdAndIndex", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_transaction_by_block_id_and_index(&self, block_id: BlockId, index: u64) -> RpcResult<TxnWithHash<Felt>>;
 
     /// Returns the information about a transaction by transaction hash.
-    #[method(name = "getTransactionByHash", and_versions = ["V0_8_0"])]
+    #[method(name = "getTransactionByHash", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_transaction_by_hash(&self, transaction_hash: Felt) -> RpcResult<TxnWithHash<Felt>>;
 
     /// Returns the receipt of a transaction by transaction hash.
@@ -133,15 +178,15 @@ pub trait StarknetReadRpcApi {
     async fn get_transaction_receipt(&self, transaction_hash: Felt) -> RpcResult<TxnReceiptWithBlockInfo<Felt>>;
 
     /// Gets the Transaction Status, Including Mempool Status and Execution Details
-    #[method(name = "getTransactionStatus", and_versions = ["V0_8_0"])]
+    #[method(name = "getTransactionStatus", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_transaction_status(&self, transaction_hash: Felt) -> RpcResult<TxnFinalityAndExecutionStatus>;
 
     /// Get an object about the sync status, or false if the node is not syncing
-    #[method(name = "syncing", and_versions = ["V0_8_0"])]
+    #[method(name = "syncing", and_versions = ["V0_6_0", "V0_8_0"])]
     async fn syncing(&self) -> RpcResult<SyncingStatus<Felt>>;
 
     /// Get the information about the result of executing the requested block
-    #[method(name = "getStateUpdate", and_versions = ["V0_8_0"])]
+    #[method(name = "getStateUpdate", and_versions = ["V0_6_0", "V0_8_0"])]
     fn get_state_update(&self, block_id: BlockId) -> RpcResult<MaybePendingStateUpdate<Felt>>;
 }
 
@@ -164,3 +209,37 @@ pub trait StarknetTraceRpcApi {
     /// Returns the execution trace of a transaction
     async fn trace_transaction(&self, transaction_hash: Felt) -> RpcResult<TraceBlockTransactionsResult<Felt>>;
 }
+
+/// Madara extensions of `starknet_call`, `starknet_estimateFee` and `starknet_simulateTransactions`, executed on top of
+/// a [`StateOverride`].
+#[versioned_rpc("V0_7_1", "madara")]
+pub trait MadaraStateOverrideRpcApi {
+    /// Call a contract function at a given block id, on top of a state override
+    #[method(name = "callWithOverrides", and_versions = ["V0_8_0"])]
+    fn call_with_overrides(
+        &self,
+        request: FunctionCall<Felt>,
+        block_id: BlockId,
+        state_override: StateOverride,
+    ) -> RpcResult<Vec<Felt>>;
+
+    /// Estimate the fee associated with transaction, on top of a state override
+    #[method(name = "estimateFeeWithOverrides", and_versions = ["V0_8_0"])]
+    async fn estimate_fee_with_overrides(
+        &self,
+        request: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlagForEstimateFee>,
+        block_id: BlockId,
+        state_override: StateOverride,
+    ) -> RpcResult<Vec<FeeEstimate<Felt>>>;
+
+    /// Returns the execution trace of a transaction by simulating it on top of a state override
+    #[method(name = "simulateTransactionsWithOverrides", and_versions = ["V0_8_0"])]
+    async fn simulate_transactions_with_overrides(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlag>,
+        state_override: StateOverride,
+    ) -> RpcResult<Vec<SimulateTransactionsResult<Felt>>>;
+}
@@ -1,3 +1,4 @@
+pub mod overrides;
 pub mod read;
 pub mod trace;
 pub mod write;
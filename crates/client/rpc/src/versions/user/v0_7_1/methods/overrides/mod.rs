@@ -0,0 +1,122 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use mp_block::BlockId;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{
+    BroadcastedTxn, FeeEstimate, FunctionCall, SimulateTransactionsResult, SimulationFlag, SimulationFlagForEstimateFee,
+};
+
+use super::read::call::call;
+use super::read::estimate_fee::estimate_fee;
+use super::trace::simulate_transactions::simulate_transactions;
+use crate::versions::user::v0_7_1::{MadaraStateOverrideRpcApiV0_7_1Server, StateOverride};
+use crate::Starknet;
+
+#[async_trait]
+impl MadaraStateOverrideRpcApiV0_7_1Server for Starknet {
+    fn call_with_overrides(
+        &self,
+        request: FunctionCall<Felt>,
+        block_id: BlockId,
+        state_override: StateOverride,
+    ) -> RpcResult<Vec<Felt>> {
+        Ok(call(self, request, block_id, Some(state_override))?)
+    }
+
+    async fn estimate_fee_with_overrides(
+        &self,
+        request: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlagForEstimateFee>,
+        block_id: BlockId,
+        state_override: StateOverride,
+    ) -> RpcResult<Vec<FeeEstimate<Felt>>> {
+        Ok(estimate_fee(self, request, simulation_flags, block_id, Some(state_override)).await?)
+    }
+
+    async fn simulate_transactions_with_overrides(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTxn<Felt>>,
+        simulation_flags: Vec<SimulationFlag>,
+        state_override: StateOverride,
+    ) -> RpcResult<Vec<SimulateTransactionsResult<Felt>>> {
+        Ok(simulate_transactions(self, block_id, transactions, simulation_flags, Some(state_override)).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{devnet_rpc_test_setup, devnet_transfer};
+    use crate::versions::user::v0_7_1::{
+        BlockOverride, ContractOverride, StarknetReadRpcApiV0_7_1Server, StorageOverride,
+    };
+    use blockifier::abi::abi_utils::get_fee_token_var_address;
+    use mc_devnet::{DevnetKeys, Selector};
+    use mp_block::BlockTag;
+    use mp_convert::ToFelt;
+    use rstest::rstest;
+    use starknet_types_rpc::ResourcePrice;
+
+    fn balance_of(rpc: &Starknet, address: Felt) -> FunctionCall<Felt> {
+        FunctionCall {
+            contract_address: rpc.backend.chain_config().native_fee_token_address.to_felt(),
+            entry_point_selector: Selector::from("balanceOf").into(),
+            calldata: vec![address],
+        }
+    }
+
+    #[rstest]
+    fn test_call_with_overrides(devnet_rpc_test_setup: (DevnetKeys, Starknet)) {
+        let (contracts, rpc) = devnet_rpc_test_setup;
+        let account = &contracts.0[0];
+        let block_id = BlockId::Tag(BlockTag::Latest);
+
+        assert_eq!(
+            rpc.call(balance_of(&rpc, account.address), block_id.clone()).unwrap(),
+            [account.balance.fri, Felt::ZERO]
+        );
+
+        let state_override = StateOverride {
+            contracts: vec![ContractOverride {
+                address: rpc.backend.chain_config().native_fee_token_address.to_felt(),
+                nonce: None,
+                class_hash: None,
+                storage: vec![StorageOverride {
+                    key: get_fee_token_var_address(account.address.try_into().unwrap()).to_felt(),
+                    value: Felt::from(42),
+                }],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            rpc.call_with_overrides(balance_of(&rpc, account.address), block_id.clone(), state_override).unwrap(),
+            [Felt::from(42), Felt::ZERO]
+        );
+        // The override is not persisted.
+        assert_eq!(rpc.call(balance_of(&rpc, account.address), block_id).unwrap(), [account.balance.fri, Felt::ZERO]);
+    }
+
+    #[rstest]
+    fn test_estimate_fee_with_overrides(devnet_rpc_test_setup: (DevnetKeys, Starknet)) {
+        let (contracts, rpc) = devnet_rpc_test_setup;
+        let transfer = devnet_transfer(&contracts, &rpc);
+        let state_override = StateOverride {
+            block: Some(BlockOverride {
+                l1_gas_price: Some(ResourcePrice { price_in_wei: Felt::from(12345), price_in_fri: Felt::from(12345) }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let flags = vec![SimulationFlagForEstimateFee::SkipValidate];
+        let block_id = BlockId::Tag(BlockTag::Latest);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let estimate = rt.block_on(rpc.estimate_fee(vec![transfer.clone()], flags.clone(), block_id.clone())).unwrap();
+        let estimate_with_overrides =
+            rt.block_on(rpc.estimate_fee_with_overrides(vec![transfer], flags, block_id, state_override)).unwrap();
+
+        assert_eq!(estimate_with_overrides[0].gas_price, Felt::from(12345));
+        assert_eq!(estimate_with_overrides[0].gas_consumed, estimate[0].gas_consumed);
+        assert_ne!(estimate_with_overrides[0].overall_fee, estimate[0].overall_fee);
+    }
+}
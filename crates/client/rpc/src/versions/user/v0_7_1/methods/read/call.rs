@@ -1,13 +1,12 @@
-use std::sync::Arc;
-
-use mc_exec::ExecutionContext;
 use mp_block::BlockId;
 use starknet_types_core::felt::Felt;
 use starknet_types_rpc::FunctionCall;
 
 use crate::errors::StarknetRpcApiError;
 use crate::errors::StarknetRpcResult;
+use crate::versions::user::v0_7_1::methods::read::state_override::execution_context;
 use crate::versions::user::v0_7_1::methods::trace::trace_transaction::FALLBACK_TO_SEQUENCER_WHEN_VERSION_BELOW;
+use crate::versions::user::v0_7_1::StateOverride;
 use crate::Starknet;
 
 /// Call a Function in a Contract Without Creating a Transaction
@@ -18,6 +17,8 @@ use crate::Starknet;
 ///   contract address, function signature, and arguments.
 /// * `block_id` - The identifier of the block used to reference the state or call the transaction
 ///   on. This can be the hash of the block, its number (height), or a specific block tag.
+/// * `state_override` - Storage values, nonces, classes and block context fields to use instead of the ones of the
+///   block. They are never persisted.
 ///
 /// ### Returns
 ///
@@ -30,10 +31,15 @@ use crate::Starknet;
 /// * `CONTRACT_NOT_FOUND` - If the specified contract address does not exist.
 /// * `CONTRACT_ERROR` - If there is an error with the contract or the function call.
 /// * `BLOCK_NOT_FOUND` - If the specified block does not exist in the blockchain.
-pub fn call(starknet: &Starknet, request: FunctionCall<Felt>, block_id: BlockId) -> StarknetRpcResult<Vec<Felt>> {
+pub fn call(
+    starknet: &Starknet,
+    request: FunctionCall<Felt>,
+    block_id: BlockId,
+    state_override: Option<StateOverride>,
+) -> StarknetRpcResult<Vec<Felt>> {
     let block_info = starknet.get_block_info(&block_id)?;
 
-    let exec_context = ExecutionContext::new_in_block(Arc::clone(&starknet.backend), &block_info)?;
+    let exec_context = execution_context(starknet, &block_info, state_override)?;
 
     if block_info.protocol_version() < &FALLBACK_TO_SEQUENCER_WHEN_VERSION_BELOW {
         return Err(StarknetRpcApiError::UnsupportedTxnVersion);
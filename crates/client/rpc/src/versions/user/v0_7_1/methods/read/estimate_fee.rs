@@ -1,14 +1,14 @@
 use crate::errors::StarknetRpcApiError;
 use crate::errors::StarknetRpcResult;
 use crate::utils::ResultExt;
+use crate::versions::user::v0_7_1::methods::read::state_override::execution_context;
 use crate::versions::user::v0_7_1::methods::trace::trace_transaction::FALLBACK_TO_SEQUENCER_WHEN_VERSION_BELOW;
+use crate::versions::user::v0_7_1::StateOverride;
 use crate::Starknet;
-use mc_exec::ExecutionContext;
 use mp_block::BlockId;
 use mp_transactions::BroadcastedTransactionExt;
 use starknet_types_core::felt::Felt;
 use starknet_types_rpc::{BroadcastedTxn, FeeEstimate, SimulationFlagForEstimateFee};
-use std::sync::Arc;
 
 /// Estimate the fee associated with transaction
 ///
@@ -16,6 +16,7 @@ use std::sync::Arc;
 ///
 /// * `request` - starknet transaction request
 /// * `block_id` - hash of the requested block, number (height), or tag
+/// * `state_override` - state and block context overrides, which are never persisted
 ///
 /// # Returns
 ///
@@ -25,6 +26,7 @@ pub async fn estimate_fee(
     request: Vec<BroadcastedTxn<Felt>>,
     simulation_flags: Vec<SimulationFlagForEstimateFee>,
     block_id: BlockId,
+    state_override: Option<StateOverride>,
 ) -> StarknetRpcResult<Vec<FeeEstimate<Felt>>> {
     let block_info = starknet.get_block_info(&block_id)?;
     let starknet_version = *block_info.protocol_version();
@@ -33,7 +35,7 @@ pub async fn estimate_fee(
         return Err(StarknetRpcApiError::UnsupportedTxnVersion);
     }
 
-    let exec_context = ExecutionContext::new_in_block(Arc::clone(&starknet.backend), &block_info)?;
+    let exec_context = execution_context(starknet, &block_info, state_override)?;
 
     let transactions = request
         .into_iter()
@@ -19,6 +19,7 @@ use crate::{errors::StarknetRpcResult, Starknet};
 /// the blockchain.
 pub fn get_block_transaction_count(starknet: &Starknet, block_id: BlockId) -> StarknetRpcResult<u128> {
     let block = starknet.get_block_info(&block_id)?;
+    starknet.check_block_body(&block)?;
 
     let tx_count = match block {
         MadaraMaybePendingBlockInfo::Pending(block) => block.tx_hashes.len(),
@@ -199,6 +199,23 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn test_get_block_with_receipts_body_unavailable(
+        sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet),
+    ) {
+        let (SampleChainForBlockGetters { .. }, rpc) = sample_chain_for_block_getters;
+        rpc.clone_backend().mark_block_without_body(2).unwrap();
+
+        assert_eq!(
+            get_block_with_receipts(&rpc, BlockId::Number(2)),
+            Err(StarknetRpcApiError::BlockBodyUnavailable { block_n: 2 })
+        );
+        assert_eq!(
+            get_block_with_receipts(&rpc, BlockId::Tag(BlockTag::Latest)),
+            Err(StarknetRpcApiError::BlockBodyUnavailable { block_n: 2 })
+        );
+    }
+
     #[rstest]
     fn test_get_block_with_receipts_pending_always_present(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
         let (backend, rpc) = rpc_test_setup;
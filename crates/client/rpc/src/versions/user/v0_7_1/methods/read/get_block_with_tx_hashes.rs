@@ -25,6 +25,7 @@ pub fn get_block_with_tx_hashes(
     block_id: BlockId,
 ) -> StarknetRpcResult<MaybePendingBlockWithTxHashes<Felt>> {
     let block = starknet.get_block_info(&block_id)?;
+    starknet.check_block_body(&block)?;
 
     let block_txs_hashes = block.tx_hashes().to_vec();
 
@@ -175,4 +175,18 @@ mod tests {
             Err(StarknetRpcApiError::BlockNotFound.into())
         );
     }
+
+    #[rstest]
+    fn test_get_block_with_txs_body_unavailable(
+        sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet),
+    ) {
+        let (SampleChainForBlockGetters { .. }, rpc) = sample_chain_for_block_getters;
+        rpc.clone_backend().mark_block_without_body(1).unwrap();
+
+        assert_eq!(
+            get_block_with_txs(&rpc, BlockId::Number(1)),
+            Err(StarknetRpcApiError::BlockBodyUnavailable { block_n: 1 }.into())
+        );
+        assert!(get_block_with_txs(&rpc, BlockId::Number(0)).is_ok());
+    }
 }
@@ -103,4 +103,17 @@ mod tests {
             Err(StarknetRpcApiError::InvalidTxnIndex)
         );
     }
+
+    #[rstest]
+    fn test_get_transaction_by_block_id_and_index_body_unavailable(
+        sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet),
+    ) {
+        let (SampleChainForBlockGetters { .. }, rpc) = sample_chain_for_block_getters;
+        rpc.clone_backend().mark_block_without_body(0).unwrap();
+
+        assert_eq!(
+            get_transaction_by_block_id_and_index(&rpc, BlockId::Number(0), 0),
+            Err(StarknetRpcApiError::BlockBodyUnavailable { block_n: 0 })
+        );
+    }
 }
@@ -49,7 +49,7 @@ impl StarknetReadRpcApiV0_7_1Server for Starknet {
     }
 
     fn call(&self, request: FunctionCall<Felt>, block_id: BlockId) -> RpcResult<Vec<Felt>> {
-        Ok(call(self, request, block_id)?)
+        Ok(call(self, request, block_id, None)?)
     }
 
     fn chain_id(&self) -> RpcResult<Felt> {
@@ -66,7 +66,7 @@ impl StarknetReadRpcApiV0_7_1Server for Starknet {
         simulation_flags: Vec<SimulationFlagForEstimateFee>,
         block_id: BlockId,
     ) -> RpcResult<Vec<FeeEstimate<Felt>>> {
-        Ok(estimate_fee(self, request, simulation_flags, block_id).await?)
+        Ok(estimate_fee(self, request, simulation_flags, block_id, None).await?)
     }
 
     async fn estimate_message_fee(&self, message: MsgFromL1<Felt>, block_id: BlockId) -> RpcResult<FeeEstimate<Felt>> {
@@ -18,4 +18,5 @@ pub mod get_transaction_by_hash;
 pub mod get_transaction_receipt;
 pub mod get_transaction_status;
 pub mod lib;
+pub mod state_override;
 pub mod syncing;
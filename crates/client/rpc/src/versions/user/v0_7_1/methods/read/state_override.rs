@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use mc_exec::{BlockOverrides, ExecutionContext, StateOverrideError, StateOverrides};
+use mp_block::MadaraMaybePendingBlockInfo;
+use starknet_types_core::felt::Felt;
+
+use crate::errors::{StarknetRpcApiError, StarknetRpcResult};
+use crate::versions::user::v0_7_1::{BlockOverride, StateOverride};
+use crate::Starknet;
+
+/// Creates the execution context of `starknet_call`, `starknet_estimateFee` and `starknet_simulateTransactions`,
+/// within the requested block and on top of the state override passed to their `madara_*WithOverrides` variants.
+pub fn execution_context(
+    starknet: &Starknet,
+    block_info: &MadaraMaybePendingBlockInfo,
+    state_override: Option<StateOverride>,
+) -> StarknetRpcResult<ExecutionContext> {
+    let Some(state_override) = state_override else {
+        return Ok(ExecutionContext::new_in_block(Arc::clone(&starknet.backend), block_info)?);
+    };
+
+    let block_overrides = state_override.block.map(block_overrides).transpose()?.unwrap_or_default();
+
+    let mut state_overrides = StateOverrides::default();
+    for contract_class in state_override.declared_classes {
+        let contract_class =
+            mp_class::ContractClass::try_from(contract_class).map_err(|_| StarknetRpcApiError::InvalidContractClass)?;
+        state_overrides.declare_class(contract_class).map_err(|err| match err {
+            StateOverrideError::ClassHash(_) => StarknetRpcApiError::InvalidContractClass,
+            StateOverrideError::Compilation(_) => StarknetRpcApiError::CompilationFailed,
+        })?;
+    }
+    for contract in state_override.contracts {
+        if let Some(nonce) = contract.nonce {
+            state_overrides.nonces.insert(contract.address, nonce);
+        }
+        if let Some(class_hash) = contract.class_hash {
+            state_overrides.class_hashes.insert(contract.address, class_hash);
+        }
+        state_overrides
+            .storage
+            .extend(contract.storage.into_iter().map(|entry| ((contract.address, entry.key), entry.value)));
+    }
+
+    Ok(ExecutionContext::new_in_block_with_overrides(Arc::clone(&starknet.backend), block_info, &block_overrides)?
+        .with_state_overrides(state_overrides))
+}
+
+fn block_overrides(block: BlockOverride) -> StarknetRpcResult<BlockOverrides> {
+    let (eth_l1_gas_price, strk_l1_gas_price) = match block.l1_gas_price {
+        Some(price) => (Some(gas_price(price.price_in_wei)?), Some(gas_price(price.price_in_fri)?)),
+        None => (None, None),
+    };
+    let (eth_l1_data_gas_price, strk_l1_data_gas_price) = match block.l1_data_gas_price {
+        Some(price) => (Some(gas_price(price.price_in_wei)?), Some(gas_price(price.price_in_fri)?)),
+        None => (None, None),
+    };
+
+    Ok(BlockOverrides {
+        block_timestamp: block.timestamp,
+        sequencer_address: block.sequencer_address,
+        eth_l1_gas_price,
+        strk_l1_gas_price,
+        eth_l1_data_gas_price,
+        strk_l1_data_gas_price,
+    })
+}
+
+fn gas_price(price: Felt) -> StarknetRpcResult<u128> {
+    price
+        .try_into()
+        .map_err(|_| StarknetRpcApiError::InvalidParams { data: format!("Gas price override {price:#x} is too large") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::devnet_rpc_test_setup;
+    use crate::versions::user::v0_7_1::{ContractOverride, StorageOverride};
+    use mc_devnet::{DevnetKeys, Selector};
+    use mp_block::{BlockId, BlockTag};
+    use rstest::rstest;
+    use serde_json::json;
+    use starknet_core::types::contract::SierraClass;
+
+    #[test]
+    fn parse_state_override() {
+        let state_override: StateOverride = serde_json::from_value(json!({
+            "contracts": [{ "address": "0x1234", "nonce": "0x2", "storage": [{ "key": "0x10", "value": "0x2a" }] }],
+            "block": { "timestamp": 1700000000, "l1_gas_price": { "price_in_wei": "0x64", "price_in_fri": "0xc8" } }
+        }))
+        .unwrap();
+        assert_eq!(state_override.contracts[0].nonce, Some(Felt::TWO));
+        assert_eq!(state_override.contracts[0].class_hash, None);
+        assert!(state_override.declared_classes.is_empty());
+
+        let block_overrides = block_overrides(state_override.block.unwrap()).unwrap();
+        assert_eq!(
+            block_overrides,
+            BlockOverrides {
+                block_timestamp: Some(1700000000),
+                eth_l1_gas_price: Some(100),
+                strk_l1_gas_price: Some(200),
+                ..Default::default()
+            }
+        );
+
+        let too_large = BlockOverride {
+            l1_data_gas_price: Some(starknet_types_rpc::ResourcePrice {
+                price_in_wei: Felt::MAX,
+                price_in_fri: Felt::ONE,
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(block_overrides(too_large), Err(StarknetRpcApiError::InvalidParams { .. })));
+    }
+
+    #[rstest]
+    fn declared_class_override(devnet_rpc_test_setup: (DevnetKeys, Starknet)) {
+        let (_contracts, rpc) = devnet_rpc_test_setup;
+        let sierra_class: SierraClass = serde_json::from_slice(m_cairo_test_contracts::TEST_CONTRACT_SIERRA).unwrap();
+        let class_hash = sierra_class.class_hash().unwrap();
+        let contract_class =
+            mp_class::ContractClass::from(mp_class::FlattenedSierraClass::from(sierra_class.flatten().unwrap()));
+
+        // Deploys the account contract of a class which is only declared by the override.
+        let address = Felt::from_hex_unchecked("0x1234");
+        let state_override = StateOverride {
+            contracts: vec![ContractOverride {
+                address,
+                nonce: None,
+                class_hash: Some(class_hash),
+                storage: vec![StorageOverride {
+                    key: Selector::from("Account_public_key").into(),
+                    value: Felt::from(42),
+                }],
+            }],
+            declared_classes: vec![contract_class.into()],
+            block: None,
+        };
+
+        let block_info = rpc.get_block_info(&BlockId::Tag(BlockTag::Latest)).unwrap();
+        let exec_context = execution_context(&rpc, &block_info, Some(state_override)).unwrap();
+        assert_eq!(
+            exec_context.call_contract(&address, &Selector::from("get_public_key").into(), &[]).unwrap(),
+            [Felt::from(42)]
+        );
+
+        // Without the override, there is no contract at this address.
+        let exec_context = execution_context(&rpc, &block_info, None).unwrap();
+        assert!(exec_context.call_contract(&address, &Selector::from("get_public_key").into(), &[]).is_err());
+    }
+}
@@ -1,19 +1,21 @@
 use super::trace_transaction::FALLBACK_TO_SEQUENCER_WHEN_VERSION_BELOW;
 use crate::errors::{StarknetRpcApiError, StarknetRpcResult};
 use crate::utils::ResultExt;
+use crate::versions::user::v0_7_1::methods::read::state_override::execution_context;
+use crate::versions::user::v0_7_1::StateOverride;
 use crate::Starknet;
-use mc_exec::{execution_result_to_tx_trace, ExecutionContext};
+use mc_exec::execution_result_to_tx_trace;
 use mp_block::BlockId;
 use mp_transactions::BroadcastedTransactionExt;
 use starknet_types_core::felt::Felt;
 use starknet_types_rpc::{BroadcastedTxn, SimulateTransactionsResult, SimulationFlag};
-use std::sync::Arc;
 
 pub async fn simulate_transactions(
     starknet: &Starknet,
     block_id: BlockId,
     transactions: Vec<BroadcastedTxn<Felt>>,
     simulation_flags: Vec<SimulationFlag>,
+    state_override: Option<StateOverride>,
 ) -> StarknetRpcResult<Vec<SimulateTransactionsResult<Felt>>> {
     let block_info = starknet.get_block_info(&block_id)?;
     let starknet_version = *block_info.protocol_version();
@@ -21,7 +23,7 @@ pub async fn simulate_transactions(
     if starknet_version < FALLBACK_TO_SEQUENCER_WHEN_VERSION_BELOW {
         return Err(StarknetRpcApiError::UnsupportedTxnVersion);
     }
-    let exec_context = ExecutionContext::new_in_block(Arc::clone(&starknet.backend), &block_info)?;
+    let exec_context = execution_context(starknet, &block_info, state_override)?;
 
     let charge_fee = !simulation_flags.contains(&SimulationFlag::SkipFeeCharge);
     let validate = !simulation_flags.contains(&SimulationFlag::SkipValidate);
@@ -22,6 +22,21 @@ pub async fn trace_block_transactions(
         return Err(StarknetRpcApiError::UnsupportedTxnVersion);
     }
 
+    if let Some(block_info) = block.info.as_nonpending() {
+        let cached: Option<Vec<_>> = block_info
+            .tx_hashes
+            .iter()
+            .enumerate()
+            .map(|(tx_index, transaction_hash)| {
+                let trace_root = starknet.cached_trace(block_info, tx_index as u64)?;
+                Some(TraceBlockTransactionsResult { trace_root, transaction_hash: *transaction_hash })
+            })
+            .collect();
+        if let Some(traces) = cached {
+            return Ok(traces);
+        }
+    }
+
     let exec_context = ExecutionContext::new_in_block(Arc::clone(&starknet.backend), &block.info)?;
 
     let transactions: Vec<_> = block
@@ -47,5 +62,9 @@ pub async fn trace_block_transactions(
         })
         .collect::<Result<Vec<_>, StarknetRpcApiError>>()?;
 
+    if let Some(block_info) = block.info.as_nonpending() {
+        starknet.cache_traces(block_info, traces.iter().map(|trace| &trace.trace_root));
+    }
+
     Ok(traces)
 }
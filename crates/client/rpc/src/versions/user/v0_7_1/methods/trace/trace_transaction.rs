@@ -28,6 +28,11 @@ pub async fn trace_transaction(
         return Err(StarknetRpcApiError::UnsupportedTxnVersion);
     }
 
+    let block_info = block.info.as_nonpending();
+    if let Some(trace) = block_info.and_then(|block_info| starknet.cached_trace(block_info, tx_index.0)) {
+        return Ok(TraceBlockTransactionsResult { transaction_hash, trace_root: trace });
+    }
+
     let exec_context = ExecutionContext::new_in_block(Arc::clone(&starknet.backend), &block.info)?;
 
     let mut block_txs =
@@ -36,20 +41,31 @@ pub async fn trace_transaction(
                 .or_internal_server_error("Failed to convert transaction to blockifier format")
         });
 
-    // takes up until not including last tx
-    let transactions_before: Vec<_> = block_txs.by_ref().take(tx_index.0 as usize).collect::<Result<_, _>>()?;
-    // the one we're interested in comes next in the iterator
-    let transaction =
-        block_txs.next().ok_or_internal_server_error("There should be at least one transaction in the block")??;
+    // The transactions before this one are executed anyway: when the block is closed, trace them too so that they
+    // are cached.
+    let cached_block_info = block_info.filter(|_| starknet.trace_cache.is_some());
+    let traced_from = if cached_block_info.is_some() { 0 } else { tx_index.0 as usize };
 
-    let mut executions_results =
-        exec_context.re_execute_transactions(transactions_before, [transaction], true, true)?;
+    let transactions_before: Vec<_> = block_txs.by_ref().take(traced_from).collect::<Result<_, _>>()?;
+    let transactions_to_trace: Vec<_> =
+        block_txs.take(tx_index.0 as usize + 1 - traced_from).collect::<Result<_, _>>()?;
+    (transactions_to_trace.len() == tx_index.0 as usize + 1 - traced_from)
+        .then_some(())
+        .ok_or_internal_server_error("There should be at least one transaction in the block")?;
 
-    let execution_result =
-        executions_results.pop().ok_or_internal_server_error("No execution info returned for the last transaction")?;
+    let mut traces = exec_context
+        .re_execute_transactions(transactions_before, transactions_to_trace, true, true)?
+        .iter()
+        .map(|result| {
+            execution_result_to_tx_trace(result).or_internal_server_error("Converting execution infos to tx trace")
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(block_info) = cached_block_info {
+        starknet.cache_traces(block_info, &traces);
+    }
 
-    let trace = execution_result_to_tx_trace(&execution_result)
-        .or_internal_server_error("Converting execution infos to tx trace")?;
+    let trace = traces.pop().ok_or_internal_server_error("No execution info returned for the transaction")?;
 
     Ok(TraceBlockTransactionsResult { transaction_hash, trace_root: trace })
 }
@@ -1,8 +1,10 @@
 use jsonrpsee::core::RpcResult;
 use m_proc_macros::versioned_rpc;
 use mp_block::BlockId;
+use primitive_types::H256;
 use serde::{Deserialize, Serialize};
 use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{TxnExecutionStatus, TxnStatus};
 
 pub(crate) type NewHead = starknet_types_rpc::BlockHeader<Felt>;
 
@@ -52,10 +54,141 @@ pub struct GetStorageProofResult {
     pub global_roots: GlobalRoots,
 }
 
+/// Status of the L1 handler transaction created from an L1→L2 message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageStatus {
+    pub transaction_hash: Felt,
+    pub finality_status: TxnStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_status: Option<TxnExecutionStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+}
+
+/// Status on L1 of an L2→L1 message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageToL1Status {
+    /// The block sending the message has not been settled on L1 yet.
+    AcceptedOnL2,
+    /// The message has been settled on L1 and can be consumed.
+    AcceptedOnL1,
+    /// The message has been consumed on L1.
+    ConsumedOnL1,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageToL1WithStatus {
+    pub message_hash: H256,
+    pub transaction_hash: Felt,
+    pub block_number: u64,
+    pub from_address: Felt,
+    pub to_address: Felt,
+    pub payload: Vec<Felt>,
+    pub status: MessageToL1Status,
+}
+
+/// Status of a transaction, as sent by `starknet_subscribeTransactionStatus`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxnStatusResult {
+    pub finality_status: TxnStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_status: Option<TxnExecutionStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewTxnStatus {
+    pub transaction_hash: Felt,
+    pub status: TxnStatusResult,
+    /// Block containing the transaction, unset while it is in the mempool or in the pending block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+}
+
+/// A transaction added to the pending block, as sent by `starknet_subscribePendingTransactions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PendingTxnInfo {
+    Hash(Felt),
+    Full(starknet_types_rpc::TxnWithHash<Felt>),
+}
+
+/// Id of a websocket subscription, as returned by the `starknet_subscribe*` methods.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SubscriptionId {
+    Num(u64),
+    Str(String),
+}
+
+impl From<jsonrpsee::types::SubscriptionId<'_>> for SubscriptionId {
+    fn from(id: jsonrpsee::types::SubscriptionId<'_>) -> Self {
+        match id {
+            jsonrpsee::types::SubscriptionId::Num(n) => Self::Num(n),
+            jsonrpsee::types::SubscriptionId::Str(s) => Self::Str(s.into_owned()),
+        }
+    }
+}
+
 #[versioned_rpc("V0_8_0", "starknet")]
 pub trait StarknetWsRpcApi {
-    #[subscription(name = "subscribeNewHeads", unsubscribe = "unsubscribe", item = NewHead, param_kind = map)]
+    #[subscription(
+        name = "subscribeNewHeads",
+        unsubscribe = "unsubscribeNewHeads",
+        shared_unsubscribe = "unsubscribe",
+        item = NewHead,
+        param_kind = map
+    )]
     async fn subscribe_new_heads(&self, block_id: BlockId) -> jsonrpsee::core::SubscriptionResult;
+
+    /// Streams the events matching the filter, starting at `block_id` (the latest block by default). Events are
+    /// matched like in `starknet_getEvents`.
+    #[subscription(
+        name = "subscribeEvents",
+        unsubscribe = "unsubscribeEvents",
+        shared_unsubscribe = "unsubscribe",
+        item = starknet_types_rpc::EmittedEvent<Felt>,
+        param_kind = map
+    )]
+    async fn subscribe_events(
+        &self,
+        from_address: Option<Felt>,
+        keys: Option<Vec<Vec<Felt>>>,
+        block_id: Option<BlockId>,
+    ) -> jsonrpsee::core::SubscriptionResult;
+
+    /// Streams the status of a transaction every time it changes: received by the mempool, included in the pending
+    /// block, in a closed block, and finally accepted on L1, which ends the subscription.
+    #[subscription(
+        name = "subscribeTransactionStatus",
+        unsubscribe = "unsubscribeTransactionStatus",
+        shared_unsubscribe = "unsubscribe",
+        item = NewTxnStatus,
+        param_kind = map
+    )]
+    async fn subscribe_transaction_status(&self, transaction_hash: Felt) -> jsonrpsee::core::SubscriptionResult;
+
+    /// Streams the transactions added to the pending block, optionally only the ones sent by `sender_address`.
+    /// Only the transaction hashes are sent, unless `transaction_details` is set.
+    #[subscription(
+        name = "subscribePendingTransactions",
+        unsubscribe = "unsubscribePendingTransactions",
+        shared_unsubscribe = "unsubscribe",
+        item = PendingTxnInfo,
+        param_kind = map
+    )]
+    async fn subscribe_pending_transactions(
+        &self,
+        transaction_details: Option<bool>,
+        sender_address: Option<Vec<Felt>>,
+    ) -> jsonrpsee::core::SubscriptionResult;
+
+    /// Cancels a subscription made with any of the `starknet_subscribe*` methods. Returns whether the subscription
+    /// existed.
+    #[method(name = "unsubscribe")]
+    async fn unsubscribe(&self, subscription_id: SubscriptionId) -> RpcResult<bool>;
 }
 
 #[versioned_rpc("V0_8_0", "starknet")]
@@ -74,4 +207,23 @@ pub trait StarknetReadRpcApi {
         contract_addresses: Option<Vec<Felt>>,
         contracts_storage_keys: Option<Vec<ContractStorageKeysItem>>,
     ) -> RpcResult<GetStorageProofResult>;
+
+    #[method(name = "getMessagesStatus")]
+    fn get_messages_status(&self, transaction_hash: H256) -> RpcResult<Vec<MessageStatus>>;
+}
+
+#[versioned_rpc("V0_8_0", "madara")]
+pub trait MadaraMessagingRpcApi {
+    /// Get the L2→L1 messages with the given message hash, along with their status on L1.
+    #[method(name = "getMessagesToL1ByHash")]
+    fn get_messages_to_l1_by_hash(&self, message_hash: H256) -> RpcResult<Vec<MessageToL1WithStatus>>;
+
+    /// Get the L2→L1 messages sent to an L1 address, starting at block `from_block`, along with their status on L1.
+    #[method(name = "getMessagesToL1ByRecipient")]
+    fn get_messages_to_l1_by_recipient(
+        &self,
+        to_address: Felt,
+        from_block: Option<u64>,
+        chunk_size: Option<u64>,
+    ) -> RpcResult<Vec<MessageToL1WithStatus>>;
 }
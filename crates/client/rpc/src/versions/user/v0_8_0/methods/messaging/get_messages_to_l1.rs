@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use mc_db::msg_to_l1_db::{MsgToL1Entry, MsgToL1Status};
+use primitive_types::H256;
+use starknet_types_core::felt::Felt;
+
+use crate::constants::MAX_MESSAGES_TO_L1_CHUNK_SIZE;
+use crate::errors::{StarknetRpcApiError, StarknetRpcResult};
+use crate::utils::ResultExt;
+use crate::versions::user::v0_8_0::{MessageToL1Status, MessageToL1WithStatus};
+use crate::Starknet;
+
+/// Get the L2→L1 messages with the given hash, along with their status on L1.
+///
+/// Identical messages share the same hash. The core contract only counts how many of them have been settled and
+/// consumed, so the status of each message is derived from its position among the messages with that hash.
+pub fn get_messages_to_l1_by_hash(
+    starknet: &Starknet,
+    message_hash: H256,
+) -> StarknetRpcResult<Vec<MessageToL1WithStatus>> {
+    let l1_last_confirmed = get_l1_last_confirmed_block(starknet)?;
+    let status = get_status(starknet, &message_hash)?;
+
+    let entries = starknet
+        .backend
+        .get_messages_to_l1_by_hash(&message_hash)
+        .or_internal_server_error("Error getting messages to L1 by hash")?;
+
+    Ok(entries
+        .into_iter()
+        .enumerate()
+        .map(|(occurrence, entry)| with_status(entry, occurrence as u64, &status, l1_last_confirmed))
+        .collect())
+}
+
+/// Get the L2→L1 messages sent to `to_address`, starting at block `from_block`, along with their status on L1.
+pub fn get_messages_to_l1_by_recipient(
+    starknet: &Starknet,
+    to_address: Felt,
+    from_block: Option<u64>,
+    chunk_size: Option<u64>,
+) -> StarknetRpcResult<Vec<MessageToL1WithStatus>> {
+    let chunk_size = chunk_size.map_or(MAX_MESSAGES_TO_L1_CHUNK_SIZE, |size| size as usize);
+    if chunk_size > MAX_MESSAGES_TO_L1_CHUNK_SIZE {
+        return Err(StarknetRpcApiError::PageSizeTooBig);
+    }
+    let entries = starknet
+        .backend
+        .get_messages_to_l1_by_recipient(&to_address, from_block.unwrap_or(0), chunk_size)
+        .or_internal_server_error("Error getting messages to L1 by recipient")?;
+
+    with_statuses(starknet, entries)
+}
+
+/// Add their status on L1 to L2→L1 messages.
+pub(crate) fn with_statuses(
+    starknet: &Starknet,
+    entries: Vec<MsgToL1Entry>,
+) -> StarknetRpcResult<Vec<MessageToL1WithStatus>> {
+    let l1_last_confirmed = get_l1_last_confirmed_block(starknet)?;
+
+    let mut by_hash: HashMap<H256, (Vec<MsgToL1Entry>, MsgToL1Status)> = HashMap::new();
+    let mut res = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if !by_hash.contains_key(&entry.message_hash) {
+            let same_hash = starknet
+                .backend
+                .get_messages_to_l1_by_hash(&entry.message_hash)
+                .or_internal_server_error("Error getting messages to L1 by hash")?;
+            let status = get_status(starknet, &entry.message_hash)?;
+            by_hash.insert(entry.message_hash, (same_hash, status));
+        }
+        let (same_hash, status) = &by_hash[&entry.message_hash];
+        let occurrence = same_hash
+            .iter()
+            .position(|other| {
+                (other.block_n, other.tx_index, other.message_index)
+                    == (entry.block_n, entry.tx_index, entry.message_index)
+            })
+            .unwrap_or_default();
+        res.push(with_status(entry, occurrence as u64, status, l1_last_confirmed));
+    }
+    Ok(res)
+}
+
+fn get_l1_last_confirmed_block(starknet: &Starknet) -> StarknetRpcResult<Option<u64>> {
+    starknet.backend.get_l1_last_confirmed_block().or_internal_server_error("Error getting L1 last confirmed block")
+}
+
+fn get_status(starknet: &Starknet, message_hash: &H256) -> StarknetRpcResult<MsgToL1Status> {
+    starknet.backend.get_msg_to_l1_status(message_hash).or_internal_server_error("Error getting message to L1 status")
+}
+
+fn with_status(
+    entry: MsgToL1Entry,
+    occurrence: u64,
+    status: &MsgToL1Status,
+    l1_last_confirmed: Option<u64>,
+) -> MessageToL1WithStatus {
+    let status = if occurrence < status.consumed {
+        MessageToL1Status::ConsumedOnL1
+    } else if occurrence < status.settled || l1_last_confirmed.is_some_and(|block_n| entry.block_n <= block_n) {
+        MessageToL1Status::AcceptedOnL1
+    } else {
+        MessageToL1Status::AcceptedOnL2
+    };
+
+    MessageToL1WithStatus {
+        message_hash: entry.message_hash,
+        transaction_hash: entry.transaction_hash,
+        block_number: entry.block_n,
+        from_address: entry.message.from_address,
+        to_address: entry.message.to_address,
+        payload: entry.message.payload,
+        status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::rpc_test_setup;
+    use mc_db::l1_db::LastSyncedEventBlock;
+    use mc_db::msg_to_l1_db::MsgToL1Event;
+    use mc_db::MadaraBackend;
+    use mp_block::{Header, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock, MadaraMaybePendingBlockInfo};
+    use mp_receipt::{InvokeTransactionReceipt, MsgToL1};
+    use mp_state_update::StateDiff;
+    use mp_transactions::InvokeTransactionV0;
+    use rstest::rstest;
+    use std::sync::Arc;
+
+    fn store_block_with_messages(backend: &MadaraBackend, block_n: u64, messages: Vec<MsgToL1>) {
+        backend
+            .store_block(
+                MadaraMaybePendingBlock {
+                    info: MadaraMaybePendingBlockInfo::NotPending(MadaraBlockInfo {
+                        header: Header { block_number: block_n, ..Default::default() },
+                        block_hash: Felt::from(block_n),
+                        tx_hashes: vec![Felt::from(100 + block_n)],
+                    }),
+                    inner: MadaraBlockInner {
+                        transactions: vec![InvokeTransactionV0::default().into()],
+                        receipts: vec![
+                            InvokeTransactionReceipt { messages_sent: messages, ..Default::default() }.into()
+                        ],
+                    },
+                },
+                StateDiff::default(),
+                vec![],
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    #[rstest]
+    fn test_get_messages_to_l1(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (backend, rpc) = rpc_test_setup;
+        let message = MsgToL1 { from_address: Felt::ONE, to_address: Felt::TWO, payload: vec![Felt::THREE] };
+        let message_hash = message.compute_hash();
+        store_block_with_messages(&backend, 0, vec![message.clone()]);
+        store_block_with_messages(&backend, 1, vec![message.clone()]);
+
+        let statuses = |res: Vec<MessageToL1WithStatus>| res.into_iter().map(|m| m.status).collect::<Vec<_>>();
+
+        let res = get_messages_to_l1_by_hash(&rpc, message_hash).unwrap();
+        assert_eq!(res.iter().map(|m| m.transaction_hash).collect::<Vec<_>>(), [Felt::from(100), Felt::from(101)]);
+        assert_eq!(statuses(res), [MessageToL1Status::AcceptedOnL2, MessageToL1Status::AcceptedOnL2]);
+
+        backend.write_last_confirmed_block(0).unwrap();
+        backend.msg_to_l1_apply_event(&message_hash, MsgToL1Event::Consumed, LastSyncedEventBlock::new(1, 0)).unwrap();
+        assert_eq!(
+            statuses(get_messages_to_l1_by_hash(&rpc, message_hash).unwrap()),
+            [MessageToL1Status::ConsumedOnL1, MessageToL1Status::AcceptedOnL2]
+        );
+
+        let res = get_messages_to_l1_by_recipient(&rpc, Felt::TWO, Some(1), None).unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!((res[0].block_number, res[0].status), (1, MessageToL1Status::AcceptedOnL2));
+        assert!(get_messages_to_l1_by_recipient(&rpc, Felt::ONE, None, None).unwrap().is_empty());
+        assert!(matches!(
+            get_messages_to_l1_by_recipient(&rpc, Felt::TWO, None, Some(MAX_MESSAGES_TO_L1_CHUNK_SIZE as u64 + 1)),
+            Err(StarknetRpcApiError::PageSizeTooBig)
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+use crate::versions::user::v0_8_0::{MadaraMessagingRpcApiV0_8_0Server, MessageToL1WithStatus};
+use crate::Starknet;
+use jsonrpsee::core::{async_trait, RpcResult};
+use primitive_types::H256;
+use starknet_types_core::felt::Felt;
+
+pub mod get_messages_to_l1;
+
+#[async_trait]
+impl MadaraMessagingRpcApiV0_8_0Server for Starknet {
+    fn get_messages_to_l1_by_hash(&self, message_hash: H256) -> RpcResult<Vec<MessageToL1WithStatus>> {
+        Ok(get_messages_to_l1::get_messages_to_l1_by_hash(self, message_hash)?)
+    }
+
+    fn get_messages_to_l1_by_recipient(
+        &self,
+        to_address: Felt,
+        from_block: Option<u64>,
+        chunk_size: Option<u64>,
+    ) -> RpcResult<Vec<MessageToL1WithStatus>> {
+        Ok(get_messages_to_l1::get_messages_to_l1_by_recipient(self, to_address, from_block, chunk_size)?)
+    }
+}
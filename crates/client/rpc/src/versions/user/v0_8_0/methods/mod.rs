@@ -1,2 +1,3 @@
+pub mod messaging;
 pub mod read;
 pub mod ws;
@@ -0,0 +1,124 @@
+use mp_block::MadaraMaybePendingBlockInfo;
+use mp_receipt::ExecutionResult;
+use primitive_types::H256;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{TxnExecutionStatus, TxnStatus};
+
+use crate::errors::{StarknetRpcApiError, StarknetRpcResult};
+use crate::utils::ResultExt;
+use crate::versions::user::v0_8_0::MessageStatus;
+use crate::Starknet;
+
+/// Gets the status of the L1→L2 messages sent by an L1 transaction
+///
+/// Every message sent to L2 by the L1 transaction becomes an L1 handler transaction. This returns the status of each
+/// of these transactions, in the order the messages were sent on L1.
+///
+/// ### Arguments
+///
+/// * `transaction_hash` - The hash of the L1 transaction that sent the messages.
+///
+/// ### Returns
+///
+/// * For each message, the hash of its L1 handler transaction along with its finality and execution status. A
+///   transaction which is not part of a block yet has the `RECEIVED` finality status, and no execution status.
+pub fn get_messages_status(starknet: &Starknet, transaction_hash: H256) -> StarknetRpcResult<Vec<MessageStatus>> {
+    let l1_handler_tx_hashes = starknet
+        .backend
+        .get_l1_handler_tx_hashes(&transaction_hash)
+        .or_internal_server_error("Error getting L1 handler transactions from L1 transaction hash")?
+        .ok_or(StarknetRpcApiError::TxnHashNotFound)?;
+
+    l1_handler_tx_hashes.into_iter().map(|tx_hash| get_l1_handler_tx_status(starknet, tx_hash)).collect()
+}
+
+fn get_l1_handler_tx_status(starknet: &Starknet, transaction_hash: Felt) -> StarknetRpcResult<MessageStatus> {
+    let Some((block, tx_index)) = starknet
+        .backend
+        .find_tx_hash_block(&transaction_hash)
+        .or_internal_server_error("Error find tx hash block info from db")?
+    else {
+        // The message has been accepted into the mempool, but not executed yet.
+        return Ok(MessageStatus {
+            transaction_hash,
+            finality_status: TxnStatus::Received,
+            execution_status: None,
+            failure_reason: None,
+        });
+    };
+
+    let tx_receipt = block.inner.receipts.get(tx_index.0 as usize).ok_or(StarknetRpcApiError::TxnHashNotFound)?;
+
+    let (execution_status, failure_reason) = match tx_receipt.execution_result() {
+        ExecutionResult::Reverted { reason } => (TxnExecutionStatus::Reverted, Some(reason)),
+        ExecutionResult::Succeeded => (TxnExecutionStatus::Succeeded, None),
+    };
+
+    let finality_status = match block.info {
+        MadaraMaybePendingBlockInfo::Pending(_) => TxnStatus::AcceptedOnL2,
+        MadaraMaybePendingBlockInfo::NotPending(block) => {
+            if block.header.block_number <= starknet.get_l1_last_confirmed_block()? {
+                TxnStatus::AcceptedOnL1
+            } else {
+                TxnStatus::AcceptedOnL2
+            }
+        }
+    };
+
+    Ok(MessageStatus { transaction_hash, finality_status, execution_status: Some(execution_status), failure_reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{sample_chain_for_block_getters, SampleChainForBlockGetters};
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_get_messages_status(sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet)) {
+        let (SampleChainForBlockGetters { tx_hashes, .. }, rpc) = sample_chain_for_block_getters;
+        let l1_tx_hash = H256::repeat_byte(0x42);
+        let not_executed = Felt::from_hex_unchecked("0x7128638126378");
+        for tx_hash in [tx_hashes[0], tx_hashes[2], tx_hashes[3], not_executed] {
+            rpc.backend.add_l1_handler_tx_hash_for_l1_tx(&l1_tx_hash, tx_hash).unwrap();
+        }
+
+        assert_eq!(
+            get_messages_status(&rpc, l1_tx_hash).unwrap(),
+            vec![
+                MessageStatus {
+                    transaction_hash: tx_hashes[0],
+                    finality_status: TxnStatus::AcceptedOnL1,
+                    execution_status: Some(TxnExecutionStatus::Succeeded),
+                    failure_reason: None,
+                },
+                MessageStatus {
+                    transaction_hash: tx_hashes[2],
+                    finality_status: TxnStatus::AcceptedOnL2,
+                    execution_status: Some(TxnExecutionStatus::Reverted),
+                    failure_reason: Some("too bad".into()),
+                },
+                // Pending
+                MessageStatus {
+                    transaction_hash: tx_hashes[3],
+                    finality_status: TxnStatus::AcceptedOnL2,
+                    execution_status: Some(TxnExecutionStatus::Succeeded),
+                    failure_reason: None,
+                },
+                MessageStatus {
+                    transaction_hash: not_executed,
+                    finality_status: TxnStatus::Received,
+                    execution_status: None,
+                    failure_reason: None,
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_get_messages_status_not_found(sample_chain_for_block_getters: (SampleChainForBlockGetters, Starknet)) {
+        let (SampleChainForBlockGetters { .. }, rpc) = sample_chain_for_block_getters;
+
+        assert_eq!(get_messages_status(&rpc, H256::repeat_byte(0x42)), Err(StarknetRpcApiError::TxnHashNotFound));
+    }
+}
@@ -0,0 +1,65 @@
+use jsonrpsee::core::server::SubscriptionSink;
+use mp_block::{BlockId, BlockTag};
+use starknet_types_core::felt::Felt;
+
+use crate::constants::MAX_EVENTS_KEYS;
+use crate::errors::{ErrorExtWs, StarknetWsApiError};
+use crate::versions::user::v0_7_1::methods::read::get_events::{event_match_filter, get_block_events};
+use crate::Starknet;
+
+use super::{check_notification, starting_block_n};
+
+/// Sends the events of the stored blocks matching the filter, starting at `block_id`, then the events of every new
+/// block as it is stored.
+pub async fn subscribe_events(
+    starknet: &Starknet,
+    sink: SubscriptionSink,
+    from_address: Option<Felt>,
+    keys: Vec<Vec<Felt>>,
+    block_id: Option<BlockId>,
+) -> Result<(), StarknetWsApiError> {
+    if keys.len() > MAX_EVENTS_KEYS {
+        return Err(StarknetWsApiError::TooManyKeysInFilter);
+    }
+    // Subscribe before replaying the stored blocks, so that no block is missed in between.
+    let mut rx = starknet.backend.subscribe_block_info();
+    let mut block_n = starting_block_n(starknet, block_id.unwrap_or(BlockId::Tag(BlockTag::Latest)))?;
+
+    loop {
+        // Send the events of every block stored so far. New blocks are read back from the backend, so that a lagging
+        // receiver never makes us skip a block.
+        while let Some(block) = starknet
+            .backend
+            .get_block(&BlockId::Number(block_n))
+            .or_else_internal_server_error(|| format!("Failed to retrieve block {block_n}"))?
+        {
+            if !starknet
+                .backend
+                .has_block_body(block_n)
+                .or_else_internal_server_error(|| format!("Failed to retrieve block {block_n}"))?
+            {
+                return Err(StarknetWsApiError::BlockBodyUnavailable);
+            }
+            for event in get_block_events(starknet, &block) {
+                if !event_match_filter(&event.event, from_address, &keys) {
+                    continue;
+                }
+                let msg = jsonrpsee::SubscriptionMessage::from_json(&event).or_else_internal_server_error(|| {
+                    format!("Failed to create response message for block {block_n}")
+                })?;
+                if sink.send(msg).await.is_err() {
+                    // Subscription closed.
+                    return Ok(());
+                }
+            }
+            block_n += 1;
+        }
+
+        tokio::select! {
+            res = rx.recv() => check_notification(res)?,
+            _ = sink.closed() => {
+                return Ok(())
+            }
+        }
+    }
+}
@@ -1,11 +1,13 @@
-use mp_block::{BlockId, BlockTag};
+use jsonrpsee::core::{server::SubscriptionSink, RpcResult};
+use mp_block::BlockId;
+use starknet_types_core::felt::Felt;
 
 use crate::{
     errors::{ErrorExtWs, OptionExtWs, StarknetWsApiError},
-    versions::user::v0_8_0::StarknetWsRpcApiV0_8_0Server,
+    versions::user::v0_8_0::{StarknetWsRpcApiV0_8_0Server, SubscriptionId},
 };
 
-use super::BLOCK_PAST_LIMIT;
+use super::{events, pending_transactions, starting_block_n, transaction_status};
 
 #[jsonrpsee::core::async_trait]
 impl StarknetWsRpcApiV0_8_0Server for crate::Starknet {
@@ -16,109 +18,131 @@ impl StarknetWsRpcApiV0_8_0Server for crate::Starknet {
     ) -> jsonrpsee::core::SubscriptionResult {
         let sink =
             subscription_sink.accept().await.or_internal_server_error("Failed to establish websocket connection")?;
+        let mut unsubscribed = self.ws_subscriptions.register(sink.subscription_id().into());
+        tokio::select! {
+            res = new_heads(self, &sink, block_id) => res,
+            _ = unsubscribed.cancelled() => Ok(()),
+        }
+    }
 
-        let mut block_n = match block_id {
-            BlockId::Number(block_n) => {
-                let err = || format!("Failed to retrieve block info for block {block_n}");
-                let block_latest = self
-                    .backend
-                    .get_block_n(&BlockId::Tag(BlockTag::Latest))
-                    .or_else_internal_server_error(err)?
-                    .ok_or(StarknetWsApiError::NoBlocks)?;
-
-                if block_n < block_latest.saturating_sub(BLOCK_PAST_LIMIT) {
-                    return Err(StarknetWsApiError::TooManyBlocksBack.into());
-                }
+    async fn subscribe_events(
+        &self,
+        subscription_sink: jsonrpsee::PendingSubscriptionSink,
+        from_address: Option<Felt>,
+        keys: Option<Vec<Vec<Felt>>>,
+        block_id: Option<BlockId>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let sink =
+            subscription_sink.accept().await.or_internal_server_error("Failed to establish websocket connection")?;
+        let mut unsubscribed = self.ws_subscriptions.register(sink.subscription_id().into());
+        tokio::select! {
+            res = events::subscribe_events(self, sink, from_address, keys.unwrap_or_default(), block_id) => Ok(res?),
+            _ = unsubscribed.cancelled() => Ok(()),
+        }
+    }
 
-                block_n
-            }
-            BlockId::Hash(block_hash) => {
-                let err = || format!("Failed to retrieve block info at hash {block_hash:#x}");
-                let block_latest = self
-                    .backend
-                    .get_block_n(&BlockId::Tag(BlockTag::Latest))
-                    .or_else_internal_server_error(err)?
-                    .ok_or(StarknetWsApiError::NoBlocks)?;
-
-                let block_n = self
-                    .backend
-                    .get_block_n(&block_id)
-                    .or_else_internal_server_error(err)?
-                    .ok_or(StarknetWsApiError::BlockNotFound)?;
-
-                if block_n < block_latest.saturating_sub(BLOCK_PAST_LIMIT) {
-                    return Err(StarknetWsApiError::TooManyBlocksBack.into());
-                }
+    async fn subscribe_transaction_status(
+        &self,
+        subscription_sink: jsonrpsee::PendingSubscriptionSink,
+        transaction_hash: Felt,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let sink =
+            subscription_sink.accept().await.or_internal_server_error("Failed to establish websocket connection")?;
+        let mut unsubscribed = self.ws_subscriptions.register(sink.subscription_id().into());
+        tokio::select! {
+            res = transaction_status::subscribe_transaction_status(self, sink, transaction_hash) => Ok(res?),
+            _ = unsubscribed.cancelled() => Ok(()),
+        }
+    }
 
-                block_n
+    async fn subscribe_pending_transactions(
+        &self,
+        subscription_sink: jsonrpsee::PendingSubscriptionSink,
+        transaction_details: Option<bool>,
+        sender_address: Option<Vec<Felt>>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let pending_transactions = pending_transactions::PendingTransactions::new(
+            self,
+            transaction_details.unwrap_or_default(),
+            sender_address.unwrap_or_default(),
+        )?;
+        let sink =
+            subscription_sink.accept().await.or_internal_server_error("Failed to establish websocket connection")?;
+        let mut unsubscribed = self.ws_subscriptions.register(sink.subscription_id().into());
+        tokio::select! {
+            res = pending_transactions.run(self, sink) => Ok(res?),
+            _ = unsubscribed.cancelled() => Ok(()),
+        }
+    }
+
+    async fn unsubscribe(&self, subscription_id: SubscriptionId) -> RpcResult<bool> {
+        Ok(self.ws_subscriptions.unsubscribe(&subscription_id))
+    }
+}
+
+async fn new_heads(
+    starknet: &crate::Starknet,
+    sink: &SubscriptionSink,
+    block_id: BlockId,
+) -> jsonrpsee::core::SubscriptionResult {
+    let mut block_n = starting_block_n(starknet, block_id)?;
+
+    let mut rx = starknet.backend.subscribe_block_info();
+    for n in block_n.. {
+        if sink.is_closed() {
+            return Ok(());
+        }
+
+        let block_info = match starknet.backend.get_block_info(&BlockId::Number(n)) {
+            Ok(Some(block_info)) => {
+                let err = || format!("Failed to retrieve block info for block {n}");
+                block_info.as_nonpending_owned().ok_or_else_internal_server_error(err)?
             }
-            BlockId::Tag(BlockTag::Latest) => self
-                .backend
-                .get_latest_block_n()
-                .or_internal_server_error("Failed to retrieve block info for latest block")?
-                .ok_or(StarknetWsApiError::NoBlocks)?,
-            BlockId::Tag(BlockTag::Pending) => {
-                return Err(StarknetWsApiError::Pending.into());
+            Ok(None) => break,
+            Err(e) => {
+                let err = format!("Failed to retrieve block info for block {n}: {e}");
+                return Err(StarknetWsApiError::internal_server_error(err).into());
             }
         };
 
-        let mut rx = self.backend.subscribe_block_info();
-        for n in block_n.. {
-            if sink.is_closed() {
-                return Ok(());
-            }
-
-            let block_info = match self.backend.get_block_info(&BlockId::Number(n)) {
-                Ok(Some(block_info)) => {
-                    let err = || format!("Failed to retrieve block info for block {n}");
-                    block_info.as_nonpending_owned().ok_or_else_internal_server_error(err)?
-                }
-                Ok(None) => break,
-                Err(e) => {
-                    let err = format!("Failed to retrieve block info for block {n}: {e}");
-                    return Err(StarknetWsApiError::internal_server_error(err).into());
-                }
-            };
-
-            send_block_header(&sink, block_info, block_n).await?;
-            block_n = block_n.saturating_add(1);
-        }
+        send_block_header(sink, block_info, block_n).await?;
+        block_n = block_n.saturating_add(1);
+    }
 
-        // Catching up with the backend
-        loop {
-            tokio::select! {
-                block_info = rx.recv() => {
-                    let block_info = block_info.or_internal_server_error("Failed to retrieve block info")?;
-                    if block_info.header.block_number == block_n {
-                        break send_block_header(&sink, block_info, block_n).await?;
-                    }
-                },
-                _ = sink.closed() => {
-                    return Ok(())
+    // Catching up with the backend
+    loop {
+        tokio::select! {
+            block_info = rx.recv() => {
+                let block_info = block_info.or_internal_server_error("Failed to retrieve block info")?;
+                if block_info.header.block_number == block_n {
+                    break send_block_header(sink, block_info, block_n).await?;
                 }
+            },
+            _ = sink.closed() => {
+                return Ok(())
             }
         }
+    }
 
-        // New block headers
-        loop {
-            tokio::select! {
-                block_info = rx.recv() => {
-                    let block_info = block_info.or_internal_server_error("Failed to retrieve block info")?;
-                    if block_info.header.block_number == block_n + 1 {
-                        send_block_header(&sink, block_info, block_n).await?;
-                    } else {
-                        let err = format!(
-                            "Received non-sequential block {}, expected {}",
-                            block_info.header.block_number,
-                            block_n + 1
-                        );
-                        return Err(StarknetWsApiError::internal_server_error(err).into());
-                    }
-                    block_n = block_n.saturating_add(1);
-                },
-                _ = sink.closed() => {
-                    return Ok(())
+    // New block headers
+    loop {
+        tokio::select! {
+            block_info = rx.recv() => {
+                let block_info = block_info.or_internal_server_error("Failed to retrieve block info")?;
+                if block_info.header.block_number == block_n + 1 {
+                    send_block_header(sink, block_info, block_n).await?;
+                } else {
+                    let err = format!(
+                        "Received non-sequential block {}, expected {}",
+                        block_info.header.block_number,
+                        block_n + 1
+                    );
+                    return Err(StarknetWsApiError::internal_server_error(err).into());
                 }
+                block_n = block_n.saturating_add(1);
+            },
+            _ = sink.closed() => {
+                return Ok(())
             }
         }
     }
@@ -143,7 +167,9 @@ mod test {
     use super::*;
 
     use jsonrpsee::ws_client::WsClientBuilder;
-    use starknet_types_core::felt::Felt;
+    use mp_block::BlockTag;
+
+    use super::super::BLOCK_PAST_LIMIT;
 
     use crate::{
         test_utils::rpc_test_setup,
@@ -374,4 +400,96 @@ mod test {
         let next = sub.next().await;
         assert!(next.is_none());
     }
+
+    fn store_block_with_events(backend: &mc_db::MadaraBackend, block_n: u64, events: Vec<mp_receipt::Event>) {
+        backend
+            .store_block(
+                mp_block::MadaraMaybePendingBlock {
+                    info: mp_block::MadaraMaybePendingBlockInfo::NotPending(mp_block::MadaraBlockInfo {
+                        header: mp_block::Header { block_number: block_n, ..Default::default() },
+                        block_hash: Felt::from(block_n),
+                        tx_hashes: vec![Felt::from(100 + block_n)],
+                    }),
+                    inner: mp_block::MadaraBlockInner {
+                        transactions: vec![mp_transactions::InvokeTransactionV0::default().into()],
+                        receipts: vec![mp_receipt::InvokeTransactionReceipt {
+                            transaction_hash: Felt::from(100 + block_n),
+                            events,
+                            ..Default::default()
+                        }
+                        .into()],
+                    },
+                },
+                mp_state_update::StateDiff::default(),
+                vec![],
+                None,
+                None,
+            )
+            .expect("Storing block");
+    }
+
+    #[tokio::test]
+    #[rstest::rstest]
+    async fn subscribe_events(rpc_test_setup: (std::sync::Arc<mc_db::MadaraBackend>, Starknet)) {
+        let (backend, starknet) = rpc_test_setup;
+        let server = jsonrpsee::server::Server::builder().build("127.0.0.1:0").await.expect("Starting server");
+        let server_url = format!("ws://{}", server.local_addr().expect("Retrieving server local address"));
+        // Server will be stopped once this is dropped
+        let _server_handle = server.start(StarknetWsRpcApiV0_8_0Server::into_rpc(starknet));
+        let client = WsClientBuilder::default().build(&server_url).await.expect("Building client");
+
+        let event = |from_address: u64, key: u64| mp_receipt::Event {
+            from_address: Felt::from(from_address),
+            keys: vec![Felt::from(key)],
+            data: vec![Felt::from(from_address * 10 + key)],
+        };
+        store_block_with_events(&backend, 0, vec![event(1, 1), event(2, 1), event(1, 2)]);
+        store_block_with_events(&backend, 1, vec![event(1, 1)]);
+
+        let mut sub = client
+            .subscribe_events(Some(Felt::from(1)), Some(vec![vec![Felt::from(1)]]), Some(BlockId::Number(0)))
+            .await
+            .expect("starknet_subscribeEvents");
+
+        // Replayed events.
+        for block_n in [0, 1] {
+            let emitted = sub.next().await.expect("Waiting for event").expect("Waiting for event");
+            assert_eq!(emitted.block_number, Some(block_n));
+            assert_eq!(emitted.transaction_hash, Felt::from(100 + block_n));
+            assert_eq!(emitted.event.from_address, Felt::from(1));
+            assert_eq!(emitted.event.event_content.keys, vec![Felt::from(1)]);
+        }
+
+        // New events.
+        store_block_with_events(&backend, 2, vec![event(2, 1), event(1, 3)]);
+        store_block_with_events(&backend, 3, vec![event(1, 1)]);
+        let emitted = sub.next().await.expect("Waiting for event").expect("Waiting for event");
+        assert_eq!(emitted.block_number, Some(3));
+        assert_eq!(emitted.event.event_content.data, vec![Felt::from(11)]);
+    }
+
+    #[tokio::test]
+    #[rstest::rstest]
+    async fn unsubscribe_any_subscription(rpc_test_setup: (std::sync::Arc<mc_db::MadaraBackend>, Starknet)) {
+        let (backend, starknet) = rpc_test_setup;
+        let server = jsonrpsee::server::Server::builder().build("127.0.0.1:0").await.expect("Starting server");
+        let server_url = format!("ws://{}", server.local_addr().expect("Retrieving server local address"));
+        // Server will be stopped once this is dropped
+        let _server_handle = server.start(StarknetWsRpcApiV0_8_0Server::into_rpc(starknet));
+        let client = WsClientBuilder::default().build(&server_url).await.expect("Building client");
+
+        let event = mp_receipt::Event { from_address: Felt::ONE, keys: vec![], data: vec![] };
+        store_block_with_events(&backend, 0, vec![event]);
+
+        let mut sub = client.subscribe_events(None, None, None).await.expect("starknet_subscribeEvents");
+        // The subscription is registered once the first event is sent.
+        sub.next().await.expect("Waiting for event").expect("Waiting for event");
+        let jsonrpsee::core::client::SubscriptionKind::Subscription(id) = sub.kind() else {
+            panic!("Expected a subscription id")
+        };
+        let id = SubscriptionId::from(id.clone());
+
+        assert!(client.unsubscribe(id.clone()).await.expect("starknet_unsubscribe"));
+        assert!(!client.unsubscribe(id).await.expect("starknet_unsubscribe"));
+    }
 }
@@ -1,3 +1,102 @@
+pub mod events;
 pub mod lib;
+pub mod pending_transactions;
+pub mod transaction_status;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use mp_block::{BlockId, BlockTag};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::oneshot;
+
+use crate::errors::{ErrorExtWs, StarknetWsApiError};
+use crate::versions::user::v0_8_0::SubscriptionId;
+use crate::Starknet;
 
 const BLOCK_PAST_LIMIT: u64 = 1024;
+
+/// Resolves the block a subscription starts from, which cannot be more than [`BLOCK_PAST_LIMIT`] blocks back.
+pub(crate) fn starting_block_n(starknet: &Starknet, block_id: BlockId) -> Result<u64, StarknetWsApiError> {
+    let block_n = match block_id {
+        BlockId::Tag(BlockTag::Pending) => return Err(StarknetWsApiError::Pending),
+        BlockId::Tag(BlockTag::Latest) => {
+            return starknet
+                .backend
+                .get_latest_block_n()
+                .or_internal_server_error("Failed to retrieve block info for latest block")?
+                .ok_or(StarknetWsApiError::NoBlocks)
+        }
+        BlockId::Number(block_n) => block_n,
+        BlockId::Hash(block_hash) => starknet
+            .backend
+            .get_block_n(&block_id)
+            .or_else_internal_server_error(|| format!("Failed to retrieve block info at hash {block_hash:#x}"))?
+            .ok_or(StarknetWsApiError::BlockNotFound)?,
+    };
+
+    let block_latest = starknet
+        .backend
+        .get_latest_block_n()
+        .or_else_internal_server_error(|| format!("Failed to retrieve block info for block {block_n}"))?
+        .ok_or(StarknetWsApiError::NoBlocks)?;
+    if block_n < block_latest.saturating_sub(BLOCK_PAST_LIMIT) {
+        return Err(StarknetWsApiError::TooManyBlocksBack);
+    }
+
+    Ok(block_n)
+}
+
+/// Subscriptions read their state back from the backend when notified, so missing notifications by lagging behind is
+/// fine.
+pub(crate) fn check_notification<T>(res: Result<T, RecvError>) -> Result<(), StarknetWsApiError> {
+    match res {
+        Ok(_) | Err(RecvError::Lagged(_)) => Ok(()),
+        Err(RecvError::Closed) => Err(StarknetWsApiError::internal_server_error("Backend notification channel closed")),
+    }
+}
+
+/// The websocket subscriptions in progress, so that `starknet_unsubscribe` can cancel any of them.
+#[derive(Default)]
+pub(crate) struct WsSubscriptions {
+    subscriptions: Mutex<HashMap<SubscriptionId, oneshot::Sender<()>>>,
+}
+
+impl WsSubscriptions {
+    /// Registers a subscription until the returned [`Unsubscribed`] is dropped.
+    pub(crate) fn register(self: &Arc<Self>, id: SubscriptionId) -> Unsubscribed {
+        let (tx, rx) = oneshot::channel();
+        self.subscriptions.lock().expect("Poisoned lock").insert(id.clone(), tx);
+        Unsubscribed { subscriptions: Arc::clone(self), id, rx }
+    }
+
+    /// Cancels a subscription, returning whether it existed.
+    pub(crate) fn unsubscribe(&self, id: &SubscriptionId) -> bool {
+        match self.subscriptions.lock().expect("Poisoned lock").remove(id) {
+            Some(tx) => {
+                let _ = tx.send(());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub(crate) struct Unsubscribed {
+    subscriptions: Arc<WsSubscriptions>,
+    id: SubscriptionId,
+    rx: oneshot::Receiver<()>,
+}
+
+impl Unsubscribed {
+    /// Resolves once the subscription is cancelled with `starknet_unsubscribe`.
+    pub(crate) async fn cancelled(&mut self) {
+        let _ = (&mut self.rx).await;
+    }
+}
+
+impl Drop for Unsubscribed {
+    fn drop(&mut self) {
+        self.subscriptions.subscriptions.lock().expect("Poisoned lock").remove(&self.id);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use jsonrpsee::core::server::SubscriptionSink;
+use mp_block::{BlockId, BlockTag, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlockInfo, MadaraPendingBlock};
+use mp_receipt::TransactionReceipt;
+use mp_transactions::Transaction;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::TxnWithHash;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::constants::MAX_SENDER_ADDRESSES;
+use crate::errors::{ErrorExtWs, StarknetWsApiError};
+use crate::versions::user::v0_8_0::PendingTxnInfo;
+use crate::Starknet;
+
+use super::check_notification;
+
+/// A `starknet_subscribePendingTransactions` subscription, which sends the transactions added to the pending block
+/// after the subscription.
+///
+/// Transactions are sent every time the pending block is stored. Blocks can also be closed without their last
+/// transactions ever being stored in a pending block, or while this subscription lags behind, so the transactions of
+/// closed blocks which have not been sent are sent when the block is closed.
+pub struct PendingTransactions {
+    transaction_details: bool,
+    sender_address: HashSet<Felt>,
+    rx_pending: broadcast::Receiver<Arc<MadaraPendingBlock>>,
+    rx_block: broadcast::Receiver<MadaraBlockInfo>,
+    /// Transactions sent, or already in the pending block when subscribing, until their block is closed.
+    sent: HashSet<Felt>,
+    /// The next closed block whose transactions have not been checked.
+    next_block_n: u64,
+}
+
+impl PendingTransactions {
+    /// Subscribes to the backend. This is done before the subscription is accepted, so that no transaction added
+    /// after the request is missed.
+    pub fn new(
+        starknet: &Starknet,
+        transaction_details: bool,
+        sender_address: Vec<Felt>,
+    ) -> Result<Self, StarknetWsApiError> {
+        if sender_address.len() > MAX_SENDER_ADDRESSES {
+            return Err(StarknetWsApiError::TooManyAddressesInFilter);
+        }
+
+        let rx_pending = starknet.backend.subscribe_pending_block();
+        let rx_block = starknet.backend.subscribe_block_info();
+        // The pending block is read before the latest block, so that a block closed in between is not sent.
+        let sent = match starknet
+            .backend
+            .get_block_info(&BlockId::Tag(BlockTag::Pending))
+            .or_internal_server_error("Failed to retrieve pending block info")?
+        {
+            Some(MadaraMaybePendingBlockInfo::Pending(block_info)) => block_info.tx_hashes.into_iter().collect(),
+            _ => HashSet::new(),
+        };
+        let next_block_n = starknet
+            .backend
+            .get_latest_block_n()
+            .or_internal_server_error("Failed to retrieve latest block number")?
+            .map_or(0, |block_n| block_n + 1);
+
+        Ok(Self {
+            transaction_details,
+            sender_address: sender_address.into_iter().collect(),
+            rx_pending,
+            rx_block,
+            sent,
+            next_block_n,
+        })
+    }
+
+    pub async fn run(mut self, starknet: &Starknet, sink: SubscriptionSink) -> Result<(), StarknetWsApiError> {
+        loop {
+            tokio::select! {
+                res = self.rx_pending.recv() => match res {
+                    Ok(block) => {
+                        if !self.send_transactions(&sink, &block.inner, &block.info.tx_hashes).await? {
+                            return Ok(());
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => {
+                        return Err(StarknetWsApiError::internal_server_error("Pending block channel closed"))
+                    }
+                },
+                res = self.rx_block.recv() => {
+                    check_notification(res)?;
+                    if !self.send_closed_blocks(starknet, &sink).await? {
+                        return Ok(());
+                    }
+                },
+                _ = sink.closed() => {
+                    return Ok(())
+                }
+            }
+        }
+    }
+
+    /// Sends the transactions of the blocks closed since the last call which have not been sent yet. Blocks are read
+    /// back from the backend, so that none is skipped when lagging behind. Returns false if the subscription is
+    /// closed.
+    async fn send_closed_blocks(
+        &mut self,
+        starknet: &Starknet,
+        sink: &SubscriptionSink,
+    ) -> Result<bool, StarknetWsApiError> {
+        let Some(latest_block_n) =
+            starknet.backend.get_latest_block_n().or_internal_server_error("Failed to retrieve latest block number")?
+        else {
+            return Ok(true);
+        };
+
+        while self.next_block_n <= latest_block_n {
+            let block_n = self.next_block_n;
+            let block = starknet
+                .backend
+                .get_block(&BlockId::Number(block_n))
+                .or_else_internal_server_error(|| format!("Failed to retrieve block {block_n}"))?;
+            // Blocks synchronized without their body cannot be sent, but none of their transactions will be added to
+            // a pending block anymore either.
+            if let Some(block) = block {
+                if !self.send_transactions(sink, &block.inner, block.info.tx_hashes()).await? {
+                    return Ok(false);
+                }
+                for transaction_hash in block.info.tx_hashes() {
+                    self.sent.remove(transaction_hash);
+                }
+            }
+            self.next_block_n = block_n + 1;
+        }
+        Ok(true)
+    }
+
+    /// Sends the transactions matching the filter which have not been sent yet. Returns false if the subscription is
+    /// closed.
+    async fn send_transactions(
+        &mut self,
+        sink: &SubscriptionSink,
+        inner: &MadaraBlockInner,
+        tx_hashes: &[Felt],
+    ) -> Result<bool, StarknetWsApiError> {
+        let transactions = inner.transactions.iter().zip(&inner.receipts).zip(tx_hashes);
+        for ((transaction, receipt), transaction_hash) in transactions {
+            if !self.sent.insert(*transaction_hash) {
+                continue;
+            }
+            if !self.sender_address.is_empty()
+                && !sender(transaction, receipt).is_some_and(|sender| self.sender_address.contains(&sender))
+            {
+                continue;
+            }
+
+            let info = if self.transaction_details {
+                PendingTxnInfo::Full(TxnWithHash {
+                    transaction: transaction.clone().into(),
+                    transaction_hash: *transaction_hash,
+                })
+            } else {
+                PendingTxnInfo::Hash(*transaction_hash)
+            };
+            let msg = jsonrpsee::SubscriptionMessage::from_json(&info).or_else_internal_server_error(|| {
+                format!("Failed to create response message for {transaction_hash:#x}")
+            })?;
+            if sink.send(msg).await.is_err() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// The account sending a transaction. L1 handler and legacy deploy transactions have none.
+fn sender(transaction: &Transaction, receipt: &TransactionReceipt) -> Option<Felt> {
+    match transaction {
+        Transaction::Invoke(tx) => Some(*tx.sender_address()),
+        Transaction::Declare(tx) => Some(*tx.sender_address()),
+        Transaction::DeployAccount(_) => receipt.contract_address(),
+        Transaction::L1Handler(_) | Transaction::Deploy(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use jsonrpsee::core::client::Subscription;
+    use jsonrpsee::ws_client::WsClientBuilder;
+    use mc_db::MadaraBackend;
+    use mp_block::{header::PendingHeader, Header, MadaraMaybePendingBlock, MadaraPendingBlockInfo};
+    use mp_receipt::InvokeTransactionReceipt;
+    use mp_state_update::StateDiff;
+    use mp_transactions::InvokeTransactionV0;
+
+    use crate::test_utils::rpc_test_setup;
+    use crate::versions::user::v0_8_0::{StarknetWsRpcApiV0_8_0Client, StarknetWsRpcApiV0_8_0Server};
+
+    /// One invoke transaction per sender, with hash `0x100 + sender`.
+    fn block_inner(senders: &[u64]) -> (Vec<Felt>, MadaraBlockInner) {
+        let tx_hashes: Vec<_> = senders.iter().map(|sender| Felt::from(0x100 + sender)).collect();
+        let inner = MadaraBlockInner {
+            transactions: senders
+                .iter()
+                .map(|sender| {
+                    InvokeTransactionV0 { contract_address: Felt::from(*sender), ..Default::default() }.into()
+                })
+                .collect(),
+            receipts: tx_hashes
+                .iter()
+                .map(|transaction_hash| {
+                    InvokeTransactionReceipt { transaction_hash: *transaction_hash, ..Default::default() }.into()
+                })
+                .collect(),
+        };
+        (tx_hashes, inner)
+    }
+
+    fn store_pending_block(backend: &MadaraBackend, senders: &[u64]) {
+        let (tx_hashes, inner) = block_inner(senders);
+        let info = MadaraMaybePendingBlockInfo::Pending(MadaraPendingBlockInfo {
+            header: PendingHeader::default(),
+            tx_hashes,
+        });
+        backend
+            .store_block(MadaraMaybePendingBlock { info, inner }, StateDiff::default(), vec![], None, None)
+            .expect("Storing pending block");
+    }
+
+    fn store_closed_block(backend: &MadaraBackend, block_number: u64, senders: &[u64]) {
+        let (tx_hashes, inner) = block_inner(senders);
+        let info = MadaraMaybePendingBlockInfo::NotPending(MadaraBlockInfo {
+            header: Header { block_number, ..Default::default() },
+            block_hash: Felt::from(block_number),
+            tx_hashes,
+        });
+        backend
+            .store_block(MadaraMaybePendingBlock { info, inner }, StateDiff::default(), vec![], None, None)
+            .expect("Storing block");
+    }
+
+    async fn next_hash(sub: &mut Subscription<PendingTxnInfo>) -> Felt {
+        match sub.next().await.expect("Waiting for transaction").expect("Waiting for transaction") {
+            PendingTxnInfo::Hash(hash) => hash,
+            PendingTxnInfo::Full(tx) => tx.transaction_hash,
+        }
+    }
+
+    #[tokio::test]
+    #[rstest::rstest]
+    async fn subscribe_pending_transactions(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (backend, starknet) = rpc_test_setup;
+        let server = jsonrpsee::server::Server::builder().build("127.0.0.1:0").await.expect("Starting server");
+        let server_url = format!("ws://{}", server.local_addr().expect("Retrieving server local address"));
+        // Server will be stopped once this is dropped
+        let _server_handle = server.start(StarknetWsRpcApiV0_8_0Server::into_rpc(starknet));
+        let client = WsClientBuilder::default().build(&server_url).await.expect("Building client");
+
+        // Already in the pending block when subscribing: not sent.
+        store_pending_block(&backend, &[1]);
+
+        let mut sub_hashes = client
+            .subscribe_pending_transactions(None, Some(vec![Felt::from(1), Felt::from(2)]))
+            .await
+            .expect("starknet_subscribePendingTransactions");
+        let mut sub_full = client
+            .subscribe_pending_transactions(Some(true), None)
+            .await
+            .expect("starknet_subscribePendingTransactions");
+
+        store_pending_block(&backend, &[1, 3, 2]);
+
+        let next = sub_hashes.next().await.expect("Waiting for transaction").expect("Waiting for transaction");
+        assert!(matches!(next, PendingTxnInfo::Hash(hash) if hash == Felt::from(0x102)), "{next:?}");
+        for expected in [0x103, 0x102] {
+            let next = sub_full.next().await.expect("Waiting for transaction").expect("Waiting for transaction");
+            assert!(
+                matches!(&next, PendingTxnInfo::Full(tx) if tx.transaction_hash == Felt::from(expected)),
+                "{next:?}"
+            );
+        }
+
+        // Block closed with transactions which were never stored in a pending block.
+        store_closed_block(&backend, 0, &[1, 3, 2, 4, 5]);
+        assert_eq!(next_hash(&mut sub_full).await, Felt::from(0x104));
+        assert_eq!(next_hash(&mut sub_full).await, Felt::from(0x105));
+
+        // New pending block.
+        store_pending_block(&backend, &[6, 7]);
+        assert_eq!(next_hash(&mut sub_full).await, Felt::from(0x106));
+        assert_eq!(next_hash(&mut sub_full).await, Felt::from(0x107));
+    }
+}
@@ -0,0 +1,199 @@
+use jsonrpsee::core::server::SubscriptionSink;
+use mp_receipt::ExecutionResult;
+use starknet_types_core::felt::Felt;
+use starknet_types_rpc::{TxnExecutionStatus, TxnStatus};
+
+use crate::errors::{ErrorExtWs, OptionExtWs, StarknetWsApiError};
+use crate::versions::user::v0_8_0::{NewTxnStatus, TxnStatusResult};
+use crate::Starknet;
+
+use super::check_notification;
+
+/// Sends the status of the transaction every time it changes, until it is accepted on L1.
+///
+/// The status is read back from the backend every time the transaction is accepted into the mempool, the pending block
+/// is stored, a block is closed or the last block confirmed on L1 changes. A transaction which is neither in the
+/// mempool nor in a block is not reported until it shows up in one of them.
+pub async fn subscribe_transaction_status(
+    starknet: &Starknet,
+    sink: SubscriptionSink,
+    transaction_hash: Felt,
+) -> Result<(), StarknetWsApiError> {
+    // Subscribe before reading the status, so that no change is missed in between.
+    let mut rx_pending = starknet.backend.subscribe_pending_block();
+    let mut rx_block = starknet.backend.subscribe_block_info();
+    let mut rx_l1 = starknet.backend.subscribe_l1_last_confirmed_block();
+    // Only sequencers have a mempool, full nodes forward the transactions.
+    let mut rx_mempool = starknet.mempool.as_ref().map(|mempool| mempool.subscribe_accepted_txs());
+
+    let mut last_status = None;
+    loop {
+        let status = transaction_status(starknet, transaction_hash)?;
+        if let Some(status) = status.filter(|status| last_status.as_ref() != Some(status)) {
+            let msg = jsonrpsee::SubscriptionMessage::from_json(&status).or_else_internal_server_error(|| {
+                format!("Failed to create response message for {transaction_hash:#x}")
+            })?;
+            if sink.send(msg).await.is_err() {
+                // Subscription closed.
+                return Ok(());
+            }
+            if status.status.finality_status == TxnStatus::AcceptedOnL1 {
+                return Ok(());
+            }
+            last_status = Some(status);
+        }
+
+        tokio::select! {
+            res = rx_pending.recv() => check_notification(res)?,
+            res = rx_block.recv() => check_notification(res)?,
+            res = rx_l1.recv() => check_notification(res)?,
+            res = async {
+                match rx_mempool.as_mut() {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => match res {
+                Ok(tx) if tx.tx_hash != transaction_hash => continue,
+                res => check_notification(res)?,
+            },
+            _ = sink.closed() => {
+                return Ok(())
+            }
+        }
+    }
+}
+
+fn transaction_status(starknet: &Starknet, transaction_hash: Felt) -> Result<Option<NewTxnStatus>, StarknetWsApiError> {
+    let backend = &starknet.backend;
+    let err = || format!("Failed to retrieve the status of transaction {transaction_hash:#x}");
+
+    let Some((block, tx_index)) = backend.find_tx_hash_block(&transaction_hash).or_else_internal_server_error(err)?
+    else {
+        if !backend.has_mempool_transaction(&transaction_hash).or_else_internal_server_error(err)? {
+            return Ok(None);
+        }
+        let status =
+            TxnStatusResult { finality_status: TxnStatus::Received, execution_status: None, failure_reason: None };
+        return Ok(Some(NewTxnStatus { transaction_hash, status, block_number: None }));
+    };
+
+    let receipt = block.inner.receipts.get(tx_index.0 as usize).ok_or_else_internal_server_error(err)?;
+    let (execution_status, failure_reason) = match receipt.execution_result() {
+        ExecutionResult::Succeeded => (TxnExecutionStatus::Succeeded, None),
+        ExecutionResult::Reverted { reason } => (TxnExecutionStatus::Reverted, Some(reason)),
+    };
+
+    let block_number = block.info.block_n();
+    let l1_last_confirmed = backend.get_l1_last_confirmed_block().or_else_internal_server_error(err)?;
+    let finality_status = match (block_number, l1_last_confirmed) {
+        (Some(block_n), Some(l1_last_confirmed)) if block_n <= l1_last_confirmed => TxnStatus::AcceptedOnL1,
+        _ => TxnStatus::AcceptedOnL2,
+    };
+
+    let status = TxnStatusResult { finality_status, execution_status: Some(execution_status), failure_reason };
+    Ok(Some(NewTxnStatus { transaction_hash, status, block_number }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use jsonrpsee::core::client::Subscription;
+    use jsonrpsee::ws_client::WsClientBuilder;
+    use mc_db::mempool_db::SavedTransaction;
+    use mc_db::MadaraBackend;
+    use mp_block::{
+        header::PendingHeader, Header, MadaraBlockInfo, MadaraBlockInner, MadaraMaybePendingBlock,
+        MadaraMaybePendingBlockInfo, MadaraPendingBlockInfo,
+    };
+    use mp_receipt::InvokeTransactionReceipt;
+    use mp_state_update::StateDiff;
+    use mp_transactions::InvokeTransactionV0;
+    use std::sync::Arc;
+
+    use crate::test_utils::rpc_test_setup;
+    use crate::versions::user::v0_8_0::{StarknetWsRpcApiV0_8_0Client, StarknetWsRpcApiV0_8_0Server};
+
+    async fn next_status(sub: &mut Subscription<NewTxnStatus>) -> NewTxnStatus {
+        sub.next().await.expect("Waiting for status").expect("Waiting for status")
+    }
+
+    fn block_inner(transaction_hash: Felt) -> MadaraBlockInner {
+        MadaraBlockInner {
+            transactions: vec![InvokeTransactionV0::default().into()],
+            receipts: vec![InvokeTransactionReceipt { transaction_hash, ..Default::default() }.into()],
+        }
+    }
+
+    #[tokio::test]
+    #[rstest::rstest]
+    async fn subscribe_transaction_status(rpc_test_setup: (Arc<MadaraBackend>, Starknet)) {
+        let (backend, starknet) = rpc_test_setup;
+        let server = jsonrpsee::server::Server::builder().build("127.0.0.1:0").await.expect("Starting server");
+        let server_url = format!("ws://{}", server.local_addr().expect("Retrieving server local address"));
+        // Server will be stopped once this is dropped
+        let _server_handle = server.start(StarknetWsRpcApiV0_8_0Server::into_rpc(starknet));
+        let client = WsClientBuilder::default().build(&server_url).await.expect("Building client");
+
+        let tx_hash = Felt::from(0x1234);
+        let saved_tx = SavedTransaction {
+            tx: InvokeTransactionV0::default().into(),
+            paid_fee_on_l1: None,
+            contract_address: None,
+            only_query: false,
+            arrived_at: 0,
+        };
+        backend.save_mempool_transaction(&saved_tx, tx_hash, &None).expect("Saving mempool transaction");
+
+        let mut sub = client.subscribe_transaction_status(tx_hash).await.expect("starknet_subscribeTransactionStatus");
+        let status = next_status(&mut sub).await;
+        assert_eq!((status.status.finality_status, status.status.execution_status), (TxnStatus::Received, None));
+
+        // Pending block
+        backend.remove_mempool_transaction(&tx_hash).expect("Removing mempool transaction");
+        backend
+            .store_block(
+                MadaraMaybePendingBlock {
+                    info: MadaraMaybePendingBlockInfo::Pending(MadaraPendingBlockInfo {
+                        header: PendingHeader::default(),
+                        tx_hashes: vec![tx_hash],
+                    }),
+                    inner: block_inner(tx_hash),
+                },
+                StateDiff::default(),
+                vec![],
+                None,
+                None,
+            )
+            .expect("Storing pending block");
+        let status = next_status(&mut sub).await;
+        assert_eq!(status.status.finality_status, TxnStatus::AcceptedOnL2);
+        assert_eq!(status.status.execution_status, Some(TxnExecutionStatus::Succeeded));
+        assert_eq!(status.block_number, None);
+
+        // Closed block
+        backend
+            .store_block(
+                MadaraMaybePendingBlock {
+                    info: MadaraMaybePendingBlockInfo::NotPending(MadaraBlockInfo {
+                        header: Header { block_number: 0, ..Default::default() },
+                        block_hash: Felt::ONE,
+                        tx_hashes: vec![tx_hash],
+                    }),
+                    inner: block_inner(tx_hash),
+                },
+                StateDiff::default(),
+                vec![],
+                None,
+                None,
+            )
+            .expect("Storing block");
+        let status = next_status(&mut sub).await;
+        assert_eq!((status.status.finality_status, status.block_number), (TxnStatus::AcceptedOnL2, Some(0)));
+
+        // Confirmed on L1
+        backend.write_last_confirmed_block(0).expect("Writing l1 last confirmed block");
+        let status = next_status(&mut sub).await;
+        assert_eq!((status.status.finality_status, status.block_number), (TxnStatus::AcceptedOnL1, Some(0)));
+    }
+}
@@ -1,3 +1,8 @@
+use mp_utils::parsers::{parse_duration, parse_url};
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
 /// Parameters used to config block production.
 #[derive(Clone, Debug, clap::Parser)]
 pub struct BlockProductionParams {
@@ -14,4 +19,51 @@ pub struct BlockProductionParams {
     /// Create this number of contracts in the genesis block for the devnet configuration.
     #[arg(env = "MADARA_DEVNET_CONTRACTS", long, default_value_t = 10)]
     pub devnet_contracts: u64,
+
+    /// Number of threads used to execute transactions during block production. With more than one worker,
+    /// transactions of a batch are executed optimistically in parallel, and re-executed on conflicts: the resulting
+    /// blocks are identical to sequential execution. Batches are `execution_batch_size` transactions long, see the
+    /// chain config.
+    #[arg(env = "MADARA_BLOCK_PRODUCTION_WORKERS", long, value_name = "N", default_value_t = 1)]
+    pub block_production_workers: usize,
+
+    /// Run as a hot-standby sequencer, coordinating through a lease file on storage shared with the other
+    /// sequencers of the chain. Only the lease holder produces blocks. The other sequencers follow the chain using
+    /// the L2 sync (point their feeder gateway URL to the active sequencer), and take over once the lease expires.
+    /// The node name is used to identify the lease holder: it must be unique.
+    ///
+    /// This requires `--sequencer-standby-rpc-url`: the mempool of the active sequencer is not persisted on the
+    /// shared storage, so without replication the transactions it has not yet included in a block would be lost on
+    /// takeover.
+    #[arg(env = "MADARA_SEQUENCER_LEASE_FILE", long, value_name = "PATH", requires = "sequencer_standby_rpc_url")]
+    pub sequencer_lease_file: Option<PathBuf>,
+
+    /// Admin RPC endpoints of the other sequencers sharing the lease, comma separated. While it holds the lease, this
+    /// sequencer replicates the transactions accepted into its mempool to them. A standby keeps these transactions
+    /// aside, and adds the ones which have not been included in a block yet to its mempool when it takes over.
+    #[arg(
+        env = "MADARA_SEQUENCER_STANDBY_RPC_URL",
+        long,
+        value_parser = parse_url,
+        value_name = "ADMIN RPC URL",
+        value_delimiter = ',',
+        requires = "sequencer_lease_file"
+    )]
+    pub sequencer_standby_rpc_url: Vec<Url>,
+
+    /// API key sent in the `x-api-key` header when replicating the mempool, for standby sequencers which require admin
+    /// RPC authentication (see `--rpc-admin-api-keys`). It needs the `madara_replicateMempoolTransactions` scope.
+    #[arg(env = "MADARA_SEQUENCER_REPLICATION_API_KEY", long, value_name = "KEY")]
+    pub sequencer_replication_api_key: Option<String>,
+
+    /// How long the sequencer lease stays valid without being renewed. This is how long block production is down
+    /// before a standby takes over when the active sequencer dies.
+    #[arg(env = "MADARA_SEQUENCER_LEASE_TTL", long, value_parser = parse_duration, default_value = "10s")]
+    pub sequencer_lease_ttl: Duration,
+
+    /// When a standby takes over, it first waits to sync the blocks closed by the previous sequencer. If the previous
+    /// sequencer died while closing a block, that block may never be available: after this delay, the standby takes
+    /// over without it.
+    #[arg(env = "MADARA_SEQUENCER_TAKEOVER_TIMEOUT", long, value_parser = parse_duration, default_value = "30s")]
+    pub sequencer_takeover_timeout: Duration,
 }
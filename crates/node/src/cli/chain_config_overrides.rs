@@ -11,7 +11,7 @@ use starknet_api::core::{ChainId, ContractAddress};
 use mp_block::H160;
 use mp_chain_config::{
     deserialize_bouncer_config, deserialize_starknet_version, serialize_bouncer_config, serialize_starknet_version,
-    ChainConfig, StarknetVersion,
+    AdmissionPolicy, BlockProductionMode, ChainConfig, ProtocolUpgradeSchedule, SettlementLayer, StarknetVersion,
 };
 use mp_utils::parsers::parse_key_value_yaml;
 use mp_utils::serde::{
@@ -38,15 +38,18 @@ pub struct ChainConfigOverridesInner {
     pub parent_fee_token_address: ContractAddress,
     #[serde(deserialize_with = "deserialize_starknet_version", serialize_with = "serialize_starknet_version")]
     pub latest_protocol_version: StarknetVersion,
+    pub protocol_upgrades: ProtocolUpgradeSchedule,
     #[serde(deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
     pub block_time: Duration,
     #[serde(deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
     pub pending_block_update_time: Duration,
+    pub block_production_mode: BlockProductionMode,
     pub execution_batch_size: usize,
     #[serde(deserialize_with = "deserialize_bouncer_config", serialize_with = "serialize_bouncer_config")]
     pub bouncer_config: BouncerConfig,
     pub sequencer_address: ContractAddress,
     pub eth_core_contract_address: H160,
+    pub settlement_layer: SettlementLayer,
     pub eth_gps_statement_verifier: H160,
     #[serde(default)]
     #[serde(skip_serializing)]
@@ -56,6 +59,7 @@ pub struct ChainConfigOverridesInner {
     pub mempool_declare_tx_limit: usize,
     #[serde(deserialize_with = "deserialize_optional_duration", serialize_with = "serialize_optional_duration")]
     pub mempool_tx_max_age: Option<Duration>,
+    pub mempool_admission_policy: AdmissionPolicy,
 }
 
 impl ChainConfigOverrideParams {
@@ -68,17 +72,21 @@ impl ChainConfigOverrideParams {
             native_fee_token_address: chain_config.native_fee_token_address,
             parent_fee_token_address: chain_config.parent_fee_token_address,
             latest_protocol_version: chain_config.latest_protocol_version,
+            protocol_upgrades: chain_config.protocol_upgrades,
             block_time: chain_config.block_time,
             pending_block_update_time: chain_config.pending_block_update_time,
+            block_production_mode: chain_config.block_production_mode,
             execution_batch_size: chain_config.execution_batch_size,
             bouncer_config: chain_config.bouncer_config,
             sequencer_address: chain_config.sequencer_address,
             eth_core_contract_address: chain_config.eth_core_contract_address,
+            settlement_layer: chain_config.settlement_layer,
             eth_gps_statement_verifier: chain_config.eth_gps_statement_verifier,
             private_key: chain_config.private_key,
             mempool_tx_limit: chain_config.mempool_tx_limit,
             mempool_declare_tx_limit: chain_config.mempool_declare_tx_limit,
             mempool_tx_max_age: chain_config.mempool_tx_max_age,
+            mempool_admission_policy: chain_config.mempool_admission_policy,
             feeder_gateway_url: chain_config.feeder_gateway_url,
             gateway_url: chain_config.gateway_url,
         })
@@ -121,18 +129,22 @@ impl ChainConfigOverrideParams {
             native_fee_token_address: chain_config_overrides.native_fee_token_address,
             parent_fee_token_address: chain_config_overrides.parent_fee_token_address,
             latest_protocol_version: chain_config_overrides.latest_protocol_version,
+            protocol_upgrades: chain_config_overrides.protocol_upgrades,
             block_time: chain_config_overrides.block_time,
             pending_block_update_time: chain_config_overrides.pending_block_update_time,
+            block_production_mode: chain_config_overrides.block_production_mode,
             execution_batch_size: chain_config_overrides.execution_batch_size,
             bouncer_config: chain_config_overrides.bouncer_config,
             sequencer_address: chain_config_overrides.sequencer_address,
             eth_core_contract_address: chain_config_overrides.eth_core_contract_address,
+            settlement_layer: chain_config_overrides.settlement_layer,
             versioned_constants,
             eth_gps_statement_verifier: chain_config_overrides.eth_gps_statement_verifier,
             private_key: chain_config_overrides.private_key,
             mempool_tx_limit: chain_config_overrides.mempool_tx_limit,
             mempool_declare_tx_limit: chain_config_overrides.mempool_declare_tx_limit,
             mempool_tx_max_age: chain_config_overrides.mempool_tx_max_age,
+            mempool_admission_policy: chain_config_overrides.mempool_admission_policy,
         })
     }
 }
@@ -1,8 +1,13 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use url::Url;
 
+use mc_eth::failover::FailoverConfig;
+use mc_eth::l1_gas_price_strategy::{GasPriceSmoothing, GasPriceStrategy, L1GasPriceStrategy};
+use mc_eth::l1_handler_policy::{L1HandlerPolicy, MessageRoute};
 use mp_utils::parsers::{parse_duration, parse_url};
+use starknet_types_core::felt::Felt;
 
 #[derive(Clone, Debug, clap::Args)]
 pub struct L1SyncParams {
@@ -10,9 +15,33 @@ pub struct L1SyncParams {
     #[clap(env = "MADARA_SYNC_L1_DISABLED", long, alias = "no-l1-sync", conflicts_with = "l1_endpoint")]
     pub l1_sync_disabled: bool,
 
-    /// The L1 rpc endpoint url for state verification.
-    #[clap(env = "MADARA_L1_ENDPOINT", long, value_parser = parse_url, value_name = "ETHEREUM RPC URL")]
-    pub l1_endpoint: Option<Url>,
+    /// The L1 rpc endpoint url for state verification. Several comma-separated urls can be given: the first one is
+    /// preferred, and the others are used when it is unavailable or lagging behind.
+    #[clap(
+        env = "MADARA_L1_ENDPOINT",
+        long,
+        value_parser = parse_url,
+        value_name = "ETHEREUM RPC URL",
+        value_delimiter = ','
+    )]
+    pub l1_endpoint: Vec<Url>,
+
+    /// How often the health of the L1 endpoints is checked, when there are several.
+    #[clap(env = "MADARA_L1_HEALTH_CHECK_INTERVAL", long, default_value = "10s", value_parser = parse_duration)]
+    pub l1_health_check_interval: Duration,
+
+    /// An L1 endpoint more than this many blocks behind the most advanced one is considered unhealthy.
+    #[clap(env = "MADARA_L1_MAX_BLOCK_LAG", long, default_value_t = 5)]
+    pub l1_max_block_lag: u64,
+
+    /// Number of L1 endpoints which must agree on the state of the core contract (state root, block number and
+    /// block hash) for it to be used.
+    #[clap(env = "MADARA_L1_STATE_READ_QUORUM", long, default_value_t = 1)]
+    pub l1_state_read_quorum: usize,
+
+    /// An L1 request taking longer than this is an endpoint failure: it is retried on the other L1 endpoints.
+    #[clap(env = "MADARA_L1_REQUEST_TIMEOUT", long, default_value = "30s", value_parser = parse_duration)]
+    pub l1_request_timeout: Duration,
 
     /// Fix the gas price. If the gas price is fixed it won't fetch the fee history from the ethereum.
     #[clap(env = "MADARA_GAS_PRICE", long, alias = "gas-price")]
@@ -30,13 +59,10 @@ pub struct L1SyncParams {
     #[clap(env = "MADARA_STRK_DATA_GAS_PRICE", long, alias = "strk-blob-gas-price")]
     pub strk_blob_gas_price: Option<u64>,
 
-    /// Oracle API url.
-    #[clap(env = "ORACLE_URL", long, alias = "oracle-url")]
-    pub oracle_url: Option<Url>,
-
-    /// Oracle API key.
-    #[clap(env = "ORACLE_API_KEY", long, alias = "oracle-api-key")]
-    pub oracle_api_key: Option<String>,
+    /// Path to a YAML file selecting the oracles used for the ETH/STRK price: `pragma`, `json_http` or `fixed`
+    /// oracles, combined with `median` and `fallback` aggregators.
+    #[clap(env = "MADARA_ORACLE_CONFIG", long, value_name = "PATH")]
+    pub oracle_config: Option<PathBuf>,
 
     /// Time in which the gas price worker will fetch the gas price.
     #[clap(
@@ -46,4 +72,147 @@ pub struct L1SyncParams {
         value_parser = parse_duration,
     )]
     pub gas_price_poll: Duration,
+
+    /// How the L1 base fees are smoothed into the L1 gas price: `last`, `average`, `ema` (exponential moving average),
+    /// or a percentile such as `p75`.
+    #[clap(env = "MADARA_L1_GAS_PRICE_SMOOTHING", long, value_name = "SMOOTHING", default_value = "last")]
+    pub l1_gas_price_smoothing: GasPriceSmoothing,
+
+    /// Number of L1 blocks the L1 gas price smoothing is computed over.
+    #[clap(env = "MADARA_L1_GAS_PRICE_WINDOW", long, value_name = "L1 BLOCKS", default_value_t = 1)]
+    pub l1_gas_price_window: u64,
+
+    /// Markup added to the smoothed L1 gas price, in percent.
+    #[clap(env = "MADARA_L1_GAS_PRICE_MARKUP", long, value_name = "PERCENT", default_value_t = 0)]
+    pub l1_gas_price_markup: u64,
+
+    /// Lower bound of the L1 gas price, in wei.
+    #[clap(env = "MADARA_L1_GAS_PRICE_MIN", long, value_name = "WEI")]
+    pub l1_gas_price_min: Option<u128>,
+
+    /// Upper bound of the L1 gas price, in wei.
+    #[clap(env = "MADARA_L1_GAS_PRICE_MAX", long, value_name = "WEI")]
+    pub l1_gas_price_max: Option<u128>,
+
+    /// Maximum change of the L1 gas price between two updates, in percent.
+    #[clap(env = "MADARA_L1_GAS_PRICE_MAX_CHANGE", long, value_name = "PERCENT")]
+    pub l1_gas_price_max_change: Option<u64>,
+
+    /// How the L1 blob base fees are smoothed into the L1 data gas price: `last`, `average`, `ema` (exponential moving
+    /// average), or a percentile such as `p75`.
+    #[clap(env = "MADARA_L1_DATA_GAS_PRICE_SMOOTHING", long, value_name = "SMOOTHING", default_value = "average")]
+    pub l1_data_gas_price_smoothing: GasPriceSmoothing,
+
+    /// Number of L1 blocks the L1 data gas price smoothing is computed over.
+    #[clap(env = "MADARA_L1_DATA_GAS_PRICE_WINDOW", long, value_name = "L1 BLOCKS", default_value_t = 300)]
+    pub l1_data_gas_price_window: u64,
+
+    /// Markup added to the smoothed L1 data gas price, in percent.
+    #[clap(env = "MADARA_L1_DATA_GAS_PRICE_MARKUP", long, value_name = "PERCENT", default_value_t = 0)]
+    pub l1_data_gas_price_markup: u64,
+
+    /// Lower bound of the L1 data gas price, in wei.
+    #[clap(env = "MADARA_L1_DATA_GAS_PRICE_MIN", long, value_name = "WEI")]
+    pub l1_data_gas_price_min: Option<u128>,
+
+    /// Upper bound of the L1 data gas price, in wei.
+    #[clap(env = "MADARA_L1_DATA_GAS_PRICE_MAX", long, value_name = "WEI")]
+    pub l1_data_gas_price_max: Option<u128>,
+
+    /// Maximum change of the L1 data gas price between two updates, in percent.
+    #[clap(env = "MADARA_L1_DATA_GAS_PRICE_MAX_CHANGE", long, value_name = "PERCENT")]
+    pub l1_data_gas_price_max_change: Option<u64>,
+
+    /// Private key of the L1 account settling the blocks we produce, using `updateState` transactions on the core
+    /// contract. Settlement is disabled when not set. Requires the node to run as a sequencer.
+    ///
+    /// State diffs are always published as calldata: blob data availability is not supported by the settlement.
+    #[clap(env = "MADARA_L1_SETTLEMENT_PRIVATE_KEY", long, value_name = "PRIVATE KEY", hide_env_values = true)]
+    pub l1_settlement_private_key: Option<String>,
+
+    /// Number of L1 blocks, including the one with the settlement transaction, before a block is considered settled.
+    #[clap(env = "MADARA_L1_SETTLEMENT_CONFIRMATIONS", long, default_value_t = 6)]
+    pub l1_settlement_confirmations: u64,
+
+    /// Replace a settlement transaction with higher fees when it has not been included after this many L1 blocks.
+    #[clap(env = "MADARA_L1_SETTLEMENT_REPLACE_AFTER", long, value_name = "L1 BLOCKS", default_value_t = 5)]
+    pub l1_settlement_replace_after: u64,
+
+    /// Fee increase of replacement settlement transactions, in percent. At least 10.
+    #[clap(env = "MADARA_L1_SETTLEMENT_FEE_BUMP", long, value_name = "PERCENT", default_value_t = 20)]
+    pub l1_settlement_fee_bump: u64,
+
+    /// Upper bound for the max fee per gas of settlement transactions, in wei.
+    #[clap(env = "MADARA_L1_SETTLEMENT_MAX_FEE_PER_GAS", long, value_name = "WEI")]
+    pub l1_settlement_max_fee_per_gas: Option<u128>,
+
+    /// Starknet OS program hash written in the settled program outputs. Defaults to the program hash registered on the
+    /// core contract.
+    #[clap(env = "MADARA_L1_SETTLEMENT_OS_PROGRAM_HASH", long, value_name = "HASH")]
+    pub l1_settlement_os_program_hash: Option<Felt>,
+
+    /// Starknet OS config hash written in the settled program outputs. Defaults to the config hash registered on the
+    /// core contract.
+    #[clap(env = "MADARA_L1_SETTLEMENT_OS_CONFIG_HASH", long, value_name = "HASH")]
+    pub l1_settlement_os_config_hash: Option<Felt>,
+
+    /// Reject L1 to L2 messages paying less than this percentage of the fee of their L1 handler transaction, estimated
+    /// on top of the pending block like `starknet_estimateMessageFee`. Messages whose fee cannot be estimated are
+    /// rejected as well. Rejected messages are never retried. 0 disables the check.
+    #[clap(env = "MADARA_L1_HANDLER_MIN_FEE_PERCENT", long, default_value_t = 0)]
+    pub l1_handler_min_fee_percent: u64,
+
+    /// Only accept L1 to L2 messages sent by these settlement layer contracts, as comma-separated `<l1 address>` or
+    /// `<l1 address>:<l2 address>` to also restrict the L2 contract they are sent to. This applies to both Ethereum and
+    /// Starknet settlement layers. All messages are accepted when unset.
+    #[clap(env = "MADARA_L1_HANDLER_ALLOWED_ROUTES", long, value_name = "ROUTES", value_delimiter = ',')]
+    pub l1_handler_allowed_routes: Option<Vec<MessageRoute>>,
+
+    /// How often the settlement worker checks the inflight transaction and looks for new blocks to settle.
+    #[clap(
+        env = "MADARA_L1_SETTLEMENT_POLL",
+        long,
+        default_value = "12s",
+        value_parser = parse_duration,
+    )]
+    pub l1_settlement_poll: Duration,
+}
+
+impl L1SyncParams {
+    pub fn l1_handler_policy(&self) -> L1HandlerPolicy {
+        L1HandlerPolicy {
+            min_fee_percent: self.l1_handler_min_fee_percent,
+            allowed_routes: self.l1_handler_allowed_routes.clone(),
+        }
+    }
+
+    pub fn failover_config(&self) -> FailoverConfig {
+        FailoverConfig {
+            health_check_interval: self.l1_health_check_interval,
+            max_block_lag: self.l1_max_block_lag,
+            state_read_quorum: self.l1_state_read_quorum,
+            request_timeout: self.l1_request_timeout,
+        }
+    }
+
+    pub fn gas_price_strategy(&self) -> L1GasPriceStrategy {
+        L1GasPriceStrategy {
+            gas: GasPriceStrategy {
+                smoothing: self.l1_gas_price_smoothing,
+                window: self.l1_gas_price_window,
+                markup_percent: self.l1_gas_price_markup,
+                min: self.l1_gas_price_min,
+                max: self.l1_gas_price_max,
+                max_change_percent: self.l1_gas_price_max_change,
+            },
+            data_gas: GasPriceStrategy {
+                smoothing: self.l1_data_gas_price_smoothing,
+                window: self.l1_data_gas_price_window,
+                markup_percent: self.l1_data_gas_price_markup,
+                min: self.l1_data_gas_price_min,
+                max: self.l1_data_gas_price_max,
+                max_change_percent: self.l1_data_gas_price_max_change,
+            },
+        }
+    }
 }
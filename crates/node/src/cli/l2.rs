@@ -4,6 +4,7 @@ use mc_sync::fetch::fetchers::WarpUpdateConfig;
 use mp_chain_config::ChainConfig;
 use starknet_api::core::ChainId;
 
+use mc_eth::da_sync::DaSyncConfig;
 use mc_sync::fetch::fetchers::FetchConfig;
 use mp_utils::parsers::{parse_duration, parse_url};
 use url::Url;
@@ -144,6 +145,24 @@ pub struct L2SyncParams {
         value_parser = clap::value_parser!(u8).range(1..)
     )]
     pub sync_parallelism: u8,
+
+    /// Reconstruct the state from the data availability posted on L1 instead of syncing from a gateway. Only the
+    /// state diffs are posted on L1: blocks are stored without their transactions and receipts.
+    #[clap(env = "MADARA_L1_DA_SYNC", long)]
+    pub l1_da_sync: bool,
+
+    /// Endpoint serving the blobs of the state updates using blob data availability, at `<URL>/blobs/<versioned hash>`.
+    #[clap(env = "MADARA_L1_DA_BLOB_ENDPOINT", long, value_parser = parse_url, value_name = "URL")]
+    pub l1_da_blob_endpoint: Option<Url>,
+
+    /// Timeout of the requests to the blob endpoint.
+    #[clap(env = "MADARA_L1_DA_BLOB_TIMEOUT", long, default_value = "30s", value_parser = parse_duration)]
+    pub l1_da_blob_timeout: Duration,
+
+    /// L1 block from which the state updates are looked for when reconstructing the state from L1, usually the
+    /// deployment block of the core contract.
+    #[clap(env = "MADARA_L1_DA_FROM_BLOCK", long, value_name = "L1 BLOCK", default_value_t = 0)]
+    pub l1_da_from_block: u64,
 }
 
 impl L2SyncParams {
@@ -178,4 +197,14 @@ impl L2SyncParams {
             warp_update,
         }
     }
+
+    pub fn da_sync_config(&self) -> DaSyncConfig {
+        DaSyncConfig {
+            blob_endpoint: self.l1_da_blob_endpoint.clone(),
+            blob_request_timeout: self.l1_da_blob_timeout,
+            from_l1_block: self.l1_da_from_block,
+            poll_interval: self.sync_polling_interval,
+            ..Default::default()
+        }
+    }
 }
@@ -1,9 +1,10 @@
 use std::convert::Infallible;
 use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use jsonrpsee::server::BatchRequestConfig;
-use mc_rpc::StorageProofConfig;
+use mc_rpc::{StorageProofConfig, TraceCacheConfig};
 
 /// The default port.
 pub const RPC_DEFAULT_PORT: u16 = 9944;
@@ -20,6 +21,11 @@ pub const RPC_DEFAULT_MAX_CONNECTIONS: u32 = 100;
 /// The default number of messages the RPC server
 /// is allowed to keep in memory per connection.
 pub const RPC_DEFAULT_MESSAGE_CAPACITY_PER_CONN: u32 = 64;
+/// The default number of seconds of unused weight a client can save up when rate limited.
+pub const RPC_DEFAULT_RATE_LIMIT_BURST_SECS: u32 = 10;
+
+/// The default size of the transaction traces kept in memory, in MiB.
+pub const RPC_DEFAULT_TRACE_CACHE_SIZE_MIB: usize = 64;
 
 #[derive(Clone, Debug)]
 pub enum Cors {
@@ -78,6 +84,21 @@ pub struct RpcParams {
     #[arg(env = "MADARA_RPC_ADMIN_EXTERNAL", long, default_value_t = false)]
     pub rpc_admin_external: bool,
 
+    /// Require admin RPC clients to authenticate with a JWT signed with the hex encoded secret in this file, sent as
+    /// an `Authorization: Bearer <TOKEN>` header. Tokens are signed with HS256 and must have an `iat` claim within 60
+    /// seconds of the node time, as for the Ethereum engine API. An optional `scope` claim restricts the methods the
+    /// token can call, as a space separated list of method names or prefixes ending with `*`, such as
+    /// `"madara_ping madara_service"` or `"madara_devnet*"`.
+    #[arg(env = "MADARA_RPC_ADMIN_JWT_SECRET", long, value_name = "PATH")]
+    pub rpc_admin_jwt_secret: Option<PathBuf>,
+
+    /// Require admin RPC clients to authenticate with one of the API keys in this file, sent as an `x-api-key`
+    /// header. The file has one key per line, optionally followed by the whitespace separated scopes of the key, in
+    /// the same format as the `scope` claim of `--rpc-admin-jwt-secret` tokens. Keys without scopes can call every
+    /// method. This can be combined with `--rpc-admin-jwt-secret`.
+    #[arg(env = "MADARA_RPC_ADMIN_API_KEYS", long, value_name = "PATH")]
+    pub rpc_admin_api_keys: Option<PathBuf>,
+
     /// Set the maximum RPC request payload size for both HTTP and WebSockets in megabytes.
     #[arg(env = "MADARA_RPC_MAX_REQUEST_SIZE", long, default_value_t = RPC_DEFAULT_MAX_REQUEST_SIZE_MB)]
     pub rpc_max_request_size: u32,
@@ -156,6 +177,57 @@ pub struct RpcParams {
     /// storage is queried count as one each.
     #[arg(env = "MADARA_RPC_STORAGE_PROOF_MAX_TRIES", long, default_value_t = 5)]
     pub rpc_storage_proof_max_tries: usize,
+
+    /// Limit the total weight of the calls each client can make to the user RPC endpoint, per second. Disabled by
+    /// default.
+    ///
+    /// Every method has a weight, from 1 for simple lookups up to 100 for `starknet_traceBlockTransactions`. Clients
+    /// are identified by their IP address, or by their API key (see `--rpc-rate-limit-api-keys`). Calls over the
+    /// limit are rejected with a JSON-RPC error (code -32005) telling the client when to retry.
+    #[arg(env = "MADARA_RPC_RATE_LIMIT", long, value_name = "WEIGHT")]
+    pub rpc_rate_limit: Option<u32>,
+
+    /// How many seconds of unused weight a client can save up, to absorb short bursts of calls.
+    #[arg(env = "MADARA_RPC_RATE_LIMIT_BURST", long, value_name = "SECONDS", default_value_t = RPC_DEFAULT_RATE_LIMIT_BURST_SECS)]
+    pub rpc_rate_limit_burst: u32,
+
+    /// API keys with their own rate limit, as a comma separated list of `KEY=WEIGHT` entries. Clients sending one of
+    /// these keys in the `x-api-key` header are limited to `WEIGHT` per second, whatever their IP address.
+    #[arg(env = "MADARA_RPC_RATE_LIMIT_API_KEYS", long, value_name = "KEY=WEIGHT", value_delimiter = ',', value_parser = parse_key_value)]
+    pub rpc_rate_limit_api_keys: Vec<(String, u32)>,
+
+    /// Override the weight of RPC methods, as a comma separated list of `METHOD=WEIGHT` entries, such as
+    /// `starknet_getEvents=50`. Weights apply to every RPC version.
+    #[arg(env = "MADARA_RPC_RATE_LIMIT_METHOD_WEIGHTS", long, value_name = "METHOD=WEIGHT", value_delimiter = ',', value_parser = parse_key_value)]
+    pub rpc_rate_limit_method_weights: Vec<(String, u32)>,
+
+    /// Identify clients by the first address in this HTTP header instead of the address of the connection, such as
+    /// `x-forwarded-for` when running behind a reverse proxy. Only set this if the proxy overwrites the header,
+    /// otherwise clients can pick their own address.
+    #[arg(env = "MADARA_RPC_RATE_LIMIT_IP_HEADER", long, value_name = "HEADER")]
+    pub rpc_rate_limit_ip_header: Option<String>,
+
+    /// Size of the transaction traces of closed blocks kept in memory, in MiB, so that `starknet_traceTransaction`
+    /// and `starknet_traceBlockTransactions` do not re-execute their block every time. Traces are measured by the
+    /// size of their JSON encoding. Set to 0 to disable.
+    #[arg(env = "MADARA_RPC_TRACE_CACHE_SIZE", long, value_name = "MIB", default_value_t = RPC_DEFAULT_TRACE_CACHE_SIZE_MIB)]
+    pub rpc_trace_cache_size: usize,
+
+    /// Also store the transaction traces of the latest `BLOCKS` blocks in the database, so that they survive
+    /// restarts. Traces of older blocks are pruned. Disabled by default.
+    #[arg(env = "MADARA_RPC_TRACE_CACHE_PERSIST_BLOCKS", long, value_name = "BLOCKS")]
+    pub rpc_trace_cache_persist_blocks: Option<u64>,
+
+    /// Trace every block as soon as it is imported, so that its traces are cached before they are requested. This
+    /// costs one re-execution of every block.
+    #[arg(env = "MADARA_RPC_TRACE_CACHE_EAGER", long, default_value_t = false)]
+    pub rpc_trace_cache_eager: bool,
+}
+
+fn parse_key_value(s: &str) -> Result<(String, u32), String> {
+    let (key, value) = s.split_once('=').ok_or_else(|| format!("Expected `KEY=VALUE`, got `{s}`"))?;
+    let value = value.trim().parse().map_err(|err| format!("Invalid value in `{s}`: {err}"))?;
+    Ok((key.trim().to_string(), value))
 }
 
 impl RpcParams {
@@ -209,6 +281,13 @@ impl RpcParams {
         }
     }
 
+    pub fn trace_cache_config(&self) -> TraceCacheConfig {
+        TraceCacheConfig {
+            max_bytes: self.rpc_trace_cache_size.saturating_mul(1024 * 1024),
+            persist_blocks: self.rpc_trace_cache_persist_blocks,
+        }
+    }
+
     pub fn storage_proof_config(&self) -> StorageProofConfig {
         StorageProofConfig {
             max_keys: self.rpc_storage_proof_max_keys,
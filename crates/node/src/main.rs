@@ -17,7 +17,8 @@ use mc_mempool::{GasPriceProvider, L1DataProvider, Mempool, MempoolLimits};
 use mc_rpc::providers::{AddTransactionProvider, ForwardToProvider, MempoolAddTxProvider};
 use mc_sync::fetch::fetchers::WarpUpdateConfig;
 use mc_telemetry::{SysInfo, TelemetryService};
-use mp_oracle::pragma::PragmaOracleBuilder;
+use mp_chain_config::SettlementLayer;
+use mp_oracle::OracleConfig;
 use mp_utils::service::{MadaraServiceId, ServiceMonitor};
 use service::{BlockProductionService, GatewayService, L1SyncService, L2SyncService, RpcService};
 use std::sync::Arc;
@@ -123,19 +124,15 @@ async fn main() -> anyhow::Result<()> {
         l1_gas_setter.update_strk_l1_data_gas_price(strk_fix_blob_gas as u128);
         l1_gas_setter.set_strk_data_gas_price_sync_enabled(false);
     }
-    if let Some(ref oracle_url) = run_cmd.l1_sync_params.oracle_url {
-        if let Some(ref oracle_api_key) = run_cmd.l1_sync_params.oracle_api_key {
-            let oracle = PragmaOracleBuilder::new()
-                .with_api_url(oracle_url.clone())
-                .with_api_key(oracle_api_key.clone())
-                .build();
-            l1_gas_setter.set_oracle_provider(oracle);
-        }
+    if let Some(ref oracle_config) = run_cmd.l1_sync_params.oracle_config {
+        let oracle = OracleConfig::from_yaml(oracle_config).context("Loading the oracle config")?;
+        l1_gas_setter.set_oracle_provider(oracle);
     }
 
     if !run_cmd.full
         && !run_cmd.devnet
         && !run_cmd.l1_sync_params.l1_sync_disabled
+        && chain_config.settlement_layer == SettlementLayer::Ethereum
         && l1_gas_setter.is_oracle_needed()
         && l1_gas_setter.oracle_provider.is_none()
     {
@@ -159,6 +156,7 @@ async fn main() -> anyhow::Result<()> {
         l1_gas_setter,
         chain_config.chain_id.clone(),
         chain_config.eth_core_contract_address,
+        &chain_config.settlement_layer,
         run_cmd.is_sequencer(),
         run_cmd.is_devnet(),
         Arc::clone(&mempool),
@@ -217,6 +215,7 @@ async fn main() -> anyhow::Result<()> {
         importer,
         service_telemetry.new_handle(),
         warp_update,
+        service_l1_sync.eth_client(),
     )
     .await
     .context("Initializing sync service")?;
@@ -243,11 +242,13 @@ async fn main() -> anyhow::Result<()> {
         Arc::clone(&mempool),
         importer,
         Arc::clone(&l1_data_provider),
+        &node_name,
     )?;
 
     // Add transaction provider
     let add_tx_provider_l2_sync: Arc<dyn AddTransactionProvider> = Arc::new(ForwardToProvider::new(provider));
-    let add_tx_provider_mempool: Arc<dyn AddTransactionProvider> = Arc::new(MempoolAddTxProvider::new(mempool));
+    let add_tx_provider_mempool: Arc<dyn AddTransactionProvider> =
+        Arc::new(MempoolAddTxProvider::new(Arc::clone(&mempool)));
 
     // User-facing RPC
 
@@ -265,6 +266,10 @@ async fn main() -> anyhow::Result<()> {
         Arc::clone(service_db.backend()),
         Arc::clone(&add_tx_provider_l2_sync),
         Arc::clone(&add_tx_provider_mempool),
+        Arc::clone(&mempool),
+        service_block_production.handle(),
+        run_cmd.is_devnet(),
+        analytics.log_filter(),
     );
 
     // Feeder gateway
@@ -304,7 +309,7 @@ async fn main() -> anyhow::Result<()> {
     // app.activate(MadaraService::Database);
 
     let l1_sync_enabled = !run_cmd.l1_sync_params.l1_sync_disabled;
-    let l1_endpoint_some = run_cmd.l1_sync_params.l1_endpoint.is_some();
+    let l1_endpoint_some = !run_cmd.l1_sync_params.l1_endpoint.is_empty();
     let warp_update_receiver = run_cmd.args_preset.warp_update_receiver;
 
     if l1_sync_enabled && (l1_endpoint_some || !run_cmd.devnet) {
@@ -315,6 +320,10 @@ async fn main() -> anyhow::Result<()> {
         app.activate(MadaraServiceId::L2Sync);
     } else if run_cmd.is_sequencer() {
         app.activate(MadaraServiceId::BlockProduction);
+        // Hot-standby sequencers follow the active sequencer until they acquire the lease.
+        if run_cmd.block_production_params.sequencer_lease_file.is_some() {
+            app.activate(MadaraServiceId::L2Sync);
+        }
     } else if !run_cmd.l2_sync_params.l2_sync_disabled {
         app.activate(MadaraServiceId::L2Sync);
     }
@@ -1,12 +1,27 @@
 use crate::cli::block_production::BlockProductionParams;
 use anyhow::Context;
 use mc_block_import::{BlockImporter, BlockValidationContext};
-use mc_block_production::{metrics::BlockProductionMetrics, BlockProductionTask};
+use mc_block_production::{
+    block_production_channel, lease::FileLease, metrics::BlockProductionMetrics, BlockProductionCommands,
+    BlockProductionHandle, BlockProductionTask,
+};
 use mc_db::{DatabaseService, MadaraBackend};
 use mc_devnet::{ChainGenesisDescription, DevnetKeys};
-use mc_mempool::{L1DataProvider, Mempool};
-use mp_utils::service::{MadaraServiceId, PowerOfTwo, Service, ServiceId, ServiceRunner};
-use std::{io::Write, sync::Arc};
+use mc_mempool::{L1DataProvider, Mempool, ReplicatedTransaction};
+use mp_utils::service::{MadaraServiceId, PowerOfTwo, Service, ServiceContext, ServiceId, ServiceRunner};
+use opentelemetry::KeyValue;
+use std::{collections::VecDeque, io::Write, path::PathBuf, sync::Arc, time::Duration};
+use tokio::sync::{broadcast, mpsc};
+use url::Url;
+
+/// Maximum number of transactions sent to the standby sequencers in a single request.
+const REPLICATION_BATCH_SIZE: usize = 64;
+/// Maximum number of transactions waiting to be replicated to a standby sequencer. Past this, the oldest are dropped,
+/// as they are the most likely to have been included in a block already.
+const REPLICATION_QUEUE_SIZE: usize = 10_000;
+/// Delay before retrying to replicate a batch to a standby sequencer.
+const REPLICATION_RETRY_DELAY: Duration = Duration::from_secs(1);
+const REPLICATION_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct BlockProductionService {
     backend: Arc<MadaraBackend>,
@@ -15,6 +30,20 @@ pub struct BlockProductionService {
     metrics: Arc<BlockProductionMetrics>,
     l1_data_provider: Arc<dyn L1DataProvider>,
     n_devnet_contracts: u64,
+    n_workers: usize,
+    handle: BlockProductionHandle,
+    commands: BlockProductionCommands,
+    lease: Option<LeaseConfig>,
+}
+
+#[derive(Clone)]
+struct LeaseConfig {
+    path: PathBuf,
+    holder: String,
+    ttl: Duration,
+    takeover_timeout: Duration,
+    standby_rpc_urls: Vec<Url>,
+    replication_api_key: Option<String>,
 }
 
 impl BlockProductionService {
@@ -25,8 +54,10 @@ impl BlockProductionService {
         mempool: Arc<mc_mempool::Mempool>,
         block_import: Arc<BlockImporter>,
         l1_data_provider: Arc<dyn L1DataProvider>,
+        node_name: &str,
     ) -> anyhow::Result<Self> {
         let metrics = Arc::new(BlockProductionMetrics::register());
+        let (handle, commands) = block_production_channel();
 
         Ok(Self {
             backend: Arc::clone(db_service.backend()),
@@ -35,8 +66,24 @@ impl BlockProductionService {
             metrics,
             block_import,
             n_devnet_contracts: config.devnet_contracts,
+            n_workers: config.block_production_workers,
+            handle,
+            commands,
+            lease: config.sequencer_lease_file.clone().map(|path| LeaseConfig {
+                path,
+                holder: node_name.to_string(),
+                ttl: config.sequencer_lease_ttl,
+                takeover_timeout: config.sequencer_takeover_timeout,
+                standby_rpc_urls: config.sequencer_standby_rpc_url.clone(),
+                replication_api_key: config.sequencer_replication_api_key.clone(),
+            }),
         })
     }
+
+    /// A handle to send commands to the block production task, such as manually closing the pending block.
+    pub fn handle(&self) -> BlockProductionHandle {
+        self.handle.clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -44,7 +91,11 @@ impl Service for BlockProductionService {
     // TODO(cchudant,2024-07-30): special threading requirements for the block production task
     #[tracing::instrument(skip(self, runner), fields(module = "BlockProductionService"))]
     async fn start<'a>(&mut self, runner: ServiceRunner<'a>) -> anyhow::Result<()> {
-        let Self { backend, l1_data_provider, mempool, metrics, block_import, .. } = self;
+        if let Some(lease) = self.lease.clone() {
+            return self.start_standby(runner, lease);
+        }
+
+        let Self { backend, l1_data_provider, mempool, metrics, block_import, n_workers, commands, .. } = self;
 
         let block_production_task = BlockProductionTask::new(
             Arc::clone(backend),
@@ -52,7 +103,9 @@ impl Service for BlockProductionService {
             Arc::clone(mempool),
             Arc::clone(metrics),
             Arc::clone(l1_data_provider),
-        )?;
+        )?
+        .with_execution_workers(*n_workers)
+        .with_commands(commands.clone());
 
         runner.service_loop(move |ctx| block_production_task.block_production_task(ctx));
 
@@ -68,6 +121,63 @@ impl ServiceId for BlockProductionService {
 }
 
 impl BlockProductionService {
+    /// Hot-standby mode: the block production task is only created once the sequencer lease has been acquired. Until
+    /// then, the node follows the active sequencer through the L2 sync, which also forwards the transactions it
+    /// receives to the active sequencer. The active sequencer replicates its mempool to the standbys, see
+    /// [`replicate_mempool`].
+    fn start_standby(&self, runner: ServiceRunner<'_>, lease: LeaseConfig) -> anyhow::Result<()> {
+        let backend = Arc::clone(&self.backend);
+        let block_import = Arc::clone(&self.block_import);
+        let mempool = Arc::clone(&self.mempool);
+        let metrics = Arc::clone(&self.metrics);
+        let l1_data_provider = Arc::clone(&self.l1_data_provider);
+        let n_workers = self.n_workers;
+        let commands = self.commands.clone();
+
+        runner.service_loop(move |mut ctx| async move {
+            let LeaseConfig { path, holder, ttl, takeover_timeout, standby_rpc_urls, replication_api_key } = lease;
+            let mut lease = FileLease::new(path, holder, ttl);
+            if !lease.wait_for_takeover(&backend, &mut ctx, takeover_timeout).await? {
+                return anyhow::Ok(());
+            }
+
+            // Stop following the previous sequencer: we are producing the blocks now. The pending block synced from
+            // the previous sequencer is picked up by the block production task, its transactions are re-added to the
+            // mempool.
+            ctx.service_remove(MadaraServiceId::L2Sync);
+
+            // Subscribe before adding the transactions replicated from the previous sequencer, so that they are
+            // replicated to the remaining standbys.
+            let accepted_txs = mempool.subscribe_accepted_txs();
+            let n_replicated =
+                mempool.promote_replicated_txs().context("Adding replicated transactions to the mempool")?;
+            tracing::info!(
+                "📥 Added {n_replicated} transactions replicated from the previous sequencer to the mempool"
+            );
+
+            // Replication runs in its own task: failing to replicate must not stop block production.
+            let replication = tokio::spawn(replicate_mempool(
+                accepted_txs,
+                standby_rpc_urls,
+                replication_api_key,
+                Arc::clone(&metrics),
+                ctx.clone(),
+            ));
+
+            let block_production_task =
+                BlockProductionTask::new(backend, block_import, mempool, metrics, l1_data_provider)?
+                    .with_execution_workers(n_workers)
+                    .with_commands(commands)
+                    .with_lease(lease);
+
+            let res = block_production_task.block_production_task(ctx).await;
+            replication.abort();
+            res
+        });
+
+        Ok(())
+    }
+
     /// Initializes the genesis state of a devnet. This is needed for local sequencers.
     ///
     /// This methods was made external to [Service::start] as it needs to be
@@ -111,3 +221,132 @@ impl BlockProductionService {
         anyhow::Ok(())
     }
 }
+
+/// Forwards the transactions accepted into the mempool of the active sequencer to the standby sequencers, using the
+/// `madara_replicateMempoolTransactions` admin RPC method.
+///
+/// Every standby has its own task and queue, so that an unreachable standby does not delay the others. Failed batches
+/// are retried until the standby is reachable again, up to [`REPLICATION_QUEUE_SIZE`] queued transactions.
+async fn replicate_mempool(
+    mut accepted_txs: broadcast::Receiver<Arc<ReplicatedTransaction>>,
+    standby_rpc_urls: Vec<Url>,
+    api_key: Option<String>,
+    metrics: Arc<BlockProductionMetrics>,
+    mut ctx: ServiceContext,
+) {
+    let client = match reqwest::Client::builder().timeout(REPLICATION_REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::error!("❗ Failed to build the mempool replication HTTP client, replication is disabled: {err:#}");
+            return;
+        }
+    };
+
+    let mut standbys = Vec::new();
+    let mut tasks = tokio::task::JoinSet::new();
+    for url in standby_rpc_urls {
+        let (tx, rx) = mpsc::unbounded_channel();
+        standbys.push(tx);
+        let standby = Standby {
+            client: client.clone(),
+            url,
+            api_key: api_key.clone(),
+            metrics: Arc::clone(&metrics),
+            queue: VecDeque::new(),
+        };
+        tasks.spawn(standby.replicate(rx, ctx.clone()));
+    }
+
+    loop {
+        match ctx.run_until_cancelled(accepted_txs.recv()).await {
+            None | Some(Err(broadcast::error::RecvError::Closed)) => break,
+            Some(Ok(tx)) => {
+                for standby in &standbys {
+                    let _ = standby.send(Arc::clone(&tx));
+                }
+            }
+            Some(Err(broadcast::error::RecvError::Lagged(n))) => {
+                tracing::warn!("⚠️ {n} mempool transactions could not be replicated to the standby sequencers")
+            }
+        }
+    }
+
+    drop(standbys);
+    while tasks.join_next().await.is_some() {}
+}
+
+/// Replication to a single standby sequencer.
+struct Standby {
+    client: reqwest::Client,
+    url: Url,
+    api_key: Option<String>,
+    metrics: Arc<BlockProductionMetrics>,
+    /// Transactions waiting to be replicated, oldest first.
+    queue: VecDeque<Arc<ReplicatedTransaction>>,
+}
+
+impl Standby {
+    async fn replicate(mut self, mut rx: mpsc::UnboundedReceiver<Arc<ReplicatedTransaction>>, mut ctx: ServiceContext) {
+        loop {
+            if self.queue.is_empty() {
+                match ctx.run_until_cancelled(rx.recv()).await {
+                    Some(Some(tx)) => self.queue.push_back(tx),
+                    Some(None) | None => return,
+                }
+            }
+            while let Ok(tx) = rx.try_recv() {
+                self.queue.push_back(tx);
+            }
+            let excess = self.queue.len().saturating_sub(REPLICATION_QUEUE_SIZE);
+            if excess > 0 {
+                self.queue.drain(..excess);
+                tracing::warn!(
+                    "⚠️ {excess} mempool transactions could not be replicated to the standby sequencer at {}",
+                    self.url
+                );
+            }
+            self.record_lag();
+
+            let n = self.queue.len().min(REPLICATION_BATCH_SIZE);
+            match self.send_batch(n).await {
+                Ok(()) => {
+                    self.queue.drain(..n);
+                    self.record_lag();
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to replicate {n} mempool transactions to the standby sequencer at {}, retrying: {err:#}",
+                        self.url
+                    );
+                    if ctx.run_until_cancelled(tokio::time::sleep(REPLICATION_RETRY_DELAY)).await.is_none() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_batch(&self, n: usize) -> anyhow::Result<()> {
+        let transactions: Vec<&ReplicatedTransaction> = self.queue.iter().take(n).map(|tx| tx.as_ref()).collect();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "madara_replicateMempoolTransactions",
+            "params": [transactions],
+        });
+
+        let mut builder = self.client.post(self.url.clone()).json(&request);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.header("x-api-key", api_key);
+        }
+        let response: serde_json::Value = builder.send().await?.error_for_status()?.json().await?;
+        match response.get("error") {
+            Some(error) => anyhow::bail!("{error}"),
+            None => Ok(()),
+        }
+    }
+
+    fn record_lag(&self) {
+        self.metrics.replication_lag.record(self.queue.len() as u64, &[KeyValue::new("standby", self.url.to_string())]);
+    }
+}
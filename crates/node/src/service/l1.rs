@@ -1,12 +1,20 @@
 use crate::cli::l1::L1SyncParams;
 use alloy::primitives::Address;
+use alloy::signers::local::PrivateKeySigner;
 use anyhow::Context;
 use mc_db::{DatabaseService, MadaraBackend};
 use mc_eth::client::{EthereumClient, L1BlockMetrics};
+use mc_eth::l1_gas_price_strategy::L1GasPriceStrategy;
+use mc_eth::l1_handler_policy::L1HandlerPolicy;
+use mc_eth::settlement::{SettlementClient, SettlementConfig};
+use mc_eth::settlement_layer::settlement_layer_worker;
+use mc_eth::settlement_layer::starknet::StarknetClient;
 use mc_mempool::{GasPriceProvider, Mempool};
 use mp_block::H160;
+use mp_chain_config::SettlementLayer;
 use mp_utils::service::{MadaraServiceId, PowerOfTwo, Service, ServiceId, ServiceRunner};
 use starknet_api::core::ChainId;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -14,11 +22,16 @@ use std::time::Duration;
 pub struct L1SyncService {
     db_backend: Arc<MadaraBackend>,
     eth_client: Option<Arc<EthereumClient>>,
+    /// Base layer of an L3 appchain.
+    starknet_client: Option<Arc<StarknetClient>>,
     l1_gas_provider: GasPriceProvider,
     chain_id: ChainId,
     gas_price_sync_disabled: bool,
+    gas_price_strategy: L1GasPriceStrategy,
     gas_price_poll: Duration,
     mempool: Arc<Mempool>,
+    l1_handler_policy: L1HandlerPolicy,
+    settlement: Option<Arc<SettlementClient>>,
 }
 
 impl L1SyncService {
@@ -29,17 +42,42 @@ impl L1SyncService {
         l1_gas_provider: GasPriceProvider,
         chain_id: ChainId,
         l1_core_address: H160,
+        settlement_layer: &SettlementLayer,
         authority: bool,
         devnet: bool,
         mempool: Arc<Mempool>,
     ) -> anyhow::Result<Self> {
-        let eth_client = if !config.l1_sync_disabled && (config.l1_endpoint.is_some() || !devnet) {
-            if let Some(l1_rpc_url) = &config.l1_endpoint {
+        let starknet_client = match settlement_layer {
+            SettlementLayer::Starknet { core_contract_address } if !config.l1_sync_disabled => {
+                if config.l1_endpoint.is_empty() {
+                    anyhow::bail!(
+                        "No base layer endpoint provided. You need to provide a Starknet RPC endpoint using --l1-endpoint <RPC URL> in order to follow the base layer or disable the l1 watcher using --no-l1-sync."
+                    );
+                }
+                let client =
+                    StarknetClient::new(config.l1_endpoint.clone(), *core_contract_address, config.l1_request_timeout)
+                        .await
+                        .context("Creating the base layer starknet client")?;
+                Some(Arc::new(client))
+            }
+            _ => None,
+        };
+
+        let eth_client = if *settlement_layer == SettlementLayer::Ethereum
+            && !config.l1_sync_disabled
+            && (!config.l1_endpoint.is_empty() || !devnet)
+        {
+            if !config.l1_endpoint.is_empty() {
                 let core_address = Address::from_slice(l1_core_address.as_bytes());
                 let l1_block_metrics = L1BlockMetrics::register().expect("Registering metrics");
-                let client = EthereumClient::new(l1_rpc_url.clone(), core_address, l1_block_metrics)
-                    .await
-                    .context("Creating ethereum client")?;
+                let client = EthereumClient::new(
+                    config.l1_endpoint.clone(),
+                    core_address,
+                    l1_block_metrics,
+                    config.failover_config(),
+                )
+                .await
+                .context("Creating ethereum client")?;
 
                 Some(Arc::new(client))
             } else {
@@ -56,28 +94,69 @@ impl L1SyncService {
         let gas_price_sync_enabled =
             authority && !devnet && (config.gas_price.is_none() || config.blob_gas_price.is_none());
         let gas_price_poll = config.gas_price_poll;
+        let gas_price_strategy = config.gas_price_strategy();
 
-        if gas_price_sync_enabled {
+        // The base layer worker updates the gas prices itself.
+        if gas_price_sync_enabled && starknet_client.is_none() {
             let eth_client = eth_client
                 .clone()
                 .context("L1 gas prices require the ethereum service to be enabled. Either disable gas prices syncing using `--gas-price 0`, or disable L1 sync using the `--no-l1-sync` argument.")?;
             // running at-least once before the block production service
             tracing::info!("⏳ Getting initial L1 gas prices");
-            mc_eth::l1_gas_price::gas_price_worker_once(&eth_client, &l1_gas_provider, gas_price_poll)
-                .await
-                .context("Getting initial ethereum gas prices")?;
+            mc_eth::l1_gas_price::gas_price_worker_once(
+                &eth_client,
+                &l1_gas_provider,
+                &gas_price_strategy,
+                gas_price_poll,
+            )
+            .await
+            .context("Getting initial ethereum gas prices")?;
         }
 
+        let settlement = if let Some(private_key) = &config.l1_settlement_private_key {
+            if !authority {
+                anyhow::bail!("L1 settlement requires the node to run as a sequencer. Remove `--l1-settlement-private-key` or run with `--sequencer`.");
+            }
+            let eth_client = eth_client
+                .as_ref()
+                .context("L1 settlement requires the ethereum service to be enabled. Provide an L1 endpoint using `--l1-endpoint <RPC URL>`.")?;
+            let signer = PrivateKeySigner::from_str(private_key).context("Parsing the L1 settlement private key")?;
+            let settlement_config = SettlementConfig {
+                confirmations: config.l1_settlement_confirmations,
+                poll_interval: config.l1_settlement_poll,
+                replace_after_blocks: config.l1_settlement_replace_after,
+                fee_bump_percent: config.l1_settlement_fee_bump,
+                max_fee_per_gas: config.l1_settlement_max_fee_per_gas,
+                os_program_hash: config.l1_settlement_os_program_hash,
+                os_config_hash: config.l1_settlement_os_config_hash,
+            };
+            let client = SettlementClient::new(eth_client, signer, settlement_config)
+                .await
+                .context("Creating the L1 settlement client")?;
+            Some(Arc::new(client))
+        } else {
+            None
+        };
+
         Ok(Self {
             db_backend: Arc::clone(db.backend()),
             eth_client,
+            starknet_client,
             l1_gas_provider,
             chain_id,
             gas_price_sync_disabled: !gas_price_sync_enabled,
+            gas_price_strategy,
             gas_price_poll,
             mempool,
+            l1_handler_policy: config.l1_handler_policy(),
+            settlement,
         })
     }
+
+    /// The Ethereum client, when following an Ethereum settlement layer.
+    pub fn eth_client(&self) -> Option<Arc<EthereumClient>> {
+        self.eth_client.clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -88,8 +167,11 @@ impl Service for L1SyncService {
             l1_gas_provider,
             chain_id,
             gas_price_sync_disabled,
+            gas_price_strategy,
             gas_price_poll,
             mempool,
+            l1_handler_policy,
+            settlement,
             ..
         } = self.clone();
 
@@ -104,8 +186,27 @@ impl Service for L1SyncService {
                     chain_id,
                     l1_gas_provider,
                     gas_price_sync_disabled,
+                    gas_price_strategy,
                     gas_price_poll,
                     mempool,
+                    l1_handler_policy,
+                    settlement,
+                    ctx,
+                )
+            });
+        } else if let Some(starknet_client) = &self.starknet_client {
+            let starknet_client = Arc::clone(starknet_client);
+            let l1_block_metrics = L1BlockMetrics::register().expect("Registering metrics");
+            runner.service_loop(move |ctx| {
+                settlement_layer_worker(
+                    db_backend,
+                    starknet_client,
+                    l1_gas_provider,
+                    gas_price_sync_disabled,
+                    mempool,
+                    l1_handler_policy,
+                    l1_block_metrics,
+                    gas_price_poll,
                     ctx,
                 )
             });
@@ -1,6 +1,9 @@
 use crate::cli::L2SyncParams;
+use anyhow::Context;
 use mc_block_import::BlockImporter;
 use mc_db::{DatabaseService, MadaraBackend};
+use mc_eth::client::EthereumClient;
+use mc_eth::da_sync::{da_sync_worker, DaSync, DaSyncConfig};
 use mc_sync::fetch::fetchers::{FetchConfig, WarpUpdateConfig};
 use mc_sync::SyncConfig;
 use mc_telemetry::TelemetryHandle;
@@ -18,6 +21,8 @@ pub struct L2SyncService {
     starting_block: Option<u64>,
     telemetry: Arc<TelemetryHandle>,
     pending_block_poll_interval: Duration,
+    /// Reconstruct the state from L1 data availability instead of syncing from the gateway.
+    da_sync: Option<(Arc<EthereumClient>, DaSyncConfig)>,
 }
 
 impl L2SyncService {
@@ -28,10 +33,19 @@ impl L2SyncService {
         block_importer: Arc<BlockImporter>,
         telemetry: TelemetryHandle,
         warp_update: Option<WarpUpdateConfig>,
+        eth_client: Option<Arc<EthereumClient>>,
     ) -> anyhow::Result<Self> {
         let fetch_config = config.block_fetch_config(chain_config.chain_id.clone(), chain_config.clone(), warp_update);
 
-        tracing::info!("🛰️ Using feeder gateway URL: {}", fetch_config.feeder_gateway.as_str());
+        let da_sync = if config.l1_da_sync {
+            let eth_client = eth_client.context(
+                "Reconstructing the state from L1 requires an Ethereum L1 endpoint, provide one using --l1-endpoint",
+            )?;
+            Some((eth_client, config.da_sync_config()))
+        } else {
+            tracing::info!("🛰️ Using feeder gateway URL: {}", fetch_config.feeder_gateway.as_str());
+            None
+        };
 
         Ok(Self {
             db_backend: Arc::clone(db.backend()),
@@ -41,6 +55,7 @@ impl L2SyncService {
             block_importer,
             telemetry: Arc::new(telemetry),
             pending_block_poll_interval: config.pending_block_poll_interval,
+            da_sync,
         })
     }
 }
@@ -56,9 +71,16 @@ impl Service for L2SyncService {
             pending_block_poll_interval,
             block_importer,
             telemetry,
+            da_sync,
         } = self.clone();
         let telemetry = Arc::clone(&telemetry);
 
+        if let Some((eth_client, da_sync_config)) = da_sync {
+            let da_sync = DaSync::new(db_backend, eth_client, block_importer, da_sync_config)?;
+            runner.service_loop(move |ctx| da_sync_worker(da_sync, ctx));
+            return Ok(());
+        }
+
         runner.service_loop(move |ctx| {
             mc_sync::l2_sync_worker(
                 db_backend,
@@ -0,0 +1,442 @@
+//! Authentication for the admin RPC server.
+//!
+//! Clients authenticate every HTTP request, or the websocket upgrade request, either with a JWT bearer token signed
+//! with a shared secret (HS256, as for the Ethereum engine API) or with a static API key. Each credential grants a set
+//! of scopes, which restrict the methods it can call.
+
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use base64::Engine;
+use futures::future::{BoxFuture, FutureExt};
+use hmac::{Hmac, Mac};
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use sha2::{Digest, Sha256};
+
+use crate::cli::RpcParams;
+
+use super::middleware::unversioned_method_name;
+
+/// JSON-RPC error code returned for calls outside of the scopes of the client.
+const UNAUTHORIZED_CODE: i32 = -32001;
+const UNAUTHORIZED_MSG: &str = "Unauthorized";
+/// Header in which clients send their API key.
+const API_KEY_HEADER: &str = "x-api-key";
+/// Maximum difference between the `iat` claim of a token and the time of the node, in seconds.
+const JWT_MAX_IAT_DRIFT_SECS: u64 = 60;
+/// Minimum length of the JWT secret, in bytes.
+const JWT_MIN_SECRET_LEN: usize = 32;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("Missing credentials")]
+    MissingCredentials,
+    #[error("Unknown API key")]
+    UnknownApiKey,
+    #[error("Malformed token: {0}")]
+    MalformedToken(&'static str),
+    #[error("Unsupported token algorithm {0:?}, expected HS256")]
+    UnsupportedAlgorithm(String),
+    #[error("Invalid token signature")]
+    InvalidSignature,
+    #[error("Token issued {0}s away from the current time")]
+    StaleToken(u64),
+    #[error("Token authentication is not enabled")]
+    TokensDisabled,
+}
+
+/// Methods a client is allowed to call.
+///
+/// A scope is either a method name without version, such as `madara_service`, or a prefix followed by `*`, such as
+/// `madara_devnet*`. The `*` scope allows every method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scopes(Vec<String>);
+
+impl Scopes {
+    pub fn all() -> Self {
+        Self(vec!["*".to_string()])
+    }
+
+    fn parse<'a>(scopes: impl IntoIterator<Item = &'a str>) -> Self {
+        let scopes: Vec<_> = scopes.into_iter().map(str::to_string).collect();
+        if scopes.is_empty() {
+            Self::all()
+        } else {
+            Self(scopes)
+        }
+    }
+
+    pub fn allows(&self, method: &str) -> bool {
+        let method = unversioned_method_name(method);
+        self.0.iter().any(|scope| match scope.strip_suffix('*') {
+            Some(prefix) => method.starts_with(prefix),
+            None => *scope == method,
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(serde::Deserialize)]
+struct JwtClaims {
+    iat: u64,
+    /// Space separated list of scopes. Tokens without this claim are allowed every method.
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Debug)]
+struct ApiKey {
+    /// Only the hash of the key is kept, so that comparing keys does not leak them through timing.
+    hash: [u8; 32],
+    scopes: Scopes,
+}
+
+#[derive(Debug)]
+pub struct Authenticator {
+    jwt_secret: Option<Vec<u8>>,
+    api_keys: Vec<ApiKey>,
+}
+
+impl Authenticator {
+    /// Returns [`None`] when authentication is disabled.
+    pub fn from_params(params: &RpcParams) -> anyhow::Result<Option<Self>> {
+        let jwt_secret = params.rpc_admin_jwt_secret.as_deref().map(read_jwt_secret).transpose()?;
+        let api_keys = params.rpc_admin_api_keys.as_deref().map(read_api_keys).transpose()?;
+
+        let api_keys = match (jwt_secret.is_some(), api_keys) {
+            (false, None) => return Ok(None),
+            (_, Some(api_keys)) => api_keys,
+            (true, None) => vec![],
+        };
+        Ok(Some(Self { jwt_secret, api_keys }))
+    }
+
+    /// Authenticates an HTTP request or a websocket upgrade request, returning the scopes of the client.
+    pub fn authenticate<B>(&self, req: &hyper::Request<B>) -> Result<Scopes, AuthError> {
+        let headers = req.headers();
+        if let Some(key) = headers.get(API_KEY_HEADER) {
+            let hash: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+            return self
+                .api_keys
+                .iter()
+                .find(|api_key| api_key.hash == hash)
+                .map(|api_key| api_key.scopes.clone())
+                .ok_or(AuthError::UnknownApiKey);
+        }
+
+        let token = headers
+            .get(hyper::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(AuthError::MissingCredentials)?;
+        self.verify_jwt(token.trim())
+    }
+
+    fn verify_jwt(&self, token: &str) -> Result<Scopes, AuthError> {
+        let secret = self.jwt_secret.as_ref().ok_or(AuthError::TokensDisabled)?;
+        let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+        let (signing_input, signature) =
+            token.rsplit_once('.').ok_or(AuthError::MalformedToken("missing signature"))?;
+        let (header, claims) = signing_input.split_once('.').ok_or(AuthError::MalformedToken("missing claims"))?;
+
+        let header: JwtHeader = b64
+            .decode(header)
+            .ok()
+            .and_then(|header| serde_json::from_slice(&header).ok())
+            .ok_or(AuthError::MalformedToken("invalid header"))?;
+        if header.alg != "HS256" {
+            return Err(AuthError::UnsupportedAlgorithm(header.alg));
+        }
+
+        let signature = b64.decode(signature).map_err(|_| AuthError::MalformedToken("invalid signature encoding"))?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+        mac.update(signing_input.as_bytes());
+        mac.verify_slice(&signature).map_err(|_| AuthError::InvalidSignature)?;
+
+        let claims: JwtClaims = b64
+            .decode(claims)
+            .ok()
+            .and_then(|claims| serde_json::from_slice(&claims).ok())
+            .ok_or(AuthError::MalformedToken("invalid claims"))?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Current time is after the unix epoch").as_secs();
+        let drift = now.abs_diff(claims.iat);
+        if drift > JWT_MAX_IAT_DRIFT_SECS {
+            return Err(AuthError::StaleToken(drift));
+        }
+
+        Ok(claims.scope.as_deref().map_or_else(Scopes::all, |scope| Scopes::parse(scope.split_whitespace())))
+    }
+}
+
+/// Reads a hex encoded secret, optionally prefixed with `0x`.
+fn read_jwt_secret(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let secret = std::fs::read_to_string(path)
+        .with_context(|| format!("Reading the admin RPC JWT secret from {}", path.display()))?;
+    let secret = secret.trim();
+    let secret = hex::decode(secret.strip_prefix("0x").unwrap_or(secret))
+        .with_context(|| format!("Decoding the admin RPC JWT secret in {} as hex", path.display()))?;
+    anyhow::ensure!(
+        secret.len() >= JWT_MIN_SECRET_LEN,
+        "The admin RPC JWT secret in {} must be at least {JWT_MIN_SECRET_LEN} bytes long",
+        path.display()
+    );
+    Ok(secret)
+}
+
+/// Reads one API key per line, optionally followed by its whitespace separated scopes. Keys without scopes are
+/// allowed every method. Empty lines and lines starting with `#` are ignored.
+fn read_api_keys(path: &Path) -> anyhow::Result<Vec<ApiKey>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Reading the admin RPC API keys from {}", path.display()))?;
+    let api_keys: Vec<_> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let key = parts.next().expect("Line is not empty");
+            ApiKey { hash: Sha256::digest(key.as_bytes()).into(), scopes: Scopes::parse(parts) }
+        })
+        .collect();
+    anyhow::ensure!(!api_keys.is_empty(), "No admin RPC API keys in {}", path.display());
+    Ok(api_keys)
+}
+
+/// Listing and describing the methods is always allowed: `rpc_methods`, and `rpc.discover` which is served as
+/// `rpc_{version}_discover`.
+fn is_discovery_method(method: &str) -> bool {
+    matches!(unversioned_method_name(method).as_str(), "rpc_methods" | "rpc_discover")
+}
+
+/// Logs a request which failed authentication.
+pub fn log_rejected(remote_ip: IpAddr, err: &AuthError) {
+    tracing::warn!("🔒 Rejected admin RPC request from {remote_ip}: {err}");
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcMiddlewareLayerScopes {
+    scopes: Arc<Scopes>,
+    remote_ip: IpAddr,
+}
+
+impl RpcMiddlewareLayerScopes {
+    pub fn new(scopes: Scopes, remote_ip: IpAddr) -> Self {
+        Self { scopes: Arc::new(scopes), remote_ip }
+    }
+}
+
+impl<S> tower::Layer<S> for RpcMiddlewareLayerScopes {
+    type Service = RpcMiddlewareServiceScopes<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcMiddlewareServiceScopes { inner, scopes: Arc::clone(&self.scopes), remote_ip: self.remote_ip }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcMiddlewareServiceScopes<S> {
+    inner: S,
+    scopes: Arc<Scopes>,
+    remote_ip: IpAddr,
+}
+
+impl<'a, S> RpcServiceT<'a> for RpcMiddlewareServiceScopes<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'static,
+{
+    type Future = BoxFuture<'a, jsonrpsee::MethodResponse>;
+
+    fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
+        let inner = self.inner.clone();
+        let scopes = Arc::clone(&self.scopes);
+        let remote_ip = self.remote_ip;
+
+        async move {
+            if !is_discovery_method(req.method_name()) && !scopes.allows(req.method_name()) {
+                tracing::warn!("🔒 Rejected admin RPC call to {} from {remote_ip}: out of scope", req.method_name());
+                return jsonrpsee::MethodResponse::error(
+                    req.id,
+                    jsonrpsee::types::ErrorObject::owned(
+                        UNAUTHORIZED_CODE,
+                        UNAUTHORIZED_MSG,
+                        Some(format!("{} is not allowed by the scopes of this client", req.method_name())),
+                    ),
+                );
+            }
+
+            inner.call(req).await
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SECRET: [u8; 32] = [7; 32];
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    fn authenticator() -> Authenticator {
+        Authenticator {
+            jwt_secret: Some(SECRET.to_vec()),
+            api_keys: vec![ApiKey { hash: Sha256::digest(b"key").into(), scopes: Scopes::parse(["madara_service"]) }],
+        }
+    }
+
+    fn jwt(secret: &[u8], header: serde_json::Value, claims: serde_json::Value) -> String {
+        let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        let signing_input = format!("{}.{}", b64.encode(header.to_string()), b64.encode(claims.to_string()));
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(signing_input.as_bytes());
+        format!("{signing_input}.{}", b64.encode(mac.finalize().into_bytes()))
+    }
+
+    fn hs256_token(claims: serde_json::Value) -> String {
+        jwt(&SECRET, serde_json::json!({ "alg": "HS256", "typ": "JWT" }), claims)
+    }
+
+    fn request(headers: &[(&str, &str)]) -> hyper::Request<()> {
+        let mut builder = hyper::Request::builder();
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    fn bearer(token: &str) -> hyper::Request<()> {
+        request(&[("authorization", &format!("Bearer {token}"))])
+    }
+
+    #[test]
+    fn jwt_valid() {
+        let scopes = authenticator().authenticate(&bearer(&hs256_token(serde_json::json!({ "iat": now() }))));
+        assert_eq!(scopes.unwrap(), Scopes::all());
+
+        let token = hs256_token(serde_json::json!({ "iat": now(), "scope": "madara_service madara_devnet*" }));
+        let scopes = authenticator().authenticate(&bearer(&token)).unwrap();
+        assert_eq!(scopes, Scopes::parse(["madara_service", "madara_devnet*"]));
+    }
+
+    #[test]
+    fn jwt_bad_signature() {
+        let token = jwt(&[8; 32], serde_json::json!({ "alg": "HS256" }), serde_json::json!({ "iat": now() }));
+        assert!(matches!(authenticator().authenticate(&bearer(&token)), Err(AuthError::InvalidSignature)));
+
+        let mut token = hs256_token(serde_json::json!({ "iat": now() }));
+        token.push('A');
+        assert!(matches!(
+            authenticator().authenticate(&bearer(&token)),
+            Err(AuthError::InvalidSignature | AuthError::MalformedToken(_))
+        ));
+    }
+
+    #[test]
+    fn jwt_unsupported_algorithm() {
+        let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        let header = b64.encode(serde_json::json!({ "alg": "none" }).to_string());
+        let claims = b64.encode(serde_json::json!({ "iat": now() }).to_string());
+        let token = format!("{header}.{claims}.");
+        assert!(matches!(
+            authenticator().authenticate(&bearer(&token)),
+            Err(AuthError::UnsupportedAlgorithm(alg)) if alg == "none"
+        ));
+
+        let token = jwt(&SECRET, serde_json::json!({ "alg": "HS512" }), serde_json::json!({ "iat": now() }));
+        assert!(matches!(
+            authenticator().authenticate(&bearer(&token)),
+            Err(AuthError::UnsupportedAlgorithm(alg)) if alg == "HS512"
+        ));
+    }
+
+    #[test]
+    fn jwt_stale_or_future_iat() {
+        let drift = JWT_MAX_IAT_DRIFT_SECS + 10;
+        for iat in [now() - drift, now() + drift] {
+            let token = hs256_token(serde_json::json!({ "iat": iat }));
+            assert!(matches!(authenticator().authenticate(&bearer(&token)), Err(AuthError::StaleToken(_))));
+        }
+    }
+
+    #[test]
+    fn jwt_disabled() {
+        let authenticator = Authenticator { jwt_secret: None, ..authenticator() };
+        let token = hs256_token(serde_json::json!({ "iat": now() }));
+        assert!(matches!(authenticator.authenticate(&bearer(&token)), Err(AuthError::TokensDisabled)));
+    }
+
+    #[test]
+    fn missing_credentials() {
+        assert!(matches!(authenticator().authenticate(&request(&[])), Err(AuthError::MissingCredentials)));
+        let req = request(&[("authorization", "Basic dXNlcjpwYXNz")]);
+        assert!(matches!(authenticator().authenticate(&req), Err(AuthError::MissingCredentials)));
+    }
+
+    #[test]
+    fn api_key() {
+        let scopes = authenticator().authenticate(&request(&[(API_KEY_HEADER, "key")])).unwrap();
+        assert_eq!(scopes, Scopes::parse(["madara_service"]));
+        assert!(matches!(
+            authenticator().authenticate(&request(&[(API_KEY_HEADER, "other")])),
+            Err(AuthError::UnknownApiKey)
+        ));
+    }
+
+    #[test]
+    fn scopes_reject_out_of_scope_methods() {
+        let scopes = Scopes::parse(["madara_service", "madara_devnet*"]);
+        assert!(scopes.allows("madara_V0_1_0_service"));
+        assert!(scopes.allows("madara_V0_1_0_devnetPostMessage"));
+        assert!(!scopes.allows("madara_V0_1_0_addDeclareV0Transaction"));
+        assert!(!scopes.allows("madara_V0_1_0_services"));
+        assert!(Scopes::all().allows("madara_V0_1_0_addDeclareV0Transaction"));
+    }
+
+    #[test]
+    fn discovery_methods_exempted() {
+        assert!(is_discovery_method("rpc_methods"));
+        assert!(is_discovery_method("rpc_V0_1_0_discover"));
+        assert!(!is_discovery_method("rpc_V0_1_0_subscribe"));
+        assert!(!is_discovery_method("rpc_unknown"));
+        assert!(!is_discovery_method("madara_V0_1_0_service"));
+    }
+
+    #[test]
+    fn read_api_keys_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# comment\n\nkey1\n  key2 madara_service madara_devnet*  \n").unwrap();
+        let api_keys = read_api_keys(file.path()).unwrap();
+        assert_eq!(api_keys.len(), 2);
+        assert_eq!(api_keys[0].hash, <[u8; 32]>::from(Sha256::digest(b"key1")));
+        assert_eq!(api_keys[0].scopes, Scopes::all());
+        assert_eq!(api_keys[1].hash, <[u8; 32]>::from(Sha256::digest(b"key2")));
+        assert_eq!(api_keys[1].scopes, Scopes::parse(["madara_service", "madara_devnet*"]));
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# only comments").unwrap();
+        assert!(read_api_keys(file.path()).is_err());
+    }
+
+    #[test]
+    fn read_jwt_secret_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "0x{}", hex::encode(SECRET)).unwrap();
+        assert_eq!(read_jwt_secret(file.path()).unwrap(), SECRET.to_vec());
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{}", hex::encode([7; 16])).unwrap();
+        assert!(read_jwt_secret(file.path()).is_err());
+    }
+}
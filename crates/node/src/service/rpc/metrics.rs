@@ -26,6 +26,13 @@ pub struct RpcMetrics {
     ws_sessions_closed: Option<Counter<u64>>,
     /// Histogram over RPC websocket sessions.
     ws_sessions_time: Histogram<f64>,
+    /// Number of calls accepted by the rate limiter, per API key. Calls from clients without API key share the `ip`
+    /// label.
+    client_calls: Counter<u64>,
+    /// Total weight of the calls accepted by the rate limiter, per API key or `ip`.
+    client_weight: Counter<u64>,
+    /// Number of calls rejected by the rate limiter, per API key or `ip`, and method.
+    client_rate_limited: Counter<u64>,
 }
 
 impl RpcMetrics {
@@ -81,7 +88,38 @@ impl RpcMetrics {
             "".to_string(),
         );
 
-        Ok(Self { calls_time, calls_started, calls_finished, ws_sessions_opened, ws_sessions_closed, ws_sessions_time })
+        let client_calls = register_counter_metric_instrument(
+            &rpc_meter,
+            "client_calls".to_string(),
+            "A counter to show the number of RPC calls accepted by the rate limiter, per client".to_string(),
+            "".to_string(),
+        );
+
+        let client_weight = register_counter_metric_instrument(
+            &rpc_meter,
+            "client_weight".to_string(),
+            "A counter to show the total weight of the RPC calls accepted by the rate limiter, per client".to_string(),
+            "".to_string(),
+        );
+
+        let client_rate_limited = register_counter_metric_instrument(
+            &rpc_meter,
+            "client_rate_limited".to_string(),
+            "A counter to show the number of RPC calls rejected by the rate limiter, per client".to_string(),
+            "".to_string(),
+        );
+
+        Ok(Self {
+            calls_time,
+            calls_started,
+            calls_finished,
+            ws_sessions_opened,
+            ws_sessions_closed,
+            ws_sessions_time,
+            client_calls,
+            client_weight,
+            client_rate_limited,
+        })
     }
 
     pub(crate) fn ws_connect(&self) {
@@ -131,6 +169,20 @@ impl RpcMetrics {
             ],
         );
     }
+
+    pub(crate) fn on_client_call(&self, client: &str, weight: u32) {
+        let attributes = [KeyValue::new("client", client.to_string())];
+        self.client_calls.add(1, &attributes);
+        self.client_weight.add(weight.into(), &attributes);
+    }
+
+    pub(crate) fn on_rate_limited(&self, client: &str, req: &Request, transport_label: &'static str) {
+        tracing::trace!(target: "rpc_metrics", "[{transport_label}] on_rate_limited client={client} name={}", req.method_name());
+        self.client_rate_limited.add(
+            1,
+            &[KeyValue::new("client", client.to_string()), KeyValue::new("method", req.method_name().to_string())],
+        );
+    }
 }
 
 /// Metrics with transport label.
@@ -161,4 +213,12 @@ impl Metrics {
     pub(crate) fn on_response(&self, req: &Request, rp: &MethodResponse, now: Instant) {
         self.inner.on_response(req, rp, self.transport_label, now)
     }
+
+    pub(crate) fn on_client_call(&self, client: &str, weight: u32) {
+        self.inner.on_client_call(client, weight)
+    }
+
+    pub(crate) fn on_rate_limited(&self, client: &str, req: &Request) {
+        self.inner.on_rate_limited(client, req, self.transport_label)
+    }
 }
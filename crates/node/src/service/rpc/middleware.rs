@@ -82,6 +82,15 @@ where
     }
 }
 
+/// Strips the version from a method name once it has been through [`RpcMiddlewareServiceVersion`]:
+/// `starknet_V0_7_1_getEvents` becomes `starknet_getEvents`.
+pub(crate) fn unversioned_method_name(method: &str) -> String {
+    match (method.split_once('_'), method.rsplit_once('_')) {
+        (Some((namespace, _)), Some((_, name))) => format!("{namespace}_{name}"),
+        _ => method.to_string(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RpcMiddlewareServiceVersion<S> {
     inner: S,
@@ -128,6 +137,12 @@ where
                 }
             };
 
+            // Served by the `rpc_{version}_discover` method registered for each version.
+            if req.method == "rpc.discover" {
+                req.method = jsonrpsee::core::Cow::from(format!("rpc_{version}_discover"));
+                return inner.call(req).await;
+            }
+
             let Some((namespace, method)) = req.method.split_once('_') else {
                 return jsonrpsee::MethodResponse::error(
                     req.id(),
@@ -148,3 +163,47 @@ where
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::types::{ErrorObject, Id, Request};
+
+    /// Answers every call with the name of the method it was routed to.
+    #[derive(Clone)]
+    struct RoutedMethod;
+
+    impl<'a> RpcServiceT<'a> for RoutedMethod {
+        type Future = std::future::Ready<jsonrpsee::MethodResponse>;
+
+        fn call(&self, req: Request<'a>) -> Self::Future {
+            std::future::ready(jsonrpsee::MethodResponse::error(
+                req.id,
+                ErrorObject::owned(0, req.method.to_string(), None::<()>),
+            ))
+        }
+    }
+
+    async fn routed_method(path: &str, method: &str) -> String {
+        let service = RpcMiddlewareServiceVersion::new(RoutedMethod, path.to_string(), RpcVersion::RPC_VERSION_LATEST);
+        let response = service.call(Request::new(method.to_string().into(), None, Id::Number(1))).await;
+        let response: serde_json::Value = serde_json::from_str(response.as_result()).unwrap();
+        response["error"]["message"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_version_routing() {
+        for method in ["estimateFee", "simulateTransactions", "getBlockWithReceipts"] {
+            assert_eq!(
+                routed_method("/rpc/v0_6_0", &format!("starknet_{method}")).await,
+                format!("starknet_V0_6_0_{method}")
+            );
+        }
+        assert_eq!(
+            routed_method("/rpc/v0_7_1/", "starknet_getBlockWithReceipts").await,
+            "starknet_V0_7_1_getBlockWithReceipts"
+        );
+        assert_eq!(routed_method("/", "starknet_getEvents").await, "starknet_V0_8_0_getEvents");
+        assert_eq!(routed_method("/rpc/v0_6_0", "rpc.discover").await, "rpc_V0_6_0_discover");
+    }
+}
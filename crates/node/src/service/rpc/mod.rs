@@ -2,22 +2,30 @@ use std::sync::Arc;
 
 use jsonrpsee::server::ServerHandle;
 
+use mc_analytics::LogFilterHandle;
+use mc_block_production::BlockProductionHandle;
 use mc_db::MadaraBackend;
+use mc_mempool::Mempool;
 use mc_rpc::{
+    populate_trace_cache,
     providers::{AddTransactionProvider, AddTransactionProviderGroup},
     rpc_api_admin, rpc_api_user, Starknet,
 };
 use mp_utils::service::{MadaraServiceId, PowerOfTwo, Service, ServiceId, ServiceRunner};
 
+use auth::Authenticator;
 use metrics::RpcMetrics;
+use rate_limit::RateLimiter;
 use server::{start_server, ServerConfig};
 
 use crate::cli::RpcParams;
 
 use self::server::rpc_api_build;
 
+mod auth;
 mod metrics;
 mod middleware;
+mod rate_limit;
 mod server;
 
 #[derive(Clone)]
@@ -31,6 +39,10 @@ pub struct RpcService {
     backend: Arc<MadaraBackend>,
     add_txs_provider_l2_sync: Arc<dyn AddTransactionProvider>,
     add_txs_provider_mempool: Arc<dyn AddTransactionProvider>,
+    mempool: Option<Arc<Mempool>>,
+    block_production: Option<BlockProductionHandle>,
+    devnet: bool,
+    log_filter: Option<LogFilterHandle>,
     server_handle: Option<ServerHandle>,
     rpc_type: RpcType,
 }
@@ -47,6 +59,10 @@ impl RpcService {
             backend,
             add_txs_provider_l2_sync,
             add_txs_provider_mempool,
+            mempool: None,
+            block_production: None,
+            devnet: false,
+            log_filter: None,
             server_handle: None,
             rpc_type: RpcType::User,
         }
@@ -57,12 +73,20 @@ impl RpcService {
         backend: Arc<MadaraBackend>,
         add_txs_provider_l2_sync: Arc<dyn AddTransactionProvider>,
         add_txs_provider_mempool: Arc<dyn AddTransactionProvider>,
+        mempool: Arc<Mempool>,
+        block_production: BlockProductionHandle,
+        devnet: bool,
+        log_filter: Option<LogFilterHandle>,
     ) -> Self {
         Self {
             config,
             backend,
             add_txs_provider_l2_sync,
             add_txs_provider_mempool,
+            mempool: Some(mempool),
+            block_production: Some(block_production),
+            devnet,
+            log_filter,
             server_handle: None,
             rpc_type: RpcType::Admin,
         }
@@ -76,6 +100,10 @@ impl Service for RpcService {
         let backend = Arc::clone(&self.backend);
         let add_tx_provider_l2_sync = Arc::clone(&self.add_txs_provider_l2_sync);
         let add_tx_provider_mempool = Arc::clone(&self.add_txs_provider_mempool);
+        let mempool = self.mempool.clone();
+        let block_production = self.block_production.clone();
+        let devnet = self.devnet;
+        let log_filter = self.log_filter.clone();
         let rpc_type = self.rpc_type.clone();
 
         let (stop_handle, server_handle) = jsonrpsee::server::stop_channel();
@@ -89,24 +117,58 @@ impl Service for RpcService {
                 ctx.clone(),
             ));
 
-            let starknet = Starknet::new(backend.clone(), add_tx_provider, config.storage_proof_config(), ctx.clone());
+            let mut starknet =
+                Starknet::new(backend.clone(), add_tx_provider, config.storage_proof_config(), ctx.clone());
+            if let Some(mempool) = mempool {
+                starknet = starknet.with_mempool(mempool);
+            }
+            if let Some(block_production) = block_production {
+                starknet = starknet.with_block_production(block_production);
+            }
+            if devnet {
+                starknet = starknet.with_devnet();
+            }
+            if let Some(log_filter) = log_filter {
+                starknet = starknet.with_log_filter(log_filter);
+            }
+            let mut trace_cache_eager = false;
+            if matches!(rpc_type, RpcType::User) {
+                starknet = starknet.with_trace_cache(config.trace_cache_config());
+                if config.rpc_trace_cache_eager {
+                    anyhow::ensure!(
+                        config.trace_cache_config().is_enabled(),
+                        "--rpc-trace-cache-eager requires a trace cache, which is disabled"
+                    );
+                    trace_cache_eager = true;
+                }
+            }
             let metrics = RpcMetrics::register()?;
 
             let server_config = {
-                let (name, addr, api_rpc, rpc_version_default) = match rpc_type {
+                let (name, addr, api_rpc, rpc_version_default, rate_limiter, authenticator) = match rpc_type {
                     RpcType::User => (
                         "JSON-RPC".to_string(),
                         config.addr_user(),
                         rpc_api_user(&starknet)?,
                         mp_chain_config::RpcVersion::RPC_VERSION_LATEST,
+                        RateLimiter::from_params(&config)?.map(Arc::new),
+                        None,
                     ),
                     RpcType::Admin => (
                         "JSON-RPC (Admin)".to_string(),
                         config.addr_admin(),
                         rpc_api_admin(&starknet)?,
                         mp_chain_config::RpcVersion::RPC_VERSION_LATEST_ADMIN,
+                        None,
+                        Authenticator::from_params(&config)?.map(Arc::new),
                     ),
                 };
+                if matches!(rpc_type, RpcType::Admin) && config.rpc_admin_external && authenticator.is_none() {
+                    tracing::warn!(
+                        "🔓 The admin RPC endpoint is exposed externally without authentication, consider setting \
+                         --rpc-admin-jwt-secret or --rpc-admin-api-keys"
+                    );
+                }
                 let methods = rpc_api_build("rpc", api_rpc).into();
 
                 ServerConfig {
@@ -122,10 +184,19 @@ impl Service for RpcService {
                     metrics,
                     cors: config.cors(),
                     rpc_version_default,
+                    rate_limiter,
+                    authenticator,
                 }
             };
 
-            start_server(server_config, ctx.clone(), stop_handle).await?;
+            // Populating the trace cache runs as part of the service, so that it stops with it.
+            let trace_cache_population = async {
+                if trace_cache_eager {
+                    populate_trace_cache(starknet.clone(), ctx.clone()).await;
+                }
+                anyhow::Ok(())
+            };
+            tokio::try_join!(start_server(server_config, ctx.clone(), stop_handle), trace_cache_population)?;
 
             anyhow::Ok(())
         });
@@ -0,0 +1,412 @@
+//! Per-client rate limiting for the RPC server.
+//!
+//! Every client gets a token bucket which refills at a fixed weight per second, and every call takes the weight of
+//! its method out of the bucket. Calls which do not fit in the bucket are rejected with a JSON-RPC error.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use futures::future::{BoxFuture, FutureExt};
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+
+use crate::cli::RpcParams;
+
+use super::metrics::Metrics;
+use super::middleware::unversioned_method_name;
+
+/// JSON-RPC error code returned for calls over the rate limit.
+const RATE_LIMITED_CODE: i32 = -32005;
+const RATE_LIMITED_MSG: &str = "Rate limit exceeded";
+/// Header in which clients can send an API key with its own rate limit.
+const API_KEY_HEADER: &str = "x-api-key";
+/// Past this number of clients, buckets which are full again are dropped.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Weight of the methods which are more expensive than a simple lookup, by method name without version.
+const DEFAULT_METHOD_WEIGHTS: &[(&str, u32)] = &[
+    ("starknet_traceBlockTransactions", 100),
+    ("starknet_traceTransaction", 50),
+    ("starknet_simulateTransactions", 50),
+    ("madara_simulateTransactionsWithOverrides", 50),
+    ("starknet_estimateFee", 20),
+    ("madara_estimateFeeWithOverrides", 20),
+    ("starknet_estimateMessageFee", 20),
+    ("starknet_getEvents", 20),
+    ("starknet_getStorageProof", 20),
+    ("starknet_call", 10),
+    ("madara_callWithOverrides", 10),
+    ("starknet_getBlockWithReceipts", 5),
+    ("starknet_getBlockWithTxs", 5),
+    ("starknet_getMessagesStatus", 5),
+    ("starknet_getMessagesToL1", 5),
+    ("starknet_addInvokeTransaction", 5),
+    ("starknet_addDeclareTransaction", 5),
+    ("starknet_addDeployAccountTransaction", 5),
+    ("rpc_discover", 5),
+];
+const DEFAULT_WEIGHT: u32 = 1;
+
+/// Identifies the client a call is accounted to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    Ip(IpAddr),
+    /// Index of the API key in the configured list. The key itself is never logged nor used as a metric label.
+    ApiKey(usize),
+}
+
+impl ClientId {
+    fn label(&self) -> String {
+        match self {
+            ClientId::Ip(ip) => ip.to_string(),
+            ClientId::ApiKey(index) => format!("api_key#{index}"),
+        }
+    }
+
+    /// Label of the client in metrics. IP addresses are not bounded and are all reported as `ip`.
+    fn metric_label(&self) -> String {
+        match self {
+            ClientId::Ip(_) => "ip".to_string(),
+            ClientId::ApiKey(_) => self.label(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Refills the bucket, then takes `cost` out of it. On failure, returns how long until the call would fit.
+    fn take(&mut self, cost: f64, weight_per_sec: f64, capacity: f64, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * weight_per_sec).min(capacity);
+        self.updated_at = now;
+
+        // A method heavier than the whole bucket can still be called, once the bucket is full.
+        let cost = cost.min(capacity);
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((cost - self.tokens) / weight_per_sec))
+        }
+    }
+
+    fn is_full(&self, weight_per_sec: f64, capacity: f64, now: Instant) -> bool {
+        self.tokens + now.saturating_duration_since(self.updated_at).as_secs_f64() * weight_per_sec >= capacity
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    weight_per_sec: u32,
+    burst_secs: u32,
+    /// API key to its index in `api_key_weights`.
+    api_keys: HashMap<String, usize>,
+    api_key_weights: Vec<u32>,
+    method_weights: HashMap<String, u32>,
+    ip_header: Option<hyper::header::HeaderName>,
+    buckets: Mutex<HashMap<ClientId, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Returns [`None`] when rate limiting is disabled.
+    pub fn from_params(params: &RpcParams) -> anyhow::Result<Option<Self>> {
+        let Some(weight_per_sec) = params.rpc_rate_limit else { return Ok(None) };
+        anyhow::ensure!(weight_per_sec > 0, "The RPC rate limit must be greater than zero");
+        anyhow::ensure!(params.rpc_rate_limit_burst > 0, "The RPC rate limit burst must be greater than zero");
+
+        let ip_header = params
+            .rpc_rate_limit_ip_header
+            .as_deref()
+            .map(hyper::header::HeaderName::from_bytes)
+            .transpose()
+            .context("Parsing the RPC rate limit IP header name")?;
+
+        let mut api_keys = HashMap::new();
+        let mut api_key_weights = Vec::new();
+        for (index, (key, weight_per_sec)) in params.rpc_rate_limit_api_keys.iter().enumerate() {
+            anyhow::ensure!(*weight_per_sec > 0, "The rate limit of API key #{index} must be greater than zero");
+            api_keys.insert(key.clone(), index);
+            api_key_weights.push(*weight_per_sec);
+        }
+
+        let mut method_weights: HashMap<String, u32> =
+            DEFAULT_METHOD_WEIGHTS.iter().map(|(method, weight)| (method.to_string(), *weight)).collect();
+        method_weights.extend(params.rpc_rate_limit_method_weights.iter().cloned());
+
+        Ok(Some(Self {
+            weight_per_sec,
+            burst_secs: params.rpc_rate_limit_burst,
+            api_keys,
+            api_key_weights,
+            method_weights,
+            ip_header,
+            buckets: Default::default(),
+        }))
+    }
+
+    /// Identifies the client sending an HTTP request or opening a websocket connection.
+    pub fn client<B>(&self, req: &hyper::Request<B>, remote_ip: IpAddr) -> ClientId {
+        let headers = req.headers();
+        if let Some(&index) =
+            headers.get(API_KEY_HEADER).and_then(|key| key.to_str().ok()).and_then(|key| self.api_keys.get(key.trim()))
+        {
+            return ClientId::ApiKey(index);
+        }
+
+        let forwarded_ip = self
+            .ip_header
+            .as_ref()
+            .and_then(|header| headers.get(header)?.to_str().ok()?.split(',').next()?.trim().parse::<IpAddr>().ok());
+        ClientId::Ip(forwarded_ip.unwrap_or(remote_ip))
+    }
+
+    /// Weight of a method, which is the same for every version of it.
+    fn method_weight(&self, method: &str) -> u32 {
+        self.method_weights.get(&unversioned_method_name(method)).copied().unwrap_or(DEFAULT_WEIGHT)
+    }
+
+    fn client_weight_per_sec(&self, client: &ClientId) -> u32 {
+        match client {
+            ClientId::Ip(_) => self.weight_per_sec,
+            ClientId::ApiKey(index) => self.api_key_weights.get(*index).copied().unwrap_or(self.weight_per_sec),
+        }
+    }
+
+    /// Accounts a call to the client. On failure, returns how long until the call would fit.
+    fn check(&self, client: &ClientId, weight: u32) -> Result<(), Duration> {
+        let weight_per_sec = f64::from(self.client_weight_per_sec(client));
+        let capacity = weight_per_sec * f64::from(self.burst_secs);
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().expect("Poisoned lock");
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            // Buckets are created full, dropping the ones which have refilled does not change any limit. API keys are
+            // bounded by the configuration and are kept.
+            let ip_weight_per_sec = f64::from(self.weight_per_sec);
+            let ip_capacity = ip_weight_per_sec * f64::from(self.burst_secs);
+            buckets.retain(|client, bucket| {
+                matches!(client, ClientId::ApiKey(_)) || !bucket.is_full(ip_weight_per_sec, ip_capacity, now)
+            });
+        }
+        buckets.entry(client.clone()).or_insert_with(|| Bucket { tokens: capacity, updated_at: now }).take(
+            f64::from(weight),
+            weight_per_sec,
+            capacity,
+            now,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcMiddlewareLayerRateLimit {
+    limiter: Arc<RateLimiter>,
+    client: ClientId,
+    metrics: Metrics,
+}
+
+impl RpcMiddlewareLayerRateLimit {
+    pub fn new(limiter: Arc<RateLimiter>, client: ClientId, metrics: Metrics) -> Self {
+        Self { limiter, client, metrics }
+    }
+}
+
+impl<S> tower::Layer<S> for RpcMiddlewareLayerRateLimit {
+    type Service = RpcMiddlewareServiceRateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcMiddlewareServiceRateLimit {
+            inner,
+            limiter: Arc::clone(&self.limiter),
+            client: self.client.clone(),
+            client_label: self.client.label(),
+            client_metric_label: self.client.metric_label(),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcMiddlewareServiceRateLimit<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+    client: ClientId,
+    client_label: String,
+    client_metric_label: String,
+    metrics: Metrics,
+}
+
+impl<'a, S> RpcServiceT<'a> for RpcMiddlewareServiceRateLimit<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'static,
+{
+    type Future = BoxFuture<'a, jsonrpsee::MethodResponse>;
+
+    fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
+        let inner = self.inner.clone();
+        let limiter = Arc::clone(&self.limiter);
+        let client = self.client.clone();
+        let client_label = self.client_label.clone();
+        let client_metric_label = self.client_metric_label.clone();
+        let metrics = self.metrics.clone();
+
+        async move {
+            let weight = limiter.method_weight(req.method_name());
+            if let Err(retry_after) = limiter.check(&client, weight) {
+                tracing::debug!(
+                    target: "rpc_calls",
+                    "Rate limited {} from {client_label}, retry after {retry_after:?}",
+                    req.method_name()
+                );
+                metrics.on_rate_limited(&client_metric_label, &req);
+                return jsonrpsee::MethodResponse::error(
+                    req.id,
+                    jsonrpsee::types::ErrorObject::owned(
+                        RATE_LIMITED_CODE,
+                        RATE_LIMITED_MSG,
+                        Some(serde_json::json!({ "retry_after_ms": retry_after.as_millis() as u64 })),
+                    ),
+                );
+            }
+
+            metrics.on_client_call(&client_metric_label, weight);
+            inner.call(req).await
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REMOTE_IP: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1));
+
+    fn limiter(ip_header: Option<&'static str>) -> RateLimiter {
+        RateLimiter {
+            weight_per_sec: 10,
+            burst_secs: 2,
+            api_keys: [("secret".to_string(), 0)].into(),
+            api_key_weights: vec![100],
+            method_weights: DEFAULT_METHOD_WEIGHTS
+                .iter()
+                .map(|(method, weight)| (method.to_string(), *weight))
+                .collect(),
+            ip_header: ip_header.map(hyper::header::HeaderName::from_static),
+            buckets: Default::default(),
+        }
+    }
+
+    fn request(headers: &[(&str, &str)]) -> hyper::Request<()> {
+        let mut builder = hyper::Request::builder();
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn bucket_refills_and_retries_after() {
+        let now = Instant::now();
+        let mut bucket = Bucket { tokens: 10.0, updated_at: now };
+
+        assert_eq!(bucket.take(10.0, 5.0, 10.0, now), Ok(()));
+        assert_eq!(bucket.take(1.0, 5.0, 10.0, now), Err(Duration::from_millis(200)));
+        assert_eq!(bucket.take(5.0, 5.0, 10.0, now + Duration::from_secs(1)), Ok(()));
+
+        // Refilling is capped by the capacity.
+        let later = now + Duration::from_secs(60);
+        assert!(bucket.is_full(5.0, 10.0, later));
+        assert_eq!(bucket.take(10.0, 5.0, 10.0, later), Ok(()));
+        assert_eq!(bucket.take(5.0, 5.0, 10.0, later), Err(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn bucket_heavy_method_fits_when_full() {
+        let now = Instant::now();
+        let mut bucket = Bucket { tokens: 10.0, updated_at: now };
+
+        assert_eq!(bucket.take(100.0, 5.0, 10.0, now), Ok(()));
+        assert_eq!(bucket.take(100.0, 5.0, 10.0, now), Err(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn method_weight_is_versionless() {
+        let limiter = limiter(None);
+        assert_eq!(limiter.method_weight("starknet_traceTransaction"), 50);
+        assert_eq!(limiter.method_weight("starknet_V0_7_1_traceTransaction"), 50);
+        assert_eq!(limiter.method_weight("starknet_V0_8_0_traceTransaction"), 50);
+        assert_eq!(limiter.method_weight("starknet_V0_8_0_blockNumber"), DEFAULT_WEIGHT);
+        assert_eq!(limiter.method_weight("unknown"), DEFAULT_WEIGHT);
+    }
+
+    #[test]
+    fn client_from_api_key() {
+        let limiter = limiter(None);
+        assert_eq!(limiter.client(&request(&[(API_KEY_HEADER, "secret")]), REMOTE_IP), ClientId::ApiKey(0));
+        assert_eq!(limiter.client(&request(&[(API_KEY_HEADER, " secret ")]), REMOTE_IP), ClientId::ApiKey(0));
+        assert_eq!(limiter.client(&request(&[(API_KEY_HEADER, "wrong")]), REMOTE_IP), ClientId::Ip(REMOTE_IP));
+        assert_eq!(limiter.client_weight_per_sec(&ClientId::ApiKey(0)), 100);
+        assert_eq!(limiter.client_weight_per_sec(&ClientId::Ip(REMOTE_IP)), 10);
+    }
+
+    #[test]
+    fn client_from_ip_header() {
+        let forwarded: IpAddr = "192.168.1.1".parse().unwrap();
+
+        let limiter_header = limiter(Some("x-forwarded-for"));
+        let req = request(&[("x-forwarded-for", "192.168.1.1, 10.0.0.2")]);
+        assert_eq!(limiter_header.client(&req, REMOTE_IP), ClientId::Ip(forwarded));
+        let req = request(&[("x-forwarded-for", "not an ip")]);
+        assert_eq!(limiter_header.client(&req, REMOTE_IP), ClientId::Ip(REMOTE_IP));
+        assert_eq!(limiter_header.client(&request(&[]), REMOTE_IP), ClientId::Ip(REMOTE_IP));
+
+        // The header is only trusted when configured.
+        let req = request(&[("x-forwarded-for", "192.168.1.1")]);
+        assert_eq!(limiter(None).client(&req, REMOTE_IP), ClientId::Ip(REMOTE_IP));
+    }
+
+    #[test]
+    fn check_rate_limits_per_client() {
+        let limiter = limiter(None);
+        let client = ClientId::Ip(REMOTE_IP);
+
+        // The bucket holds `burst_secs` seconds of weight.
+        assert_eq!(limiter.check(&client, 20), Ok(()));
+        assert!(limiter.check(&client, 1).is_err());
+        assert_eq!(limiter.check(&ClientId::ApiKey(0), 200), Ok(()));
+    }
+
+    #[test]
+    fn full_buckets_evicted_past_max_tracked_clients() {
+        let limiter = limiter(None);
+        let ip = |n: usize| ClientId::Ip(IpAddr::V6(std::net::Ipv6Addr::from(n as u128)));
+
+        // Drained buckets are kept, whatever their number.
+        assert_eq!(limiter.check(&ip(0), 20), Ok(()));
+        assert_eq!(limiter.check(&ClientId::ApiKey(0), 0), Ok(()));
+        for n in 1..MAX_TRACKED_CLIENTS - 1 {
+            assert_eq!(limiter.check(&ip(n), 0), Ok(()));
+        }
+        assert_eq!(limiter.buckets.lock().unwrap().len(), MAX_TRACKED_CLIENTS);
+
+        assert_eq!(limiter.check(&ip(MAX_TRACKED_CLIENTS), 0), Ok(()));
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert!(buckets.contains_key(&ip(0)));
+        assert!(buckets.contains_key(&ClientId::ApiKey(0)));
+        assert!(buckets.contains_key(&ip(MAX_TRACKED_CLIENTS)));
+    }
+
+    #[test]
+    fn metric_label_is_bounded() {
+        assert_eq!(ClientId::Ip(REMOTE_IP).metric_label(), "ip");
+        assert_eq!(ClientId::ApiKey(3).metric_label(), "api_key#3");
+    }
+}
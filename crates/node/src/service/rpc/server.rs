@@ -3,6 +3,7 @@
 
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
@@ -11,8 +12,10 @@ use tower::Service;
 
 use crate::service::rpc::middleware::RpcMiddlewareServiceVersion;
 
+use super::auth::{log_rejected, Authenticator, RpcMiddlewareLayerScopes};
 use super::metrics::RpcMetrics;
 use super::middleware::{Metrics, RpcMiddlewareLayerMetrics};
+use super::rate_limit::{RateLimiter, RpcMiddlewareLayerRateLimit};
 
 const MEGABYTE: u32 = 1024 * 1024;
 
@@ -32,6 +35,10 @@ pub struct ServerConfig {
     pub methods: jsonrpsee::Methods,
     /// Batch request config.
     pub batch_config: jsonrpsee::server::BatchRequestConfig,
+    /// Per-client rate limiting, disabled when [`None`].
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Client authentication, disabled when [`None`].
+    pub authenticator: Option<Arc<Authenticator>>,
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +46,8 @@ struct PerConnection<RpcMiddleware, HttpMiddleware> {
     methods: jsonrpsee::Methods,
     stop_handle: jsonrpsee::server::StopHandle,
     metrics: RpcMetrics,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    authenticator: Option<Arc<Authenticator>>,
     service_builder: jsonrpsee::server::TowerServiceBuilder<RpcMiddleware, HttpMiddleware>,
 }
 
@@ -63,6 +72,8 @@ pub async fn start_server<'a>(
         message_buffer_capacity,
         methods,
         batch_config,
+        rate_limiter,
+        authenticator,
     } = config;
 
     let listener = tokio::net::TcpListener::bind(addr)
@@ -94,11 +105,14 @@ pub async fn start_server<'a>(
         methods,
         stop_handle: stop_handle.clone(),
         metrics,
+        rate_limiter,
+        authenticator,
         service_builder: builder.to_service_builder(),
     };
     let ctx1 = ctx.clone();
 
-    let make_service = hyper::service::make_service_fn(move |_| {
+    let make_service = hyper::service::make_service_fn(move |conn: &hyper::server::conn::AddrStream| {
+        let remote_ip = conn.remote_addr().ip();
         let cfg = cfg.clone();
         let ctx1 = ctx1.clone();
 
@@ -106,19 +120,35 @@ pub async fn start_server<'a>(
             let cfg = cfg.clone();
 
             Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
-                let PerConnection { service_builder, metrics, stop_handle, methods } = cfg.clone();
+                let PerConnection { service_builder, metrics, rate_limiter, authenticator, stop_handle, methods } =
+                    cfg.clone();
                 let ctx1 = ctx1.clone();
 
                 let is_websocket = jsonrpsee::server::ws::is_upgrade_request(&req);
                 let transport_label = if is_websocket { "ws" } else { "http" };
                 let path = req.uri().path().to_string();
-                let metrics_layer = RpcMiddlewareLayerMetrics::new(Metrics::new(metrics, transport_label));
+                let metrics = Metrics::new(metrics, transport_label);
+                let metrics_layer = RpcMiddlewareLayerMetrics::new(metrics.clone());
+                // Websocket calls are accounted to the client which opened the connection.
+                let rate_limit_layer = rate_limiter.map(|rate_limiter| {
+                    let client = rate_limiter.client(&req, remote_ip);
+                    RpcMiddlewareLayerRateLimit::new(rate_limiter, client, metrics)
+                });
+
+                // Websocket connections are authenticated once, when upgrading.
+                let auth = authenticator.map(|authenticator| authenticator.authenticate(&req));
+                let scopes_layer = match &auth {
+                    Some(Ok(scopes)) => Some(RpcMiddlewareLayerScopes::new(scopes.clone(), remote_ip)),
+                    _ => None,
+                };
 
                 let rpc_middleware = jsonrpsee::server::RpcServiceBuilder::new()
                     .layer_fn(move |service| {
                         RpcMiddlewareServiceVersion::new(service, path.clone(), rpc_version_default)
                     })
-                    .layer(metrics_layer.clone());
+                    .layer(metrics_layer.clone())
+                    .option_layer(rate_limit_layer)
+                    .option_layer(scopes_layer);
 
                 let mut svc = service_builder.set_rpc_middleware(rpc_middleware).build(methods, stop_handle);
 
@@ -129,6 +159,11 @@ pub async fn start_server<'a>(
                             .body(hyper::Body::from("GONE"))?)
                     } else if req.uri().path() == "/health" {
                         Ok(hyper::Response::builder().status(hyper::StatusCode::OK).body(hyper::Body::from("OK"))?)
+                    } else if let Some(Err(err)) = auth {
+                        log_rejected(remote_ip, &err);
+                        Ok(hyper::Response::builder()
+                            .status(hyper::StatusCode::UNAUTHORIZED)
+                            .body(hyper::Body::from(err.to_string()))?)
                     } else {
                         if is_websocket {
                             // Utilize the session close future to know when the actual WebSocket
@@ -213,7 +248,11 @@ pub(crate) fn rpc_api_build<M: Send + Sync + 'static>(
                 let minor = split[2];
                 let patch = split[3];
                 let method = split[4];
-                format!("{service}/{major}_{minor}_{patch}/{namespace}_{method}")
+                if namespace == "rpc" && method == "discover" {
+                    format!("{service}/{major}_{minor}_{patch}/rpc.discover")
+                } else {
+                    format!("{service}/{major}_{minor}_{patch}/{namespace}_{method}")
+                }
             }
         })
         .collect::<Vec<_>>();
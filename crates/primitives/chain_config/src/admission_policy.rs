@@ -0,0 +1,37 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt;
+
+/// Sequencer-side transaction admission rules, applied by the mempool before a transaction is validated and
+/// inserted. This is how an appchain operator can enforce compliance rules at the sequencer level.
+///
+/// An empty policy (the default) admits every transaction. L1 handler transactions are never subject to these
+/// rules, since they have already been accepted on the settlement layer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdmissionPolicy {
+    /// When set, only these sender addresses can submit transactions.
+    pub sender_allow_list: Option<HashSet<Felt>>,
+    /// Sender addresses that are never allowed to submit transactions.
+    pub sender_deny_list: HashSet<Felt>,
+    /// When set, invoke transactions may only call into these contracts.
+    pub target_allow_list: Option<HashSet<Felt>>,
+    /// Contracts that invoke transactions may never call into.
+    pub target_deny_list: HashSet<Felt>,
+    /// Entry-point selectors that invoke transactions may never call.
+    pub blocked_selectors: HashSet<Felt>,
+    /// Minimum tip. Transactions older than v3 do not have a tip and are considered to have a tip of zero.
+    pub min_tip: u64,
+    /// Maximum calldata length, in felts. For deploy account transactions, this is the constructor calldata.
+    pub max_calldata_length: Option<usize>,
+    /// When set, only these sender addresses can declare classes.
+    pub declare_allow_list: Option<HashSet<Felt>>,
+}
+
+impl AdmissionPolicy {
+    /// Whether the invoke calldata needs to be decoded into calls for this policy to be enforced.
+    pub fn filters_calls(&self) -> bool {
+        self.target_allow_list.is_some() || !self.target_deny_list.is_empty() || !self.blocked_selectors.is_empty()
+    }
+}
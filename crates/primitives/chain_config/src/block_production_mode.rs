@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// When the block production task closes the pending block.
+///
+/// In every mode, a block that reaches the bouncer limits is closed right away. Blocks can also always be closed
+/// manually using the `madara_closeBlock` admin RPC method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockProductionMode {
+    /// Close a block every [`block_time`], updating the pending block every [`pending_block_update_time`]. Empty
+    /// blocks are produced when there are no transactions in the mempool.
+    ///
+    /// [`block_time`]: crate::ChainConfig::block_time
+    /// [`pending_block_update_time`]: crate::ChainConfig::pending_block_update_time
+    #[default]
+    Interval,
+    /// Same as [`BlockProductionMode::Interval`], except that blocks with no transactions are not closed: the pending
+    /// block is kept open until a transaction is included.
+    SkipEmpty,
+    /// Close blocks as soon as transactions enter the mempool, without waiting for the block time. The mempool is
+    /// drained into as many blocks as needed: each block is filled up to the bouncer limits before being closed.
+    Instant,
+    /// Only close blocks when requested with the `madara_closeBlock` admin RPC method. The pending block is still
+    /// updated every [`pending_block_update_time`].
+    ///
+    /// [`pending_block_update_time`]: crate::ChainConfig::pending_block_update_time
+    Manual,
+}
+
+impl BlockProductionMode {
+    /// Whether blocks are closed when the block time elapses.
+    pub fn closes_on_block_time(&self) -> bool {
+        matches!(self, Self::Interval | Self::SkipEmpty)
+    }
+
+    /// Whether the pending block is updated every pending tick.
+    pub fn updates_pending_block(&self) -> bool {
+        !matches!(self, Self::Instant)
+    }
+
+    /// Whether a block with no transactions should be closed when the block time elapses.
+    pub fn closes_empty_blocks(&self) -> bool {
+        matches!(self, Self::Interval)
+    }
+}
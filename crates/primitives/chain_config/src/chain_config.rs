@@ -27,7 +27,10 @@ use url::Url;
 
 use mp_utils::serde::{deserialize_duration, deserialize_optional_duration, deserialize_private_key};
 
-use crate::StarknetVersion;
+use crate::{
+    AdmissionPolicy, BlockProductionMode, ProtocolUpgradeSchedule, ProtocolUpgradeScheduleError, SettlementLayer,
+    StarknetVersion,
+};
 
 pub mod eth_core_contract_address {
     pub const MAINNET: &str = "0xc662c410C0ECf747543f5bA90660f6ABeBD9C8c4";
@@ -87,9 +90,17 @@ pub struct ChainConfig {
     #[serde(default)]
     pub versioned_constants: ChainVersionedConstants,
 
+    /// Protocol version of new blocks. When protocol upgrades are scheduled, this is the version used until the first
+    /// upgrade activates.
     #[serde(deserialize_with = "deserialize_starknet_version")]
     pub latest_protocol_version: StarknetVersion,
 
+    /// Scheduled protocol upgrades, in activation order. Block production switches to the new version (and the
+    /// matching versioned constants) for the first block created after the activation, and the full node checks that
+    /// synced blocks follow this schedule.
+    #[serde(default)]
+    pub protocol_upgrades: ProtocolUpgradeSchedule,
+
     /// Only used for block production.
     #[serde(deserialize_with = "deserialize_duration")]
     pub block_time: Duration,
@@ -99,6 +110,12 @@ pub struct ChainConfig {
     #[serde(deserialize_with = "deserialize_duration")]
     pub pending_block_update_time: Duration,
 
+    /// Only used for block production.
+    /// When blocks are closed: on block time (the default), on block time but skipping empty blocks, as soon as
+    /// transactions arrive, or only on demand through the admin RPC.
+    #[serde(default)]
+    pub block_production_mode: BlockProductionMode,
+
     /// Only used for block production.
     /// Block production is handled in batches; each batch will pop this number of transactions from the mempool. This is
     /// primarily useful for optimistic parallelization.
@@ -116,6 +133,10 @@ pub struct ChainConfig {
     /// The Starknet core contract address for the L1 watcher.
     pub eth_core_contract_address: H160,
 
+    /// The chain this chain settles on. Ethereum by default, or a Starknet chain for L3 appchains.
+    #[serde(default)]
+    pub settlement_layer: SettlementLayer,
+
     /// The Starknet SHARP verifier La address. Check out the [docs](https://docs.starknet.io/architecture-and-concepts/solidity-verifier/)
     /// for more information
     pub eth_gps_statement_verifier: H160,
@@ -138,6 +159,9 @@ pub struct ChainConfig {
     /// Max age of a transaction in the mempool.
     #[serde(deserialize_with = "deserialize_optional_duration")]
     pub mempool_tx_max_age: Option<Duration>,
+    /// Transaction admission rules enforced by the mempool. These can be reloaded at runtime using the admin RPC.
+    #[serde(default)]
+    pub mempool_admission_policy: AdmissionPolicy,
 }
 
 impl ChainConfig {
@@ -160,7 +184,23 @@ impl ChainConfig {
         let chain_config: ChainConfig =
             serde_yaml::from_str(&config_str).context("While deserializing chain config")?;
 
-        Ok(ChainConfig { versioned_constants, ..chain_config })
+        let chain_config = ChainConfig { versioned_constants, ..chain_config };
+        chain_config.check_protocol_upgrades()?;
+        Ok(chain_config)
+    }
+
+    /// Verify that the protocol upgrade schedule is well-formed, and that versioned constants are available for
+    /// every scheduled version. Like [`ChainConfig::exec_constants_by_protocol_version`], a version uses the constants
+    /// of the closest version at or below it.
+    pub fn check_protocol_upgrades(&self) -> anyhow::Result<()> {
+        self.protocol_upgrades.check(self.latest_protocol_version).context("Invalid protocol upgrade schedule")?;
+        for upgrade in &self.protocol_upgrades.0 {
+            if self.exec_constants_by_protocol_version(upgrade.version).is_err() {
+                return Err(ProtocolUpgradeScheduleError::MissingVersionedConstants(upgrade.version))
+                    .context("Invalid protocol upgrade schedule");
+            }
+        }
+        Ok(())
     }
 
     /// Verify that the chain config is valid for block production.
@@ -175,6 +215,7 @@ impl ChainConfig {
         if self.pending_block_update_time.as_millis() == 0 {
             bail!("Block time cannot be zero for block production.")
         }
+        self.check_protocol_upgrades()?;
         Ok(())
     }
 
@@ -205,12 +246,15 @@ impl ChainConfig {
             versioned_constants: ChainVersionedConstants::default(),
 
             eth_core_contract_address: eth_core_contract_address::MAINNET.parse().expect("parsing a constant"),
+            settlement_layer: SettlementLayer::Ethereum,
 
             eth_gps_statement_verifier: eth_gps_statement_verifier::MAINNET.parse().expect("parsing a constant"),
 
             latest_protocol_version: StarknetVersion::V0_13_2,
+            protocol_upgrades: ProtocolUpgradeSchedule::default(),
             block_time: Duration::from_secs(30),
             pending_block_update_time: Duration::from_secs(2),
+            block_production_mode: BlockProductionMode::Interval,
 
             execution_batch_size: 16,
 
@@ -248,6 +292,7 @@ impl ChainConfig {
             mempool_tx_limit: 10_000,
             mempool_declare_tx_limit: 20,
             mempool_tx_max_age: Some(Duration::from_secs(60 * 60)), // an hour?
+            mempool_admission_policy: AdmissionPolicy::default(),
         }
     }
 
@@ -308,6 +353,12 @@ impl ChainConfig {
         }
     }
 
+    /// Protocol version of the block with the given number and timestamp, following the
+    /// [`ChainConfig::protocol_upgrades`] schedule.
+    pub fn protocol_version_at(&self, block_n: u64, block_timestamp: u64) -> StarknetVersion {
+        self.protocol_upgrades.version_at(block_n, block_timestamp).unwrap_or(self.latest_protocol_version)
+    }
+
     /// This is the number of pending ticks (see [`ChainConfig::pending_block_update_time`]) in a block.
     pub fn n_pending_ticks_per_block(&self) -> usize {
         (self.block_time.as_millis() / self.pending_block_update_time.as_millis()) as usize
@@ -619,4 +670,39 @@ mod tests {
         );
         assert!(chain_config.exec_constants_by_protocol_version(StarknetVersion::new(0, 0, 0, 0)).is_err(),);
     }
+
+    #[rstest]
+    fn test_protocol_upgrades() {
+        let chain_config = ChainConfig {
+            latest_protocol_version: StarknetVersion::V0_13_1,
+            protocol_upgrades: ProtocolUpgradeSchedule(vec![crate::ProtocolUpgrade {
+                activation: crate::UpgradeActivation::AtBlock(5),
+                version: StarknetVersion::V0_13_2,
+            }]),
+            ..ChainConfig::madara_test()
+        };
+        chain_config.check_protocol_upgrades().unwrap();
+        assert_eq!(chain_config.protocol_version_at(4, 0), StarknetVersion::V0_13_1);
+        assert_eq!(chain_config.protocol_version_at(5, 0), StarknetVersion::V0_13_2);
+
+        let chain_config = ChainConfig {
+            protocol_upgrades: ProtocolUpgradeSchedule(vec![crate::ProtocolUpgrade {
+                activation: crate::UpgradeActivation::AtBlock(5),
+                version: StarknetVersion::new(0, 13, 3, 0),
+            }]),
+            ..chain_config
+        };
+        // 0.13.3 falls back to the 0.13.2 constants, like in the executor.
+        chain_config.check_protocol_upgrades().unwrap();
+
+        let chain_config = ChainConfig {
+            latest_protocol_version: StarknetVersion::new(0, 12, 0, 0),
+            protocol_upgrades: ProtocolUpgradeSchedule(vec![crate::ProtocolUpgrade {
+                activation: crate::UpgradeActivation::AtBlock(5),
+                version: StarknetVersion::new(0, 12, 3, 0),
+            }]),
+            ..chain_config
+        };
+        assert!(chain_config.check_protocol_upgrades().is_err());
+    }
 }
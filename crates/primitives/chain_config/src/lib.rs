@@ -1,7 +1,15 @@
+mod admission_policy;
+mod block_production_mode;
 mod chain_config;
+mod protocol_upgrades;
 mod rpc_version;
+mod settlement_layer;
 mod starknet_version;
 
+pub use admission_policy::*;
+pub use block_production_mode::*;
 pub use chain_config::*;
+pub use protocol_upgrades::*;
 pub use rpc_version::*;
+pub use settlement_layer::*;
 pub use starknet_version::*;
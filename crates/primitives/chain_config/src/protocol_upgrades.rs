@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{deserialize_starknet_version, serialize_starknet_version, StarknetVersion};
+
+/// When a [`ProtocolUpgrade`] takes effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeActivation {
+    /// The upgrade applies to every block starting from this block number.
+    AtBlock(u64),
+    /// The upgrade applies to every block which timestamp is greater or equal to this unix timestamp (in seconds).
+    AtTimestamp(u64),
+}
+
+impl UpgradeActivation {
+    pub fn is_active(&self, block_n: u64, block_timestamp: u64) -> bool {
+        match *self {
+            Self::AtBlock(at) => block_n >= at,
+            Self::AtTimestamp(at) => block_timestamp >= at,
+        }
+    }
+}
+
+/// A scheduled switch to a new protocol version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolUpgrade {
+    #[serde(flatten)]
+    pub activation: UpgradeActivation,
+    #[serde(deserialize_with = "deserialize_starknet_version", serialize_with = "serialize_starknet_version")]
+    pub version: StarknetVersion,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ProtocolUpgradeScheduleError {
+    #[error("Scheduled protocol version {version} must be greater than the previous version {previous}")]
+    VersionNotIncreasing { version: StarknetVersion, previous: StarknetVersion },
+    #[error("Protocol upgrade to {version} is scheduled before the upgrade to {previous}")]
+    ActivationNotIncreasing { version: StarknetVersion, previous: StarknetVersion },
+    #[error("No versioned constants are available for the scheduled protocol version {0}")]
+    MissingVersionedConstants(StarknetVersion),
+}
+
+/// The protocol upgrades scheduled for a chain, in activation order. Before the first upgrade activates, blocks use the
+/// chain's `latest_protocol_version`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProtocolUpgradeSchedule(pub Vec<ProtocolUpgrade>);
+
+impl ProtocolUpgradeSchedule {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the protocol version of the block with the given number and timestamp, or `None` if no scheduled
+    /// upgrade is active yet.
+    pub fn version_at(&self, block_n: u64, block_timestamp: u64) -> Option<StarknetVersion> {
+        // Upgrades are sorted, the last active one wins.
+        self.0.iter().rev().find(|upgrade| upgrade.activation.is_active(block_n, block_timestamp)).map(|u| u.version)
+    }
+
+    /// Checks that the versions are strictly increasing, starting from `initial_version`, and that activations of the
+    /// same kind are in order. Activations of different kinds cannot be compared and are trusted to be in order.
+    pub fn check(&self, initial_version: StarknetVersion) -> Result<(), ProtocolUpgradeScheduleError> {
+        let mut previous_version = initial_version;
+        let mut previous_block = None;
+        let mut previous_timestamp = None;
+        for upgrade in &self.0 {
+            if upgrade.version <= previous_version {
+                return Err(ProtocolUpgradeScheduleError::VersionNotIncreasing {
+                    version: upgrade.version,
+                    previous: previous_version,
+                });
+            }
+            let previous_activation = match upgrade.activation {
+                UpgradeActivation::AtBlock(at) => previous_block.replace(at).filter(|prev| *prev > at),
+                UpgradeActivation::AtTimestamp(at) => previous_timestamp.replace(at).filter(|prev| *prev > at),
+            };
+            if previous_activation.is_some() {
+                return Err(ProtocolUpgradeScheduleError::ActivationNotIncreasing {
+                    version: upgrade.version,
+                    previous: previous_version,
+                });
+            }
+            previous_version = upgrade.version;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn schedule() -> ProtocolUpgradeSchedule {
+        ProtocolUpgradeSchedule(vec![
+            ProtocolUpgrade { activation: UpgradeActivation::AtBlock(10), version: StarknetVersion::V0_13_1 },
+            ProtocolUpgrade { activation: UpgradeActivation::AtTimestamp(1000), version: StarknetVersion::V0_13_1_1 },
+            ProtocolUpgrade { activation: UpgradeActivation::AtBlock(20), version: StarknetVersion::V0_13_2 },
+        ])
+    }
+
+    #[rstest]
+    #[case(0, 0, None)]
+    #[case(10, 0, Some(StarknetVersion::V0_13_1))]
+    #[case(15, 999, Some(StarknetVersion::V0_13_1))]
+    #[case(15, 1000, Some(StarknetVersion::V0_13_1_1))]
+    #[case(20, 1000, Some(StarknetVersion::V0_13_2))]
+    fn test_version_at(#[case] block_n: u64, #[case] timestamp: u64, #[case] expected: Option<StarknetVersion>) {
+        assert_eq!(schedule().version_at(block_n, timestamp), expected);
+    }
+
+    #[rstest]
+    fn test_check() {
+        assert_eq!(schedule().check(StarknetVersion::V0_13_0), Ok(()));
+        assert_eq!(
+            schedule().check(StarknetVersion::V0_13_1),
+            Err(ProtocolUpgradeScheduleError::VersionNotIncreasing {
+                version: StarknetVersion::V0_13_1,
+                previous: StarknetVersion::V0_13_1
+            })
+        );
+
+        let mut schedule = schedule();
+        schedule.0[2].activation = UpgradeActivation::AtBlock(5);
+        assert_eq!(
+            schedule.check(StarknetVersion::V0_13_0),
+            Err(ProtocolUpgradeScheduleError::ActivationNotIncreasing {
+                version: StarknetVersion::V0_13_2,
+                previous: StarknetVersion::V0_13_1_1
+            })
+        );
+    }
+
+    #[rstest]
+    fn test_deserialize() {
+        let schedule: ProtocolUpgradeSchedule = serde_yaml::from_str(
+            r#"
+            - at_block: 10
+              version: "0.13.1"
+            - at_timestamp: 1000
+              version: "0.13.1.1"
+            - at_block: 20
+              version: "0.13.2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schedule, self::schedule());
+    }
+}
@@ -1,8 +1,12 @@
 use std::hash::Hash;
 use std::str::FromStr;
 
-const SUPPORTED_RPC_VERSIONS: [RpcVersion; 3] =
-    [RpcVersion::RPC_VERSION_0_7_1, RpcVersion::RPC_VERSION_0_8_0, RpcVersion::RPC_VERSION_ADMIN_0_1_0];
+const SUPPORTED_RPC_VERSIONS: [RpcVersion; 4] = [
+    RpcVersion::RPC_VERSION_0_6_0,
+    RpcVersion::RPC_VERSION_0_7_1,
+    RpcVersion::RPC_VERSION_0_8_0,
+    RpcVersion::RPC_VERSION_ADMIN_0_1_0,
+];
 
 #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize, Hash)]
 pub struct RpcVersion([u8; 3]);
@@ -79,6 +83,7 @@ impl RpcVersion {
         format!("V{}_{}_{}", self.0[0], self.0[1], self.0[2])
     }
 
+    pub const RPC_VERSION_0_6_0: RpcVersion = RpcVersion([0, 6, 0]);
     pub const RPC_VERSION_0_7_1: RpcVersion = RpcVersion([0, 7, 1]);
     pub const RPC_VERSION_0_8_0: RpcVersion = RpcVersion([0, 8, 0]);
     pub const RPC_VERSION_LATEST: RpcVersion = Self::RPC_VERSION_0_8_0;
@@ -169,6 +174,10 @@ mod tests {
 
     #[test]
     fn test_from_request_path_valid() {
+        assert_eq!(
+            RpcVersion::from_request_path("/rpc/v0_6_0", RpcVersion::RPC_VERSION_LATEST).unwrap(),
+            RpcVersion::RPC_VERSION_0_6_0
+        );
         assert_eq!(
             RpcVersion::from_request_path("/rpc/v0_7_1/", RpcVersion::RPC_VERSION_LATEST).unwrap(),
             RpcVersion::RPC_VERSION_0_7_1
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt;
+
+/// The chain this chain settles on, and reads its confirmed state and its incoming messages from.
+///
+/// ```yaml
+/// settlement_layer:
+///   type: starknet
+///   core_contract_address: "0x2bd1d3f8f45a011cbd0674ded291d58985761bbcbc04f4d01c8285d1b35c411"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettlementLayer {
+    /// Ethereum, with the Starknet core contract at [`eth_core_contract_address`].
+    ///
+    /// [`eth_core_contract_address`]: crate::ChainConfig::eth_core_contract_address
+    #[default]
+    Ethereum,
+    /// A Starknet chain, making this chain an L3 appchain. The appchain core contract is a Cairo contract deployed on
+    /// the base layer.
+    Starknet { core_contract_address: Felt },
+}
@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::fallback::FallbackOracle;
+use crate::fixed::FixedRateOracle;
+use crate::json_http::JsonHttpOracle;
+use crate::median::MedianOracle;
+use crate::pragma::PragmaOracle;
+use crate::{Oracle, PriceQuote};
+
+/// Selects the oracle used for the ETH/STRK price. Aggregating oracles take other oracle configs as sources, so that
+/// they can be nested:
+///
+/// ```yaml
+/// type: fallback
+/// oracles:
+///   - type: median
+///     min_sources: 2
+///     max_staleness: 5min
+///     max_deviation_bps: 300
+///     sources:
+///       - type: pragma
+///         api_url: https://api.dev.pragma.build/node/v1/data/
+///         api_key: ...
+///       - type: json_http
+///         url: https://example.com/api/v1/price?pair=ETH-STRK
+///         price_path: data.price
+///         decimals: 18
+///   - type: fixed
+///     price: 3000
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OracleConfig {
+    Pragma(PragmaOracle),
+    Fixed(FixedRateOracle),
+    JsonHttp(JsonHttpOracle),
+    Median(MedianOracle),
+    Fallback(FallbackOracle),
+}
+
+impl OracleConfig {
+    pub fn from_yaml(path: &Path) -> anyhow::Result<Self> {
+        let config_str = std::fs::read_to_string(path)
+            .with_context(|| format!("While reading oracle config from {}", path.display()))?;
+        serde_yaml::from_str(&config_str).context("While deserializing oracle config")
+    }
+
+    fn as_oracle(&self) -> &dyn Oracle {
+        match self {
+            Self::Pragma(oracle) => oracle,
+            Self::Fixed(oracle) => oracle,
+            Self::JsonHttp(oracle) => oracle,
+            Self::Median(oracle) => oracle,
+            Self::Fallback(oracle) => oracle,
+        }
+    }
+}
+
+#[async_trait]
+impl Oracle for OracleConfig {
+    async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)> {
+        self.as_oracle().fetch_eth_strk_price().await
+    }
+
+    async fn fetch_eth_strk_quote(&self) -> anyhow::Result<PriceQuote> {
+        self.as_oracle().fetch_eth_strk_quote().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle_config_yaml() {
+        let config: OracleConfig = serde_yaml::from_str(
+            r#"
+            type: fallback
+            oracles:
+              - type: median
+                min_sources: 2
+                max_staleness: 5min
+                sources:
+                  - type: pragma
+                    api_key: key
+                  - type: json_http
+                    url: https://example.com/price
+                    price_path: data.price
+                    timeout: 3s
+                    headers:
+                      x-api-key: key
+              - type: fixed
+                price: 3000
+            "#,
+        )
+        .unwrap();
+
+        let OracleConfig::Fallback(FallbackOracle { oracles }) = config else { panic!("expected a fallback oracle") };
+        let OracleConfig::Median(median) = &oracles[0] else { panic!("expected a median oracle") };
+        assert_eq!(median.min_sources, 2);
+        assert_eq!(median.max_staleness, Some(std::time::Duration::from_secs(300)));
+        let [OracleConfig::Pragma(pragma), OracleConfig::JsonHttp(json_http)] = &median.sources[..] else {
+            panic!("expected a pragma and a json_http oracle")
+        };
+        assert_eq!(pragma.timeout, crate::DEFAULT_REQUEST_TIMEOUT);
+        assert_eq!(json_http.timeout, std::time::Duration::from_secs(3));
+        assert!(matches!(&oracles[1], OracleConfig::Fixed(FixedRateOracle { price: 3000, decimals: 0 })));
+    }
+}
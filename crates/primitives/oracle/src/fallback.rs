@@ -0,0 +1,56 @@
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{Oracle, OracleConfig, PriceQuote};
+
+/// Queries its oracles in order, and returns the price of the first one which succeeds. This way, the outage of one
+/// oracle does not stall the STRK gas price updates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackOracle {
+    pub oracles: Vec<OracleConfig>,
+}
+
+#[async_trait]
+impl Oracle for FallbackOracle {
+    async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)> {
+        let quote = self.fetch_eth_strk_quote().await?;
+        Ok((quote.price, quote.decimals))
+    }
+
+    async fn fetch_eth_strk_quote(&self) -> anyhow::Result<PriceQuote> {
+        let mut last_error = anyhow!("No oracle configured");
+        for (i, oracle) in self.oracles.iter().enumerate() {
+            match oracle.fetch_eth_strk_quote().await {
+                Ok(quote) => return Ok(quote),
+                Err(err) => {
+                    tracing::warn!("Oracle #{i} failed to provide the ETH/STRK price, trying the next one: {err:#}");
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error).context("All oracles failed to provide the ETH/STRK price")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::FixedRateOracle;
+
+    #[tokio::test]
+    async fn test_fallback_oracle() {
+        let oracle = FallbackOracle {
+            oracles: vec![
+                OracleConfig::Fixed(FixedRateOracle::new(0, 0)),
+                OracleConfig::Fixed(FixedRateOracle::new(3000, 1)),
+                OracleConfig::Fixed(FixedRateOracle::new(4000, 1)),
+            ],
+        };
+        assert_eq!(oracle.fetch_eth_strk_price().await.unwrap(), (3000, 1));
+
+        let oracle = FallbackOracle { oracles: vec![OracleConfig::Fixed(FixedRateOracle::new(0, 0))] };
+        assert!(oracle.fetch_eth_strk_price().await.is_err());
+        assert!(FallbackOracle { oracles: vec![] }.fetch_eth_strk_price().await.is_err());
+    }
+}
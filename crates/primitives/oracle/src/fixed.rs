@@ -0,0 +1,29 @@
+use anyhow::ensure;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::Oracle;
+
+/// An oracle returning a constant ETH/STRK price, `price / 10^decimals` STRK for one ETH.
+///
+/// Useful as the last resort of a [`crate::fallback::FallbackOracle`], or for networks where STRK has no market price.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedRateOracle {
+    pub price: u128,
+    #[serde(default)]
+    pub decimals: u32,
+}
+
+impl FixedRateOracle {
+    pub fn new(price: u128, decimals: u32) -> Self {
+        Self { price, decimals }
+    }
+}
+
+#[async_trait]
+impl Oracle for FixedRateOracle {
+    async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)> {
+        ensure!(self.price != 0, "Fixed ETH/STRK price is 0");
+        Ok((self.price, self.decimals))
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, ensure, Context};
+use async_trait::async_trait;
+use mp_utils::serde::{deserialize_duration, serialize_duration};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::pragma::PriceBounds;
+use crate::{default_request_timeout, http_client, Oracle, PriceQuote};
+
+/// An oracle fetching the ETH/STRK price from any HTTP API returning JSON.
+///
+/// Paths into the response are written `data.prices[0].value`, with an optional leading `$.`.
+///
+/// The value at `price_path` can be a JSON number or a string, in decimal or `0x`-prefixed hexadecimal. It is
+/// converted to an integer with `decimals` decimals, so that `"3120.5"` with 2 decimals becomes `(312050, 2)`. When
+/// `decimals_path` is set instead, the price is expected to already be an integer with that many decimals, as
+/// returned by Pragma.
+///
+/// When `timestamp_path` is set, the value at that path is the Unix timestamp of the price, in seconds.
+///
+/// Requests fail after `timeout`, 10 seconds by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonHttpOracle {
+    pub url: Url,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub price_path: String,
+    #[serde(default)]
+    pub decimals: u32,
+    #[serde(default)]
+    pub decimals_path: Option<String>,
+    #[serde(default)]
+    pub timestamp_path: Option<String>,
+    #[serde(default)]
+    pub price_bounds: PriceBounds,
+    #[serde(
+        default = "default_request_timeout",
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration"
+    )]
+    pub timeout: Duration,
+}
+
+#[async_trait]
+impl Oracle for JsonHttpOracle {
+    async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)> {
+        let quote = self.fetch_eth_strk_quote().await?;
+        Ok((quote.price, quote.decimals))
+    }
+
+    async fn fetch_eth_strk_quote(&self) -> anyhow::Result<PriceQuote> {
+        let mut request = http_client(self.timeout)?.get(self.url.clone());
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("failed to retrieve price from {}", self.url))?;
+        let body = response.json::<Value>().await.context("failed to parse api response")?;
+
+        let quote = self.parse_response(&body)?;
+        ensure!(quote.price != 0, "Oracle api returned 0 for eth/strk price");
+        ensure!(self.price_bounds.is_in_bounds(quote.price), "ETH/STRK price outside of bounds");
+        Ok(quote)
+    }
+}
+
+impl JsonHttpOracle {
+    fn parse_response(&self, body: &Value) -> anyhow::Result<PriceQuote> {
+        let price = get_path(body, &self.price_path)?;
+        let (price, decimals) = match &self.decimals_path {
+            Some(decimals_path) => {
+                let decimals = parse_integer(get_path(body, decimals_path)?).context("failed to parse decimals")?;
+                let decimals = u32::try_from(decimals).context("decimals out of range")?;
+                (parse_integer(price).context("failed to parse price")?, decimals)
+            }
+            None => (parse_decimal(price, self.decimals).context("failed to parse price")?, self.decimals),
+        };
+
+        let timestamp = match &self.timestamp_path {
+            Some(timestamp_path) => {
+                let secs = parse_integer(get_path(body, timestamp_path)?).context("failed to parse timestamp")?;
+                UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).context("timestamp out of range")?)
+            }
+            None => SystemTime::now(),
+        };
+
+        Ok(PriceQuote { price, decimals, timestamp })
+    }
+}
+
+/// Resolve a `a.b[0].c` path by converting it to a JSON pointer.
+fn get_path<'a>(body: &'a Value, path: &str) -> anyhow::Result<&'a Value> {
+    let pointer: String = path
+        .trim_start_matches('$')
+        .replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{segment}"))
+        .collect();
+    body.pointer(&pointer).with_context(|| format!("no value at path {path:?} in api response"))
+}
+
+fn value_as_str(value: &Value) -> anyhow::Result<String> {
+    match value {
+        Value::String(s) => Ok(s.trim().to_owned()),
+        Value::Number(n) => Ok(n.to_string()),
+        _ => bail!("expected a number or a string, got {value}"),
+    }
+}
+
+fn parse_integer(value: &Value) -> anyhow::Result<u128> {
+    let s = value_as_str(value)?;
+    match s.strip_prefix("0x") {
+        Some(hex) => Ok(u128::from_str_radix(hex, 16)?),
+        None => Ok(s.parse()?),
+    }
+}
+
+/// Parse a decimal number into an integer with `decimals` decimals, truncating the extra digits.
+fn parse_decimal(value: &Value, decimals: u32) -> anyhow::Result<u128> {
+    let s = value_as_str(value)?;
+    if s.starts_with("0x") {
+        return parse_integer(value)?.checked_mul(10u128.pow(decimals)).context("price overflow");
+    }
+    ensure!(!s.contains(['e', 'E']), "exponent notation is not supported: {s}");
+
+    let (int_part, frac_part) = s.split_once('.').unwrap_or((&s, ""));
+    let frac_part: String = frac_part.chars().chain(std::iter::repeat('0')).take(decimals as usize).collect();
+    let int_part: u128 = if int_part.is_empty() { 0 } else { int_part.parse()? };
+    let frac_part: u128 = if frac_part.is_empty() { 0 } else { frac_part.parse()? };
+
+    int_part.checked_mul(10u128.pow(decimals)).and_then(|int| int.checked_add(frac_part)).context("price overflow")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn oracle(price_path: &str, decimals: u32) -> JsonHttpOracle {
+        JsonHttpOracle {
+            url: Url::parse("http://localhost").unwrap(),
+            headers: Default::default(),
+            price_path: price_path.into(),
+            decimals,
+            decimals_path: None,
+            timestamp_path: None,
+            price_bounds: Default::default(),
+            timeout: default_request_timeout(),
+        }
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({ "data": { "prices": [{ "value": "3120.567" }, { "value": 3100.5 }] } });
+        assert_eq!(oracle("$.data.prices[0].value", 2).parse_response(&body).unwrap().price, 312056);
+        assert_eq!(oracle("data.prices.1.value", 3).parse_response(&body).unwrap().price, 3100500);
+        assert!(oracle("data.prices[2].value", 2).parse_response(&body).is_err());
+
+        let body = json!({ "price": "0x10", "decimals": 8, "timestamp": 1700000000 });
+        let mut oracle = oracle("price", 0);
+        oracle.decimals_path = Some("decimals".into());
+        oracle.timestamp_path = Some("timestamp".into());
+        assert_eq!(
+            oracle.parse_response(&body).unwrap(),
+            PriceQuote { price: 16, decimals: 8, timestamp: UNIX_EPOCH + Duration::from_secs(1700000000) }
+        );
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal(&json!("12"), 3).unwrap(), 12000);
+        assert_eq!(parse_decimal(&json!(".5"), 1).unwrap(), 5);
+        assert_eq!(parse_decimal(&json!("1.23456"), 0).unwrap(), 1);
+        assert!(parse_decimal(&json!("1e3"), 0).is_err());
+        assert!(parse_decimal(&json!(true), 0).is_err());
+    }
+}
@@ -1,8 +1,45 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
 use async_trait::async_trait;
 
+pub mod config;
+pub mod fallback;
+pub mod fixed;
+pub mod json_http;
+pub mod median;
 pub mod pragma;
 
+pub use config::OracleConfig;
+
+/// An ETH/STRK price, `price / 10^decimals` STRK for one ETH, as published at `timestamp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceQuote {
+    pub price: u128,
+    pub decimals: u32,
+    pub timestamp: SystemTime,
+}
+
+/// How long the HTTP oracles wait for a response, unless configured otherwise.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn default_request_timeout() -> Duration {
+    DEFAULT_REQUEST_TIMEOUT
+}
+
+/// HTTP client used by the oracles. Requests fail after `timeout`, so that a hanging API is reported as a failure.
+fn http_client(timeout: Duration) -> anyhow::Result<reqwest::Client> {
+    reqwest::Client::builder().timeout(timeout).build().context("failed to build the oracle HTTP client")
+}
+
 #[async_trait]
 pub trait Oracle: Send + Sync {
     async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)>;
+
+    /// Same as [`Oracle::fetch_eth_strk_price`], along with the time at which the price was published. Oracles which
+    /// do not know it report the time of the fetch.
+    async fn fetch_eth_strk_quote(&self) -> anyhow::Result<PriceQuote> {
+        let (price, decimals) = self.fetch_eth_strk_price().await?;
+        Ok(PriceQuote { price, decimals, timestamp: SystemTime::now() })
+    }
 }
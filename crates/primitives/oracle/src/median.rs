@@ -0,0 +1,158 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{ensure, Context};
+use async_trait::async_trait;
+use mp_utils::serde::{deserialize_optional_duration, serialize_optional_duration};
+use serde::{Deserialize, Serialize};
+
+use crate::{Oracle, OracleConfig, PriceQuote};
+
+/// Queries all of its sources, and returns the median of their prices.
+///
+/// Quotes older than `max_staleness`, and quotes deviating from the median by more than `max_deviation_bps` basis
+/// points, are discarded. The median of the remaining quotes is returned when there are at least `min_sources` of
+/// them, an error otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MedianOracle {
+    pub sources: Vec<OracleConfig>,
+    #[serde(default = "default_min_sources")]
+    pub min_sources: usize,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_duration",
+        serialize_with = "serialize_optional_duration"
+    )]
+    pub max_staleness: Option<Duration>,
+    #[serde(default)]
+    pub max_deviation_bps: Option<u128>,
+}
+
+fn default_min_sources() -> usize {
+    1
+}
+
+#[async_trait]
+impl Oracle for MedianOracle {
+    async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)> {
+        let quote = self.fetch_eth_strk_quote().await?;
+        Ok((quote.price, quote.decimals))
+    }
+
+    async fn fetch_eth_strk_quote(&self) -> anyhow::Result<PriceQuote> {
+        let results = futures::future::join_all(self.sources.iter().map(|source| source.fetch_eth_strk_quote())).await;
+        let quotes = results
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, res)| {
+                res.inspect_err(|err| tracing::warn!("Median oracle source #{i} failed: {err:#}")).ok()
+            })
+            .collect();
+        self.aggregate(quotes, SystemTime::now())
+    }
+}
+
+impl MedianOracle {
+    fn aggregate(&self, quotes: Vec<PriceQuote>, now: SystemTime) -> anyhow::Result<PriceQuote> {
+        let quotes: Vec<_> = quotes
+            .into_iter()
+            .filter(|quote| {
+                let Some(max_staleness) = self.max_staleness else { return true };
+                let age = now.duration_since(quote.timestamp).unwrap_or_default();
+                if age > max_staleness {
+                    tracing::warn!("Discarding ETH/STRK price {quote:?}: stale for {age:?}");
+                }
+                age <= max_staleness
+            })
+            .collect();
+
+        // Bring all the prices to the same number of decimals.
+        let decimals = quotes.iter().map(|quote| quote.decimals).max().unwrap_or_default();
+        let mut prices: Vec<(u128, SystemTime)> = quotes
+            .iter()
+            .filter_map(|quote| {
+                let price =
+                    10u128.checked_pow(decimals - quote.decimals).and_then(|factor| quote.price.checked_mul(factor));
+                price.map(|price| (price, quote.timestamp))
+            })
+            .collect();
+        ensure!(
+            prices.len() >= self.min_sources,
+            "Only {} ETH/STRK prices available, {} required",
+            prices.len(),
+            self.min_sources
+        );
+
+        if let Some(max_deviation_bps) = self.max_deviation_bps {
+            let median = median(&mut prices).context("No ETH/STRK price available")?;
+            prices.retain(|(price, _)| {
+                let deviates = price.abs_diff(median).saturating_mul(10_000) > median.saturating_mul(max_deviation_bps);
+                if deviates {
+                    tracing::warn!("Discarding ETH/STRK price {price}: deviates too much from the median {median}");
+                }
+                !deviates
+            });
+            ensure!(
+                prices.len() >= self.min_sources,
+                "Only {} ETH/STRK prices within {max_deviation_bps} bps of the median, {} required",
+                prices.len(),
+                self.min_sources
+            );
+        }
+
+        let price = median(&mut prices).context("No ETH/STRK price available")?;
+        // The aggregate is only as fresh as its oldest source.
+        let timestamp = prices.iter().map(|(_, timestamp)| *timestamp).min().unwrap_or(now);
+        Ok(PriceQuote { price, decimals, timestamp })
+    }
+}
+
+fn median(prices: &mut [(u128, SystemTime)]) -> Option<u128> {
+    prices.sort_unstable_by_key(|(price, _)| *price);
+    let mid = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(prices[mid].0),
+        _ => {
+            let (a, b) = (prices[mid - 1].0, prices[mid].0);
+            Some(a / 2 + b / 2 + (a % 2 + b % 2) / 2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(price: u128, decimals: u32, age_secs: u64, now: SystemTime) -> PriceQuote {
+        PriceQuote { price, decimals, timestamp: now - Duration::from_secs(age_secs) }
+    }
+
+    fn oracle(min_sources: usize, max_staleness: Option<Duration>, max_deviation_bps: Option<u128>) -> MedianOracle {
+        MedianOracle { sources: vec![], min_sources, max_staleness, max_deviation_bps }
+    }
+
+    #[test]
+    fn test_median_oracle_aggregate() {
+        let now = SystemTime::now();
+
+        // Prices are normalized to the largest number of decimals.
+        let quotes = vec![quote(300, 1, 10, now), quote(3100, 2, 20, now), quote(31, 0, 5, now)];
+        assert_eq!(
+            oracle(1, None, None).aggregate(quotes.clone(), now).unwrap(),
+            PriceQuote { price: 3100, decimals: 2, timestamp: now - Duration::from_secs(20) }
+        );
+
+        // Stale quotes are discarded.
+        let res = oracle(2, Some(Duration::from_secs(15)), None).aggregate(quotes.clone(), now).unwrap();
+        assert_eq!((res.price, res.decimals), (305, 1));
+        assert!(oracle(3, Some(Duration::from_secs(15)), None).aggregate(quotes, now).is_err());
+
+        // Outliers are discarded.
+        let quotes = vec![quote(1000, 0, 0, now), quote(1010, 0, 0, now), quote(2000, 0, 0, now)];
+        assert_eq!(oracle(2, None, Some(100)).aggregate(quotes.clone(), now).unwrap().price, 1005);
+        assert!(oracle(3, None, Some(100)).aggregate(quotes, now).is_err());
+
+        assert!(oracle(1, None, None).aggregate(vec![], now).is_err());
+        assert!(oracle(0, None, None).aggregate(vec![], now).is_err());
+    }
+}
@@ -1,11 +1,13 @@
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{bail, Context};
 use async_trait::async_trait;
+use mp_utils::serde::{deserialize_duration, serialize_duration};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 
-use crate::Oracle;
+use crate::{default_request_timeout, http_client, Oracle, PriceQuote};
 
 pub const DEFAULT_API_URL: &str = "https://api.dev.pragma.build/node/v1/data/";
 
@@ -21,6 +23,13 @@ pub struct PragmaOracle {
     pub interval: Interval,
     #[serde(default)]
     pub price_bounds: PriceBounds,
+    /// Requests to the Pragma API fail after this long.
+    #[serde(
+        default = "default_request_timeout",
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration"
+    )]
+    pub timeout: Duration,
 }
 
 impl Default for PragmaOracle {
@@ -31,6 +40,7 @@ impl Default for PragmaOracle {
             aggregation_method: AggregationMethod::Median,
             interval: Interval::OneMinute,
             price_bounds: Default::default(),
+            timeout: default_request_timeout(),
         }
     }
 }
@@ -41,7 +51,7 @@ impl PragmaOracle {
     }
 
     fn is_in_bounds(&self, price: u128) -> bool {
-        self.price_bounds.low <= price && price <= self.price_bounds.high
+        self.price_bounds.is_in_bounds(price)
     }
 }
 
@@ -53,7 +63,12 @@ impl Oracle for PragmaOracle {
     /// Ok((u128, u32)) : return the price tuple as (price, decimals)
     /// Err(e) : return an error if anything went wrong in the fetching process or eth/strk price is 0
     async fn fetch_eth_strk_price(&self) -> anyhow::Result<(u128, u32)> {
-        let response = reqwest::Client::new()
+        let quote = self.fetch_eth_strk_quote().await?;
+        Ok((quote.price, quote.decimals))
+    }
+
+    async fn fetch_eth_strk_quote(&self) -> anyhow::Result<PriceQuote> {
+        let response = http_client(self.timeout)?
             .get(self.get_fetch_url(String::from("eth"), String::from("strk")))
             .header("x-api-key", self.api_key.clone())
             .send()
@@ -69,7 +84,11 @@ impl Oracle for PragmaOracle {
         if !self.is_in_bounds(eth_strk_price) {
             bail!("ETH/STRK price outside of bounds");
         }
-        Ok((eth_strk_price, oracle_api_response.decimals))
+        let timestamp = match oracle_api_response.timestamp {
+            Some(millis) => UNIX_EPOCH + Duration::from_millis(millis),
+            None => SystemTime::now(),
+        };
+        Ok(PriceQuote { price: eth_strk_price, decimals: oracle_api_response.decimals, timestamp })
     }
 }
 
@@ -129,6 +148,12 @@ pub struct PriceBounds {
     pub high: u128,
 }
 
+impl PriceBounds {
+    pub fn is_in_bounds(&self, price: u128) -> bool {
+        self.low <= price && price <= self.high
+    }
+}
+
 impl Default for PriceBounds {
     fn default() -> Self {
         Self { low: 0, high: u128::MAX }
@@ -144,6 +169,9 @@ fn default_oracle_api_url() -> Url {
 struct PragmaApiResponse {
     price: String,
     decimals: u32,
+    /// Unix timestamp of the price, in milliseconds.
+    #[serde(default)]
+    timestamp: Option<u64>,
 }
 
 pub struct PragmaOracleBuilder {
@@ -179,6 +207,7 @@ impl PragmaOracleBuilder {
             aggregation_method: AggregationMethod::default(),
             interval: Interval::default(),
             price_bounds: PriceBounds::default(),
+            timeout: default_request_timeout(),
         }
     }
 }
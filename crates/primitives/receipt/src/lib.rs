@@ -242,6 +242,18 @@ pub struct MsgToL1 {
     pub payload: Vec<Felt>,
 }
 
+impl MsgToL1 {
+    /// Hash of the message, as computed by the L1 core contract to track the messages to consume.
+    pub fn compute_hash(&self) -> H256 {
+        let message = starknet_core::types::MsgToL1 {
+            from_address: self.from_address,
+            to_address: self.to_address,
+            payload: self.payload.clone(),
+        };
+        H256::from_slice(message.hash().as_bytes())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Event {
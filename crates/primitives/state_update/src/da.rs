@@ -0,0 +1,303 @@
+//! Encoding of state diffs as published by the Starknet OS for data availability.
+
+use crate::{
+    ContractStorageDiffItem, DeclaredClassItem, DeployedContractItem, NonceUpdate, ReplacedClassItem, StateDiff,
+    StorageEntry,
+};
+use starknet_types_core::felt::Felt;
+use std::collections::BTreeMap;
+use std::fmt;
+
+const TWO_64: Felt = Felt::from_hex_unchecked("0x10000000000000000");
+const TWO_128: Felt = Felt::from_hex_unchecked("0x100000000000000000000000000000000");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaDecodingError<E> {
+    /// The data ends in the middle of the state diff.
+    UnexpectedEnd,
+    /// The felt at `index` is not a valid length or contract header.
+    InvalidField { index: usize, value: Felt },
+    /// Getting the state of an updated contract failed.
+    ContractState(E),
+}
+
+impl<E: fmt::Display> fmt::Display for DaDecodingError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "Unexpected end of the data availability"),
+            Self::InvalidField { index, value } => {
+                write!(f, "Invalid field {value:#x} at index {index} of the data availability")
+            }
+            Self::ContractState(err) => write!(f, "Getting the contract state: {err}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for DaDecodingError<E> {}
+
+struct DaReader<'a> {
+    data: &'a [Felt],
+    index: usize,
+}
+
+impl DaReader<'_> {
+    fn read<E>(&mut self) -> Result<Felt, DaDecodingError<E>> {
+        let value = *self.data.get(self.index).ok_or(DaDecodingError::UnexpectedEnd)?;
+        self.index += 1;
+        Ok(value)
+    }
+
+    /// Reads the number of items that follow, each taking `item_len` felts.
+    fn read_len<E>(&mut self, item_len: usize) -> Result<usize, DaDecodingError<E>> {
+        let index = self.index;
+        let value = self.read()?;
+        let remaining = (self.data.len() - self.index) / item_len;
+        match u64::try_from(value) {
+            Ok(len) if len <= remaining as u64 => Ok(len as usize),
+            _ => Err(DaDecodingError::InvalidField { index, value }),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ContractDa {
+    class_hash: Option<Felt>,
+    nonce: Option<Felt>,
+    storage: Vec<(Felt, Felt)>,
+}
+
+impl StateDiff {
+    /// Encodes the state diff in the (uncompressed) data availability format of the Starknet OS:
+    ///
+    /// ```text
+    /// [number of updated contracts]
+    /// for each updated contract, by ascending address:
+    ///     address
+    ///     class_flag * 2^128 + nonce * 2^64 + number of storage updates
+    ///     [new class hash, if class_flag is set]
+    ///     key, value  (for each storage update)
+    /// [number of declared classes]
+    /// class hash, compiled class hash  (for each declared class)
+    /// ```
+    ///
+    /// The encoding always carries the nonce of every updated contract. `current_nonce` is called for contracts whose
+    /// nonce is not part of this diff, and should return their nonce as of this state diff.
+    ///
+    /// Deprecated (cairo 0) declared classes are not part of the data availability.
+    pub fn to_da_encoding<E>(&self, mut current_nonce: impl FnMut(&Felt) -> Result<Felt, E>) -> Result<Vec<Felt>, E> {
+        let mut contracts: BTreeMap<Felt, ContractDa> = BTreeMap::new();
+        for item in &self.deployed_contracts {
+            contracts.entry(item.address).or_default().class_hash = Some(item.class_hash);
+        }
+        for item in &self.replaced_classes {
+            contracts.entry(item.contract_address).or_default().class_hash = Some(item.class_hash);
+        }
+        for item in &self.nonces {
+            contracts.entry(item.contract_address).or_default().nonce = Some(item.nonce);
+        }
+        for item in &self.storage_diffs {
+            let contract = contracts.entry(item.address).or_default();
+            contract.storage.extend(item.storage_entries.iter().map(|entry| (entry.key, entry.value)));
+        }
+
+        let mut out = vec![Felt::from(contracts.len() as u64)];
+        for (address, mut contract) in contracts {
+            let nonce = match contract.nonce {
+                Some(nonce) => nonce,
+                None => current_nonce(&address)?,
+            };
+            contract.storage.sort_by_key(|(key, _)| *key);
+
+            out.push(address);
+            out.push(
+                Felt::from(contract.class_hash.is_some() as u64) * TWO_128
+                    + nonce * TWO_64
+                    + Felt::from(contract.storage.len() as u64),
+            );
+            out.extend(contract.class_hash);
+            out.extend(contract.storage.into_iter().flat_map(|(key, value)| [key, value]));
+        }
+
+        let mut declared_classes = self.declared_classes.clone();
+        declared_classes.sort_by_key(|class| class.class_hash);
+        out.push(Felt::from(declared_classes.len() as u64));
+        out.extend(declared_classes.into_iter().flat_map(|class| [class.class_hash, class.compiled_class_hash]));
+
+        Ok(out)
+    }
+
+    /// Decodes a state diff encoded with [`StateDiff::to_da_encoding`]. Returns the state diff, and the number of felts
+    /// it spans at the start of `data`.
+    ///
+    /// The encoding does not tell deployed contracts from replaced classes, nor updated nonces from unchanged ones.
+    /// `contract_state` is called for every updated contract, and should return its class hash (`None` when it is not
+    /// deployed) and its nonce from before this state diff.
+    pub fn from_da_encoding<E>(
+        data: &[Felt],
+        mut contract_state: impl FnMut(&Felt) -> Result<(Option<Felt>, Felt), E>,
+    ) -> Result<(Self, usize), DaDecodingError<E>> {
+        let mut reader = DaReader { data, index: 0 };
+        let mut state_diff = StateDiff::default();
+
+        let n_contracts = reader.read_len(2)?;
+        for _ in 0..n_contracts {
+            let address = reader.read()?;
+            let header_index = reader.index;
+            let header = reader.read()?;
+            // Little endian: number of storage updates, nonce, class flag.
+            let [n_storage_updates, nonce, class_flag, high] = header.to_le_digits();
+            if class_flag > 1 || high != 0 || n_storage_updates > (data.len() - reader.index) as u64 / 2 {
+                return Err(DaDecodingError::InvalidField { index: header_index, value: header });
+            }
+
+            let (current_class_hash, current_nonce) =
+                contract_state(&address).map_err(DaDecodingError::ContractState)?;
+            if class_flag == 1 {
+                let class_hash = reader.read()?;
+                match current_class_hash {
+                    Some(_) => {
+                        state_diff.replaced_classes.push(ReplacedClassItem { contract_address: address, class_hash })
+                    }
+                    None => state_diff.deployed_contracts.push(DeployedContractItem { address, class_hash }),
+                }
+            }
+            if Felt::from(nonce) != current_nonce {
+                state_diff.nonces.push(NonceUpdate { contract_address: address, nonce: Felt::from(nonce) });
+            }
+            if n_storage_updates > 0 {
+                let storage_entries = (0..n_storage_updates)
+                    .map(|_| Ok(StorageEntry { key: reader.read()?, value: reader.read()? }))
+                    .collect::<Result<_, _>>()?;
+                state_diff.storage_diffs.push(ContractStorageDiffItem { address, storage_entries });
+            }
+        }
+
+        let n_declared_classes = reader.read_len(2)?;
+        for _ in 0..n_declared_classes {
+            let class_hash = reader.read()?;
+            let compiled_class_hash = reader.read()?;
+            state_diff.declared_classes.push(DeclaredClassItem { class_hash, compiled_class_hash });
+        }
+
+        Ok((state_diff, reader.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    fn sample_state_diff() -> StateDiff {
+        StateDiff {
+            storage_diffs: vec![
+                ContractStorageDiffItem {
+                    address: Felt::from(7),
+                    storage_entries: vec![
+                        StorageEntry { key: Felt::from(4), value: Felt::from(5) },
+                        StorageEntry { key: Felt::from(2), value: Felt::from(3) },
+                    ],
+                },
+                ContractStorageDiffItem {
+                    address: Felt::from(1),
+                    storage_entries: vec![StorageEntry { key: Felt::from(8), value: Felt::from(9) }],
+                },
+            ],
+            deprecated_declared_classes: vec![Felt::from(100)],
+            declared_classes: vec![DeclaredClassItem {
+                class_hash: Felt::from(11),
+                compiled_class_hash: Felt::from(12),
+            }],
+            deployed_contracts: vec![DeployedContractItem { address: Felt::from(7), class_hash: Felt::from(13) }],
+            replaced_classes: vec![],
+            nonces: vec![NonceUpdate { contract_address: Felt::from(1), nonce: Felt::from(2) }],
+        }
+    }
+
+    #[test]
+    fn test_da_encoding() {
+        let state_diff = sample_state_diff();
+
+        let encoded = state_diff.to_da_encoding(|_| Ok::<_, Infallible>(Felt::ZERO)).unwrap();
+        assert_eq!(
+            encoded,
+            vec![
+                Felt::from(2),
+                // contract 1: nonce 2, one storage update
+                Felt::from(1),
+                Felt::from(2) * TWO_64 + Felt::ONE,
+                Felt::from(8),
+                Felt::from(9),
+                // contract 7: deployed, nonce 0, two storage updates
+                Felt::from(7),
+                TWO_128 + Felt::from(2),
+                Felt::from(13),
+                Felt::from(2),
+                Felt::from(3),
+                Felt::from(4),
+                Felt::from(5),
+                // declared classes
+                Felt::ONE,
+                Felt::from(11),
+                Felt::from(12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_da_decoding() {
+        let state_diff = sample_state_diff();
+        let mut encoded = state_diff.to_da_encoding(|_| Ok::<_, Infallible>(Felt::ZERO)).unwrap();
+        let encoded_len = encoded.len();
+        // Trailing data is not part of the state diff.
+        encoded.push(Felt::from(42));
+
+        // Contract 1 is already deployed, contract 7 is not.
+        let (decoded, len) = StateDiff::from_da_encoding(&encoded, |address| {
+            Ok::<_, Infallible>(if *address == Felt::from(1) {
+                (Some(Felt::from(14)), Felt::ONE)
+            } else {
+                (None, Felt::ZERO)
+            })
+        })
+        .unwrap();
+        assert_eq!(len, encoded_len);
+
+        let mut expected = state_diff;
+        expected.deprecated_declared_classes.clear();
+        expected.sort();
+        assert_eq!(decoded, expected);
+
+        // Contract 1 with a new class hash: the class is replaced.
+        let (decoded, _) =
+            StateDiff::from_da_encoding(&encoded, |_| Ok::<_, Infallible>((Some(Felt::ONE), Felt::ZERO))).unwrap();
+        assert_eq!(
+            decoded.replaced_classes,
+            vec![ReplacedClassItem { contract_address: Felt::from(7), class_hash: Felt::from(13) }]
+        );
+        assert!(decoded.deployed_contracts.is_empty());
+    }
+
+    #[test]
+    fn test_da_decoding_invalid() {
+        let encoded = sample_state_diff().to_da_encoding(|_| Ok::<_, Infallible>(Felt::ZERO)).unwrap();
+        let contract_state = |_: &Felt| Ok::<_, Infallible>((None, Felt::ZERO));
+
+        assert_eq!(
+            StateDiff::from_da_encoding(&encoded[..encoded.len() - 1], contract_state),
+            Err(DaDecodingError::UnexpectedEnd)
+        );
+        let mut invalid = encoded.clone();
+        invalid[0] = Felt::from(1000);
+        assert_eq!(
+            StateDiff::from_da_encoding(&invalid, contract_state),
+            Err(DaDecodingError::InvalidField { index: 0, value: Felt::from(1000) })
+        );
+        let mut invalid = encoded;
+        invalid[2] = Felt::from(2) * TWO_128;
+        assert_eq!(
+            StateDiff::from_da_encoding(&invalid, contract_state),
+            Err(DaDecodingError::InvalidField { index: 2, value: Felt::from(2) * TWO_128 })
+        );
+    }
+}
@@ -1,5 +1,8 @@
+mod da;
 mod into_starknet_types;
 
+pub use da::DaDecodingError;
+
 use starknet_types_core::{
     felt::Felt,
     hash::{Poseidon, StarkHash},
@@ -40,6 +40,25 @@
 //! - `and_versions`: implementations of this method will also work for the
 //!     supplied versions. Note that these versions must not already contain
 //!     a method with the same name.
+//! - `shared_unsubscribe`: for subscriptions, the method documented as their
+//!     unsubscribe method instead of `unsubscribe`. This is for subscriptions
+//!     which are all cancelled by a single regular method, as jsonrpsee needs
+//!     a distinct `unsubscribe` method for each of them.
+//!
+//! ---
+//!
+//! **Method metadata**
+//!
+//! ---
+//!
+//! Next to the versioned trait, `versioned_rpc` emits a `&str` constant with
+//! the metadata of its methods as a JSON array, named after the trait in
+//! screaming snake case with a `_METHODS` suffix. Every entry has the method
+//! `name` and `namespace`, its `kind` (`method` or `subscription`), the
+//! `versions` it is served under, a `summary` taken from its doc comment, its
+//! `params` with their Rust `type` and whether they are `required`, and the
+//! Rust type of its `result`. Subscriptions also have an `unsubscribe` method.
+//! This is used to serve OpenRPC documents.
 //!
 //! # Example:
 //!
@@ -71,6 +90,8 @@
 //!     #[method(name = "V0_7_1_blockNumber", aliases = ["starknet_V0_8_0blockNumber"])]
 //!     fn block_number(&self) -> RpcResult<u64>;
 //! }
+//!
+//! pub const JSON_RPC_V0_7_1_METHODS: &str = r#"[{"name":"blockNumber","namespace":"starknet",...}]"#;
 //! ```
 
 use proc_macro::TokenStream;
@@ -140,6 +161,138 @@ enum CallType {
     Subscribe,
 }
 
+/// Metadata of a method, emitted as JSON next to the versioned trait.
+#[derive(Debug, Default)]
+struct MethodMeta {
+    name: String,
+    subscription: bool,
+    versions: Vec<String>,
+    unsubscribe: Option<String>,
+    summary: String,
+    /// Name, type and whether the parameter is required.
+    params: Vec<(String, String, bool)>,
+    result: String,
+}
+
+impl MethodMeta {
+    fn to_json(&self, namespace: &str) -> String {
+        let versions = self.versions.iter().map(|version| json_string(version)).collect::<Vec<_>>().join(",");
+        let params = self
+            .params
+            .iter()
+            .map(|(name, ty, required)| {
+                format!(r#"{{"name":{},"type":{},"required":{required}}}"#, json_string(name), json_string(ty))
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let unsubscribe = match &self.unsubscribe {
+            Some(unsubscribe) => format!(r#","unsubscribe":{}"#, json_string(unsubscribe)),
+            None => String::new(),
+        };
+
+        format!(
+            r#"{{"name":{},"namespace":{},"kind":{},"versions":[{versions}],"summary":{},"params":[{params}],"result":{}{unsubscribe}}}"#,
+            json_string(&self.name),
+            json_string(namespace),
+            json_string(if self.subscription { "subscription" } else { "method" }),
+            json_string(&self.summary),
+            json_string(&self.result),
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn to_screaming_snake_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 8);
+    let mut prev_lowercase = false;
+    for c in s.chars() {
+        if c.is_ascii_uppercase() && prev_lowercase {
+            out.push('_');
+        }
+        prev_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+/// Renders a type the way it is written in the source.
+fn type_to_string(ty: &syn::Type) -> String {
+    quote!(#ty)
+        .to_string()
+        .replace(" < ", "<")
+        .replace("< ", "<")
+        .replace(" <", "<")
+        .replace(" >", ">")
+        .replace(" , ", ", ")
+        .replace(" :: ", "::")
+        .replace("& ", "&")
+}
+
+/// Returns the generic argument of a `Wrapper<T>` type.
+fn unwrap_type<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(syn::TypePath { path, .. }) = ty else { return None };
+    let segment = path.segments.last().filter(|segment| segment.ident == wrapper)?;
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+/// First paragraph of the doc comment of a method.
+fn doc_summary(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn method_meta(method: &syn::TraitItemFn) -> MethodMeta {
+    let params = method
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| {
+            let syn::FnArg::Typed(syn::PatType { pat, ty, .. }) = input else { return None };
+            let name = match pat.as_ref() {
+                syn::Pat::Ident(pat) => pat.ident.to_string(),
+                pat => quote!(#pat).to_string(),
+            };
+            Some((name, type_to_string(ty), unwrap_type(ty, "Option").is_none()))
+        })
+        .collect();
+    let result = match &method.sig.output {
+        syn::ReturnType::Type(_, ty) => type_to_string(unwrap_type(ty, "RpcResult").unwrap_or(ty)),
+        syn::ReturnType::Default => "()".to_string(),
+    };
+
+    MethodMeta { summary: doc_summary(&method.attrs), params, result, ..Default::default() }
+}
+
 #[proc_macro_attribute]
 pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
     let VersionedRpcAttr { version, namespace } = syn::parse_macro_input!(attr as VersionedRpcAttr);
@@ -147,6 +300,11 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
 
     let trait_name = &item_trait.ident;
     let train_name_with_version = syn::Ident::new(&format!("{trait_name}{version}"), trait_name.span());
+    let methods_const = syn::Ident::new(
+        &format!("{}_{}_METHODS", to_screaming_snake_case(&trait_name.to_string()), version.to_ascii_uppercase()),
+        trait_name.span(),
+    );
+    let mut methods_meta = Vec::new();
 
     // This next section is reponsible for versioning the method name declared
     // with jsonrpsee
@@ -168,7 +326,10 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
             ));
         };
 
-        method.attrs.iter_mut().try_fold((), |_, attr| {
+        let mut meta = method_meta(method);
+        meta.versions.push(version.clone());
+
+        let res = method.attrs.iter_mut().try_fold((), |_, attr| {
             // We leave simple attribute parsing errors to be handled by
             // jsonrpsee
             let path = attr.path();
@@ -207,6 +368,7 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
             //
             // Any other attribute is skipped over and is not overwritten
             let mut method_name = None;
+            let mut shared_unsubscribe = None;
             let mut method_tokens = attr_args
                 .iter()
                 .filter_map(|expr| {
@@ -229,11 +391,22 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
                         return Some(syn::Expr::Assign(expr.clone()));
                     };
 
-                    if !path.is_ident("name") && !path.is_ident("unsubscribe") {
+                    // `shared_unsubscribe` is not a jsonrpsee macro attribute
+                    // either, it only changes the documented unsubscribe
+                    // method
+                    if path.is_ident("shared_unsubscribe") {
+                        shared_unsubscribe = Some(right.value());
+                        return None;
+                    }
+
+                    if path.is_ident("name") {
+                        method_name = Some(right.value());
+                    } else if path.is_ident("unsubscribe") {
+                        meta.unsubscribe = Some(format!("{namespace}_{}", right.value()));
+                    } else {
                         return Some(syn::Expr::Assign(expr.clone()));
                     }
 
-                    method_name = Some(right.value());
                     let method_with_version = format!("{version}_{}", right.value());
                     let expr = syn::Expr::Assign(syn::ExprAssign {
                         right: Box::new(syn::Expr::Lit(syn::ExprLit {
@@ -247,6 +420,10 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
                 })
                 .collect::<Vec<_>>();
 
+            if let Some(shared_unsubscribe) = shared_unsubscribe {
+                meta.unsubscribe = Some(format!("{namespace}_{shared_unsubscribe}"));
+            }
+
             // Method name is required by jsonrpsee anyways and this makes it
             // easier to work with
             let Some(method) = method_name else {
@@ -269,6 +446,13 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
                 let syn::Expr::Path(syn::ExprPath { path, .. }) = *expr.left.clone() else {
                     return Ok(acc);
                 };
+                if path.is_ident("item") {
+                    let item = &expr.right;
+                    meta.result = syn::parse2::<syn::Type>(quote!(#item))
+                        .map(|ty| type_to_string(&ty))
+                        .unwrap_or_else(|_| quote!(#item).to_string());
+                    return Ok(acc);
+                }
                 let syn::Expr::Array(syn::ExprArray { elems, .. }) = *expr.right.clone() else {
                     return Ok(acc);
                 };
@@ -281,6 +465,7 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
                     if let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(version), attrs }) = elem {
                         let version_str = version.value();
                         validate_version(&version_str)?;
+                        meta.versions.push(version_str.clone());
                         let method_with_version = format!("{namespace}_{}_{method}", version_str);
 
                         let lit = syn::Expr::Lit(syn::ExprLit {
@@ -307,9 +492,16 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
                 CallType::Method => syn::parse_quote!(method(#(#method_tokens),*)),
                 CallType::Subscribe => syn::parse_quote!(subscription(#(#method_tokens),*)),
             };
+            meta.name = method;
+            meta.subscription = matches!(ident, CallType::Subscribe);
 
             Ok(())
-        })
+        });
+
+        if !meta.name.is_empty() {
+            methods_meta.push(meta.to_json(&namespace));
+        }
+        res
     });
 
     if let Err(e) = err {
@@ -322,8 +514,15 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
         ..item_trait
     };
 
+    let methods_json = format!("[{}]", methods_meta.join(","));
+    let methods_doc =
+        format!(" Metadata of the methods of the `{}` RPC trait, as a JSON array.", trait_with_version.ident);
+
     quote! {
         #trait_with_version
+
+        #[doc = #methods_doc]
+        pub const #methods_const: &str = #methods_json;
     }
     .into()
 }
@@ -355,6 +554,35 @@ mod tests {
         assert_eq!(result.unwrap_err().to_string(), "Version must have exactly three parts (VMAJOR_MINOR_PATCH)");
     }
 
+    #[test]
+    fn test_method_meta() {
+        let method: syn::TraitItemFn = parse_quote! {
+            /// Get the nonce of a contract.
+            /// Second line of the summary.
+            ///
+            /// Details.
+            #[method(name = "getNonce")]
+            fn get_nonce(&self, block_id: BlockId, contract_address: Option<Felt>) -> RpcResult<Felt>;
+        };
+        let meta =
+            MethodMeta { name: "getNonce".to_string(), versions: vec!["V0_7_1".to_string()], ..method_meta(&method) };
+        assert_eq!(
+            meta.to_json("starknet"),
+            r#"{"name":"getNonce","namespace":"starknet","kind":"method","versions":["V0_7_1"],"summary":"Get the nonce of a contract. Second line of the summary.","params":[{"name":"block_id","type":"BlockId","required":true},{"name":"contract_address","type":"Option<Felt>","required":false}],"result":"Felt"}"#
+        );
+    }
+
+    #[test]
+    fn test_screaming_snake_case() {
+        assert_eq!(to_screaming_snake_case("StarknetReadRpcApi"), "STARKNET_READ_RPC_API");
+        assert_eq!(to_screaming_snake_case("StarknetWsRpcApi"), "STARKNET_WS_RPC_API");
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a \"b\"\n"), r#""a \"b\"\n""#);
+    }
+
     #[test]
     fn test_versioned_rpc_attribute_parsing_empty_namespace() {
         let result: syn::Result<VersionedRpcAttr> = syn::parse2(quote!("V0_7_1", ""));